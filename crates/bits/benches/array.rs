@@ -0,0 +1,23 @@
+use std::hint::black_box;
+
+use bitvec::{order::Msb0, vec::BitVec};
+use criterion::{criterion_group, criterion_main, Criterion};
+use tlbits::{
+    de::{r#as::unpack_as, unpack},
+    r#as::NBits,
+    ser::pack,
+};
+
+fn unpack_array(c: &mut Criterion) {
+    let packed: BitVec<u8, Msb0> = pack([0x1234_5678u32; 256]).unwrap();
+
+    c.bench_function("unpack [u32; 256]", |b| {
+        b.iter(|| unpack::<[u32; 256]>(black_box(&packed)).unwrap())
+    });
+    c.bench_function("unpack_as [u32; 256] as [NBits<32>; 256]", |b| {
+        b.iter(|| unpack_as::<[u32; 256], [NBits<32>; 256]>(black_box(&packed)).unwrap())
+    });
+}
+
+criterion_group!(benches, unpack_array);
+criterion_main!(benches);
@@ -1,4 +1,6 @@
 //! Adapters for [`BitReader`](crate::de::BitReader) / [`BitWriter`](crate::ser::BitWriter)
+use core::ops::Range;
+
 use impl_tools::autoimpl;
 
 /// Adapter that maps an error using given closure
@@ -51,3 +53,88 @@ impl<T> BitCounter<T> {
         self.inner
     }
 }
+
+/// Adapter for recording ranges of bits read, so that bits which were
+/// [skipped](crate::de::BitReader::skip) or left unread can be reported
+/// with [`.gaps()`](Recorder::gaps) after parsing:
+/// ```rust
+/// # use tlbits::{
+/// #     bitvec::{bits, order::Msb0},
+/// #     de::{BitReader, BitReaderExt},
+/// # };
+/// let data = bits![u8, Msb0; 1; 16];
+/// let mut reader = data.recorded();
+/// let _: u8 = reader.unpack().unwrap();
+/// reader.skip(2).unwrap();
+/// let _: bool = reader.unpack().unwrap();
+///
+/// assert_eq!(reader.read_ranges(), [0..8, 10..11]);
+/// assert_eq!(reader.gaps(data.len()), [8..10, 11..16]);
+/// ```
+#[autoimpl(Deref using self.inner)]
+pub struct Recorder<T> {
+    pub(crate) inner: T,
+    /// number of bits read or skipped so far
+    pub(crate) position: usize,
+    /// sorted and merged ranges of read bits
+    pub(crate) read: Vec<Range<usize>>,
+}
+
+impl<T> Recorder<T> {
+    #[inline]
+    pub const fn new(inner: T) -> Self {
+        Self {
+            inner,
+            position: 0,
+            read: Vec::new(),
+        }
+    }
+
+    /// Number of bits read or skipped so far
+    #[inline]
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Sorted and merged ranges of bits that were read
+    #[inline]
+    pub fn read_ranges(&self) -> &[Range<usize>] {
+        &self.read
+    }
+
+    /// Ranges of bits out of the first `len` ones that were skipped or
+    /// not read at all
+    pub fn gaps(&self, len: usize) -> Vec<Range<usize>> {
+        let mut gaps = Vec::new();
+        let mut start = 0;
+        for r in &self.read {
+            if r.start >= len {
+                break;
+            }
+            if start < r.start {
+                gaps.push(start..r.start);
+            }
+            start = r.end;
+        }
+        if start < len {
+            gaps.push(start..len);
+        }
+        gaps
+    }
+
+    #[inline]
+    pub(crate) fn record(&mut self, n: usize) {
+        let end = self.position + n;
+        match self.read.last_mut() {
+            Some(last) if last.end == self.position => last.end = end,
+            _ if n > 0 => self.read.push(self.position..end),
+            _ => {}
+        }
+        self.position = end;
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
@@ -3,6 +3,7 @@ use core::marker::PhantomData;
 use crate::{
     de::{args::r#as::BitUnpackAsWithArgs, r#as::BitUnpackAs, BitReader},
     ser::{args::r#as::BitPackAsWithArgs, r#as::BitPackAs, BitWriter},
+    ResultExt,
 };
 
 use super::Same;
@@ -71,3 +72,113 @@ where
         As::unpack_as_with(reader, <As::Args>::default())
     }
 }
+
+/// Short human-readable description of args, which is added to error
+/// context by [`ArgsContext`] adapter.
+pub trait DescribeArgs {
+    fn describe_args(&self) -> String;
+}
+
+impl DescribeArgs for () {
+    #[inline]
+    fn describe_args(&self) -> String {
+        "()".to_string()
+    }
+}
+
+macro_rules! impl_describe_args_for_display {
+    ($($t:ty)+) => {$(
+        impl DescribeArgs for $t {
+            #[inline]
+            fn describe_args(&self) -> String {
+                self.to_string()
+            }
+        }
+    )+};
+}
+impl_describe_args_for_display! {
+    bool u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize
+}
+
+macro_rules! impl_describe_args_for_tuple {
+    ($($n:tt:$t:ident),+) => {
+        impl<$($t),+> DescribeArgs for ($($t,)+)
+        where $(
+            $t: DescribeArgs,
+        )+
+        {
+            #[inline]
+            fn describe_args(&self) -> String {
+                let args: &[String] = &[$(self.$n.describe_args()),+];
+                format!("({})", args.join(", "))
+            }
+        }
+    };
+}
+impl_describe_args_for_tuple!(0:T0);
+impl_describe_args_for_tuple!(0:T0,1:T1);
+impl_describe_args_for_tuple!(0:T0,1:T1,2:T2);
+impl_describe_args_for_tuple!(0:T0,1:T1,2:T2,3:T3);
+impl_describe_args_for_tuple!(0:T0,1:T1,2:T2,3:T3,4:T4);
+
+/// Adapter to add [description](DescribeArgs) of args in effect to the
+/// context of errors returned by `As`.
+/// ```rust
+/// # use tlbits::{
+/// #     bitvec::{bits, order::Msb0},
+/// #     de::args::r#as::unpack_as_with,
+/// #     r#as::{args::ArgsContext, VarNBits},
+/// # };
+/// let err = unpack_as_with::<u32, ArgsContext<VarNBits>>(bits![u8, Msb0; 1; 8], 9)
+///     .unwrap_err();
+/// assert_eq!(err.to_string(), "args 9: [8]: EOF");
+/// ```
+pub struct ArgsContext<As: ?Sized = Same>(PhantomData<As>);
+
+impl<T, As> BitPackAsWithArgs<T> for ArgsContext<As>
+where
+    T: ?Sized,
+    As: BitPackAsWithArgs<T> + ?Sized,
+    As::Args: DescribeArgs + Clone,
+{
+    type Args = As::Args;
+
+    #[inline]
+    fn pack_as_with<W>(source: &T, writer: W, args: Self::Args) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        As::pack_as_with(source, writer, args.clone())
+            .with_context(|| format!("args {}", args.describe_args()))
+    }
+}
+
+impl<T, As> BitUnpackAsWithArgs<T> for ArgsContext<As>
+where
+    As: BitUnpackAsWithArgs<T> + ?Sized,
+    As::Args: DescribeArgs + Clone,
+{
+    type Args = As::Args;
+
+    #[inline]
+    fn unpack_as_with<R>(reader: R, args: Self::Args) -> Result<T, R::Error>
+    where
+        R: BitReader,
+    {
+        As::unpack_as_with(reader, args.clone())
+            .with_context(|| format!("args {}", args.describe_args()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe_args() {
+        assert_eq!(
+            (267u32, (), (true, -1i8)).describe_args(),
+            "(267, (), (true, -1))"
+        );
+    }
+}
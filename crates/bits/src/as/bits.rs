@@ -38,8 +38,39 @@ where
 }
 
 /// **De**/**ser**ialize value from/into exactly `N` bits.
+///
+/// Returns an error if the value does not fit into `N` bits, i.e. it is
+/// the same as [`Checked<NBits<N>>`](super::Checked). Use
+/// [`Truncate<NBits<N>>`](super::Truncate) to discard excessive bits
+/// instead.
+///
+/// Primitive integers are packed as their bit representation, while
+/// [`BigInt`](num_bigint::BigInt) is packed in two's complement form,
+/// as `int N` in TL-B.
 pub struct NBits<const BITS: usize>;
 
+/// `bool` is a single bit
+impl BitPackAs<bool> for NBits<1> {
+    #[inline]
+    fn pack_as<W>(source: &bool, writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        source.pack(writer)
+    }
+}
+
+/// `bool` is a single bit
+impl BitUnpackAs<bool> for NBits<1> {
+    #[inline]
+    fn unpack_as<R>(mut reader: R) -> Result<bool, R::Error>
+    where
+        R: BitReader,
+    {
+        reader.unpack()
+    }
+}
+
 /// **De**/**ser**ialize bytes by prefixing its length with `N`-bit integer.
 pub struct VarBytes<const BITS_FOR_BYTES_LEN: usize>;
 
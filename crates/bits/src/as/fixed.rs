@@ -0,0 +1,358 @@
+use crate::{
+    de::{r#as::BitUnpackAs, BitReader, BitReaderExt},
+    ser::{args::r#as::BitPackAsWithArgs, r#as::BitPackAs, BitWriter, BitWriterExt},
+    Error,
+};
+
+use super::VarNBits;
+
+/// Rounding mode used by [`FixedPoint`] when the value can not be
+/// represented exactly with given number of fractional bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Return an error if the value is not representable exactly
+    #[default]
+    Exact,
+    /// Round towards negative infinity
+    Floor,
+    /// Round towards positive infinity
+    Ceil,
+    /// Round to the nearest, ties away from zero
+    Nearest,
+    /// Round to the nearest, ties to even
+    NearestEven,
+}
+
+/// **De**/**ser**ialize a non-negative fixed-point number as
+/// `uint (INT_BITS + FRAC_BITS)` holding `value * 2^FRAC_BITS`.
+///
+/// [`BitPackAs`] requires the value to be representable exactly, use
+/// [`BitPackAsWithArgs`] to specify [`Rounding`] explicitly:
+/// ```rust
+/// # use tlbits::{
+/// #     r#as::{FixedPoint, Rounding},
+/// #     de::r#as::unpack_fully_as,
+/// #     ser::{args::r#as::pack_as_with, r#as::pack_as},
+/// # };
+/// # fn main() -> Result<(), tlbits::StringError> {
+/// type Price = FixedPoint<8, 8>;
+///
+/// let packed = pack_as::<_, Price>(1.5)?;
+/// assert_eq!(packed.as_raw_slice(), [0x01, 0x80]);
+/// assert_eq!(unpack_fully_as::<f64, Price>(&packed)?, 1.5);
+///
+/// assert!(pack_as::<_, Price>(0.1).is_err());
+/// let packed = pack_as_with::<_, Price>(0.1, Rounding::Nearest)?;
+/// assert_eq!(packed.as_raw_slice(), [0x00, 0x1a]);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// With `rust_decimal` feature enabled, it also supports
+/// `rust_decimal::Decimal`.
+pub struct FixedPoint<const INT_BITS: usize, const FRAC_BITS: usize>;
+
+impl<const INT_BITS: usize, const FRAC_BITS: usize> FixedPoint<INT_BITS, FRAC_BITS> {
+    const BITS: usize = INT_BITS + FRAC_BITS;
+
+    #[inline]
+    fn pack_raw<W>(raw: u128, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer.pack_as_with::<_, VarNBits>(raw, Self::BITS as u32)?;
+        Ok(())
+    }
+
+    #[inline]
+    fn unpack_raw<R>(mut reader: R) -> Result<u128, R::Error>
+    where
+        R: BitReader,
+    {
+        reader.unpack_as_with::<_, VarNBits>(Self::BITS as u32)
+    }
+}
+
+impl<const INT_BITS: usize, const FRAC_BITS: usize> BitPackAs<f64>
+    for FixedPoint<INT_BITS, FRAC_BITS>
+{
+    #[inline]
+    fn pack_as<W>(source: &f64, writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        Self::pack_as_with(source, writer, Rounding::Exact)
+    }
+}
+
+impl<const INT_BITS: usize, const FRAC_BITS: usize> BitPackAsWithArgs<f64>
+    for FixedPoint<INT_BITS, FRAC_BITS>
+{
+    type Args = Rounding;
+
+    #[inline]
+    fn pack_as_with<W>(source: &f64, writer: W, rounding: Self::Args) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        if !source.is_finite() {
+            return Err(Error::custom(format!("{source} is not finite")));
+        }
+        let scaled = source * 2f64.powi(FRAC_BITS as i32);
+        let rounded = match rounding {
+            Rounding::Exact => {
+                if scaled.fract() != 0.0 {
+                    return Err(Error::custom(format!(
+                        "{source} cannot be represented exactly with {FRAC_BITS} fractional bits"
+                    )));
+                }
+                scaled
+            }
+            Rounding::Floor => scaled.floor(),
+            Rounding::Ceil => scaled.ceil(),
+            Rounding::Nearest => scaled.round(),
+            Rounding::NearestEven => scaled.round_ties_even(),
+        };
+        if rounded < 0.0 {
+            return Err(Error::custom(format!("{source} is negative")));
+        }
+        if rounded >= 2f64.powi(Self::BITS as i32) {
+            return Err(Error::custom(format!(
+                "{source} does not fit into {INT_BITS} integer bits"
+            )));
+        }
+        Self::pack_raw(rounded as u128, writer)
+    }
+}
+
+impl<const INT_BITS: usize, const FRAC_BITS: usize> BitUnpackAs<f64>
+    for FixedPoint<INT_BITS, FRAC_BITS>
+{
+    #[inline]
+    fn unpack_as<R>(reader: R) -> Result<f64, R::Error>
+    where
+        R: BitReader,
+    {
+        let raw = Self::unpack_raw(reader)?;
+        Ok(raw as f64 / 2f64.powi(FRAC_BITS as i32))
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+mod decimal {
+    use num_traits::FromPrimitive;
+    use rust_decimal::Decimal;
+
+    use super::*;
+
+    impl<const INT_BITS: usize, const FRAC_BITS: usize> FixedPoint<INT_BITS, FRAC_BITS> {
+        #[inline]
+        fn decimal_scale<E>() -> Result<Decimal, E>
+        where
+            E: Error,
+        {
+            1u128
+                .checked_shl(FRAC_BITS as u32)
+                .and_then(Decimal::from_u128)
+                .ok_or_else(|| Error::custom(format!("{FRAC_BITS} fractional bits overflow")))
+        }
+    }
+
+    impl<const INT_BITS: usize, const FRAC_BITS: usize> BitPackAs<Decimal>
+        for FixedPoint<INT_BITS, FRAC_BITS>
+    {
+        #[inline]
+        fn pack_as<W>(source: &Decimal, writer: W) -> Result<(), W::Error>
+        where
+            W: BitWriter,
+        {
+            Self::pack_as_with(source, writer, Rounding::Exact)
+        }
+    }
+
+    impl<const INT_BITS: usize, const FRAC_BITS: usize> BitPackAsWithArgs<Decimal>
+        for FixedPoint<INT_BITS, FRAC_BITS>
+    {
+        type Args = Rounding;
+
+        #[inline]
+        fn pack_as_with<W>(
+            source: &Decimal,
+            writer: W,
+            rounding: Self::Args,
+        ) -> Result<(), W::Error>
+        where
+            W: BitWriter,
+        {
+            if Self::BITS > u128::BITS as usize {
+                return Err(Error::custom(format!("{} bits overflow", Self::BITS)));
+            }
+            let too_large = || {
+                Error::custom(format!(
+                    "{source} does not fit into {INT_BITS} integer bits"
+                ))
+            };
+
+            // scaling is done on integers, since Decimal arithmetic
+            // silently rounds results exceeding its precision
+            let den = 10u128.pow(source.scale());
+            let mantissa = source.mantissa().unsigned_abs();
+            let (int, mut rem) = (mantissa / den, mantissa % den);
+            if INT_BITS < u128::BITS as usize && int >> INT_BITS != 0 {
+                return Err(too_large());
+            }
+            // binary long division of the fractional part, rem < den < 2^94
+            let mut frac = 0u128;
+            for _ in 0..FRAC_BITS {
+                rem <<= 1;
+                frac <<= 1;
+                if rem >= den {
+                    rem -= den;
+                    frac |= 1;
+                }
+            }
+            let mut raw = int.checked_shl(FRAC_BITS as u32).unwrap_or(0) | frac;
+
+            let negative = source.is_sign_negative();
+            let inexact = rem != 0;
+            // whether to round magnitude up
+            let round_up = match rounding {
+                Rounding::Exact => {
+                    if inexact {
+                        return Err(Error::custom(format!(
+                            "{source} cannot be represented exactly with {FRAC_BITS} fractional bits"
+                        )));
+                    }
+                    false
+                }
+                Rounding::Floor => negative && inexact,
+                Rounding::Ceil => !negative && inexact,
+                Rounding::Nearest => 2 * rem >= den,
+                Rounding::NearestEven => 2 * rem > den || (2 * rem == den && raw & 1 == 1),
+            };
+            if round_up {
+                raw = raw.checked_add(1).ok_or_else(too_large)?;
+            }
+            if negative && raw != 0 {
+                return Err(Error::custom(format!("{source} is negative")));
+            }
+            if Self::BITS < u128::BITS as usize && raw >> Self::BITS != 0 {
+                return Err(too_large());
+            }
+            Self::pack_raw(raw, writer)
+        }
+    }
+
+    impl<const INT_BITS: usize, const FRAC_BITS: usize> BitUnpackAs<Decimal>
+        for FixedPoint<INT_BITS, FRAC_BITS>
+    {
+        #[inline]
+        fn unpack_as<R>(reader: R) -> Result<Decimal, R::Error>
+        where
+            R: BitReader,
+        {
+            let raw = Self::unpack_raw(reader)?;
+            Decimal::from_u128(raw)
+                .and_then(|v| v.checked_div(Self::decimal_scale::<R::Error>().ok()?))
+                .ok_or_else(|| Error::custom(format!("{raw:#x} does not fit into Decimal")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ser::args::r#as::pack_as_with, tests::assert_pack_unpack_as_eq};
+
+    use super::*;
+
+    #[test]
+    fn fixed_point_f64() {
+        assert_pack_unpack_as_eq::<f64, FixedPoint<8, 8>>(0.0);
+        assert_pack_unpack_as_eq::<f64, FixedPoint<8, 8>>(255.99609375);
+        assert_pack_unpack_as_eq::<f64, FixedPoint<64, 64>>(12345.125);
+    }
+
+    #[test]
+    fn fixed_point_f64_rounding() {
+        type F = FixedPoint<4, 1>;
+        for (rounding, v, expected) in [
+            (Rounding::Floor, 1.3, 0b00010),
+            (Rounding::Ceil, 1.3, 0b00011),
+            (Rounding::Nearest, 1.25, 0b00011),
+            (Rounding::NearestEven, 1.25, 0b00010),
+            (Rounding::NearestEven, 1.75, 0b00100),
+        ] {
+            assert_eq!(
+                pack_as_with::<_, F>(v, rounding).unwrap(),
+                pack_as_with::<_, VarNBits>(expected as u8, 5).unwrap(),
+                "{rounding:?}({v})",
+            );
+        }
+    }
+
+    #[test]
+    fn fixed_point_f64_invalid() {
+        type F = FixedPoint<4, 4>;
+        assert!(pack_as_with::<_, F>(0.01, Rounding::Exact).is_err());
+        assert!(pack_as_with::<_, F>(-1.0, Rounding::Floor).is_err());
+        assert!(pack_as_with::<_, F>(16.0, Rounding::Floor).is_err());
+        assert!(pack_as_with::<_, F>(f64::NAN, Rounding::Floor).is_err());
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn fixed_point_decimal() {
+        use rust_decimal::Decimal;
+
+        type F = FixedPoint<16, 8>;
+        assert_pack_unpack_as_eq::<_, F>(Decimal::new(12_375, 3));
+        assert!(pack_as_with::<_, F>(Decimal::new(1, 1), Rounding::Exact).is_err());
+        for rounding in [
+            Rounding::Floor,
+            Rounding::Ceil,
+            Rounding::Nearest,
+            Rounding::NearestEven,
+        ] {
+            assert_eq!(
+                pack_as_with::<_, F>(Decimal::new(1, 1), rounding).unwrap(),
+                pack_as_with::<_, F>(0.1, rounding).unwrap(),
+                "{rounding:?}",
+            );
+        }
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn fixed_point_decimal_invalid() {
+        use rust_decimal::Decimal;
+
+        type F = FixedPoint<8, 8>;
+        let err = |v, rounding| pack_as_with::<_, F>(v, rounding).unwrap_err().to_string();
+
+        // product with scale overflows Decimal precision and would be
+        // rounded to 256.0
+        let almost_one = Decimal::from_i128_with_scale(9_999_999_999_999_999_999_999_999_999, 28);
+        assert_eq!(
+            err(almost_one, Rounding::Exact),
+            format!("{almost_one} cannot be represented exactly with 8 fractional bits"),
+        );
+        assert_eq!(
+            pack_as_with::<_, F>(almost_one, Rounding::Floor).unwrap(),
+            pack_as_with::<_, VarNBits>(0xffu16, 16).unwrap(),
+        );
+
+        assert_eq!(
+            err(Decimal::MAX, Rounding::Floor),
+            format!("{} does not fit into 8 integer bits", Decimal::MAX),
+        );
+        assert_eq!(
+            err(Decimal::new(25_599_999, 5), Rounding::Ceil),
+            "255.99999 does not fit into 8 integer bits",
+        );
+        assert_eq!(
+            err(Decimal::new(-1, 3), Rounding::Floor),
+            "-0.001 is negative"
+        );
+        assert!(pack_as_with::<_, F>(Decimal::new(-1, 3), Rounding::Ceil).is_ok());
+    }
+}
@@ -1,10 +1,16 @@
 use core::{
     fmt::{Binary, LowerHex},
+    marker::PhantomData,
     mem::size_of,
 };
 
-use bitvec::{mem::bits_of, order::Msb0, vec::BitVec, view::AsBits};
+use bitvec::{mem::bits_of, view::AsBits};
+#[cfg(feature = "bigint")]
+use bitvec::{order::Msb0, vec::BitVec};
+#[cfg(feature = "bigint")]
 use num_bigint::{BigInt, BigUint};
+#[cfg(feature = "bigint")]
+use num_traits::{One, Signed, Zero};
 use num_traits::{PrimInt, ToBytes};
 
 use crate::{
@@ -13,8 +19,11 @@ use crate::{
     Error,
 };
 
-use super::{NBits, VarBytes};
+use super::NBits;
+#[cfg(feature = "bigint")]
+use super::VarBytes;
 
+#[cfg(feature = "bigint")]
 impl<const BITS: usize> BitPackAs<BigUint> for NBits<BITS> {
     #[inline]
     fn pack_as<W>(source: &BigUint, mut writer: W) -> Result<(), W::Error>
@@ -38,6 +47,7 @@ impl<const BITS: usize> BitPackAs<BigUint> for NBits<BITS> {
     }
 }
 
+#[cfg(feature = "bigint")]
 impl<const BITS: usize> BitUnpackAs<BigUint> for NBits<BITS> {
     #[inline]
     fn unpack_as<R>(mut reader: R) -> Result<BigUint, R::Error>
@@ -52,40 +62,226 @@ impl<const BITS: usize> BitUnpackAs<BigUint> for NBits<BITS> {
     }
 }
 
+#[cfg(feature = "bigint")]
 impl<const BITS: usize> BitPackAs<BigInt> for NBits<BITS> {
     #[inline]
     fn pack_as<W>(source: &BigInt, mut writer: W) -> Result<(), W::Error>
     where
         W: BitWriter,
     {
-        let used_bits = source.bits() as usize;
+        // one more bit for sign
+        let used_bits = if source.is_zero() {
+            0
+        } else if source.is_negative() {
+            (-source - 1u32).bits() as usize + 1
+        } else {
+            source.bits() as usize + 1
+        };
         if BITS < used_bits {
             return Err(Error::custom(format!(
                 "{source:#b} cannot be packed into {BITS} bits"
             )));
         }
+        writer.pack_as::<_, &Truncate<NBits<BITS>>>(source)?;
+        Ok(())
+    }
+}
 
-        writer.repeat_bit(BITS - used_bits, false)?;
+#[cfg(feature = "bigint")]
+impl<const BITS: usize> BitUnpackAs<BigInt> for NBits<BITS> {
+    #[inline]
+    fn unpack_as<R>(mut reader: R) -> Result<BigInt, R::Error>
+    where
+        R: BitReader,
+    {
+        let v: BigUint = reader.unpack_as::<_, NBits<BITS>>()?;
+        Ok(if BITS > 0 && v.bit(BITS as u64 - 1) {
+            // negative in two's complement
+            BigInt::from(v) - (BigInt::one() << BITS)
+        } else {
+            v.into()
+        })
+    }
+}
 
-        let bytes = source.to_signed_bytes_be();
-        let mut bits = bytes.as_bits::<Msb0>();
-        bits = &bits[bits.len() - used_bits..];
-        writer.pack(bits)?;
+#[cfg(feature = "bigint")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bigint")))]
+/// Adapter for TVM integers, which are
+/// [257-bit signed](https://docs.ton.org/learn/tvm-instructions/tvm-overview#tvm-is-a-stack-machine)
+/// integers in range `-2^256..2^256`:
+/// ```tlb
+/// int257 = Int257;
+/// ```
+/// Unlike [`NBits<257>`](NBits), values out of this range are reported
+/// with a dedicated error.
+/// ```rust
+/// # use num_bigint::BigInt;
+/// # use tlbits::{r#as::Int257, ser::r#as::pack_as};
+/// assert_eq!(pack_as::<_, Int257>(Int257::max()).unwrap().len(), 257);
+/// let err = pack_as::<_, Int257>(Int257::max() + 1).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     format!("{} is out of int257 range", Int257::max() + 1),
+/// );
+/// ```
+pub struct Int257;
+
+#[cfg(feature = "bigint")]
+impl Int257 {
+    /// Number of bits used to represent the integer
+    pub const BITS: usize = 257;
+
+    /// Minimal value: `-2^256`
+    #[inline]
+    pub fn min() -> BigInt {
+        -(BigInt::one() << (Self::BITS - 1))
+    }
+
+    /// Maximal value: `2^256 - 1`
+    #[inline]
+    pub fn max() -> BigInt {
+        (BigInt::one() << (Self::BITS - 1)) - 1
+    }
+
+    /// Returns whether given value fits into `int257`
+    #[inline]
+    pub fn contains(v: &BigInt) -> bool {
+        // |v| < 2^256 or v == -2^256
+        v.bits() < Self::BITS as u64 || *v == Self::min()
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl BitPackAs<BigInt> for Int257 {
+    #[inline]
+    fn pack_as<W>(source: &BigInt, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        if !Self::contains(source) {
+            return Err(Error::custom(format!("{source} is out of int257 range")));
+        }
+        writer.pack_as::<_, &NBits<{ Self::BITS }>>(source)?;
         Ok(())
     }
 }
 
-impl<const BITS: usize> BitUnpackAs<BigInt> for NBits<BITS> {
+#[cfg(feature = "bigint")]
+impl BitUnpackAs<BigInt> for Int257 {
     #[inline]
     fn unpack_as<R>(mut reader: R) -> Result<BigInt, R::Error>
     where
         R: BitReader,
     {
-        let mut bits = reader.read_bitvec(BITS)?;
-        let total_bits = (BITS + 7) & !7;
-        bits.resize(total_bits, false);
-        bits.shift_right(total_bits - BITS);
-        Ok(BigInt::from_signed_bytes_be(bits.as_raw_slice()))
+        reader.unpack_as::<_, NBits<{ Self::BITS }>>()
+    }
+}
+
+/// Adapter that returns an error if the value does not fit into the
+/// underlying type, e.g. `Checked<NBits<N>>`.
+///
+/// This is the default behavior of [`NBits`] itself, so this adapter
+/// only exists for explicitness in contrast with [`Truncate`].
+pub struct Checked<As: ?Sized>(PhantomData<As>);
+
+impl<T, As> BitPackAs<T> for Checked<As>
+where
+    T: ?Sized,
+    As: BitPackAs<T> + ?Sized,
+{
+    #[inline]
+    fn pack_as<W>(source: &T, writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        As::pack_as(source, writer)
+    }
+}
+
+impl<T, As> BitUnpackAs<T> for Checked<As>
+where
+    As: BitUnpackAs<T> + ?Sized,
+{
+    #[inline]
+    fn unpack_as<R>(reader: R) -> Result<T, R::Error>
+    where
+        R: BitReader,
+    {
+        As::unpack_as(reader)
+    }
+}
+
+/// Adapter that silently discards the most significant bits of the value
+/// that do not fit into the underlying type, e.g. `Truncate<NBits<N>>`.
+/// When **de**serializing, the most significant bits that do not fit into
+/// the output type are discarded as well.
+/// ```rust
+/// # use tlbits::{
+/// #     r#as::{NBits, Truncate},
+/// #     ser::r#as::pack_as,
+/// # };
+/// assert!(pack_as::<_, NBits<4>>(0x1F_u8).is_err());
+/// assert_eq!(
+///     pack_as::<_, Truncate<NBits<4>>>(0x1F_u8).unwrap(),
+///     pack_as::<_, NBits<4>>(0x0F_u8).unwrap(),
+/// );
+/// ```
+pub struct Truncate<As: ?Sized>(PhantomData<As>);
+
+#[cfg(feature = "bigint")]
+impl<const BITS: usize> BitPackAs<BigUint> for Truncate<NBits<BITS>> {
+    #[inline]
+    fn pack_as<W>(source: &BigUint, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        if source.bits() as usize > BITS {
+            let mask = (BigUint::one() << BITS) - 1u32;
+            writer.pack_as::<_, NBits<BITS>>(source & mask)?;
+        } else {
+            writer.pack_as::<_, &NBits<BITS>>(source)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl<const BITS: usize> BitUnpackAs<BigUint> for Truncate<NBits<BITS>> {
+    #[inline]
+    fn unpack_as<R>(reader: R) -> Result<BigUint, R::Error>
+    where
+        R: BitReader,
+    {
+        NBits::<BITS>::unpack_as(reader)
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl<const BITS: usize> BitPackAs<BigInt> for Truncate<NBits<BITS>> {
+    #[inline]
+    fn pack_as<W>(source: &BigInt, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        // two's complement: source mod 2^BITS
+        let modulus = BigInt::one() << BITS;
+        let mut v = source % &modulus;
+        if v.is_negative() {
+            v += modulus;
+        }
+        writer.pack_as::<_, &NBits<BITS>>(v.magnitude())?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl<const BITS: usize> BitUnpackAs<BigInt> for Truncate<NBits<BITS>> {
+    #[inline]
+    fn unpack_as<R>(reader: R) -> Result<BigInt, R::Error>
+    where
+        R: BitReader,
+    {
+        NBits::<BITS>::unpack_as(reader)
     }
 }
 
@@ -99,6 +295,7 @@ impl<const BITS: usize> BitUnpackAs<BigInt> for NBits<BITS> {
 /// See [`VarNBits`] for *dynamic* version.
 pub struct VarInt<const BITS_FOR_BYTES_LEN: usize>;
 
+#[cfg(feature = "bigint")]
 impl<const BITS_FOR_BYTES_LEN: usize> BitPackAs<BigUint> for VarInt<BITS_FOR_BYTES_LEN> {
     #[inline]
     fn pack_as<W>(source: &BigUint, mut writer: W) -> Result<(), W::Error>
@@ -116,6 +313,7 @@ impl<const BITS_FOR_BYTES_LEN: usize> BitPackAs<BigUint> for VarInt<BITS_FOR_BYT
     }
 }
 
+#[cfg(feature = "bigint")]
 impl<const BITS_FOR_BYTES_LEN: usize> BitUnpackAs<BigUint> for VarInt<BITS_FOR_BYTES_LEN> {
     #[inline]
     fn unpack_as<R>(mut reader: R) -> Result<BigUint, R::Error>
@@ -133,6 +331,7 @@ impl<const BITS_FOR_BYTES_LEN: usize> BitUnpackAs<BigUint> for VarInt<BITS_FOR_B
     }
 }
 
+#[cfg(feature = "bigint")]
 impl<const BITS_FOR_BYTES_LEN: usize> BitPackAs<BigInt> for VarInt<BITS_FOR_BYTES_LEN> {
     #[inline]
     fn pack_as<W>(source: &BigInt, mut writer: W) -> Result<(), W::Error>
@@ -144,6 +343,7 @@ impl<const BITS_FOR_BYTES_LEN: usize> BitPackAs<BigInt> for VarInt<BITS_FOR_BYTE
     }
 }
 
+#[cfg(feature = "bigint")]
 impl<const BITS_FOR_BYTES_LEN: usize> BitUnpackAs<BigInt> for VarInt<BITS_FOR_BYTES_LEN> {
     #[inline]
     fn unpack_as<R>(mut reader: R) -> Result<BigInt, R::Error>
@@ -161,12 +361,120 @@ impl<const BITS_FOR_BYTES_LEN: usize> BitUnpackAs<BigInt> for VarInt<BITS_FOR_BY
     }
 }
 
+macro_rules! impl_var_int_for_prims {
+    ($($t:ty)+) => {$(
+        impl<const BITS_FOR_BYTES_LEN: usize> BitPackAs<$t> for VarInt<BITS_FOR_BYTES_LEN> {
+            #[inline]
+            fn pack_as<W>(source: &$t, mut writer: W) -> Result<(), W::Error>
+            where
+                W: BitWriter,
+            {
+                let num_bytes = used_bits(*source).div_ceil(8);
+                writer
+                    .pack_as::<_, NBits<BITS_FOR_BYTES_LEN>>(num_bytes)?
+                    .pack_as_with::<_, VarNBytes>(*source, num_bytes)?;
+                Ok(())
+            }
+        }
+
+        impl<const BITS_FOR_BYTES_LEN: usize> BitUnpackAs<$t> for VarInt<BITS_FOR_BYTES_LEN> {
+            #[inline]
+            fn unpack_as<R>(mut reader: R) -> Result<$t, R::Error>
+            where
+                R: BitReader,
+            {
+                let num_bytes: u32 = reader.unpack_as::<_, NBits<BITS_FOR_BYTES_LEN>>()?;
+                if num_bytes > size_of::<$t>() as u32 {
+                    return Err(Error::custom(format!(
+                        concat!("{} bytes overflow ", stringify!($t)),
+                        num_bytes,
+                    )));
+                }
+                reader.unpack_as_with::<_, VarNBytes>(num_bytes)
+            }
+        }
+    )+};
+}
+impl_var_int_for_prims! {
+    u8 u16 u32 u64 u128
+    i8 i16 i32 i64 i128
+}
+
+/// Minimal number of bits needed to represent the value, including the
+/// sign bit in two's complement form for signed types
+#[inline]
+fn used_bits<T>(v: T) -> u32
+where
+    T: PrimInt,
+{
+    let size_bits = bits_of::<T>() as u32;
+    if !is_signed::<T>() {
+        return size_bits - v.leading_zeros();
+    }
+    if v.is_zero() {
+        return 0;
+    }
+    let sign_bits = if v < T::zero() {
+        v.leading_ones()
+    } else {
+        v.leading_zeros()
+    };
+    // one more bit for sign
+    size_bits - sign_bits + 1
+}
+
+#[inline]
+fn is_signed<T>() -> bool
+where
+    T: PrimInt,
+{
+    T::min_value() < T::zero()
+}
+
+/// Read `num_bits` bits into the least significant bits of the value,
+/// extending sign bit for signed types
+#[inline]
+fn read_var_bits<T, R>(mut reader: R, num_bits: u32) -> Result<T, R::Error>
+where
+    T: PrimInt,
+    R: BitReader,
+{
+    let mut v: T = T::zero();
+    for bit in reader.unpack_iter::<bool>().take(num_bits as usize) {
+        v = v << 1;
+        v = v | if bit? { T::one() } else { T::zero() };
+    }
+    let size_bits = bits_of::<T>() as u32;
+    if is_signed::<T>()
+        && num_bits > 0
+        && num_bits < size_bits
+        && v & (T::one() << (num_bits as usize - 1)) != T::zero()
+    {
+        // negative in two's complement
+        v = v | (!T::zero() << num_bits as usize);
+    }
+    Ok(v)
+}
+
 /// Adapter for [`Var[U]Integer (n * 8)`](https://docs.ton.org/develop/data-formats/msg-tlb#varuinteger-n) where `n` is *dynamic*.
 /// ```tlb
 /// var_uint$_ {n:#} len:(#< n) value:(uint (len * 8)) = VarUInteger n;
 /// var_int$_ {n:#} len:(#< n) value:(int (len * 8)) = VarInteger n;
 /// ```
 /// See [`VarInt`] for *constant* version.
+///
+/// Signed types are packed in two's complement form, so that the sign bit
+/// must fit into given number of bits as well:
+/// ```rust
+/// # use tlbits::{
+/// #     de::args::r#as::unpack_as_with,
+/// #     r#as::VarNBits,
+/// #     ser::args::r#as::pack_as_with,
+/// # };
+/// let packed = pack_as_with::<_, VarNBits>(-3_i32, 3).unwrap();
+/// assert_eq!(unpack_as_with::<i32, VarNBits>(&packed, 3).unwrap(), -3);
+/// assert!(pack_as_with::<_, VarNBits>(3_i32, 2).is_err());
+/// ```
 pub struct VarNBits;
 
 impl<T> BitPackAsWithArgs<T> for VarNBits
@@ -182,9 +490,10 @@ where
         W: BitWriter,
     {
         let size_bits: u32 = bits_of::<T>() as u32;
-        let leading_zeroes = source.leading_zeros();
-        let used_bits = size_bits - leading_zeroes;
-        if num_bits < used_bits {
+        if num_bits > size_bits {
+            return Err(Error::custom("excessive bits for the type"));
+        }
+        if num_bits < used_bits(*source) {
             return Err(Error::custom(format!(
                 "{source:0b} cannot be packed into {num_bits} bits",
             )));
@@ -204,7 +513,7 @@ where
     type Args = u32;
 
     #[inline]
-    fn unpack_as_with<R>(mut reader: R, num_bits: Self::Args) -> Result<T, R::Error>
+    fn unpack_as_with<R>(reader: R, num_bits: Self::Args) -> Result<T, R::Error>
     where
         R: BitReader,
     {
@@ -212,12 +521,7 @@ where
         if num_bits > size_bits {
             return Err(Error::custom("excessive bits for the type"));
         }
-        let mut v: T = T::zero();
-        for bit in reader.unpack_iter::<bool>().take(num_bits as usize) {
-            v = v << 1;
-            v = v | if bit? { T::one() } else { T::zero() };
-        }
-        Ok(v)
+        read_var_bits(reader, num_bits)
     }
 }
 
@@ -227,6 +531,8 @@ where
 /// var_int$_ {n:#} len:(#< n) value:(int (len * 8)) = VarInteger n;
 /// ```
 /// See [`VarInt`] for *constant* version.
+///
+/// Signed types are packed in two's complement form, see [`VarNBits`].
 pub struct VarNBytes;
 
 impl<T> BitPackAsWithArgs<T> for VarNBytes
@@ -242,9 +548,10 @@ where
         W: BitWriter,
     {
         let size_bytes: u32 = size_of::<T>() as u32;
-        let leading_zeroes = source.leading_zeros();
-        let used_bytes = size_bytes - leading_zeroes / 8;
-        if num_bytes < used_bytes {
+        if num_bytes > size_bytes {
+            return Err(Error::custom("excessive bits for type"));
+        }
+        if num_bytes < used_bits(*source).div_ceil(8) {
             return Err(Error::custom(format!(
                 "{source:0x} cannot be packed into {num_bytes} bytes",
             )));
@@ -264,7 +571,7 @@ where
     type Args = u32;
 
     #[inline]
-    fn unpack_as_with<R>(mut reader: R, num_bytes: Self::Args) -> Result<T, R::Error>
+    fn unpack_as_with<R>(reader: R, num_bytes: Self::Args) -> Result<T, R::Error>
     where
         R: BitReader,
     {
@@ -272,11 +579,6 @@ where
         if num_bytes > size_bytes {
             return Err(Error::custom("excessive bits for type"));
         }
-        let mut v: T = T::zero();
-        for byte in reader.unpack_iter::<u8>().take(num_bytes as usize) {
-            v = v << 8;
-            v = v | T::from(byte?).unwrap();
-        }
-        Ok(v)
+        read_var_bits(reader, num_bytes * 8)
     }
 }
@@ -0,0 +1,156 @@
+use core::marker::PhantomData;
+
+use crate::{
+    de::{r#as::BitUnpackAs, BitReader, BitReaderExt},
+    integer::ConstBit,
+    ser::{r#as::BitPackAs, BitWriter, BitWriterExt},
+    ResultExt,
+};
+
+/// Static label to be added as a context to errors by [`Flag`] and
+/// [`TagBit`] adapters.
+/// See [`label!`](crate::label) for declaring labels.
+pub trait Label {
+    const LABEL: &'static str;
+}
+
+/// Declare unit structs implementing [`Label`].
+/// ```rust
+/// # use tlbits::{label, r#as::Label};
+/// label! {
+///     pub IsSignatureAllowed = "is_signature_allowed";
+///     Bounce = "bounce";
+/// }
+///
+/// assert_eq!(IsSignatureAllowed::LABEL, "is_signature_allowed");
+/// ```
+#[macro_export]
+macro_rules! label {
+    ($($vis:vis $name:ident = $label:literal);+ $(;)?) => {$(
+        #[doc = concat!("Label `", $label, "`")]
+        $vis struct $name;
+
+        impl $crate::r#as::Label for $name {
+            const LABEL: &'static str = $label;
+        }
+    )+};
+}
+
+/// **De**/**ser**ialize `bool` as a single bit, wrapping errors in
+/// [`Label`] context.
+/// ```rust
+/// # use tlbits::{
+/// #     bitvec::{order::Msb0, vec::BitVec},
+/// #     de::BitReaderExt,
+/// #     label,
+/// #     r#as::Flag,
+/// # };
+/// label!(IsSignatureAllowed = "is_signature_allowed");
+///
+/// let mut reader = BitVec::<u8, Msb0>::new();
+/// let err = reader
+///     .as_bitslice()
+///     .unpack_as::<bool, Flag<IsSignatureAllowed>>()
+///     .unwrap_err();
+/// assert_eq!(err.to_string(), "is_signature_allowed: EOF");
+/// ```
+pub struct Flag<L: ?Sized>(PhantomData<L>);
+
+impl<L> BitPackAs<bool> for Flag<L>
+where
+    L: Label + ?Sized,
+{
+    #[inline]
+    fn pack_as<W>(source: &bool, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer.pack(*source).context(L::LABEL)?;
+        Ok(())
+    }
+}
+
+impl<L> BitUnpackAs<bool> for Flag<L>
+where
+    L: Label + ?Sized,
+{
+    #[inline]
+    fn unpack_as<R>(mut reader: R) -> Result<bool, R::Error>
+    where
+        R: BitReader,
+    {
+        reader.unpack().context(L::LABEL)
+    }
+}
+
+/// **De**/**ser**ialize `()` as a constant bit, wrapping errors in
+/// [`Label`] context.
+/// Same as [`ConstBit`], but with error context:
+/// ```tlb
+/// tag$1 = TagBit;
+/// ```
+/// ```rust
+/// # use tlbits::{
+/// #     bitvec::{bits, order::Msb0},
+/// #     de::BitReaderExt,
+/// #     label,
+/// #     r#as::TagBit,
+/// # };
+/// label!(Tag = "tag");
+///
+/// let mut reader = bits![u8, Msb0; 0];
+/// let err = reader.unpack_as::<(), TagBit<true, Tag>>().unwrap_err();
+/// assert_eq!(err.to_string(), "tag: expected 0b1, got 0b0");
+/// ```
+pub struct TagBit<const VALUE: bool, L: ?Sized>(PhantomData<L>);
+
+impl<const VALUE: bool, L> BitPackAs<()> for TagBit<VALUE, L>
+where
+    L: Label + ?Sized,
+{
+    #[inline]
+    fn pack_as<W>(_: &(), mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer.pack(ConstBit::<VALUE>).context(L::LABEL)?;
+        Ok(())
+    }
+}
+
+impl<const VALUE: bool, L> BitUnpackAs<()> for TagBit<VALUE, L>
+where
+    L: Label + ?Sized,
+{
+    #[inline]
+    fn unpack_as<R>(mut reader: R) -> Result<(), R::Error>
+    where
+        R: BitReader,
+    {
+        reader
+            .unpack::<ConstBit<VALUE>>()
+            .map(|_| ())
+            .context(L::LABEL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::assert_pack_unpack_as_eq;
+
+    use super::*;
+
+    label!(Test = "test");
+
+    #[test]
+    fn flag() {
+        assert_pack_unpack_as_eq::<_, Flag<Test>>(true);
+        assert_pack_unpack_as_eq::<_, Flag<Test>>(false);
+    }
+
+    #[test]
+    fn tag_bit() {
+        assert_pack_unpack_as_eq::<_, TagBit<true, Test>>(());
+        assert_pack_unpack_as_eq::<_, TagBit<false, Test>>(());
+    }
+}
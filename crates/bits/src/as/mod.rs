@@ -7,8 +7,11 @@
 pub mod args;
 mod bits;
 mod default;
+mod fixed;
 mod from_into;
 mod integer;
+mod label;
+mod order;
 mod same;
 mod unary;
 
@@ -29,7 +32,9 @@ use crate::{
     },
 };
 
-pub use self::{bits::*, default::*, from_into::*, integer::*, same::*, unary::*};
+pub use self::{
+    bits::*, default::*, fixed::*, from_into::*, integer::*, label::*, order::*, same::*, unary::*,
+};
 
 /// Helper to implement **de**/**ser**ialize trait for adapters
 #[autoimpl(Clone where T: Clone)]
@@ -39,7 +44,9 @@ where
     As: ?Sized,
 {
     value: T,
-    _phantom: PhantomData<As>,
+    // adapter is only a marker, so it must not affect auto traits like
+    // `Send` and `Sync`
+    _phantom: PhantomData<fn() -> As>,
 }
 
 impl<T, As> AsWrap<T, As>
@@ -62,7 +69,7 @@ where
     }
 }
 
-impl<'a, T, As> BitPack for AsWrap<&'a T, As>
+impl<T, As> BitPack for AsWrap<&T, As>
 where
     T: ?Sized,
     As: BitPackAs<T> + ?Sized,
@@ -76,7 +83,7 @@ where
     }
 }
 
-impl<'a, T, As> BitPackWithArgs for AsWrap<&'a T, As>
+impl<T, As> BitPackWithArgs for AsWrap<&T, As>
 where
     T: ?Sized,
     As: BitPackAsWithArgs<T> + ?Sized,
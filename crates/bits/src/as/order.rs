@@ -0,0 +1,243 @@
+use bitvec::{order::Msb0, vec::BitVec};
+
+use crate::{
+    de::{args::r#as::BitUnpackAsWithArgs, r#as::BitUnpackAs, BitReader, BitReaderExt},
+    ser::{
+        args::r#as::BitPackAsWithArgs,
+        r#as::{pack_as, BitPackAs},
+        BitWriter,
+    },
+    Error,
+};
+
+use super::Same;
+
+/// **De**/**ser**ialize value with the order of all its bits reversed,
+/// i.e. the last bit produced by `As` is stored first.
+///
+/// Since the length of reversed bit string can not be known in advance,
+/// **de**serialization requires number of bits to be passed as args:
+/// ```rust
+/// # use bitvec::{bits, order::Msb0};
+/// # use tlbits::{
+/// #     r#as::ReverseBits,
+/// #     de::args::r#as::unpack_as_with,
+/// #     ser::r#as::pack_as,
+/// # };
+/// # fn main() -> Result<(), tlbits::StringError> {
+/// let packed = pack_as::<_, ReverseBits>(0b0000_0011u8)?;
+/// assert_eq!(packed, bits![u8, Msb0; 1, 1, 0, 0, 0, 0, 0, 0]);
+///
+/// let got: u8 = unpack_as_with::<_, ReverseBits>(&packed, 8)?;
+/// assert_eq!(got, 0b0000_0011);
+/// # Ok(())
+/// # }
+/// ```
+pub struct ReverseBits<As: ?Sized = Same>(As);
+
+impl<T, As> BitPackAs<T> for ReverseBits<As>
+where
+    T: ?Sized,
+    As: BitPackAs<T> + ?Sized,
+{
+    #[inline]
+    fn pack_as<W>(source: &T, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        let mut bits = pack_as::<_, &As>(source).map_err(Error::custom)?;
+        bits.reverse();
+        writer.write_bitslice(&bits)
+    }
+}
+
+impl<T, As> BitPackAsWithArgs<T> for ReverseBits<As>
+where
+    T: ?Sized,
+    As: BitPackAs<T> + ?Sized,
+{
+    /// number of bits
+    type Args = usize;
+
+    #[inline]
+    fn pack_as_with<W>(source: &T, mut writer: W, len: Self::Args) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        let mut bits = pack_as::<_, &As>(source).map_err(Error::custom)?;
+        if bits.len() != len {
+            return Err(Error::custom(format!(
+                "expected {len} bits, got {}",
+                bits.len()
+            )));
+        }
+        bits.reverse();
+        writer.write_bitslice(&bits)
+    }
+}
+
+impl<T, As> BitUnpackAsWithArgs<T> for ReverseBits<As>
+where
+    As: BitUnpackAs<T> + ?Sized,
+{
+    /// number of bits
+    type Args = usize;
+
+    #[inline]
+    fn unpack_as_with<R>(mut reader: R, len: Self::Args) -> Result<T, R::Error>
+    where
+        R: BitReader,
+    {
+        let mut bits = reader.read_bitvec(len)?;
+        bits.reverse();
+        let mut bits = bits.as_bitslice();
+        let v = bits.unpack_as::<_, As>().map_err(Error::custom)?;
+        if !bits.is_empty() {
+            return Err(Error::custom("more data left"));
+        }
+        Ok(v)
+    }
+}
+
+/// **De**/**ser**ialize value with the order of bits reversed within
+/// each byte, i.e. bytes are stored LSB-first.
+///
+/// The value produced by `As` must be byte-aligned.
+/// ```rust
+/// # use bitvec::{bits, order::Msb0};
+/// # use tlbits::{
+/// #     r#as::Lsb0Bytes,
+/// #     de::r#as::unpack_fully_as,
+/// #     ser::r#as::pack_as,
+/// # };
+/// # fn main() -> Result<(), tlbits::StringError> {
+/// let packed = pack_as::<_, Lsb0Bytes>([0x01u8, 0x80])?;
+/// assert_eq!(packed.as_raw_slice(), [0x80, 0x01]);
+///
+/// let got: [u8; 2] = unpack_fully_as::<_, Lsb0Bytes>(&packed)?;
+/// assert_eq!(got, [0x01, 0x80]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Lsb0Bytes<As: ?Sized = Same>(As);
+
+impl<T, As> BitPackAs<T> for Lsb0Bytes<As>
+where
+    T: ?Sized,
+    As: BitPackAs<T> + ?Sized,
+{
+    #[inline]
+    fn pack_as<W>(source: &T, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        let mut bits: BitVec<u8, Msb0> = pack_as::<_, &As>(source).map_err(Error::custom)?;
+        if !bits.len().is_multiple_of(8) {
+            return Err(Error::custom(format!(
+                "not byte-aligned: {} bits",
+                bits.len()
+            )));
+        }
+        for byte in bits.as_raw_mut_slice() {
+            *byte = byte.reverse_bits();
+        }
+        writer.write_bitslice(&bits)
+    }
+}
+
+impl<T, As> BitUnpackAs<T> for Lsb0Bytes<As>
+where
+    As: BitUnpackAs<T> + ?Sized,
+{
+    #[inline]
+    fn unpack_as<R>(reader: R) -> Result<T, R::Error>
+    where
+        R: BitReader,
+    {
+        let mut reader = Lsb0Reader {
+            inner: reader,
+            byte: 0,
+            left: 0,
+        };
+        let v = As::unpack_as(&mut reader)?;
+        if reader.left != 0 {
+            return Err(Error::custom(format!(
+                "not byte-aligned: {} bits left",
+                reader.left
+            )));
+        }
+        Ok(v)
+    }
+}
+
+/// Reader that reads whole bytes from the inner reader and yields
+/// their bits starting from the least significant one.
+struct Lsb0Reader<R> {
+    inner: R,
+    byte: u8,
+    left: u8,
+}
+
+impl<R> BitReader for Lsb0Reader<R>
+where
+    R: BitReader,
+{
+    type Error = R::Error;
+
+    #[inline]
+    fn read_bit(&mut self) -> Result<bool, Self::Error> {
+        if self.left == 0 {
+            self.byte = self.inner.unpack()?;
+            self.left = 8;
+        }
+        let bit = self.byte & 1 == 1;
+        self.byte >>= 1;
+        self.left -= 1;
+        Ok(bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitvec::bits;
+
+    use crate::{
+        de::{args::r#as::unpack_as_with, r#as::unpack_fully_as},
+        ser::args::r#as::pack_as_with,
+        tests::assert_pack_unpack_as_eq,
+    };
+
+    use super::*;
+
+    #[test]
+    fn reverse_bits() {
+        let packed = pack_as_with::<_, ReverseBits>(0b1010_0000_1111u16, 16).unwrap();
+        assert_eq!(
+            packed,
+            bits![u8, Msb0; 1, 1, 1, 1, 0, 0, 0, 0, 0, 1, 0, 1, 0, 0, 0, 0]
+        );
+        let got: u16 = unpack_as_with::<_, ReverseBits>(&packed, 16).unwrap();
+        assert_eq!(got, 0b1010_0000_1111);
+    }
+
+    #[test]
+    fn reverse_bits_len_mismatch() {
+        assert!(pack_as_with::<_, ReverseBits>(0u8, 7).is_err());
+    }
+
+    #[test]
+    fn lsb0_bytes() {
+        assert_pack_unpack_as_eq::<_, Lsb0Bytes>([0x01u8, 0x02, 0xf0]);
+        assert_pack_unpack_as_eq::<_, Lsb0Bytes>(0x1234_5678u32);
+        assert_eq!(
+            pack_as::<_, Lsb0Bytes>(0x0102u16).unwrap().as_raw_slice(),
+            [0x80, 0x40]
+        );
+    }
+
+    #[test]
+    fn lsb0_bytes_not_aligned() {
+        assert!(pack_as::<_, Lsb0Bytes>(true).is_err());
+        assert!(unpack_fully_as::<bool, Lsb0Bytes>(bits![u8, Msb0; 1; 8]).is_err());
+    }
+}
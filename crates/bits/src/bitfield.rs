@@ -0,0 +1,166 @@
+/// Declare structs of named sub-fields with fixed widths, packed one
+/// after another starting from the most significant bit, so that there is
+/// no need to shift and mask integers by hand.
+///
+/// Each field is **de**/**ser**ialized as [`NBits<N>`](crate::r#as::NBits),
+/// so it can be of any type supported by it, including `bool` for 1-bit
+/// flags. Values that do not fit into their widths result in errors with
+/// the name of the field as a context. Total width is available as
+/// `BITS` associated constant.
+///
+/// ```rust
+/// # use tlbits::{
+/// #     bitfield,
+/// #     de::{unpack_fully, BitReaderExt},
+/// #     ser::pack,
+/// #     StringError,
+/// # };
+/// bitfield! {
+///     /// ```tlb
+///     /// level:(## 3) with_hashes:(## 1) is_exotic:(## 1) refs:(## 3)
+///     /// ```
+///     #[derive(Debug, PartialEq)]
+///     pub struct RefsDescriptor {
+///         pub level: u8 = 3,
+///         pub with_hashes: bool = 1,
+///         pub is_exotic: bool = 1,
+///         pub refs: u8 = 3,
+///     }
+/// }
+///
+/// # fn main() -> Result<(), StringError> {
+/// assert_eq!(RefsDescriptor::BITS, 8);
+/// let d = RefsDescriptor {
+///     level: 1,
+///     with_hashes: false,
+///     is_exotic: true,
+///     refs: 2,
+/// };
+/// let packed = pack(&d)?;
+/// assert_eq!(packed.as_bitslice().unpack::<u8>()?, 0b001_0_1_010);
+/// assert_eq!(unpack_fully::<RefsDescriptor>(packed)?, d);
+///
+/// let err = pack(RefsDescriptor { refs: 8, ..d }).unwrap_err();
+/// assert_eq!(err.to_string(), "refs: 0b1000 cannot be packed into 3 bits");
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! bitfield {
+    ($(
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {$(
+            $(#[$field_attr:meta])*
+            $field_vis:vis $field:ident: $ty:ty = $bits:literal
+        ),+ $(,)?}
+    )+) => {$(
+        $(#[$attr])*
+        $vis struct $name {$(
+            $(#[$field_attr])*
+            $field_vis $field: $ty,
+        )+}
+
+        impl $name {
+            /// Total number of bits in all fields
+            pub const BITS: usize = 0 $(+ $bits)+;
+        }
+
+        impl $crate::ser::BitPack for $name {
+            #[inline]
+            fn pack<W>(&self, mut writer: W) -> ::core::result::Result<(), W::Error>
+            where
+                W: $crate::ser::BitWriter,
+            {
+                $(
+                    $crate::ResultExt::context(
+                        $crate::ser::BitWriterExt::pack_as::<_, &$crate::r#as::NBits<$bits>>(
+                            &mut writer,
+                            &self.$field,
+                        ),
+                        ::core::stringify!($field),
+                    )?;
+                )+
+                ::core::result::Result::Ok(())
+            }
+        }
+
+        impl $crate::de::BitUnpack for $name {
+            #[inline]
+            fn unpack<R>(mut reader: R) -> ::core::result::Result<Self, R::Error>
+            where
+                R: $crate::de::BitReader,
+            {
+                ::core::result::Result::Ok(Self {$(
+                    $field: $crate::ResultExt::context(
+                        $crate::de::BitReaderExt::unpack_as::<_, $crate::r#as::NBits<$bits>>(
+                            &mut reader,
+                        ),
+                        ::core::stringify!($field),
+                    )?,
+                )+})
+            }
+        }
+    )+};
+}
+
+#[cfg(test)]
+mod tests {
+    use bitvec::{bits, order::Msb0};
+
+    use crate::{
+        de::{unpack_fully, BitReaderExt},
+        ser::pack,
+        tests::assert_pack_unpack_eq,
+    };
+
+    bitfield! {
+        #[derive(Debug, PartialEq, Clone, Copy)]
+        struct Descriptor {
+            head: u8 = 3,
+            flag: bool = 1,
+            wide: u16 = 12,
+        }
+
+        #[derive(Debug, PartialEq)]
+        struct Single {
+            flag: bool = 1,
+        }
+    }
+
+    #[test]
+    fn bitfield_bits() {
+        assert_eq!(Descriptor::BITS, 16);
+        assert_eq!(Single::BITS, 1);
+    }
+
+    #[test]
+    fn bitfield_same_as_shifts() {
+        for (head, flag, wide) in [(0, false, 0), (0b111, true, 0xfff), (0b101, false, 0xabc)] {
+            let d = Descriptor { head, flag, wide };
+            let packed = pack(d).unwrap();
+            assert_eq!(
+                packed.as_bitslice().unpack::<u16>().unwrap(),
+                (head as u16) << 13 | (flag as u16) << 12 | wide,
+            );
+            assert_pack_unpack_eq(d);
+        }
+        assert_pack_unpack_eq(Single { flag: true });
+    }
+
+    #[test]
+    fn bitfield_errors() {
+        let err = pack(Descriptor {
+            head: 0,
+            flag: false,
+            wide: 0x1000,
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "wide: 0b1000000000000 cannot be packed into 12 bits"
+        );
+
+        let err = unpack_fully::<Descriptor>(bits![u8, Msb0; 1, 0, 1, 1, 0]).unwrap_err();
+        assert_eq!(err.to_string(), "wide: EOF");
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{
+    de::{r#as::BitUnpackAs, BitReader, BitReaderExt, BitUnpack},
+    r#as::AsWrap,
+    ser::{r#as::BitPackAs, BitPack, BitWriter, BitWriterExt},
+    ResultExt,
+};
+
+/// Implementation of `Both X Y`:
+/// ```tlb
+/// pair$_ {X:Type} {Y:Type} first:X second:Y = Both X Y;
+/// ```
+///
+/// Errors are wrapped into `first` and `second` contexts matching field
+/// names from TL-B schema.
+/// Since it mirrors TL-B combinator, `Both<AsX, AsY>` can also be used as
+/// an adapter for `Both<X, Y>`:
+/// ```rust
+/// # use tlbits::{
+/// #     de::{r#as::unpack_fully_as, unpack_fully},
+/// #     r#as::NBits,
+/// #     ser::{pack, r#as::pack_as},
+/// #     Both,
+/// # };
+/// # fn main() -> Result<(), tlbits::StringError> {
+/// let v = Both::new(0x12u8, 0x3u8);
+/// let packed = pack_as::<_, Both<NBits<8>, NBits<4>>>(v)?;
+/// assert_eq!(packed.len(), 12);
+/// let got: Both<u8, u8> = unpack_fully_as::<_, Both<NBits<8>, NBits<4>>>(&packed)?;
+/// assert_eq!(got, v);
+///
+/// let err = unpack_fully::<Both<u8, u8>>(&packed).unwrap_err();
+/// assert_eq!(err.to_string(), "second: EOF");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Both<X, Y> {
+    pub first: X,
+    pub second: Y,
+}
+
+impl<X, Y> Both<X, Y> {
+    #[inline]
+    pub const fn new(first: X, second: Y) -> Self {
+        Self { first, second }
+    }
+
+    /// Convert into a tuple
+    #[inline]
+    pub fn into_tuple(self) -> (X, Y) {
+        (self.first, self.second)
+    }
+}
+
+impl<X, Y> From<(X, Y)> for Both<X, Y> {
+    #[inline]
+    fn from((first, second): (X, Y)) -> Self {
+        Self::new(first, second)
+    }
+}
+
+impl<X, Y> From<Both<X, Y>> for (X, Y) {
+    #[inline]
+    fn from(both: Both<X, Y>) -> Self {
+        both.into_tuple()
+    }
+}
+
+impl<X, Y> BitPack for Both<X, Y>
+where
+    X: BitPack,
+    Y: BitPack,
+{
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            .pack(&self.first)
+            .context("first")?
+            .pack(&self.second)
+            .context("second")?;
+        Ok(())
+    }
+}
+
+impl<X, Y> BitUnpack for Both<X, Y>
+where
+    X: BitUnpack,
+    Y: BitUnpack,
+{
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(Self {
+            first: reader.unpack().context("first")?,
+            second: reader.unpack().context("second")?,
+        })
+    }
+}
+
+impl<X, Y, AsX, AsY> BitPackAs<Both<X, Y>> for Both<AsX, AsY>
+where
+    AsX: BitPackAs<X>,
+    AsY: BitPackAs<Y>,
+{
+    #[inline]
+    fn pack_as<W>(source: &Both<X, Y>, writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        Both::new(
+            AsWrap::<&X, AsX>::new(&source.first),
+            AsWrap::<&Y, AsY>::new(&source.second),
+        )
+        .pack(writer)
+    }
+}
+
+impl<X, Y, AsX, AsY> BitUnpackAs<Both<X, Y>> for Both<AsX, AsY>
+where
+    AsX: BitUnpackAs<X>,
+    AsY: BitUnpackAs<Y>,
+{
+    #[inline]
+    fn unpack_as<R>(reader: R) -> Result<Both<X, Y>, R::Error>
+    where
+        R: BitReader,
+    {
+        let Both { first, second } = Both::<AsWrap<X, AsX>, AsWrap<Y, AsY>>::unpack(reader)?;
+        Ok(Both::new(first.into_inner(), second.into_inner()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{r#as::NBits, tests::assert_pack_unpack_as_eq, tests::assert_pack_unpack_eq};
+
+    use super::*;
+
+    #[test]
+    fn both() {
+        assert_pack_unpack_eq(Both::new(true, 0x1234u16));
+        assert_pack_unpack_as_eq::<_, Both<NBits<3>, ()>>(Both::new(5u8, ()));
+    }
+}
@@ -1,4 +1,3 @@
-use core::mem::MaybeUninit;
 use std::{rc::Rc, sync::Arc};
 
 use bitvec::{order::Msb0, slice::BitSlice};
@@ -51,11 +50,11 @@ where
     where
         R: BitReader,
     {
-        let mut arr: [MaybeUninit<T>; N] = unsafe { MaybeUninit::uninit().assume_init() };
-        for a in &mut arr {
-            a.write(reader.unpack_as_with::<T, As>(args.clone())?);
-        }
-        Ok(unsafe { arr.as_ptr().cast::<[T; N]>().read() })
+        array_util::try_from_fn(|i| {
+            reader
+                .unpack_as_with::<T, As>(args.clone())
+                .with_context(|| format!("[{i}]"))
+        })
     }
 }
 
@@ -1,6 +1,5 @@
 pub mod r#as;
 
-use core::mem::MaybeUninit;
 use std::{rc::Rc, sync::Arc};
 
 use bitvec::{order::Msb0, vec::BitVec};
@@ -38,11 +37,9 @@ where
     where
         R: BitReader,
     {
-        let mut arr: [MaybeUninit<T>; N] = unsafe { MaybeUninit::uninit().assume_init() };
-        for (i, a) in arr.iter_mut().enumerate() {
-            a.write(T::unpack_with(&mut reader, args.clone()).with_context(|| format!("[{i}]"))?);
-        }
-        Ok(unsafe { arr.as_ptr().cast::<[T; N]>().read() })
+        array_util::try_from_fn(|i| {
+            T::unpack_with(&mut reader, args.clone()).with_context(|| format!("[{i}]"))
+        })
     }
 }
 
@@ -1,4 +1,3 @@
-use core::mem::MaybeUninit;
 use std::{rc::Rc, sync::Arc};
 
 use bitvec::{order::Msb0, slice::BitSlice, view::AsBits};
@@ -72,11 +71,11 @@ where
     where
         R: BitReader,
     {
-        let mut arr: [MaybeUninit<T>; N] = unsafe { MaybeUninit::uninit().assume_init() };
-        for a in &mut arr {
-            a.write(reader.unpack_as::<T, As>()?);
-        }
-        Ok(unsafe { arr.as_ptr().cast::<[T; N]>().read() })
+        array_util::try_from_fn(|i| {
+            reader
+                .unpack_as::<T, As>()
+                .with_context(|| format!("[{i}]"))
+        })
     }
 }
 
@@ -156,6 +155,20 @@ where
     }
 }
 
+/// Implementation of `Unit`:
+/// ```tlb
+/// unit$_ = Unit;
+/// ```
+impl BitUnpackAs<()> for () {
+    #[inline]
+    fn unpack_as<R>(_reader: R) -> Result<(), R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(())
+    }
+}
+
 /// Implementation of [`Either X Y`](https://docs.ton.org/develop/data-formats/tl-b-types#either):
 /// ```tlb
 /// left$0 {X:Type} {Y:Type} value:X = Either X Y;
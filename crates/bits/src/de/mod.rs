@@ -5,7 +5,6 @@ mod reader;
 
 pub use self::reader::*;
 
-use core::mem::MaybeUninit;
 use std::{rc::Rc, sync::Arc};
 
 use bitvec::{order::Msb0, slice::BitSlice, view::AsBits};
@@ -57,7 +56,6 @@ where
 }
 
 /// **De**serialize the value from bytes slice and ensure that no more data left.
-
 #[inline]
 pub fn unpack_bytes_fully<T>(bytes: impl AsRef<[u8]>) -> Result<T, StringError>
 where
@@ -66,6 +64,10 @@ where
     unpack_fully(bytes.as_bits())
 }
 
+/// Implementation of `Unit`:
+/// ```tlb
+/// unit$_ = Unit;
+/// ```
 impl BitUnpack for () {
     #[inline]
     fn unpack<R>(_reader: R) -> Result<Self, R::Error>
@@ -95,11 +97,7 @@ where
     where
         R: BitReader,
     {
-        let mut arr: [MaybeUninit<T>; N] = unsafe { MaybeUninit::uninit().assume_init() };
-        for (i, a) in arr.iter_mut().enumerate() {
-            a.write(T::unpack(&mut reader).with_context(|| format!("[{i}]"))?);
-        }
-        Ok(unsafe { arr.as_ptr().cast::<[T; N]>().read() })
+        array_util::try_from_fn(|i| T::unpack(&mut reader).with_context(|| format!("[{i}]")))
     }
 }
 
@@ -211,3 +209,45 @@ where
         reader.unpack_as::<_, Either<(), Same>>()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use bitvec::bits;
+
+    use super::*;
+
+    thread_local! {
+        static ALIVE: Cell<usize> = const { Cell::new(0) };
+    }
+
+    struct Counted;
+
+    impl BitUnpack for Counted {
+        fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+        where
+            R: BitReader,
+        {
+            let _: bool = reader.unpack()?;
+            ALIVE.set(ALIVE.get() + 1);
+            Ok(Self)
+        }
+    }
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            ALIVE.set(ALIVE.get() - 1);
+        }
+    }
+
+    #[test]
+    fn array_partial_failure_drops_unpacked() {
+        let err = bits![u8, Msb0; 0; 2]
+            .unpack::<[Counted; 4]>()
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "[2]: EOF");
+        assert_eq!(ALIVE.get(), 0);
+    }
+}
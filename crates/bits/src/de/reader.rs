@@ -4,7 +4,7 @@ use ::bitvec::{order::Msb0, slice::BitSlice, vec::BitVec, view::AsMutBits};
 use impl_tools::autoimpl;
 
 use crate::{
-    adapters::{MapErr, Tee},
+    adapters::{MapErr, Recorder, Tee},
     ser::BitWriter,
     Error, ResultExt, StringError,
 };
@@ -184,6 +184,16 @@ pub trait BitReaderExt: BitReader {
         MapErr { inner: self, f }
     }
 
+    /// Wrap this reader to record ranges of read bits by using
+    /// [`.gaps()`](Recorder::gaps).
+    #[inline]
+    fn recorded(self) -> Recorder<Self>
+    where
+        Self: Sized,
+    {
+        Recorder::new(self)
+    }
+
     /// Mirror all read data to given writer as well.
     #[inline]
     fn tee<W>(self, writer: W) -> Tee<Self, W>
@@ -249,6 +259,34 @@ where
     }
 }
 
+impl<R> BitReader for Recorder<R>
+where
+    R: BitReader,
+{
+    type Error = R::Error;
+
+    #[inline]
+    fn read_bit(&mut self) -> Result<bool, Self::Error> {
+        let bit = self.inner.read_bit()?;
+        self.record(1);
+        Ok(bit)
+    }
+
+    #[inline]
+    fn read_bits_into(&mut self, dst: &mut BitSlice<u8, Msb0>) -> Result<(), Self::Error> {
+        self.inner.read_bits_into(dst)?;
+        self.record(dst.len());
+        Ok(())
+    }
+
+    #[inline]
+    fn skip(&mut self, n: usize) -> Result<(), Self::Error> {
+        self.inner.skip(n)?;
+        self.position += n;
+        Ok(())
+    }
+}
+
 impl BitReader for &BitSlice<u8, Msb0> {
     type Error = StringError;
 
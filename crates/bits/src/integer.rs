@@ -1,5 +1,8 @@
 //! Collection of **de**/**ser**ialization helpers for integers
-use core::mem;
+use core::{
+    fmt::{self, Debug, Display},
+    mem,
+};
 
 use bitvec::{
     mem::bits_of,
@@ -9,7 +12,7 @@ use bitvec::{
 
 use crate::{
     de::{r#as::BitUnpackAs, BitReader, BitReaderExt, BitUnpack},
-    r#as::{AsBytes, NBits},
+    r#as::{AsBytes, NBits, Truncate},
     ser::{r#as::BitPackAs, BitPack, BitWriter, BitWriterExt},
     Error,
 };
@@ -119,16 +122,15 @@ macro_rules! impl_bit_serde_for_integers {
                 W: BitWriter,
             {
                 const BITS_SIZE: usize = bits_of::<$t>();
-                assert!(BITS <= BITS_SIZE, "excessive bits for type");
+                if BITS > BITS_SIZE {
+                    return Err(Error::custom("excessive bits for type"));
+                }
                 if BITS < BITS_SIZE - source.leading_zeros() as usize {
                     return Err(Error::custom(
                         format!("{source:#b} cannot be packed into {BITS} bits"),
                     ));
                 }
-                let bytes = source.to_be_bytes();
-                let mut bits = bytes.as_bits::<Msb0>();
-                bits = &bits[bits.len() - BITS..];
-                writer.write_bitslice(bits)?;
+                writer.pack_as::<_, &Truncate<NBits<BITS>>>(source)?;
                 Ok(())
             }
         }
@@ -140,12 +142,48 @@ macro_rules! impl_bit_serde_for_integers {
                 R: BitReader,
             {
                 const BITS_SIZE: usize = bits_of::<$t>();
-                assert!(BITS <= BITS_SIZE, "excessive bits for type");
+                if BITS > BITS_SIZE {
+                    return Err(Error::custom("excessive bits for type"));
+                }
                 let mut arr = [0u8; mem::size_of::<$t>()];
                 reader.read_bits_into(&mut arr.as_mut_bits()[BITS_SIZE - BITS..])?;
                 Ok($t::from_be_bytes(arr))
             }
         }
+
+        impl<const BITS: usize> BitPackAs<$t> for Truncate<NBits<BITS>> {
+            #[inline]
+            fn pack_as<W>(source: &$t, mut writer: W) -> Result<(), W::Error>
+            where
+                W: BitWriter,
+            {
+                const BITS_SIZE: usize = bits_of::<$t>();
+                let bytes = source.to_be_bytes();
+                let bits = bytes.as_bits::<Msb0>();
+                if BITS > BITS_SIZE {
+                    writer.repeat_bit(BITS - BITS_SIZE, false)?;
+                    writer.write_bitslice(bits)?;
+                } else {
+                    writer.write_bitslice(&bits[BITS_SIZE - BITS..])?;
+                }
+                Ok(())
+            }
+        }
+
+        impl<const BITS: usize> BitUnpackAs<$t> for Truncate<NBits<BITS>> {
+            #[inline]
+            fn unpack_as<R>(mut reader: R) -> Result<$t, R::Error>
+            where
+                R: BitReader,
+            {
+                const BITS_SIZE: usize = bits_of::<$t>();
+                if BITS > BITS_SIZE {
+                    reader.skip(BITS - BITS_SIZE)?;
+                    return reader.unpack();
+                }
+                reader.unpack_as::<_, NBits<BITS>>()
+            }
+        }
     )+};
 }
 impl_bit_serde_for_integers! {
@@ -153,8 +191,68 @@ impl_bit_serde_for_integers! {
     i8 i16 i32 i64 i128 isize
 }
 
+/// 32-bit tag, i.e. opcode or magic prefix, which is always displayed in
+/// canonical `0x%08x` format, so that it's easy to grep for in errors and
+/// logs:
+/// ```rust
+/// # use tlbits::integer::Tag32;
+/// assert_eq!(Tag32(0x0f8a7ea5).to_string(), "0x0f8a7ea5");
+/// assert_eq!(format!("{:?}", Tag32(0xd53276db)), "0xd53276db");
+/// assert_eq!(Tag32(1).to_string(), "0x00000001");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Tag32(pub u32);
+
+impl Display for Tag32 {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#010x}", self.0)
+    }
+}
+
+impl Debug for Tag32 {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl From<u32> for Tag32 {
+    #[inline]
+    fn from(tag: u32) -> Self {
+        Self(tag)
+    }
+}
+
+impl From<Tag32> for u32 {
+    #[inline]
+    fn from(tag: Tag32) -> Self {
+        tag.0
+    }
+}
+
+impl BitPack for Tag32 {
+    #[inline]
+    fn pack<W>(&self, writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        self.0.pack(writer)
+    }
+}
+
+impl BitUnpack for Tag32 {
+    #[inline]
+    fn unpack<R>(reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        u32::unpack(reader).map(Self)
+    }
+}
+
 macro_rules! const_uint {
-    ($($vis:vis $name:ident<$typ:tt, $bits:literal>)+) => {$(
+    ($($vis:vis $name:ident<$typ:tt, $bits:literal $(, $display:ident)?>)+) => {$(
         #[doc = concat!("Constant version of `", stringify!($typ), "`")]
         /// ## Deserialization
         #[doc = concat!(
@@ -233,13 +331,17 @@ macro_rules! const_uint {
                 let v = reader.unpack_as::<$typ, NBits<BITS>>()?;
                 if v != VALUE {
                     return Err(Error::custom(format!(
-                        "expected {VALUE:#b}, got: {v:#b}"
+                        "expected {}, got: {}",
+                        const_uint!(@display VALUE $(as $display)?),
+                        const_uint!(@display v $(as $display)?),
                     )));
                 }
                 Ok(Self)
             }
         }
     )+};
+    (@display $v:ident) => { format_args!("{:#b}", $v) };
+    (@display $v:ident as $display:ident) => { $display($v) };
 }
 
 const_uint! {
@@ -247,7 +349,7 @@ const_uint! {
     pub ConstI8  <i8,   8>
     pub ConstU16 <u16,  16>
     pub ConstI16 <i16,  16>
-    pub ConstU32 <u32,  32>
+    pub ConstU32 <u32,  32, Tag32>
     pub ConstI32 <i32,  32>
     pub ConstU64 <u64,  64>
     pub ConstI64 <i64,  64>
@@ -258,13 +360,22 @@ const_uint! {
 #[cfg(test)]
 mod tests {
     use bitvec::{bits, order::Msb0};
-    use num_bigint::BigUint;
+    #[cfg(feature = "bigint")]
+    use num_bigint::{BigInt, BigUint};
+    #[cfg(feature = "bigint")]
+    use num_traits::One;
 
     use crate::{
-        ser::{pack, r#as::pack_as},
+        de::args::r#as::unpack_as_with,
+        de::{r#as::unpack_fully_as, unpack_fully},
+        r#as::{Checked, VarNBits, VarNBytes},
+        ser::{args::r#as::pack_as_with, pack, r#as::pack_as},
         tests::{assert_pack_unpack_as_eq, assert_pack_unpack_eq},
     };
 
+    #[cfg(feature = "bigint")]
+    use crate::r#as::{Int257, VarInt};
+
     use super::*;
 
     #[test]
@@ -280,6 +391,27 @@ mod tests {
         assert_pack_unpack_eq(12345_u32);
     }
 
+    #[test]
+    fn serde_tag32() {
+        assert_pack_unpack_eq(Tag32(0x0f8a7ea5));
+        assert_eq!(
+            pack(Tag32(0x0f8a7ea5)).unwrap(),
+            pack(0x0f8a7ea5u32).unwrap()
+        );
+    }
+
+    #[test]
+    fn const_u32_error_format() {
+        let err = unpack_fully::<ConstU32<0x0f8a7ea5>>(pack(0x7362d09cu32).unwrap())
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "expected 0x0f8a7ea5, got: 0x7362d09c");
+        let err = unpack_fully::<ConstU8<0b101>>(pack(0b11u8).unwrap())
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "expected 0b101, got: 0b11");
+    }
+
     #[test]
     fn store_nbits_uint() {
         assert_eq!(
@@ -304,8 +436,254 @@ mod tests {
         assert_pack_unpack_as_eq::<u8, NBits<7>>(0x7E);
     }
 
+    #[cfg(feature = "bigint")]
     #[test]
     fn serde_big_nbits() {
         assert_pack_unpack_as_eq::<BigUint, NBits<100>>(12345_u64.into());
     }
+
+    fn check_nbits_u8<const BITS: usize>() {
+        for v in u8::MIN..=u8::MAX {
+            let fits = (v as u32) < 1 << BITS;
+            let packed = pack_as::<_, NBits<BITS>>(v);
+            assert_eq!(packed.is_ok(), fits, "{v} into {BITS} bits");
+            assert_eq!(
+                pack_as::<_, Checked<NBits<BITS>>>(v).ok(),
+                packed.as_ref().ok().cloned(),
+            );
+            if fits {
+                assert_pack_unpack_as_eq::<_, NBits<BITS>>(v);
+            }
+
+            let truncated = pack_as::<_, Truncate<NBits<BITS>>>(v).unwrap();
+            assert_eq!(truncated.len(), BITS);
+            let got: u8 = unpack_fully_as::<_, Truncate<NBits<BITS>>>(&truncated).unwrap();
+            assert_eq!(
+                got as u32,
+                v as u32 & ((1 << BITS) - 1),
+                "{v} into {BITS} bits"
+            );
+        }
+    }
+
+    fn check_nbits_i8<const BITS: usize>() {
+        for v in i8::MIN..=i8::MAX {
+            // signed integers are packed as their bit representation
+            let fits = BITS == 8 || (v >= 0 && (v as u32) < 1 << BITS);
+            let packed = pack_as::<_, NBits<BITS>>(v);
+            assert_eq!(packed.is_ok(), fits, "{v} into {BITS} bits");
+            if fits {
+                assert_pack_unpack_as_eq::<_, NBits<BITS>>(v);
+            }
+
+            let truncated = pack_as::<_, Truncate<NBits<BITS>>>(v).unwrap();
+            assert_eq!(
+                truncated,
+                pack_as::<_, Truncate<NBits<BITS>>>(v as u8).unwrap()
+            );
+            let got: i8 = unpack_fully_as::<_, Truncate<NBits<BITS>>>(&truncated).unwrap();
+            assert_eq!(got as u8 as u32, v as u8 as u32 & ((1 << BITS) - 1));
+        }
+    }
+
+    #[cfg(feature = "bigint")]
+    fn check_nbits_big_int<const BITS: usize>() {
+        for v in -300i32..=300 {
+            // two's complement
+            let fits = BITS > 0 && v >= -(1 << (BITS - 1)) && v < 1 << (BITS - 1) || v == 0;
+            let packed = pack_as::<BigInt, NBits<BITS>>(v.into());
+            assert_eq!(packed.is_ok(), fits, "{v} into {BITS} bits");
+            if fits {
+                assert_pack_unpack_as_eq::<BigInt, NBits<BITS>>(v.into());
+            }
+
+            let truncated = pack_as::<BigInt, Truncate<NBits<BITS>>>(v.into()).unwrap();
+            assert_eq!(
+                truncated,
+                pack_as::<_, Truncate<NBits<BITS>>>(v as u32).unwrap()
+            );
+            // sign-extended lower bits
+            let expected = if BITS == 0 {
+                0
+            } else {
+                (v << (32 - BITS)) >> (32 - BITS)
+            };
+            let got: BigInt = unpack_fully_as::<_, Truncate<NBits<BITS>>>(&truncated).unwrap();
+            assert_eq!(got, expected.into(), "{v} into {BITS} bits");
+        }
+    }
+
+    #[test]
+    fn nbits_boundaries() {
+        check_nbits_u8::<0>();
+        check_nbits_u8::<1>();
+        check_nbits_u8::<2>();
+        check_nbits_u8::<3>();
+        check_nbits_u8::<4>();
+        check_nbits_u8::<5>();
+        check_nbits_u8::<6>();
+        check_nbits_u8::<7>();
+        check_nbits_u8::<8>();
+
+        check_nbits_i8::<0>();
+        check_nbits_i8::<1>();
+        check_nbits_i8::<2>();
+        check_nbits_i8::<3>();
+        check_nbits_i8::<4>();
+        check_nbits_i8::<5>();
+        check_nbits_i8::<6>();
+        check_nbits_i8::<7>();
+        check_nbits_i8::<8>();
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn nbits_big_int() {
+        check_nbits_big_int::<0>();
+        check_nbits_big_int::<1>();
+        check_nbits_big_int::<2>();
+        check_nbits_big_int::<7>();
+        check_nbits_big_int::<8>();
+        check_nbits_big_int::<9>();
+        check_nbits_big_int::<10>();
+    }
+
+    #[test]
+    fn nbits_excessive_bits() {
+        assert!(pack_as::<_, NBits<9>>(1u8).is_err());
+        assert!(unpack_fully_as::<u8, NBits<9>>(bits![u8, Msb0; 0; 9]).is_err());
+
+        assert_eq!(
+            pack_as::<_, Truncate<NBits<12>>>(0xFFu8).unwrap(),
+            pack_as::<_, NBits<12>>(0xFFu16).unwrap(),
+        );
+        assert_eq!(
+            unpack_fully_as::<u8, Truncate<NBits<12>>>(bits![u8, Msb0; 1; 12]).unwrap(),
+            0xFF
+        );
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn big_nbits_boundaries() {
+        let max: BigUint = (BigUint::one() << 256) - 1u32;
+        assert_pack_unpack_as_eq::<_, NBits<256>>(max.clone());
+        assert!(pack_as::<_, NBits<256>>(&max + 1u32).is_err());
+        assert_eq!(
+            pack_as::<_, Truncate<NBits<256>>>(&max + 1u32).unwrap(),
+            pack_as::<_, NBits<256>>(BigUint::ZERO).unwrap(),
+        );
+
+        let min = -(BigInt::one() << 256usize);
+        assert_pack_unpack_as_eq::<_, NBits<257>>(min.clone());
+        assert_pack_unpack_as_eq::<_, NBits<257>>(-&min - 1);
+        assert!(pack_as::<_, NBits<257>>(&min - 1).is_err());
+        assert!(pack_as::<_, NBits<257>>(-&min).is_err());
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn int257_boundaries() {
+        for v in [
+            Int257::min(),
+            Int257::min() + 1,
+            BigInt::from(-1),
+            BigInt::ZERO,
+            BigInt::one(),
+            Int257::max() - 1,
+            Int257::max(),
+        ] {
+            assert!(Int257::contains(&v));
+            assert_pack_unpack_as_eq::<_, Int257>(v.clone());
+            assert_eq!(
+                pack_as::<_, &Int257>(&v).unwrap(),
+                pack_as::<_, &NBits<257>>(&v).unwrap(),
+            );
+        }
+        for v in [Int257::min() - 1, Int257::max() + 1, BigInt::one() << 300] {
+            assert!(!Int257::contains(&v));
+            assert!(pack_as::<_, &Int257>(&v).is_err());
+        }
+
+        assert_eq!(
+            unpack_fully_as::<BigInt, Int257>(pack_as::<_, NBits<257>>(Int257::min()).unwrap())
+                .unwrap(),
+            Int257::min(),
+        );
+        assert!(unpack_fully_as::<BigInt, Int257>(bits![u8, Msb0; 1; 256]).is_err());
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn var_int_prim_same_as_big() {
+        for v in [0, 1, 0xFF, 0x100, 1_000_000_000, u64::MAX] {
+            let big = pack_as::<BigUint, VarInt<4>>(v.into()).unwrap();
+            assert_eq!(pack_as::<_, VarInt<4>>(v).unwrap(), big);
+            assert_eq!(pack_as::<_, VarInt<4>>(v as u128).unwrap(), big);
+            assert_pack_unpack_as_eq::<_, VarInt<4>>(v);
+            assert_pack_unpack_as_eq::<_, VarInt<4>>(v as u128);
+        }
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn var_int_prim_overflow() {
+        // length does not fit into 4 bits
+        assert!(pack_as::<_, VarInt<4>>(u128::MAX).is_err());
+        assert_pack_unpack_as_eq::<_, VarInt<5>>(u128::MAX);
+
+        let packed = pack_as::<BigUint, VarInt<4>>(BigUint::from(u64::MAX) + 1u32).unwrap();
+        assert!(unpack_fully_as::<u64, VarInt<4>>(&packed).is_err());
+        assert_eq!(
+            unpack_fully_as::<u128, VarInt<4>>(&packed).unwrap(),
+            u64::MAX as u128 + 1
+        );
+    }
+
+    #[test]
+    fn var_n_bits_signed() {
+        for (v, num_bits) in [(0_i32, 0), (-1, 1), (1, 2), (-4, 3), (3, 3), (i32::MIN, 32)] {
+            let packed = pack_as_with::<_, VarNBits>(v, num_bits).unwrap();
+            assert_eq!(packed.len(), num_bits as usize);
+            assert_eq!(
+                unpack_as_with::<i32, VarNBits>(&packed, num_bits).unwrap(),
+                v
+            );
+            // sign bit must fit as well
+            if num_bits > 0 {
+                assert!(pack_as_with::<_, VarNBits>(v, num_bits - 1).is_err());
+            }
+        }
+        assert!(pack_as_with::<_, VarNBits>(0_i8, 9).is_err());
+        // unsigned values do not need sign bit
+        assert_eq!(pack_as_with::<_, VarNBits>(3_u32, 2).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn var_n_bytes_signed() {
+        for (v, num_bytes) in [(0_i64, 0), (-1, 1), (-128, 1), (128, 2), (i64::MAX, 8)] {
+            let packed = pack_as_with::<_, VarNBytes>(v, num_bytes).unwrap();
+            assert_eq!(packed.len(), num_bytes as usize * 8);
+            assert_eq!(
+                unpack_as_with::<i64, VarNBytes>(&packed, num_bytes).unwrap(),
+                v
+            );
+            if num_bytes > 0 {
+                assert!(pack_as_with::<_, VarNBytes>(v, num_bytes - 1).is_err());
+            }
+        }
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn var_int_signed_same_as_big() {
+        for v in [-1_i64, 1, -128, 127, 128, -129, i64::MIN, i64::MAX] {
+            let big = pack_as::<BigInt, VarInt<4>>(v.into()).unwrap();
+            assert_eq!(pack_as::<_, VarInt<4>>(v).unwrap(), big);
+            assert_eq!(pack_as::<_, VarInt<4>>(v as i128).unwrap(), big);
+            assert_pack_unpack_as_eq::<_, VarInt<4>>(v);
+            assert_pack_unpack_as_eq::<_, VarInt<4>>(v as i128);
+        }
+        assert_eq!(pack_as::<_, VarInt<4>>(0_i32).unwrap().len(), 4);
+    }
 }
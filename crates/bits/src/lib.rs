@@ -10,10 +10,9 @@
 //! Let's first define a struct `Hello` that holds these parameters:
 //!
 //! ```rust
-//! # use num_bigint::BigUint;
 //! struct Hello {
 //!     pub query_id: u64,
-//!     pub amount: BigUint,
+//!     pub amount: u128,
 //! }
 //! ```
 //!
@@ -24,7 +23,6 @@
 //!
 //! ```
 //! # use bitvec::{vec::BitVec, order::Msb0};
-//! # use num_bigint::BigUint;
 //! # use tlbits::{
 //! #   r#as::{NBits, VarInt},
 //! #   ser::{BitPack, BitWriter, BitWriterExt, pack},
@@ -33,7 +31,7 @@
 //! #
 //! # struct Hello {
 //! #     pub query_id: u64,
-//! #     pub amount: BigUint,
+//! #     pub amount: u128,
 //! # }
 //! impl BitPack for Hello {
 //!     fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
@@ -54,7 +52,7 @@
 //! # let mut writer = BitVec::<u8, Msb0>::new().counted();
 //! writer.pack(Hello {
 //!     query_id: 0,
-//!     amount: 1_000u64.into(),
+//!     amount: 1_000,
 //! })?;
 //! # Ok(())
 //! # }
@@ -67,7 +65,6 @@
 //!
 //! ```rust
 //! # use bitvec::{vec::BitVec, order::Msb0};
-//! # use num_bigint::BigUint;
 //! # use tlbits::{
 //! #   r#as::{NBits, VarInt},
 //! #   de::{BitReaderExt, BitReader, BitUnpack},
@@ -78,7 +75,7 @@
 //! # #[derive(Debug, PartialEq)]
 //! # struct Hello {
 //! #     pub query_id: u64,
-//! #     pub amount: BigUint,
+//! #     pub amount: u128,
 //! # }
 //! # impl BitPack for Hello {
 //! #     fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
@@ -115,7 +112,7 @@
 //! # fn main() -> Result<(), StringError> {
 //! # let orig = Hello {
 //! #     query_id: 0,
-//! #     amount: 1_000u64.into(),
+//! #     amount: 1_000,
 //! # };
 //! # let mut writer = BitVec::<u8, Msb0>::new().counted();
 //! # writer.pack(&orig)?;
@@ -127,12 +124,15 @@
 //! ```
 pub mod adapters;
 pub mod r#as;
+mod bitfield;
+mod both;
 pub mod de;
 mod error;
 pub mod integer;
+pub mod prelude;
 pub mod ser;
 
-pub use self::error::*;
+pub use self::{both::*, error::*};
 
 pub use bitvec;
 pub use either;
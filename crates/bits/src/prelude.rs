@@ -0,0 +1,32 @@
+//! Commonly used items of [`bitvec`](crate::bitvec), which is re-exported
+//! by this crate, so there is no need to depend on it directly:
+//! ```rust
+//! # use tlbits::{prelude::*, ser::BitWriterExt, de::BitReaderExt};
+//! # fn main() -> Result<(), tlbits::StringError> {
+//! let mut writer: BitVec<u8, Msb0> = BitVec::new();
+//! writer.pack(0xcafe_u16)?;
+//! assert_eq!(writer, bits![u8, Msb0; 1,1,0,0, 1,0,1,0, 1,1,1,1, 1,1,1,0]);
+//!
+//! let mut reader: &BitSlice<u8, Msb0> = [0xca, 0xfe].as_bits();
+//! assert_eq!(reader.unpack::<u16>()?, 0xcafe);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Traits of this crate are implemented for types of `bitvec` **1.x**.
+//! If you still depend on `bitvec` directly, make sure to use a
+//! semver-compatible version (i.e. `bitvec = "1"`), so that cargo unifies
+//! it with the one used here. Otherwise, types like `BitVec<u8, Msb0>`
+//! would come from two different crates and won't implement
+//! [`BitWriter`](crate::ser::BitWriter) and
+//! [`BitReader`](crate::de::BitReader).
+pub use bitvec::{
+    array::BitArray,
+    bitarr, bits, bitvec,
+    field::BitField,
+    order::{BitOrder, Lsb0, Msb0},
+    slice::BitSlice,
+    store::BitStore,
+    vec::BitVec,
+    view::{AsBits, AsMutBits, BitView},
+};
@@ -159,6 +159,20 @@ where
     }
 }
 
+/// Implementation of `Unit`:
+/// ```tlb
+/// unit$_ = Unit;
+/// ```
+impl BitPackAs<()> for () {
+    #[inline]
+    fn pack_as<W>(_source: &(), _writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        Ok(())
+    }
+}
+
 /// Implementation of [`Either X Y`](https://docs.ton.org/develop/data-formats/tl-b-types#either):
 /// ```tlb
 /// left$0 {X:Type} {Y:Type} value:X = Either X Y;
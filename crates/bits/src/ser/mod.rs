@@ -49,6 +49,10 @@ where
     Ok(writer)
 }
 
+/// Implementation of `Unit`:
+/// ```tlb
+/// unit$_ = Unit;
+/// ```
 impl BitPack for () {
     #[inline]
     fn pack<W>(&self, _writer: W) -> Result<(), W::Error>
@@ -65,7 +69,7 @@ impl BitPack for bool {
     where
         W: BitWriter,
     {
-        writer.write_bit(*self).map_err(Into::into)
+        writer.write_bit(*self)
     }
 }
 
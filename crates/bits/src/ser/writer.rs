@@ -263,6 +263,12 @@ where
         }
     }
 
+    /// Maximum number of bits allowed to be written
+    #[inline]
+    pub const fn limit(&self) -> usize {
+        self.limit
+    }
+
     #[inline]
     fn ensure_more(&self, n: usize) -> Result<(), W::Error> {
         if self.bit_count() + n > self.limit {
@@ -277,6 +283,20 @@ where
     }
 }
 
+impl<S> LimitWriter<BitVec<S, Msb0>>
+where
+    S: BitStore,
+{
+    /// Discard bits written after first `len` ones, so that they can be
+    /// written again within the limit.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        let discarded = self.inner.inner.len().saturating_sub(len);
+        self.inner.inner.truncate(len);
+        self.inner.counter -= discarded.min(self.inner.counter);
+    }
+}
+
 impl<W> BitWriter for LimitWriter<W>
 where
     W: BitWriter,
@@ -371,3 +391,97 @@ impl BitWriter for String {
         Ok(())
     }
 }
+
+/// [`BitWriter`] over a fixed-size byte buffer, which does not require
+/// heap allocations, except for errors.
+///
+/// Bits are written starting from the most significant bit of the first
+/// byte. Writing more bits than the buffer can hold results in an error.
+/// ```rust
+/// # use tlbits::{
+/// #     r#as::NBits,
+/// #     ser::{BitWriterExt, SliceWriter},
+/// # };
+/// let mut buf = [0u8; 2];
+/// let mut writer = SliceWriter::new(&mut buf);
+/// writer.pack(0xAB_u8).unwrap().pack_as::<_, NBits<4>>(0xC_u8).unwrap();
+/// assert_eq!(writer.capacity_left(), 4);
+/// assert!(writer.pack(0_u8).is_err());
+///
+/// assert_eq!(writer.finish(), 12);
+/// assert_eq!(buf, [0xAB, 0xC0]);
+/// ```
+pub struct SliceWriter<'a> {
+    buf: &'a mut BitSlice<u8, Msb0>,
+    used: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    /// Create writer over given buffer
+    #[inline]
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self {
+            buf: BitSlice::from_slice_mut(buf),
+            used: 0,
+        }
+    }
+
+    /// Total number of bits the buffer can hold
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Number of bits that can still be written
+    #[inline]
+    pub fn capacity_left(&self) -> usize {
+        self.capacity() - self.used
+    }
+
+    /// Bits written so far
+    #[inline]
+    pub fn as_bitslice(&self) -> &BitSlice<u8, Msb0> {
+        &self.buf[..self.used]
+    }
+
+    /// Release the buffer and return the number of written bits
+    #[inline]
+    pub fn finish(self) -> usize {
+        self.used
+    }
+
+    #[inline]
+    fn next_mut(&mut self, n: usize) -> Result<&mut BitSlice<u8, Msb0>, StringError> {
+        if n > self.capacity_left() {
+            return Err(Error::custom(format!(
+                "not enough capacity: {n} bits requested, {} bits left",
+                self.capacity_left()
+            )));
+        }
+        let bits = &mut self.buf[self.used..self.used + n];
+        self.used += n;
+        Ok(bits)
+    }
+}
+
+impl BitWriter for SliceWriter<'_> {
+    type Error = StringError;
+
+    #[inline]
+    fn write_bit(&mut self, bit: bool) -> Result<(), Self::Error> {
+        self.next_mut(1)?.set(0, bit);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_bitslice(&mut self, bits: &BitSlice<u8, Msb0>) -> Result<(), Self::Error> {
+        self.next_mut(bits.len())?.copy_from_bitslice(bits);
+        Ok(())
+    }
+
+    #[inline]
+    fn repeat_bit(&mut self, n: usize, bit: bool) -> Result<(), Self::Error> {
+        self.next_mut(n)?.fill(bit);
+        Ok(())
+    }
+}
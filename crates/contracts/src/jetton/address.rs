@@ -0,0 +1,294 @@
+use std::sync::Arc;
+
+use num_bigint::BigUint;
+use tlb::{
+    bits::{de::BitReaderExt, r#as::NBits, ser::BitWriterExt},
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::Ref,
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Cell, ResultExt,
+};
+use tlb_ton::{currency::Coins, state_init::StateInit, MsgAddress};
+
+/// Layout of jetton wallet data, which determines the address of the jetton
+/// wallet for given owner, since implementations differ in it:
+/// ```rust
+/// # use std::sync::Arc;
+/// # use tlb::Cell;
+/// # use tlb_ton::MsgAddress;
+/// # use ton_contracts::jetton::JettonWalletKind;
+/// # let code = Arc::new(Cell::new());
+/// let master: MsgAddress = "EQAWezezpqKTbO6xjCussXDdIeJ7XxTcErjA6uD3T3r7AwTk".parse().unwrap();
+/// let owner = MsgAddress { workchain_id: 0, address: [1; 32] };
+///
+/// // code of jetton wallet as returned by `get_jetton_data` of the minter
+/// let wallet = JettonWalletKind::Standard
+///     .wallet_address(master, owner, code.clone())
+///     .unwrap();
+/// assert_ne!(
+///     wallet,
+///     JettonWalletKind::Governed.wallet_address(master, owner, code).unwrap(),
+/// );
+/// ```
+///
+/// Note that some jettons deploy wallets with code referred to by
+/// `LibraryReference` cell, which can not be represented by [`Cell`], so
+/// [`get_wallet_address`](https://github.com/ton-blockchain/TEPs/blob/master/text/0074-jettons-standard.md#get-methods)
+/// of the minter should be used for them instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JettonWalletKind {
+    /// [TEP-74](https://github.com/ton-blockchain/token-contract/blob/main/ft/jetton-wallet.fc)
+    /// reference implementation, see [`JettonWalletData`]
+    Standard,
+    /// [Governed](https://github.com/ton-blockchain/stablecoin-contract/blob/main/contracts/jetton-wallet.fc)
+    /// jettons, i.e. stablecoins, see [`GovernedJettonWalletData`]
+    Governed,
+}
+
+impl JettonWalletKind {
+    /// Initial data of jetton wallet of `owner` for jetton `master`
+    pub fn init_data(
+        self,
+        master: MsgAddress,
+        owner: MsgAddress,
+        code: Arc<Cell>,
+    ) -> Result<Cell, CellBuilderError> {
+        let mut builder = Cell::builder();
+        match self {
+            Self::Standard => builder.store(JettonWalletData {
+                balance: BigUint::ZERO,
+                owner,
+                master,
+                wallet_code: code,
+            })?,
+            Self::Governed => builder.store(GovernedJettonWalletData {
+                status: 0,
+                balance: BigUint::ZERO,
+                owner,
+                master,
+            })?,
+        };
+        Ok(builder.into_cell())
+    }
+
+    /// Derive address of jetton wallet of `owner` for jetton `master` from
+    /// `code` of jetton wallet. Jetton wallets are deployed in basechain.
+    pub fn wallet_address(
+        self,
+        master: MsgAddress,
+        owner: MsgAddress,
+        code: Arc<Cell>,
+    ) -> Result<MsgAddress, CellBuilderError> {
+        let data = self.init_data(master, owner, code.clone())?;
+        MsgAddress::derive(
+            0,
+            StateInit::<_, _> {
+                code: Some(code),
+                data: Some(data),
+                ..Default::default()
+            }
+            .normalize()?,
+        )
+    }
+}
+
+/// Data of [`Standard`](JettonWalletKind::Standard) jetton wallet
+/// ```tlb
+/// _ balance:Coins owner_address:MsgAddress jetton_master_address:MsgAddress
+/// jetton_wallet_code:^Cell = JettonWalletData;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JettonWalletData {
+    pub balance: BigUint,
+    pub owner: MsgAddress,
+    pub master: MsgAddress,
+    pub wallet_code: Arc<Cell>,
+}
+
+impl CellSerialize for JettonWalletData {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // balance:Coins
+            .pack_as::<_, &Coins>(&self.balance)?
+            // owner_address:MsgAddress
+            .pack(self.owner)?
+            // jetton_master_address:MsgAddress
+            .pack(self.master)?
+            // jetton_wallet_code:^Cell
+            .store_as::<_, Ref>(&self.wallet_code)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for JettonWalletData {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            // balance:Coins
+            balance: parser.unpack_as::<_, Coins>().context("balance")?,
+            // owner_address:MsgAddress
+            owner: parser.unpack().context("owner_address")?,
+            // jetton_master_address:MsgAddress
+            master: parser.unpack().context("jetton_master_address")?,
+            // jetton_wallet_code:^Cell
+            wallet_code: parser.parse_as::<_, Ref>().context("jetton_wallet_code")?,
+        })
+    }
+}
+
+/// Data of [`Governed`](JettonWalletKind::Governed) jetton wallet, which
+/// does not store its code:
+/// ```tlb
+/// _ status:uint4 balance:Coins owner_address:MsgAddress
+/// jetton_master_address:MsgAddress = GovernedJettonWalletData;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GovernedJettonWalletData {
+    /// Lock status set by the admin of the jetton, `0` for new wallets
+    pub status: u8,
+    pub balance: BigUint,
+    pub owner: MsgAddress,
+    pub master: MsgAddress,
+}
+
+impl CellSerialize for GovernedJettonWalletData {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // status:uint4
+            .pack_as::<_, NBits<4>>(self.status)?
+            // balance:Coins
+            .pack_as::<_, &Coins>(&self.balance)?
+            // owner_address:MsgAddress
+            .pack(self.owner)?
+            // jetton_master_address:MsgAddress
+            .pack(self.master)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for GovernedJettonWalletData {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            // status:uint4
+            status: parser.unpack_as::<_, NBits<4>>().context("status")?,
+            // balance:Coins
+            balance: parser.unpack_as::<_, Coins>().context("balance")?,
+            // owner_address:MsgAddress
+            owner: parser.unpack().context("owner_address")?,
+            // jetton_master_address:MsgAddress
+            master: parser.unpack().context("jetton_master_address")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MASTER: MsgAddress = MsgAddress {
+        workchain_id: 0,
+        address: [1; 32],
+    };
+    const OWNER: MsgAddress = MsgAddress {
+        workchain_id: 0,
+        address: [2; 32],
+    };
+
+    fn code() -> Arc<Cell> {
+        Arc::new(Cell::from_hex_data("deadbeef", 32).unwrap())
+    }
+
+    #[test]
+    fn init_data_layout() {
+        let data = JettonWalletKind::Standard
+            .init_data(MASTER, OWNER, code())
+            .unwrap();
+        // zero balance takes 4 bits, each address takes 267 bits
+        assert_eq!(data.data.len(), 4 + 267 + 267);
+        assert_eq!(data.references, [code()]);
+        assert_eq!(
+            data.parse_fully::<JettonWalletData>().unwrap(),
+            JettonWalletData {
+                balance: BigUint::ZERO,
+                owner: OWNER,
+                master: MASTER,
+                wallet_code: code(),
+            }
+        );
+
+        let data = JettonWalletKind::Governed
+            .init_data(MASTER, OWNER, code())
+            .unwrap();
+        assert_eq!(data.data.len(), 4 + 4 + 267 + 267);
+        assert!(data.references.is_empty());
+        assert_eq!(
+            data.parse_fully::<GovernedJettonWalletData>()
+                .unwrap()
+                .owner,
+            OWNER
+        );
+    }
+
+    /// Address of contract with given `code` and `data`, built bit by bit
+    /// rather than with [`StateInit`]
+    fn state_init_address(code: Arc<Cell>, data: Cell) -> MsgAddress {
+        let mut state_init = Cell::builder();
+        state_init
+            // split_depth:(Maybe (## 5)) special:(Maybe TickTock)
+            // code:(Maybe ^Cell) data:(Maybe ^Cell)
+            // library:(HashmapE 256 SimpleLib)
+            .pack_as::<_, NBits<5>>(0b00110u8)
+            .unwrap()
+            .store_reference(code)
+            .unwrap()
+            .store_as::<_, Ref>(data)
+            .unwrap();
+        MsgAddress {
+            workchain_id: 0,
+            address: state_init.into_cell().hash(),
+        }
+    }
+
+    #[test]
+    fn wallet_address() {
+        // balance:Coins with zero balance is 4 zero bits
+        let mut data = Cell::builder();
+        data.pack_as::<_, NBits<4>>(0u8)
+            .unwrap()
+            .pack(OWNER)
+            .unwrap()
+            .pack(MASTER)
+            .unwrap()
+            .store_reference(code())
+            .unwrap();
+        assert_eq!(
+            JettonWalletKind::Standard
+                .wallet_address(MASTER, OWNER, code())
+                .unwrap(),
+            state_init_address(code(), data.into_cell()),
+        );
+
+        // status:uint4 followed by zero balance:Coins
+        let mut data = Cell::builder();
+        data.pack_as::<_, NBits<8>>(0u8)
+            .unwrap()
+            .pack(OWNER)
+            .unwrap()
+            .pack(MASTER)
+            .unwrap();
+        assert_eq!(
+            JettonWalletKind::Governed
+                .wallet_address(MASTER, OWNER, code())
+                .unwrap(),
+            state_init_address(code(), data.into_cell()),
+        );
+
+        assert_ne!(
+            JettonWalletKind::Standard
+                .wallet_address(MASTER, OWNER, code())
+                .unwrap(),
+            JettonWalletKind::Standard
+                .wallet_address(MASTER, MASTER, code())
+                .unwrap(),
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use tlb::{
+    bits::{de::BitReaderExt, integer::ConstU32, ser::BitWriterExt},
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::{Data, MaybeRef},
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+};
+use tlb_ton::MsgAddress;
+
+use crate::opcodes::{JETTON_PROVIDE_WALLET_ADDRESS, JETTON_TAKE_WALLET_ADDRESS};
+
+/// Request to jetton minter for the address of jetton wallet from
+/// [TEP-89](https://github.com/ton-blockchain/TEPs/blob/master/text/0089-jetton-wallet-discovery.md#scheme)
+/// ```tlb
+/// provide_wallet_address#2c76b973 query_id:uint64 owner_address:MsgAddress
+/// include_address:Bool = InternalMsgBody;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JettonProvideWalletAddress {
+    pub query_id: u64,
+    pub owner: MsgAddress,
+    /// Whether to include `owner` in the response
+    pub include_address: bool,
+}
+
+impl CellSerialize for JettonProvideWalletAddress {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // provide_wallet_address#2c76b973
+            .pack(JETTON_PROVIDE_WALLET_ADDRESS)?
+            // query_id:uint64
+            .pack(self.query_id)?
+            // owner_address:MsgAddress
+            .pack(self.owner)?
+            // include_address:Bool
+            .pack(self.include_address)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for JettonProvideWalletAddress {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        // provide_wallet_address#2c76b973
+        parser.unpack::<ConstU32<JETTON_PROVIDE_WALLET_ADDRESS>>()?;
+        Ok(Self {
+            // query_id:uint64
+            query_id: parser.unpack()?,
+            // owner_address:MsgAddress
+            owner: parser.unpack()?,
+            // include_address:Bool
+            include_address: parser.unpack()?,
+        })
+    }
+}
+
+/// Response from jetton minter to [`JettonProvideWalletAddress`] from
+/// [TEP-89](https://github.com/ton-blockchain/TEPs/blob/master/text/0089-jetton-wallet-discovery.md#scheme)
+/// ```tlb
+/// take_wallet_address#d1735400 query_id:uint64 wallet_address:MsgAddress
+/// owner_address:(Maybe ^MsgAddress) = InternalMsgBody;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JettonTakeWalletAddress {
+    pub query_id: u64,
+    /// [`NULL`](MsgAddress::NULL) if the owner can not have a jetton wallet
+    /// in the same workchain
+    pub wallet: MsgAddress,
+    /// Present if [`include_address`](JettonProvideWalletAddress::include_address)
+    /// was requested
+    pub owner: Option<MsgAddress>,
+}
+
+impl CellSerialize for JettonTakeWalletAddress {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // take_wallet_address#d1735400
+            .pack(JETTON_TAKE_WALLET_ADDRESS)?
+            // query_id:uint64
+            .pack(self.query_id)?
+            // wallet_address:MsgAddress
+            .pack(self.wallet)?
+            // owner_address:(Maybe ^MsgAddress)
+            .store_as::<_, MaybeRef<Data>>(self.owner)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for JettonTakeWalletAddress {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        // take_wallet_address#d1735400
+        parser.unpack::<ConstU32<JETTON_TAKE_WALLET_ADDRESS>>()?;
+        Ok(Self {
+            // query_id:uint64
+            query_id: parser.unpack()?,
+            // wallet_address:MsgAddress
+            wallet: parser.unpack()?,
+            // owner_address:(Maybe ^MsgAddress)
+            owner: parser.parse_as::<_, MaybeRef<Data>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::ser::CellSerializeExt;
+
+    use super::*;
+
+    #[test]
+    fn provide_take_wallet_address() {
+        let owner = MsgAddress {
+            workchain_id: 0,
+            address: [1; 32],
+        };
+        let provide = JettonProvideWalletAddress {
+            query_id: 7,
+            owner,
+            include_address: true,
+        };
+        let cell = provide.to_cell().unwrap();
+        assert_eq!(cell.data.len(), 32 + 64 + 267 + 1);
+        assert_eq!(
+            cell.parse_fully::<JettonProvideWalletAddress>().unwrap(),
+            provide
+        );
+
+        for owner in [None, Some(owner)] {
+            let take = JettonTakeWalletAddress {
+                query_id: 7,
+                wallet: MsgAddress {
+                    workchain_id: 0,
+                    address: [2; 32],
+                },
+                owner,
+            };
+            let cell = take.to_cell().unwrap();
+            assert_eq!(cell.references.len(), usize::from(owner.is_some()));
+            assert_eq!(cell.parse_fully::<JettonTakeWalletAddress>().unwrap(), take);
+        }
+    }
+}
@@ -1,4 +1,7 @@
 //! Jetton standard [TEP-74](https://github.com/ton-blockchain/TEPs/blob/b7fffeb8d20006e2d47149c3a20cf2e4fac3269c/text/0074-jettons-standard.md)
+mod address;
+mod minter;
+mod payload;
 mod wallet;
 
-pub use self::wallet::*;
+pub use self::{address::*, minter::*, payload::*, wallet::*};
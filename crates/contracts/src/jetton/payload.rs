@@ -0,0 +1,151 @@
+use tlb::{
+    bits::{de::BitReaderExt, ser::BitWriterExt},
+    de::{CellDeserialize, CellParser, CellParserError},
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Cell, Error,
+};
+
+use crate::opcodes::TEXT_COMMENT;
+
+/// Payload forwarded to the receiver, i.e. `forward_payload` in
+/// [`JettonTransfer`](super::JettonTransfer) and
+/// [`JettonTransferNotification`](super::JettonTransferNotification).
+///
+/// This type only **de**/**ser**ializes the contents of the payload, so use
+/// [`EitherInlineOrRef`](tlb::r#as::EitherInlineOrRef) to store it as
+/// `Either X ^X`, which keeps it inline when it fits into the current cell
+/// and puts it into a reference otherwise:
+/// ```rust
+/// # use tlb::{r#as::EitherInlineOrRef, Cell};
+/// # use ton_contracts::jetton::ForwardPayload;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let payload = ForwardPayload::Comment("hello".to_string());
+///
+/// let mut builder = Cell::builder();
+/// builder.store_as::<_, EitherInlineOrRef>(&payload)?;
+/// let cell = builder.into_cell();
+/// assert!(cell.references.is_empty());
+///
+/// let got: ForwardPayload = cell.parse_fully_as::<_, EitherInlineOrRef>()?;
+/// assert_eq!(got, payload);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ForwardPayload {
+    /// No payload
+    #[default]
+    Empty,
+    /// Text comment, which must fit into a single cell:
+    /// ```tlb
+    /// text_comment#00000000 text:bytes = ForwardPayload;
+    /// ```
+    Comment(String),
+    /// Arbitrary payload
+    Raw(Cell),
+}
+
+impl ForwardPayload {
+    /// Tag of [`Comment`](ForwardPayload::Comment)
+    pub const COMMENT_TAG: u32 = TEXT_COMMENT;
+
+    /// Maximum length of [`Comment`](ForwardPayload::Comment) in bytes
+    pub const MAX_COMMENT_LEN: usize = (Cell::MAX_BITS_LEN - 32) / 8;
+}
+
+impl CellSerialize for ForwardPayload {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        match self {
+            Self::Empty => {}
+            Self::Comment(comment) => {
+                if comment.len() > Self::MAX_COMMENT_LEN {
+                    return Err(Error::custom(format!(
+                        "comment is too long: {} bytes, max: {}",
+                        comment.len(),
+                        Self::MAX_COMMENT_LEN,
+                    )));
+                }
+                builder
+                    // text_comment#00000000
+                    .pack(Self::COMMENT_TAG)?
+                    // text:bytes
+                    .pack(comment.as_bytes())?;
+            }
+            Self::Raw(cell) => {
+                builder.store(cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for ForwardPayload {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let cell: Cell = parser.parse()?;
+        if cell.is_empty() {
+            return Ok(Self::Empty);
+        }
+        if cell.references.is_empty() && cell.data.len() >= 32 && cell.data.len().is_multiple_of(8)
+        {
+            let mut p = cell.parser();
+            if p.unpack::<u32>()? == Self::COMMENT_TAG {
+                let bytes = p
+                    .unpack_iter::<u8>()
+                    .take(cell.data.len() / 8 - 4)
+                    .collect::<Result<Vec<_>, _>>()?;
+                if let Ok(comment) = String::from_utf8(bytes) {
+                    return Ok(Self::Comment(comment));
+                }
+            }
+        }
+        Ok(Self::Raw(cell))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::{r#as::EitherInlineOrRef, ser::CellSerializeExt};
+
+    use super::*;
+
+    #[test]
+    fn forward_payload_serde() {
+        for payload in [
+            ForwardPayload::Empty,
+            ForwardPayload::Comment(String::new()),
+            ForwardPayload::Comment("x".repeat(ForwardPayload::MAX_COMMENT_LEN)),
+            ForwardPayload::Raw(Cell::from_hex_data("deadbeef", 31).unwrap()),
+        ] {
+            let cell = payload.to_cell().unwrap();
+            let got: ForwardPayload = cell.parse_fully().unwrap();
+            assert_eq!(got, payload);
+        }
+    }
+
+    #[test]
+    fn forward_payload_comment_too_long() {
+        let payload = ForwardPayload::Comment("x".repeat(ForwardPayload::MAX_COMMENT_LEN + 1));
+        assert!(payload.to_cell().is_err());
+    }
+
+    #[test]
+    fn forward_payload_promoted_to_ref() {
+        let payload = ForwardPayload::Comment("x".repeat(ForwardPayload::MAX_COMMENT_LEN));
+
+        let mut builder = Cell::builder();
+        builder.pack(0u8).unwrap();
+        builder.store_as::<_, EitherInlineOrRef>(&payload).unwrap();
+        let cell = builder.into_cell();
+        assert_eq!(cell.data.len(), 9);
+        assert_eq!(cell.references.len(), 1);
+
+        let mut parser = cell.parser();
+        parser.unpack::<u8>().unwrap();
+        assert_eq!(
+            parser
+                .parse_as::<ForwardPayload, EitherInlineOrRef>()
+                .unwrap(),
+            payload,
+        );
+    }
+}
@@ -3,12 +3,17 @@ use tlb::{
     bits::{de::BitReaderExt, integer::ConstU32, r#as::VarInt, ser::BitWriterExt},
     de::{CellDeserialize, CellParser, CellParserError},
     either::Either,
-    r#as::{ParseFully, Ref, Same},
+    r#as::{MaybeRef, ParseFully, Ref, Same},
     ser::{CellBuilder, CellBuilderError, CellSerialize, CellSerializeExt},
     Cell,
 };
 use tlb_ton::MsgAddress;
 
+use crate::opcodes::{
+    JETTON_BURN, JETTON_EXCESSES, JETTON_INTERNAL_TRANSFER, JETTON_TRANSFER,
+    JETTON_TRANSFER_NOTIFICATION,
+};
+
 /// Jetton Transfer message from [TEP-74](https://github.com/ton-blockchain/TEPs/blob/master/text/0074-jettons-standard.md#tl-b-schema)
 /// ```tlb
 /// transfer#0f8a7ea5 query_id:uint64 amount:(VarUInteger 16) destination:MsgAddress
@@ -16,6 +21,39 @@ use tlb_ton::MsgAddress;
 /// forward_ton_amount:(VarUInteger 16) forward_payload:(Either Cell ^Cell)
 /// = InternalMsgBody;
 /// ```
+///
+/// It's sent by the owner to their jetton wallet, e.g. with
+/// [`Wallet::create_external_message()`](crate::wallet::Wallet::create_external_message):
+/// ```rust
+/// # use tlb::Cell;
+/// # use tlb_ton::{action::SendMsgAction, currency::consts::MILLITON, message::Message, MsgAddress};
+/// # use ton_contracts::jetton::{ForwardPayload, JettonTransfer};
+/// # #[cfg(feature = "wallet")]
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # use ton_contracts::wallet::WalletOpSendMessage;
+/// # let (jetton_wallet, owner, dst) = (MsgAddress::NULL, MsgAddress::NULL, MsgAddress::NULL);
+/// let op = WalletOpSendMessage {
+///     mode: SendMsgAction::PAY_FEES_SEPARATELY | SendMsgAction::IGNORE_ERRORS,
+///     message: Message::transfer(jetton_wallet, (50 * MILLITON).into(), true)
+///         .map_body(|()| JettonTransfer {
+///             query_id: 0,
+///             amount: 1_000u32.into(),
+///             dst,
+///             // return excesses back to the owner
+///             response_dst: owner,
+///             custom_payload: None::<Cell>,
+///             // notify the recipient
+///             forward_ton_amount: 1u8.into(),
+///             forward_payload: ForwardPayload::Comment("hi".to_string()),
+///         })
+///         .normalize()?,
+/// };
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "wallet"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct JettonTransfer<P = Cell, F = Cell> {
     pub query_id: u64,
     pub amount: BigUint,
@@ -26,8 +64,6 @@ pub struct JettonTransfer<P = Cell, F = Cell> {
     pub forward_payload: F,
 }
 
-const JETTON_TRANSFER_TAG: u32 = 0x0f8a7ea5;
-
 impl<P, F> CellSerialize for JettonTransfer<P, F>
 where
     P: CellSerialize,
@@ -36,7 +72,7 @@ where
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
         builder
             // transfer#0f8a7ea5
-            .pack(JETTON_TRANSFER_TAG)?
+            .pack(JETTON_TRANSFER)?
             // query_id:uint64
             .pack(self.query_id)?
             // amount:(VarUInteger 16)
@@ -46,7 +82,7 @@ where
             // response_destination:MsgAddress
             .pack(self.response_dst)?
             // custom_payload:(Maybe ^Cell)
-            .store_as::<_, Option<Ref>>(self.custom_payload.as_ref())?
+            .store_as::<_, MaybeRef>(self.custom_payload.as_ref())?
             // forward_ton_amount:(VarUInteger 16)
             .pack_as::<_, &VarInt<4>>(&self.forward_ton_amount)?
             // forward_payload:(Either Cell ^Cell)
@@ -66,7 +102,7 @@ where
 {
     fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
         // transfer#0f8a7ea5
-        parser.unpack::<ConstU32<JETTON_TRANSFER_TAG>>()?;
+        parser.unpack::<ConstU32<JETTON_TRANSFER>>()?;
         Ok(Self {
             // query_id:uint64
             query_id: parser.unpack()?,
@@ -77,7 +113,7 @@ where
             // response_destination:MsgAddress
             response_dst: parser.unpack()?,
             // custom_payload:(Maybe ^Cell)
-            custom_payload: parser.parse_as::<_, Option<Ref<ParseFully>>>()?,
+            custom_payload: parser.parse_as::<_, MaybeRef<ParseFully>>()?,
             // forward_ton_amount:(VarUInteger 16)
             forward_ton_amount: parser.unpack_as::<_, VarInt<4>>()?,
             // forward_payload:(Either Cell ^Cell)
@@ -94,6 +130,7 @@ where
 /// sender:MsgAddress forward_payload:(Either Cell ^Cell)
 /// = InternalMsgBody;
 /// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct JettonTransferNotification<P = Cell> {
     pub query_id: u64,
     pub amount: BigUint,
@@ -101,19 +138,26 @@ pub struct JettonTransferNotification<P = Cell> {
     pub forward_payload: P,
 }
 
-const JETTON_TRANSFER_NOTIFICATION_TAG: u32 = 0x7362d09c;
-
 impl<P> CellSerialize for JettonTransferNotification<P>
 where
     P: CellSerialize,
 {
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
         builder
-            .pack(JETTON_TRANSFER_NOTIFICATION_TAG)?
+            // transfer_notification#7362d09c
+            .pack(JETTON_TRANSFER_NOTIFICATION)?
+            // query_id:uint64
             .pack(self.query_id)?
+            // amount:(VarUInteger 16)
             .pack_as::<_, &VarInt<4>>(&self.amount)?
+            // sender:MsgAddress
             .pack(self.sender)?
-            .store_as::<Either<(), _>, Either<Same, Ref>>(Either::Right(&self.forward_payload))?;
+            // forward_payload:(Either Cell ^Cell)
+            .store_as::<_, Either<(), Ref>>(
+                Some(&self.forward_payload.to_cell()?)
+                    // store empty cell inline
+                    .filter(|cell| !cell.is_empty()),
+            )?;
         Ok(())
     }
 }
@@ -123,11 +167,16 @@ where
     P: CellDeserialize<'de>,
 {
     fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
-        parser.unpack::<ConstU32<JETTON_TRANSFER_NOTIFICATION_TAG>>()?;
+        // transfer_notification#7362d09c
+        parser.unpack::<ConstU32<JETTON_TRANSFER_NOTIFICATION>>()?;
         Ok(Self {
+            // query_id:uint64
             query_id: parser.unpack()?,
+            // amount:(VarUInteger 16)
             amount: parser.unpack_as::<_, VarInt<4>>()?,
+            // sender:MsgAddress
             sender: parser.unpack()?,
+            // forward_payload:(Either Cell ^Cell)
             forward_payload: parser
                 .parse_as::<Either<P, P>, Either<Same, Ref<ParseFully>>>()?
                 .into_inner(),
@@ -135,12 +184,45 @@ where
     }
 }
 
+/// Jetton Excesses message from [TEP-74](https://github.com/ton-blockchain/TEPs/blob/master/text/0074-jettons-standard.md#tl-b-schema)
+/// sent to `response_destination` with the rest of incoming TON:
+/// ```tlb
+/// excesses#d53276db query_id:uint64 = InternalMsgBody;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JettonExcesses {
+    pub query_id: u64,
+}
+
+impl CellSerialize for JettonExcesses {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // excesses#d53276db
+            .pack(JETTON_EXCESSES)?
+            // query_id:uint64
+            .pack(self.query_id)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for JettonExcesses {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        // excesses#d53276db
+        parser.unpack::<ConstU32<JETTON_EXCESSES>>()?;
+        Ok(Self {
+            // query_id:uint64
+            query_id: parser.unpack()?,
+        })
+    }
+}
+
 /// Jetton Burn message from [TEP-74](https://github.com/ton-blockchain/TEPs/blob/master/text/0074-jettons-standard.md#tl-b-schema)
 /// ```tlb
 /// burn#595f07bc query_id:uint64 amount:(VarUInteger 16)
 /// response_destination:MsgAddress custom_payload:(Maybe ^Cell)
 /// = InternalMsgBody;
 /// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct JettonBurn<P = Cell> {
     pub query_id: u64,
     pub amount: BigUint,
@@ -148,18 +230,22 @@ pub struct JettonBurn<P = Cell> {
     pub custom_payload: Option<P>,
 }
 
-const JETTON_BURN_TAG: u32 = 0x595f07bc;
-
 impl<P> CellSerialize for JettonBurn<P>
 where
     P: CellSerialize,
 {
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
         builder
-            .pack(JETTON_BURN_TAG)?
+            // burn#595f07bc
+            .pack(JETTON_BURN)?
+            // query_id:uint64
+            .pack(self.query_id)?
+            // amount:(VarUInteger 16)
             .pack_as::<_, &VarInt<4>>(&self.amount)?
+            // response_destination:MsgAddress
             .pack(self.response_dst)?
-            .store_as::<_, Option<Ref>>(self.custom_payload.as_ref())?;
+            // custom_payload:(Maybe ^Cell)
+            .store_as::<_, MaybeRef>(self.custom_payload.as_ref())?;
         Ok(())
     }
 }
@@ -169,12 +255,198 @@ where
     P: CellDeserialize<'de>,
 {
     fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
-        parser.unpack::<ConstU32<JETTON_BURN_TAG>>()?;
+        // burn#595f07bc
+        parser.unpack::<ConstU32<JETTON_BURN>>()?;
         Ok(Self {
+            // query_id:uint64
             query_id: parser.unpack()?,
+            // amount:(VarUInteger 16)
             amount: parser.unpack_as::<_, VarInt<4>>()?,
+            // response_destination:MsgAddress
             response_dst: parser.unpack()?,
-            custom_payload: parser.parse_as::<_, Option<Ref<ParseFully>>>()?,
+            // custom_payload:(Maybe ^Cell)
+            custom_payload: parser.parse_as::<_, MaybeRef<ParseFully>>()?,
+        })
+    }
+}
+
+/// Jetton Internal Transfer message sent between jetton wallets in
+/// [reference implementation](https://github.com/ton-blockchain/token-contract/blob/main/ft/jetton-wallet.fc)
+/// as a result of [`JettonTransfer`]
+/// ```tlb
+/// internal_transfer#178d4519 query_id:uint64 amount:(VarUInteger 16) from:MsgAddress
+/// response_address:MsgAddress forward_ton_amount:(VarUInteger 16)
+/// forward_payload:(Either Cell ^Cell)
+/// = InternalMsgBody;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JettonInternalTransfer<F = Cell> {
+    pub query_id: u64,
+    pub amount: BigUint,
+    pub from: MsgAddress,
+    pub response_address: MsgAddress,
+    pub forward_ton_amount: BigUint,
+    pub forward_payload: F,
+}
+
+impl<F> CellSerialize for JettonInternalTransfer<F>
+where
+    F: CellSerialize,
+{
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // internal_transfer#178d4519
+            .pack(JETTON_INTERNAL_TRANSFER)?
+            // query_id:uint64
+            .pack(self.query_id)?
+            // amount:(VarUInteger 16)
+            .pack_as::<_, &VarInt<4>>(&self.amount)?
+            // from:MsgAddress
+            .pack(self.from)?
+            // response_address:MsgAddress
+            .pack(self.response_address)?
+            // forward_ton_amount:(VarUInteger 16)
+            .pack_as::<_, &VarInt<4>>(&self.forward_ton_amount)?
+            // forward_payload:(Either Cell ^Cell)
+            .store_as::<_, Either<(), Ref>>(
+                Some(&self.forward_payload.to_cell()?)
+                    // store empty cell inline
+                    .filter(|cell| !cell.is_empty()),
+            )?;
+        Ok(())
+    }
+}
+
+impl<'de, F> CellDeserialize<'de> for JettonInternalTransfer<F>
+where
+    F: CellDeserialize<'de>,
+{
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        // internal_transfer#178d4519
+        parser.unpack::<ConstU32<JETTON_INTERNAL_TRANSFER>>()?;
+        Ok(Self {
+            // query_id:uint64
+            query_id: parser.unpack()?,
+            // amount:(VarUInteger 16)
+            amount: parser.unpack_as::<_, VarInt<4>>()?,
+            // from:MsgAddress
+            from: parser.unpack()?,
+            // response_address:MsgAddress
+            response_address: parser.unpack()?,
+            // forward_ton_amount:(VarUInteger 16)
+            forward_ton_amount: parser.unpack_as::<_, VarInt<4>>()?,
+            // forward_payload:(Either Cell ^Cell)
+            forward_payload: parser
+                .parse_as::<Either<F, F>, Either<ParseFully, Ref<ParseFully>>>()?
+                .into_inner(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tlb_ton::{action::SendMsgAction, message::Message};
+
+    use crate::jetton::ForwardPayload;
+
+    use super::*;
+
+    #[test]
+    fn transfer_notification_inline_empty_payload() {
+        let cell = JettonTransferNotification {
+            query_id: 1,
+            amount: 2u8.into(),
+            sender: MsgAddress::NULL,
+            forward_payload: Cell::default(),
+        }
+        .to_cell()
+        .unwrap();
+        assert!(cell.references.is_empty());
+
+        let got: JettonTransferNotification = cell.parse_fully().unwrap();
+        assert_eq!(got.query_id, 1);
+        assert!(got.forward_payload.is_empty());
+    }
+
+    #[test]
+    fn excesses() {
+        let excesses = JettonExcesses { query_id: 42 };
+        let cell = excesses.to_cell().unwrap();
+        assert_eq!(cell.peek_uint(32), Some(0xd53276db));
+        assert_eq!(cell.parse_fully::<JettonExcesses>().unwrap(), excesses);
+    }
+
+    #[test]
+    fn burn_round_trip() {
+        let burn = JettonBurn {
+            query_id: 7,
+            amount: 100u8.into(),
+            response_dst: MsgAddress::NULL,
+            custom_payload: Some(Cell::from_hex_data("cafe", 16).unwrap()),
+        };
+        let cell = burn.to_cell().unwrap();
+        assert_eq!(cell.peek_uint(32), Some(JETTON_BURN.into()));
+        assert_eq!(cell.parse_fully::<JettonBurn>().unwrap(), burn);
+    }
+
+    #[test]
+    fn internal_transfer_round_trip() {
+        for forward_payload in [
+            ForwardPayload::Empty,
+            ForwardPayload::Comment("hi".to_string()),
+        ] {
+            let transfer = JettonInternalTransfer {
+                query_id: 1,
+                amount: 100u8.into(),
+                from: MsgAddress {
+                    workchain_id: 0,
+                    address: [1; 32],
+                },
+                response_address: MsgAddress::NULL,
+                forward_ton_amount: 1u8.into(),
+                forward_payload,
+            };
+            let cell = transfer.to_cell().unwrap();
+            assert_eq!(cell.peek_uint(32), Some(JETTON_INTERNAL_TRANSFER.into()));
+            assert_eq!(
+                cell.parse_fully::<JettonInternalTransfer<ForwardPayload>>()
+                    .unwrap(),
+                transfer
+            );
+        }
+    }
+
+    #[test]
+    fn typed_send_msg_action() {
+        let wallet = MsgAddress {
+            workchain_id: 0,
+            address: [1; 32],
+        };
+        let action = SendMsgAction::builder()
+            .mode(SendMsgAction::PAY_FEES_SEPARATELY)
+            .message(
+                Message::<()>::transfer(wallet, 1u8.into(), true).map_body(|()| JettonTransfer {
+                    query_id: 1,
+                    amount: 100u8.into(),
+                    dst: MsgAddress::NULL,
+                    response_dst: MsgAddress::NULL,
+                    custom_payload: None::<Cell>,
+                    forward_ton_amount: 0u8.into(),
+                    forward_payload: ForwardPayload::Comment("hi".to_string()),
+                }),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(action.message.body.amount, 100u8.into());
+
+        let cell = action.to_cell().unwrap();
+        assert_eq!(cell, action.normalize().unwrap().to_cell().unwrap());
+
+        let got: SendMsgAction<JettonTransfer<Cell, ForwardPayload>> = cell.parse_fully().unwrap();
+        assert_eq!(got.mode, action.mode);
+        assert_eq!(
+            got.message.body.forward_payload,
+            ForwardPayload::Comment("hi".to_string())
+        );
+    }
+}
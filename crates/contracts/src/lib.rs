@@ -6,3 +6,9 @@ pub mod wallet;
 #[cfg(feature = "jetton")]
 #[cfg_attr(docsrs, doc(cfg(feature = "jetton")))]
 pub mod jetton;
+
+#[cfg(feature = "nft")]
+#[cfg_attr(docsrs, doc(cfg(feature = "nft")))]
+pub mod nft;
+
+pub mod opcodes;
@@ -0,0 +1,211 @@
+use std::sync::Arc;
+
+use tlb::{
+    bits::{
+        de::{BitReader, BitReaderExt, BitUnpack},
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::{Data, ParseFully, Ref, SharedRef},
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Cell, ResultExt,
+};
+use tlb_ton::MsgAddress;
+
+use super::NftItemData;
+
+/// Data of NFT collection in
+/// [reference implementation](https://github.com/ton-blockchain/token-contract/blob/main/nft/nft-collection.fc)
+/// ```tlb
+/// _ owner_address:MsgAddress next_item_index:uint64
+/// content:^[collection_content:^Cell common_content:^Cell]
+/// nft_item_code:^Cell royalty_params:^NftRoyaltyParams = NftCollectionData;
+/// ```
+///
+/// `content` is usually [`Content`](super::Content) of the collection
+/// itself, while `common_content` is a prefix, e.g. base URI, for
+/// individual [`content`](NftItemData::content) of its items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NftCollectionData<C = Cell> {
+    pub owner: MsgAddress,
+    /// Index of the next item to be deployed
+    pub next_item_index: u64,
+    pub content: C,
+    pub common_content: Cell,
+    pub item_code: Arc<Cell>,
+    pub royalty: NftRoyaltyParams,
+}
+
+impl<C> NftCollectionData<C> {
+    /// Address of NFT item with given `index` in collection deployed at
+    /// `collection` address, see [`NftItemData::address()`]
+    #[inline]
+    pub fn item_address(
+        &self,
+        collection: MsgAddress,
+        index: u64,
+    ) -> Result<MsgAddress, CellBuilderError> {
+        NftItemData::address(index, collection, self.item_code.clone())
+    }
+}
+
+impl<C> CellSerialize for NftCollectionData<C>
+where
+    C: CellSerialize,
+{
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        let mut content = Cell::builder();
+        content
+            // collection_content:^Cell
+            .store_as::<_, Ref>(&self.content)
+            .context("collection_content")?
+            // common_content:^Cell
+            .store_as::<_, Ref>(&self.common_content)
+            .context("common_content")?;
+        builder
+            // owner_address:MsgAddress
+            .pack(self.owner)?
+            // next_item_index:uint64
+            .pack(self.next_item_index)?
+            // content:^[collection_content:^Cell common_content:^Cell]
+            .store_as::<_, Ref>(content.into_cell())?
+            // nft_item_code:^Cell
+            .store_as::<_, &SharedRef>(&self.item_code)?
+            // royalty_params:^NftRoyaltyParams
+            .store_as::<_, Ref<Data>>(self.royalty)?;
+        Ok(())
+    }
+}
+
+impl<'de, C> CellDeserialize<'de> for NftCollectionData<C>
+where
+    C: CellDeserialize<'de>,
+{
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let owner = parser.unpack().context("owner_address")?;
+        let next_item_index = parser.unpack().context("next_item_index")?;
+        let (content, common_content) = parser
+            .parse_as::<_, Ref<ParseFully<(Ref<ParseFully>, Ref)>>>()
+            .context("content")?;
+        Ok(Self {
+            // owner_address:MsgAddress
+            owner,
+            // next_item_index:uint64
+            next_item_index,
+            // content:^[collection_content:^Cell common_content:^Cell]
+            content,
+            common_content,
+            // nft_item_code:^Cell
+            item_code: parser.parse_as::<_, SharedRef>().context("nft_item_code")?,
+            // royalty_params:^NftRoyaltyParams
+            royalty: parser
+                .parse_as::<_, Ref<ParseFully<Data>>>()
+                .context("royalty_params")?,
+        })
+    }
+}
+
+/// Royalty parameters from [TEP-66](https://github.com/ton-blockchain/TEPs/blob/master/text/0066-nft-royalty-standard.md),
+/// i.e. share of `numerator / denominator` of the sale price is paid to
+/// `destination`:
+/// ```tlb
+/// royalty_params#_ numerator:uint16 denominator:uint16 destination:MsgAddress
+/// = NftRoyaltyParams;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NftRoyaltyParams {
+    pub numerator: u16,
+    pub denominator: u16,
+    pub destination: MsgAddress,
+}
+
+impl NftRoyaltyParams {
+    /// Royalty for given sale `price`, rounded down, or `None` if
+    /// `denominator` is zero
+    #[inline]
+    pub fn royalty(&self, price: u64) -> Option<u64> {
+        (u128::from(price) * u128::from(self.numerator))
+            .checked_div(self.denominator.into())?
+            .try_into()
+            .ok()
+    }
+}
+
+impl BitPack for NftRoyaltyParams {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // numerator:uint16
+            .pack(self.numerator)?
+            // denominator:uint16
+            .pack(self.denominator)?
+            // destination:MsgAddress
+            .pack(self.destination)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for NftRoyaltyParams {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(Self {
+            // numerator:uint16
+            numerator: reader.unpack()?,
+            // denominator:uint16
+            denominator: reader.unpack()?,
+            // destination:MsgAddress
+            destination: reader.unpack()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::ser::CellSerializeExt;
+
+    use crate::nft::Content;
+
+    use super::*;
+
+    const COLLECTION: MsgAddress = MsgAddress {
+        workchain_id: 0,
+        address: [1; 32],
+    };
+
+    #[test]
+    fn collection_data_round_trip() {
+        let data = NftCollectionData {
+            owner: MsgAddress {
+                workchain_id: 0,
+                address: [2; 32],
+            },
+            next_item_index: 3,
+            content: Content::OffChain("https://example.com/collection.json".to_string()),
+            common_content: Cell::from_hex_data("cafe", 16).unwrap(),
+            item_code: Arc::new(Cell::from_hex_data("deadbeef", 32).unwrap()),
+            royalty: NftRoyaltyParams {
+                numerator: 5,
+                denominator: 100,
+                destination: COLLECTION,
+            },
+        };
+        let cell = data.to_cell().unwrap();
+        assert_eq!(cell.references.len(), 3);
+        assert!(Arc::ptr_eq(&cell.references[1], &data.item_code));
+        let got: NftCollectionData<Content> = cell.parse_fully().unwrap();
+        assert_eq!(got, data);
+        assert!(Arc::ptr_eq(&got.item_code, &cell.references[1]));
+
+        assert_ne!(
+            data.item_address(COLLECTION, 1).unwrap(),
+            data.item_address(COLLECTION, 2).unwrap()
+        );
+        assert_eq!(data.royalty.royalty(1_000), Some(50));
+    }
+}
@@ -0,0 +1,379 @@
+use tlb::{
+    bits::{de::BitReaderExt, ser::BitWriterExt},
+    de::{r#as::CellDeserializeAs, CellDeserialize, CellParser, CellParserError},
+    hash::{digest::Digest, Sha256},
+    r#as::Ref,
+    ser::{r#as::CellSerializeAs, CellBuilder, CellBuilderError, CellSerialize},
+    tag::{Tag, UnknownTag},
+    Cell, Error, ResultExt,
+};
+use tlb_ton::hashmap::Dict;
+
+/// Token data from [TEP-64](https://github.com/ton-blockchain/TEPs/blob/master/text/0064-token-data-standard.md#content-representation),
+/// i.e. `content` of NFT collections and jetton minters, as well as
+/// metadata returned by `get_nft_content` get-method of the collection:
+/// ```tlb
+/// onchain#00 data:(HashmapE 256 ^ContentData) = FullContent;
+/// offchain#01 uri:Text = FullContent;
+/// ```
+///
+/// Attributes of on-chain content are keyed by SHA-256 of their names:
+/// ```rust
+/// # use tlb::ser::CellSerializeExt;
+/// # use ton_contracts::nft::Content;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let content = Content::on_chain([
+///     ("name", "Toner"),
+///     ("image", "https://example.com/toner.png"),
+/// ]);
+///
+/// let cell = content.to_cell()?;
+/// let got: Content = cell.parse_fully()?;
+/// assert_eq!(got.get_str("name"), Some("Toner"));
+/// assert_eq!(got.uri(), None);
+///
+/// // semi-chain content refers to the rest of the metadata by URI
+/// let content = Content::on_chain([("uri", "https://example.com/toner.json")]);
+/// assert_eq!(content.uri(), Some("https://example.com/toner.json"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Content {
+    /// On-chain attributes
+    OnChain(OnChainContent),
+    /// URI of JSON document with attributes
+    OffChain(String),
+}
+
+/// Attributes of [`Content::OnChain`] keyed by SHA-256 of their names,
+/// see [`Content::key()`]
+pub type OnChainContent = Dict<256, [u8; 32], Vec<u8>, Ref<ContentData>>;
+
+impl Content {
+    /// Tag of [`OnChain`](Content::OnChain)
+    pub const ON_CHAIN_TAG: u8 = 0x00;
+    /// Tag of [`OffChain`](Content::OffChain)
+    pub const OFF_CHAIN_TAG: u8 = 0x01;
+
+    /// On-chain content with given attributes
+    #[inline]
+    pub fn on_chain<N, V>(attributes: impl IntoIterator<Item = (N, V)>) -> Self
+    where
+        N: AsRef<str>,
+        V: Into<Vec<u8>>,
+    {
+        Self::OnChain(
+            attributes
+                .into_iter()
+                .map(|(name, value)| (Self::key(name.as_ref()), value.into()))
+                .collect(),
+        )
+    }
+
+    /// Key of attribute in [`OnChainContent`], i.e. SHA-256 of its name
+    #[inline]
+    pub fn key(name: &str) -> [u8; 32] {
+        Sha256::digest(name).into()
+    }
+
+    /// Value of on-chain attribute with given name
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<&[u8]> {
+        match self {
+            Self::OnChain(attributes) => attributes.get(&Self::key(name)).map(Vec::as_slice),
+            Self::OffChain(_) => None,
+        }
+    }
+
+    /// Value of on-chain attribute with given name, if it's valid UTF-8
+    #[inline]
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.get(name)
+            .and_then(|value| core::str::from_utf8(value).ok())
+    }
+
+    /// URI of JSON document with attributes: either off-chain one or
+    /// `uri` attribute of semi-chain content
+    #[inline]
+    pub fn uri(&self) -> Option<&str> {
+        match self {
+            Self::OnChain(_) => self.get_str("uri"),
+            Self::OffChain(uri) => Some(uri),
+        }
+    }
+}
+
+impl CellSerialize for Content {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        match self {
+            Self::OnChain(attributes) => {
+                builder
+                    // onchain#00
+                    .pack(Self::ON_CHAIN_TAG)?
+                    // data:(HashmapE 256 ^ContentData)
+                    .store(attributes)
+                    .context("data")?;
+            }
+            Self::OffChain(uri) => {
+                builder
+                    // offchain#01
+                    .pack(Self::OFF_CHAIN_TAG)?
+                    // uri:Text
+                    .store_as::<_, &SnakeData>(uri)
+                    .context("uri")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for Content {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        match parser.unpack()? {
+            // onchain#00
+            Self::ON_CHAIN_TAG => Ok(Self::OnChain(
+                // data:(HashmapE 256 ^ContentData)
+                parser.parse().context("data")?,
+            )),
+            // offchain#01
+            Self::OFF_CHAIN_TAG => {
+                // uri:Text
+                let uri = parser.parse_as::<_, SnakeData>().context("uri")?;
+                String::from_utf8(uri)
+                    .map(Self::OffChain)
+                    .map_err(Error::custom)
+                    .context("uri")
+            }
+            tag => Err(Error::custom(UnknownTag {
+                got: Tag::new(tag.into(), 8),
+                expected: [Self::ON_CHAIN_TAG, Self::OFF_CHAIN_TAG]
+                    .map(|tag| Tag::new(tag.into(), 8))
+                    .to_vec(),
+            }))
+            .context("content"),
+        }
+    }
+}
+
+/// **De**/**ser**ialize bytes as value of [`OnChainContent`] attribute.
+/// Only [`SnakeData`] is used for **ser**ialization, while both layouts
+/// are accepted on **de**serialization:
+/// ```tlb
+/// snake#00 data:(SnakeData ~n) = ContentData;
+/// chunks#01 data:ChunkedData = ContentData;
+/// chunked_data#_ data:(HashmapE 32 ^(SnakeData ~0)) = ChunkedData;
+/// ```
+pub struct ContentData;
+
+impl ContentData {
+    /// Tag of snake layout
+    pub const SNAKE_TAG: u8 = 0x00;
+    /// Tag of chunked layout
+    pub const CHUNKS_TAG: u8 = 0x01;
+}
+
+impl<T> CellSerializeAs<T> for ContentData
+where
+    T: AsRef<[u8]> + ?Sized,
+{
+    #[inline]
+    fn store_as(source: &T, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // snake#00
+            .pack(Self::SNAKE_TAG)?
+            // data:(SnakeData ~n)
+            .store_as::<_, &SnakeData>(source)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserializeAs<'de, Vec<u8>> for ContentData {
+    fn parse_as(parser: &mut CellParser<'de>) -> Result<Vec<u8>, CellParserError<'de>> {
+        match parser.unpack()? {
+            // snake#00 data:(SnakeData ~n)
+            Self::SNAKE_TAG => parser.parse_as::<_, SnakeData>(),
+            // chunks#01 data:ChunkedData
+            Self::CHUNKS_TAG => {
+                // chunked_data#_ data:(HashmapE 32 ^(SnakeData ~0))
+                let chunks: Dict<32, u32, Vec<u8>, Ref<SnakeData>> =
+                    parser.parse().context("chunks")?;
+                Ok(chunks.into_inner().into_values().flatten().collect())
+            }
+            tag => Err(Error::custom(UnknownTag {
+                got: Tag::new(tag.into(), 8),
+                expected: [Self::SNAKE_TAG, Self::CHUNKS_TAG]
+                    .map(|tag| Tag::new(tag.into(), 8))
+                    .to_vec(),
+            }))
+            .context("content data"),
+        }
+    }
+}
+
+/// **De**/**ser**ialize bytes as
+/// [`SnakeData`](https://github.com/ton-blockchain/TEPs/blob/master/text/0064-token-data-standard.md#data-serialization),
+/// i.e. `Text` in TL-B, which takes the rest of the current cell and
+/// continues in a chain of references:
+/// ```tlb
+/// tail#_ {bn:#} b:(bits bn) = SnakeData ~0;
+/// cons#_ {bn:#} {n:#} b:(bits bn) next:^(SnakeData ~n) = SnakeData ~(n + 1);
+/// ```
+///
+/// ```rust
+/// # use tlb::Cell;
+/// # use ton_contracts::nft::SnakeData;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let text = "x".repeat(300);
+///
+/// let mut builder = Cell::builder();
+/// builder.store_as::<_, &SnakeData>(text.as_str())?;
+/// let cell = builder.into_cell();
+/// assert_eq!(cell.data.len(), 127 * 8);
+/// assert_eq!(cell.references.len(), 1);
+///
+/// let got: Vec<u8> = cell.parse_fully_as::<_, SnakeData>()?;
+/// assert_eq!(got, text.as_bytes());
+/// # Ok(())
+/// # }
+/// ```
+pub struct SnakeData;
+
+impl<T> CellSerializeAs<T> for SnakeData
+where
+    T: AsRef<[u8]> + ?Sized,
+{
+    #[inline]
+    fn store_as(source: &T, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        let bytes = source.as_ref();
+        let (head, tail) = bytes.split_at(bytes.len().min(builder.bits_left() / 8));
+        // b:(bits bn)
+        builder.pack(head)?;
+        if !tail.is_empty() {
+            // next:^(SnakeData ~n)
+            builder.store_as::<_, Ref<&SnakeData>>(tail)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserializeAs<'de, Vec<u8>> for SnakeData {
+    fn parse_as(parser: &mut CellParser<'de>) -> Result<Vec<u8>, CellParserError<'de>> {
+        let head: Cell = parser.parse()?;
+        let mut bytes = Vec::new();
+        let mut cell = &head;
+        loop {
+            if !cell.data.len().is_multiple_of(8) {
+                return Err(Error::custom(format!(
+                    "data is not aligned to bytes: {} bits",
+                    cell.data.len()
+                )));
+            }
+            bytes.extend_from_slice(cell.data.as_raw_slice());
+            cell = match cell.references.as_slice() {
+                [] => return Ok(bytes),
+                [next] => next,
+                _ => return Err(Error::custom("too many references")),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::{
+        bits::bitvec::{order::Msb0, vec::BitVec},
+        ser::CellSerializeExt,
+        CellType,
+    };
+
+    use super::*;
+
+    #[test]
+    fn content_round_trip() {
+        for content in [
+            Content::on_chain([("name", "x".repeat(200)), ("symbol", "X".to_string())]),
+            Content::on_chain::<&str, Vec<u8>>([]),
+            Content::OffChain("https://example.com/".repeat(20)),
+        ] {
+            let cell = content.to_cell().unwrap();
+            assert_eq!(cell.parse_fully::<Content>().unwrap(), content);
+        }
+    }
+
+    #[test]
+    fn content_unknown_tag() {
+        let cell = Cell::from_hex_data("02", 8).unwrap();
+        assert_eq!(
+            cell.parse_fully::<Content>().unwrap_err().to_string(),
+            "content: unknown tag: #02, expected one of: #00, #01"
+        );
+    }
+
+    #[test]
+    fn content_data_chunks() {
+        let chunks: Dict<32, u32, Cell, Ref> = ["hello, ", "world"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, chunk)| {
+                (
+                    i as u32,
+                    Cell {
+                        r#type: CellType::Ordinary,
+                        data: BitVec::<u8, Msb0>::from_slice(chunk.as_bytes()),
+                        references: Vec::new(),
+                    },
+                )
+            })
+            .collect();
+        let mut builder = Cell::builder();
+        builder
+            .pack(ContentData::CHUNKS_TAG)
+            .unwrap()
+            .store(chunks)
+            .unwrap();
+        let value = builder.into_cell();
+
+        let mut attributes = OnChainContent::new();
+        attributes.insert(Content::key("description"), b"hello, world".to_vec());
+        let expected = Content::OnChain(attributes);
+
+        let mut builder = Cell::builder();
+        builder
+            .pack(Content::ON_CHAIN_TAG)
+            .unwrap()
+            .store(
+                [(Content::key("description"), value)]
+                    .into_iter()
+                    .collect::<Dict<256, [u8; 32], Cell, Ref>>(),
+            )
+            .unwrap();
+        let got: Content = builder.into_cell().parse_fully().unwrap();
+        assert_eq!(got, expected);
+        assert_eq!(got.get_str("description"), Some("hello, world"));
+    }
+
+    #[test]
+    fn snake_data_prefixed() {
+        let text = "y".repeat(Cell::MAX_BITS_LEN / 8 * 2);
+        let mut builder = Cell::builder();
+        builder
+            .pack(ContentData::SNAKE_TAG)
+            .unwrap()
+            .store_as::<_, &SnakeData>(text.as_str())
+            .unwrap();
+        let cell = builder.into_cell();
+        // first cell is shorter by the prefix
+        assert_eq!(cell.data.len(), 8 + 126 * 8);
+        assert_eq!(
+            cell.parse_fully_as::<Vec<u8>, ContentData>().unwrap(),
+            text.as_bytes()
+        );
+    }
+
+    #[test]
+    fn snake_data_unaligned() {
+        let cell = Cell::from_hex_data("f0", 4).unwrap();
+        assert!(cell.parse_fully_as::<Vec<u8>, SnakeData>().is_err());
+    }
+}
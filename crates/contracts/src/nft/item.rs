@@ -0,0 +1,489 @@
+use std::sync::Arc;
+
+use num_bigint::BigUint;
+use tlb::{
+    bits::{
+        de::BitReaderExt,
+        integer::ConstU32,
+        r#as::{NBits, VarInt},
+        ser::BitWriterExt,
+    },
+    de::{CellDeserialize, CellParser, CellParserError},
+    either::Either,
+    r#as::{MaybeRef, ParseFully, Ref},
+    ser::{CellBuilder, CellBuilderError, CellSerialize, CellSerializeExt},
+    Cell, ResultExt,
+};
+use tlb_ton::{state_init::StateInit, MsgAddress};
+
+use crate::opcodes::{
+    NFT_GET_STATIC_DATA, NFT_OWNERSHIP_ASSIGNED, NFT_REPORT_STATIC_DATA, NFT_TRANSFER,
+};
+
+/// NFT Transfer message from [TEP-62](https://github.com/ton-blockchain/TEPs/blob/master/text/0062-nft-standard.md#1-transfer)
+/// ```tlb
+/// transfer#5fcc3d14 query_id:uint64 new_owner:MsgAddress
+/// response_destination:MsgAddress custom_payload:(Maybe ^Cell)
+/// forward_amount:(VarUInteger 16) forward_payload:(Either Cell ^Cell)
+/// = InternalMsgBody;
+/// ```
+///
+/// It's sent by the owner to the NFT item, e.g. with
+/// [`Wallet::create_external_message()`](crate::wallet::Wallet::create_external_message):
+/// ```rust
+/// # use tlb::Cell;
+/// # use tlb_ton::{action::SendMsgAction, currency::consts::MILLITON, message::Message, MsgAddress};
+/// # use ton_contracts::{jetton::ForwardPayload, nft::NftTransfer};
+/// # #[cfg(feature = "wallet")]
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # use ton_contracts::wallet::WalletOpSendMessage;
+/// # let (item, owner, new_owner) = (MsgAddress::NULL, MsgAddress::NULL, MsgAddress::NULL);
+/// let op = WalletOpSendMessage {
+///     mode: SendMsgAction::PAY_FEES_SEPARATELY | SendMsgAction::IGNORE_ERRORS,
+///     message: Message::transfer(item, (50 * MILLITON).into(), true)
+///         .map_body(|()| NftTransfer {
+///             query_id: 0,
+///             new_owner,
+///             // return excesses back to the owner
+///             response_dst: owner,
+///             custom_payload: None::<Cell>,
+///             // notify the new owner
+///             forward_amount: 1u8.into(),
+///             forward_payload: ForwardPayload::Comment("gift".to_string()),
+///         })
+///         .normalize()?,
+/// };
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "wallet"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NftTransfer<P = Cell, F = Cell> {
+    pub query_id: u64,
+    pub new_owner: MsgAddress,
+    pub response_dst: MsgAddress,
+    pub custom_payload: Option<P>,
+    pub forward_amount: BigUint,
+    pub forward_payload: F,
+}
+
+impl<P, F> CellSerialize for NftTransfer<P, F>
+where
+    P: CellSerialize,
+    F: CellSerialize,
+{
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // transfer#5fcc3d14
+            .pack(NFT_TRANSFER)?
+            // query_id:uint64
+            .pack(self.query_id)?
+            // new_owner:MsgAddress
+            .pack(self.new_owner)?
+            // response_destination:MsgAddress
+            .pack(self.response_dst)?
+            // custom_payload:(Maybe ^Cell)
+            .store_as::<_, MaybeRef>(self.custom_payload.as_ref())?
+            // forward_amount:(VarUInteger 16)
+            .pack_as::<_, &VarInt<4>>(&self.forward_amount)?
+            // forward_payload:(Either Cell ^Cell)
+            .store_as::<_, Either<(), Ref>>(
+                Some(&self.forward_payload.to_cell()?)
+                    // store empty cell inline
+                    .filter(|cell| !cell.is_empty()),
+            )?;
+        Ok(())
+    }
+}
+
+impl<'de, P, F> CellDeserialize<'de> for NftTransfer<P, F>
+where
+    P: CellDeserialize<'de>,
+    F: CellDeserialize<'de>,
+{
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        // transfer#5fcc3d14
+        parser.unpack::<ConstU32<NFT_TRANSFER>>()?;
+        Ok(Self {
+            // query_id:uint64
+            query_id: parser.unpack()?,
+            // new_owner:MsgAddress
+            new_owner: parser.unpack()?,
+            // response_destination:MsgAddress
+            response_dst: parser.unpack()?,
+            // custom_payload:(Maybe ^Cell)
+            custom_payload: parser.parse_as::<_, MaybeRef<ParseFully>>()?,
+            // forward_amount:(VarUInteger 16)
+            forward_amount: parser.unpack_as::<_, VarInt<4>>()?,
+            // forward_payload:(Either Cell ^Cell)
+            forward_payload: parser
+                .parse_as::<Either<F, F>, Either<ParseFully, Ref<ParseFully>>>()?
+                .into_inner(),
+        })
+    }
+}
+
+/// NFT Ownership Assigned message from [TEP-62](https://github.com/ton-blockchain/TEPs/blob/master/text/0062-nft-standard.md#1-transfer)
+/// sent to the new owner if [`forward_amount`](NftTransfer::forward_amount)
+/// is non-zero:
+/// ```tlb
+/// ownership_assigned#05138d91 query_id:uint64 prev_owner:MsgAddress
+/// forward_payload:(Either Cell ^Cell) = InternalMsgBody;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NftOwnershipAssigned<F = Cell> {
+    pub query_id: u64,
+    pub prev_owner: MsgAddress,
+    pub forward_payload: F,
+}
+
+impl<F> CellSerialize for NftOwnershipAssigned<F>
+where
+    F: CellSerialize,
+{
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // ownership_assigned#05138d91
+            .pack(NFT_OWNERSHIP_ASSIGNED)?
+            // query_id:uint64
+            .pack(self.query_id)?
+            // prev_owner:MsgAddress
+            .pack(self.prev_owner)?
+            // forward_payload:(Either Cell ^Cell)
+            .store_as::<_, Either<(), Ref>>(
+                Some(&self.forward_payload.to_cell()?)
+                    // store empty cell inline
+                    .filter(|cell| !cell.is_empty()),
+            )?;
+        Ok(())
+    }
+}
+
+impl<'de, F> CellDeserialize<'de> for NftOwnershipAssigned<F>
+where
+    F: CellDeserialize<'de>,
+{
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        // ownership_assigned#05138d91
+        parser.unpack::<ConstU32<NFT_OWNERSHIP_ASSIGNED>>()?;
+        Ok(Self {
+            // query_id:uint64
+            query_id: parser.unpack()?,
+            // prev_owner:MsgAddress
+            prev_owner: parser.unpack()?,
+            // forward_payload:(Either Cell ^Cell)
+            forward_payload: parser
+                .parse_as::<Either<F, F>, Either<ParseFully, Ref<ParseFully>>>()?
+                .into_inner(),
+        })
+    }
+}
+
+/// NFT Get Static Data message from [TEP-62](https://github.com/ton-blockchain/TEPs/blob/master/text/0062-nft-standard.md#2-get_static_data)
+/// sent by anyone to the NFT item, which responds with
+/// [`NftReportStaticData`]:
+/// ```tlb
+/// get_static_data#2fcb26a2 query_id:uint64 = InternalMsgBody;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NftGetStaticData {
+    pub query_id: u64,
+}
+
+impl CellSerialize for NftGetStaticData {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // get_static_data#2fcb26a2
+            .pack(NFT_GET_STATIC_DATA)?
+            // query_id:uint64
+            .pack(self.query_id)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for NftGetStaticData {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        // get_static_data#2fcb26a2
+        parser.unpack::<ConstU32<NFT_GET_STATIC_DATA>>()?;
+        Ok(Self {
+            // query_id:uint64
+            query_id: parser.unpack()?,
+        })
+    }
+}
+
+/// NFT Report Static Data message from [TEP-62](https://github.com/ton-blockchain/TEPs/blob/master/text/0062-nft-standard.md#2-get_static_data)
+/// sent by the NFT item in response to [`NftGetStaticData`]:
+/// ```tlb
+/// report_static_data#8b771735 query_id:uint64 index:uint256 collection:MsgAddress
+/// = InternalMsgBody;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NftReportStaticData {
+    pub query_id: u64,
+    pub index: BigUint,
+    /// [`NULL`](MsgAddress::NULL) if the item does not belong to any
+    /// collection
+    pub collection: MsgAddress,
+}
+
+impl CellSerialize for NftReportStaticData {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // report_static_data#8b771735
+            .pack(NFT_REPORT_STATIC_DATA)?
+            // query_id:uint64
+            .pack(self.query_id)?
+            // index:uint256
+            .pack_as::<_, &NBits<256>>(&self.index)?
+            // collection:MsgAddress
+            .pack(self.collection)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for NftReportStaticData {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        // report_static_data#8b771735
+        parser.unpack::<ConstU32<NFT_REPORT_STATIC_DATA>>()?;
+        Ok(Self {
+            // query_id:uint64
+            query_id: parser.unpack()?,
+            // index:uint256
+            index: parser.unpack_as::<_, NBits<256>>()?,
+            // collection:MsgAddress
+            collection: parser.unpack()?,
+        })
+    }
+}
+
+/// Data of NFT item in
+/// [reference implementation](https://github.com/ton-blockchain/token-contract/blob/main/nft/nft-item.fc)
+/// after it was deployed by the collection:
+/// ```tlb
+/// _ index:uint64 collection_address:MsgAddress owner_address:MsgAddress
+/// content:^Cell = NftItemData;
+/// ```
+///
+/// `content` is usually individual part of [`Content`](super::Content),
+/// e.g. suffix of URI of its metadata, which is combined with
+/// [`common_content`](super::NftCollectionData::common_content) of the
+/// collection by its `get_nft_content` get-method.
+///
+/// Items are deployed by the collection with only `index` and
+/// `collection_address` as initial data, so that their addresses can be
+/// derived from them:
+/// ```rust
+/// # use std::sync::Arc;
+/// # use tlb::Cell;
+/// # use tlb_ton::MsgAddress;
+/// # use ton_contracts::nft::NftItemData;
+/// # let code = Arc::new(Cell::new());
+/// let collection = MsgAddress { workchain_id: 0, address: [1; 32] };
+///
+/// // item code as stored in the collection data
+/// let first = NftItemData::address(0, collection, code.clone()).unwrap();
+/// assert_ne!(first, NftItemData::address(1, collection, code).unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NftItemData<C = Cell> {
+    pub index: u64,
+    pub collection: MsgAddress,
+    pub owner: MsgAddress,
+    pub content: C,
+}
+
+impl NftItemData {
+    /// Initial data of NFT item with given `index` in `collection`
+    pub fn init_data(index: u64, collection: MsgAddress) -> Result<Cell, CellBuilderError> {
+        let mut builder = Cell::builder();
+        builder
+            // index:uint64
+            .pack(index)?
+            // collection_address:MsgAddress
+            .pack(collection)?;
+        Ok(builder.into_cell())
+    }
+
+    /// Derive address of NFT item with given `index` in `collection` from
+    /// `code` of NFT item. Items are deployed in basechain.
+    pub fn address(
+        index: u64,
+        collection: MsgAddress,
+        code: Arc<Cell>,
+    ) -> Result<MsgAddress, CellBuilderError> {
+        MsgAddress::derive(
+            0,
+            StateInit::<_, _> {
+                code: Some(code),
+                data: Some(Self::init_data(index, collection)?),
+                ..Default::default()
+            }
+            .normalize()?,
+        )
+    }
+}
+
+impl<C> CellSerialize for NftItemData<C>
+where
+    C: CellSerialize,
+{
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // index:uint64
+            .pack(self.index)?
+            // collection_address:MsgAddress
+            .pack(self.collection)?
+            // owner_address:MsgAddress
+            .pack(self.owner)?
+            // content:^Cell
+            .store_as::<_, Ref>(&self.content)?;
+        Ok(())
+    }
+}
+
+impl<'de, C> CellDeserialize<'de> for NftItemData<C>
+where
+    C: CellDeserialize<'de>,
+{
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            // index:uint64
+            index: parser.unpack().context("index")?,
+            // collection_address:MsgAddress
+            collection: parser.unpack().context("collection_address")?,
+            // owner_address:MsgAddress
+            owner: parser.unpack().context("owner_address")?,
+            // content:^Cell
+            content: parser.parse_as::<_, Ref<ParseFully>>().context("content")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{jetton::ForwardPayload, nft::SnakeData};
+
+    use super::*;
+
+    const OWNER: MsgAddress = MsgAddress {
+        workchain_id: 0,
+        address: [1; 32],
+    };
+
+    #[test]
+    fn transfer_round_trip() {
+        for forward_payload in [
+            ForwardPayload::Empty,
+            ForwardPayload::Comment("gift".to_string()),
+        ] {
+            let transfer = NftTransfer {
+                query_id: 1,
+                new_owner: OWNER,
+                response_dst: MsgAddress::NULL,
+                custom_payload: None::<Cell>,
+                forward_amount: 1u8.into(),
+                forward_payload,
+            };
+            let cell = transfer.to_cell().unwrap();
+            assert_eq!(cell.peek_uint(32), Some(NFT_TRANSFER.into()));
+            assert_eq!(
+                cell.parse_fully::<NftTransfer<Cell, ForwardPayload>>()
+                    .unwrap(),
+                transfer
+            );
+        }
+    }
+
+    #[test]
+    fn ownership_assigned_round_trip() {
+        let assigned = NftOwnershipAssigned {
+            query_id: 1,
+            prev_owner: OWNER,
+            forward_payload: ForwardPayload::Comment("gift".to_string()),
+        };
+        let cell = assigned.to_cell().unwrap();
+        assert_eq!(cell.peek_uint(32), Some(NFT_OWNERSHIP_ASSIGNED.into()));
+        assert_eq!(
+            cell.parse_fully::<NftOwnershipAssigned<ForwardPayload>>()
+                .unwrap(),
+            assigned
+        );
+    }
+
+    #[test]
+    fn static_data_round_trip() {
+        let get = NftGetStaticData { query_id: 7 };
+        let cell = get.to_cell().unwrap();
+        assert_eq!(cell.data.len(), 32 + 64);
+        assert_eq!(cell.parse_fully::<NftGetStaticData>().unwrap(), get);
+
+        let report = NftReportStaticData {
+            query_id: 7,
+            index: BigUint::from(u64::MAX) << 128,
+            collection: OWNER,
+        };
+        let cell = report.to_cell().unwrap();
+        assert_eq!(cell.data.len(), 32 + 64 + 256 + 267);
+        assert_eq!(cell.parse_fully::<NftReportStaticData>().unwrap(), report);
+    }
+
+    #[test]
+    fn item_data_round_trip() {
+        let mut content = Cell::builder();
+        content.store_as::<_, &SnakeData>(b"5.json").unwrap();
+        let data = NftItemData {
+            index: 5,
+            collection: MsgAddress {
+                workchain_id: 0,
+                address: [2; 32],
+            },
+            owner: OWNER,
+            content: content.into_cell(),
+        }
+        .to_cell()
+        .unwrap();
+        let got: NftItemData = data.parse_fully().unwrap();
+        assert_eq!(got.index, 5);
+        assert_eq!(
+            got.content.parse_fully_as::<Vec<u8>, SnakeData>().unwrap(),
+            b"5.json"
+        );
+
+        // initial data is a prefix of the data of deployed item
+        let init = NftItemData::init_data(5, got.collection).unwrap();
+        assert!(data.data.starts_with(&init.data));
+    }
+
+    #[test]
+    fn item_address() {
+        let collection = MsgAddress {
+            workchain_id: 0,
+            address: [2; 32],
+        };
+        let code = Arc::new(Cell::from_hex_data("deadbeef", 32).unwrap());
+
+        // index:uint64 collection_address:MsgAddress
+        let mut data = Cell::builder();
+        data.pack(5u64).unwrap().pack(collection).unwrap();
+        // split_depth:(Maybe ...) special:(Maybe ...) code:(Maybe ^Cell)
+        // data:(Maybe ^Cell) library:(HashmapE 256 SimpleLib)
+        let mut state_init = Cell::builder();
+        state_init
+            .pack_as::<_, NBits<5>>(0b00110u8)
+            .unwrap()
+            .store_reference(code.clone())
+            .unwrap()
+            .store_as::<_, Ref>(data.into_cell())
+            .unwrap();
+
+        assert_eq!(
+            NftItemData::address(5, collection, code).unwrap(),
+            MsgAddress {
+                workchain_id: 0,
+                address: state_init.into_cell().hash(),
+            }
+        );
+    }
+}
@@ -0,0 +1,7 @@
+//! NFT standard [TEP-62](https://github.com/ton-blockchain/TEPs/blob/master/text/0062-nft-standard.md)
+//! with token data from [TEP-64](https://github.com/ton-blockchain/TEPs/blob/master/text/0064-token-data-standard.md)
+mod collection;
+mod content;
+mod item;
+
+pub use self::{collection::*, content::*, item::*};
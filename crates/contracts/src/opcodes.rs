@@ -0,0 +1,111 @@
+//! Opcodes and prefixes of messages to match bodies against without
+//! parsing them:
+//! ```rust
+//! # use tlb::{bits::de::BitReaderExt, Cell};
+//! # use ton_contracts::opcodes;
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let body = Cell::from_hex_data("7362d09c", 32)?;
+//! match body.parser().unpack::<u32>()? {
+//!     opcodes::JETTON_TRANSFER_NOTIFICATION => { /* incoming jettons */ }
+//!     opcodes::NFT_OWNERSHIP_ASSIGNED => { /* incoming NFT */ }
+//!     opcodes::TEXT_COMMENT => { /* comment */ }
+//!     _ => {}
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+/// Text comment, see `jetton::ForwardPayload::Comment`
+/// ```tlb
+/// text_comment#00000000 text:bytes = InternalMsgBody;
+/// ```
+pub const TEXT_COMMENT: u32 = 0x00000000;
+
+/// [TEP-74](https://github.com/ton-blockchain/TEPs/blob/master/text/0074-jettons-standard.md#1-transfer)
+/// `transfer#0f8a7ea5`, see `jetton::JettonTransfer`
+pub const JETTON_TRANSFER: u32 = 0x0f8a7ea5;
+/// [TEP-74](https://github.com/ton-blockchain/TEPs/blob/master/text/0074-jettons-standard.md#1-transfer)
+/// `transfer_notification#7362d09c`, see
+/// `jetton::JettonTransferNotification`
+pub const JETTON_TRANSFER_NOTIFICATION: u32 = 0x7362d09c;
+/// [TEP-74](https://github.com/ton-blockchain/TEPs/blob/master/text/0074-jettons-standard.md#1-transfer)
+/// `excesses#d53276db`, see `jetton::JettonExcesses`
+pub const JETTON_EXCESSES: u32 = 0xd53276db;
+/// [TEP-74](https://github.com/ton-blockchain/TEPs/blob/master/text/0074-jettons-standard.md#2-burn)
+/// `burn#595f07bc`, see `jetton::JettonBurn`
+pub const JETTON_BURN: u32 = 0x595f07bc;
+/// `internal_transfer#178d4519` between jetton wallets in
+/// [reference implementation](https://github.com/ton-blockchain/token-contract/blob/main/ft/op-codes.fc),
+/// see `jetton::JettonInternalTransfer`
+pub const JETTON_INTERNAL_TRANSFER: u32 = 0x178d4519;
+/// `burn_notification#7bdd97de` from jetton wallet to the minter in
+/// [reference implementation](https://github.com/ton-blockchain/token-contract/blob/main/ft/op-codes.fc)
+pub const JETTON_BURN_NOTIFICATION: u32 = 0x7bdd97de;
+/// [TEP-89](https://github.com/ton-blockchain/TEPs/blob/master/text/0089-jetton-wallet-discovery.md)
+/// `provide_wallet_address#2c76b973`, see
+/// `jetton::JettonProvideWalletAddress`
+pub const JETTON_PROVIDE_WALLET_ADDRESS: u32 = 0x2c76b973;
+/// [TEP-89](https://github.com/ton-blockchain/TEPs/blob/master/text/0089-jetton-wallet-discovery.md)
+/// `take_wallet_address#d1735400`, see
+/// `jetton::JettonTakeWalletAddress`
+pub const JETTON_TAKE_WALLET_ADDRESS: u32 = 0xd1735400;
+
+/// [TEP-62](https://github.com/ton-blockchain/TEPs/blob/master/text/0062-nft-standard.md#1-transfer)
+/// `transfer#5fcc3d14`, see `nft::NftTransfer`
+pub const NFT_TRANSFER: u32 = 0x5fcc3d14;
+/// [TEP-62](https://github.com/ton-blockchain/TEPs/blob/master/text/0062-nft-standard.md#1-transfer)
+/// `ownership_assigned#05138d91`, see
+/// `nft::NftOwnershipAssigned`
+pub const NFT_OWNERSHIP_ASSIGNED: u32 = 0x05138d91;
+/// [TEP-62](https://github.com/ton-blockchain/TEPs/blob/master/text/0062-nft-standard.md#1-transfer)
+/// `excesses#d53276db`, same as [`JETTON_EXCESSES`]
+pub const NFT_EXCESSES: u32 = 0xd53276db;
+/// [TEP-62](https://github.com/ton-blockchain/TEPs/blob/master/text/0062-nft-standard.md#2-get_static_data)
+/// `get_static_data#2fcb26a2`, see `nft::NftGetStaticData`
+pub const NFT_GET_STATIC_DATA: u32 = 0x2fcb26a2;
+/// [TEP-62](https://github.com/ton-blockchain/TEPs/blob/master/text/0062-nft-standard.md#2-get_static_data)
+/// `report_static_data#8b771735`, see
+/// `nft::NftReportStaticData`
+pub const NFT_REPORT_STATIC_DATA: u32 = 0x8b771735;
+
+/// [Wallet V4](https://github.com/ton-blockchain/wallet-contract/blob/main/func/wallet-v4-code.fc)
+/// op to send messages, see `wallet::v4r2::WalletV4R2Op::Send`
+pub const WALLET_V4_SEND: u8 = 0;
+/// [Wallet V4](https://github.com/ton-blockchain/wallet-contract/blob/main/func/wallet-v4-code.fc)
+/// op to deploy and install plugin, see
+/// `wallet::v4r2::WalletV4R2Op::DeployAndInstall`
+pub const WALLET_V4_DEPLOY_AND_INSTALL_PLUGIN: u8 = 1;
+/// [Wallet V4](https://github.com/ton-blockchain/wallet-contract/blob/main/func/wallet-v4-code.fc)
+/// op to install plugin, see `wallet::v4r2::WalletV4R2Op::Install`
+pub const WALLET_V4_INSTALL_PLUGIN: u8 = 2;
+/// [Wallet V4](https://github.com/ton-blockchain/wallet-contract/blob/main/func/wallet-v4-code.fc)
+/// op to remove plugin, see `wallet::v4r2::WalletV4R2Op::Remove`
+pub const WALLET_V4_REMOVE_PLUGIN: u8 = 3;
+/// [Wallet V4](https://github.com/ton-blockchain/wallet-contract/blob/main/func/wallet-v4-code.fc)
+/// request of funds from plugin, i.e. `"plug"`
+pub const WALLET_V4_PLUGIN_REQUEST_FUNDS: u32 = 0x706c7567;
+/// [Wallet V4](https://github.com/ton-blockchain/wallet-contract/blob/main/func/wallet-v4-code.fc)
+/// request of plugin to remove itself, i.e. `"dstr"`
+pub const WALLET_V4_PLUGIN_REMOVE: u32 = 0x64737472;
+
+/// [Wallet V5](https://github.com/ton-blockchain/wallet-contract-v5/blob/main/types.tlb)
+/// prefix of signed external message, i.e. `"sign"`
+pub const WALLET_V5R1_SIGNED_EXTERNAL: u32 = 0x7369676e;
+/// [Wallet V5](https://github.com/ton-blockchain/wallet-contract-v5/blob/main/types.tlb)
+/// prefix of signed internal message, i.e. `"sint"`
+pub const WALLET_V5R1_SIGNED_INTERNAL: u32 = 0x73696e74;
+/// [Wallet V5](https://github.com/ton-blockchain/wallet-contract-v5/blob/main/types.tlb)
+/// prefix of internal message from extension, i.e. `"extn"`
+pub const WALLET_V5R1_EXTENSION_ACTION: u32 = 0x6578746e;
+/// [Wallet V5](https://github.com/ton-blockchain/wallet-contract-v5/blob/main/types.tlb)
+/// `action_send_msg#0ec3c86d` in the list of out actions
+pub const WALLET_V5R1_ACTION_SEND_MSG: u32 = 0x0ec3c86d;
+/// [Wallet V5](https://github.com/ton-blockchain/wallet-contract-v5/blob/main/types.tlb)
+/// `add_extension#02` extended action
+pub const WALLET_V5R1_ADD_EXTENSION: u8 = 0x02;
+/// [Wallet V5](https://github.com/ton-blockchain/wallet-contract-v5/blob/main/types.tlb)
+/// `delete_extension#03` extended action
+pub const WALLET_V5R1_DELETE_EXTENSION: u8 = 0x03;
+/// [Wallet V5](https://github.com/ton-blockchain/wallet-contract-v5/blob/main/types.tlb)
+/// `set_signature_auth_allowed#04` extended action
+pub const WALLET_V5R1_SET_SIGNATURE_AUTH_ALLOWED: u8 = 0x04;
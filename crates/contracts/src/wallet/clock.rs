@@ -0,0 +1,165 @@
+use std::{
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Duration, Utc};
+
+/// Source of current time used to compute expiration of external messages,
+/// see [`PendingExternal::new()`](super::PendingExternal::new).
+///
+/// Use [`SystemClock`] or [`BlockClock`] in production and [`MockClock`]
+/// in tests.
+pub trait Clock {
+    /// Current time
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Expiration time for messages valid for given `ttl` from now
+    #[inline]
+    fn expire_in(&self, ttl: Duration) -> DateTime<Utc> {
+        self.now() + ttl
+    }
+}
+
+impl<C> Clock for &C
+where
+    C: Clock + ?Sized,
+{
+    #[inline]
+    fn now(&self) -> DateTime<Utc> {
+        (**self).now()
+    }
+}
+
+/// [`Clock`] backed by system time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline]
+    fn now(&self) -> DateTime<Utc> {
+        let d = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time is before UNIX epoch");
+        DateTime::from_timestamp(d.as_secs() as i64, d.subsec_nanos())
+            .expect("system time is out of range")
+    }
+}
+
+/// [`Clock`] which is controlled manually, so that expiration can be
+/// tested deterministically.
+/// ```rust
+/// # use chrono::{DateTime, Duration};
+/// # use ton_contracts::wallet::{Clock, MockClock};
+/// let clock = MockClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+/// let expire_at = clock.expire_in(Duration::minutes(1));
+///
+/// clock.advance(Duration::minutes(1));
+/// assert_eq!(clock.now(), expire_at);
+/// ```
+#[derive(Debug, Default)]
+pub struct MockClock(Mutex<DateTime<Utc>>);
+
+impl MockClock {
+    /// Create clock stopped at given time
+    #[inline]
+    pub const fn new(now: DateTime<Utc>) -> Self {
+        Self(Mutex::new(now))
+    }
+
+    /// Set current time
+    #[inline]
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.0.lock().unwrap() = now;
+    }
+
+    /// Move current time forward by given duration
+    #[inline]
+    pub fn advance(&self, d: Duration) {
+        *self.0.lock().unwrap() += d;
+    }
+}
+
+impl Clock for MockClock {
+    #[inline]
+    fn now(&self) -> DateTime<Utc> {
+        *self.0.lock().unwrap()
+    }
+}
+
+/// [`Clock`] following the blockchain time rather than the local one, so
+/// that expiration of external messages is not affected by local clock
+/// drift.
+///
+/// Current time is the timestamp of the latest
+/// [observed](BlockClock::observe_block) block plus time elapsed since
+/// the observation according to the `local` clock, shifted by `skew`.
+/// Positive `skew` compensates the delay between block generation and
+/// its observation. Until any block is observed, `local` clock is used.
+/// ```rust
+/// # use chrono::{DateTime, Duration};
+/// # use ton_contracts::wallet::{BlockClock, Clock, MockClock};
+/// // local clock is 1 hour ahead
+/// let local = MockClock::new(DateTime::from_timestamp(1_700_003_600, 0).unwrap());
+/// let clock = BlockClock::new(&local, Duration::seconds(5));
+///
+/// clock.observe_block(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+/// local.advance(Duration::seconds(10));
+/// assert_eq!(
+///     clock.expire_in(Duration::minutes(1)),
+///     DateTime::from_timestamp(1_700_000_000 + 10 + 5 + 60, 0).unwrap(),
+/// );
+/// ```
+#[derive(Debug)]
+pub struct BlockClock<C = SystemClock> {
+    local: C,
+    skew: Duration,
+    /// timestamp of the latest observed block and local time of observation
+    latest: Mutex<Option<(DateTime<Utc>, DateTime<Utc>)>>,
+}
+
+impl<C> BlockClock<C>
+where
+    C: Clock,
+{
+    /// Create clock with given `local` clock and `skew`
+    #[inline]
+    pub const fn new(local: C, skew: Duration) -> Self {
+        Self {
+            local,
+            skew,
+            latest: Mutex::new(None),
+        }
+    }
+
+    /// Record `gen_utime` of the latest block seen on the blockchain.
+    /// Timestamps older than already observed ones are ignored.
+    pub fn observe_block(&self, gen_utime: DateTime<Utc>) {
+        let mut latest = self.latest.lock().unwrap();
+        if latest.is_some_and(|(block, _)| block >= gen_utime) {
+            return;
+        }
+        *latest = Some((gen_utime, self.local.now()));
+    }
+
+    /// Timestamp of the latest observed block
+    #[inline]
+    pub fn latest_block(&self) -> Option<DateTime<Utc>> {
+        self.latest.lock().unwrap().map(|(block, _)| block)
+    }
+}
+
+impl<C> Clock for BlockClock<C>
+where
+    C: Clock,
+{
+    #[inline]
+    fn now(&self) -> DateTime<Utc> {
+        let now = self.local.now();
+        let now = match *self.latest.lock().unwrap() {
+            Some((block, observed_at)) => block + (now - observed_at),
+            None => now,
+        };
+        now + self.skew
+    }
+}
@@ -0,0 +1,137 @@
+use hex_literal::hex;
+use nacl::sign::PUBLIC_KEY_LENGTH;
+use tlb::{
+    bits::{de::BitReaderExt, label, r#as::Flag},
+    de::{CellParser, CellParserError},
+    Cell,
+};
+use tlb_ton::state_init::StateInit;
+
+label!(IsSignatureAllowed = "is_signature_allowed");
+
+/// Kind of well-known [wallet](https://docs.ton.org/participate/wallets/contracts)
+/// contract, which can be [detected](WalletVersionKind::detect) by its code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WalletVersionKind {
+    V3R1,
+    V3R2,
+    V4R1,
+    V4R2,
+    V5R1,
+}
+
+impl WalletVersionKind {
+    /// All known wallet versions
+    pub const ALL: [Self; 5] = [Self::V3R1, Self::V3R2, Self::V4R1, Self::V4R2, Self::V5R1];
+
+    /// Representation hash of the code cell
+    #[inline]
+    pub const fn code_hash(self) -> [u8; 32] {
+        match self {
+            Self::V3R1 => hex!("b61041a58a7980b946e8fb9e198e3c904d24799ffa36574ea4251c41a566f581"),
+            Self::V3R2 => hex!("84dafa449f98a6987789ba232358072bc0f76dc4524002a5d0918b9a75d2d599"),
+            Self::V4R1 => hex!("64dd54805522c5be8a9db59cea0105ccf0d08786ca79beb8cb79e880a8d7322d"),
+            Self::V4R2 => hex!("feb5ff6820e2ff0d9483e7e0d62c817d846789fb4ae580c878866d959dabd5c0"),
+            Self::V5R1 => hex!("20834b7b72b112147e1b2fb457b84e74d1a30f04f737d4f62a668e9552d2b72f"),
+        }
+    }
+
+    /// Detect wallet version by its code
+    #[inline]
+    pub fn detect(code: &Cell) -> Option<Self> {
+        let hash = code.hash();
+        Self::ALL.into_iter().find(|v| v.code_hash() == hash)
+    }
+
+    /// Detect wallet version by [`StateInit::code`] and parse its
+    /// [`StateInit::data`] according to the data layout of this version.
+    ///
+    /// Returns `Ok(None)` if the code is missing or unknown.
+    pub fn detect_state_init(
+        state_init: &StateInit,
+    ) -> Result<Option<(Self, WalletInfo)>, CellParserError<'_>> {
+        let (Some(code), Some(data)) = (&state_init.code, &state_init.data) else {
+            return Ok(None);
+        };
+        let Some(version) = Self::detect(code) else {
+            return Ok(None);
+        };
+        Ok(Some((version, version.parse_data(data)?)))
+    }
+
+    /// Parse common fields from wallet's persistent data according to the
+    /// data layout of this version.
+    pub fn parse_data(self, data: &Cell) -> Result<WalletInfo, CellParserError<'_>> {
+        let mut parser = data.parser();
+        match self {
+            // seqno:uint32 wallet_id:uint32 public_key:bits256
+            // v4 also has: plugins:(HashmapE (8 + 256) Unit)
+            Self::V3R1 | Self::V3R2 | Self::V4R1 | Self::V4R2 => WalletInfo::parse(&mut parser),
+            // is_signature_allowed:Bool seqno:uint32 wallet_id:uint32
+            // public_key:bits256 extensions_dict:(HashmapE 256 int1)
+            Self::V5R1 => {
+                let _: bool = parser.unpack_as::<_, Flag<IsSignatureAllowed>>()?;
+                WalletInfo::parse(&mut parser)
+            }
+        }
+    }
+}
+
+/// Common fields of wallet's persistent data parsed by
+/// [`WalletVersionKind::parse_data()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalletInfo {
+    pub seqno: u32,
+    pub wallet_id: u32,
+    pub pubkey: [u8; PUBLIC_KEY_LENGTH],
+}
+
+impl WalletInfo {
+    #[inline]
+    fn parse<'de>(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            seqno: parser.unpack()?,
+            wallet_id: parser.unpack()?,
+            pubkey: parser.unpack()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::ser::CellSerializeExt;
+
+    use crate::wallet::{v4r2::V4R2, WalletVersion};
+
+    use super::*;
+
+    #[test]
+    fn detect_v4r2() {
+        let state_init = StateInit {
+            code: Some(V4R2::code().as_ref().clone()),
+            data: Some(
+                V4R2::init_data(V4R2::DEFAULT_WALLET_ID_MAINNET, [1; PUBLIC_KEY_LENGTH])
+                    .to_cell()
+                    .unwrap(),
+            ),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            WalletVersionKind::detect_state_init(&state_init).unwrap(),
+            Some((
+                WalletVersionKind::V4R2,
+                WalletInfo {
+                    seqno: 0,
+                    wallet_id: V4R2::DEFAULT_WALLET_ID_MAINNET,
+                    pubkey: [1; PUBLIC_KEY_LENGTH],
+                }
+            )),
+        );
+    }
+
+    #[test]
+    fn detect_unknown() {
+        assert_eq!(WalletVersionKind::detect(&Cell::new()), None);
+    }
+}
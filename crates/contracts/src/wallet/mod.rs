@@ -1,12 +1,17 @@
 //! TON [Wallet](https://docs.ton.org/participate/wallets/contracts)
+mod clock;
+mod kind;
 pub mod mnemonic;
 pub mod v4r2;
 
+pub use self::{clock::*, kind::*};
+
+use core::fmt::{self, Display};
 use std::{marker::PhantomData, sync::Arc};
 
 use anyhow::anyhow;
-use chrono::{DateTime, Utc};
-use nacl::sign::{signature, Keypair, PUBLIC_KEY_LENGTH};
+use chrono::{DateTime, Duration, Utc};
+use nacl::sign::{signature, verify, Keypair, PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH};
 use num_bigint::BigUint;
 use tlb::{
     bits::{de::BitReaderExt, ser::BitWriterExt},
@@ -16,22 +21,33 @@ use tlb::{
     Cell,
 };
 use tlb_ton::{
+    action::SendMsgAction,
     message::{CommonMsgInfo, ExternalInMsgInfo, Message},
     state_init::StateInit,
     MsgAddress,
 };
 
+#[deprecated(note = "use WalletVersion::DEFAULT_WALLET_ID_MAINNET instead")]
 pub const DEFAULT_WALLET_ID: u32 = 0x29a9a317;
 
+/// TON network, which default [wallet id](WalletVersion::default_wallet_id)
+/// might depend on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+}
+
 /// Generic wallet for signing messages
 ///
 /// ```rust
-/// # use ton_contracts::wallet::{mnemonic::Mnemonic, Wallet, v4r2::V4R2};
+/// # use ton_contracts::wallet::{mnemonic::Mnemonic, Network, Wallet, v4r2::V4R2};
 /// let mnemonic: Mnemonic = "jewel loop vast intact snack drip fatigue lunch erode green indoor balance together scrub hen monster hour narrow banner warfare increase panel sound spell"
 ///     .parse()
 ///     .unwrap();
 /// let keypair = mnemonic.generate_keypair(None).unwrap();
-/// let wallet = Wallet::<V4R2>::derive_default(keypair).unwrap();
+/// let wallet = Wallet::<V4R2>::derive_default_for(keypair, Network::Mainnet).unwrap();
 ///
 /// assert_eq!(
 ///     wallet.address(),
@@ -41,8 +57,10 @@ pub const DEFAULT_WALLET_ID: u32 = 0x29a9a317;
 pub struct Wallet<V> {
     address: MsgAddress,
     wallet_id: u32,
-    key_pair: Keypair,
-    _phantom: PhantomData<V>,
+    pubkey: [u8; PUBLIC_KEY_LENGTH],
+    /// `None` if messages are signed externally
+    skey: Option<[u8; SECRET_KEY_LENGTH]>,
+    _phantom: PhantomData<fn() -> V>,
 }
 
 impl<V> Wallet<V>
@@ -51,25 +69,55 @@ where
 {
     /// Derive wallet from its workchain, keypair and id
     pub fn derive(workchain_id: i32, key_pair: Keypair, wallet_id: u32) -> anyhow::Result<Self> {
+        Ok(Self {
+            skey: Some(key_pair.skey),
+            ..Self::derive_with_pubkey(workchain_id, key_pair.pkey, wallet_id)?
+        })
+    }
+
+    /// Derive wallet from its workchain, public key and id without access
+    /// to the private key.
+    ///
+    /// Such wallet can not [sign](Wallet::sign_body) messages itself,
+    /// use [`.external_message_from_signature()`](Wallet::external_message_from_signature)
+    /// or [`.assemble_external_message()`](Wallet::assemble_external_message)
+    /// with signatures produced elsewhere, i.e. by threshold-signature
+    /// backends.
+    pub fn derive_with_pubkey(
+        workchain_id: i32,
+        pubkey: [u8; PUBLIC_KEY_LENGTH],
+        wallet_id: u32,
+    ) -> anyhow::Result<Self> {
         Ok(Self {
             address: MsgAddress::derive(
                 workchain_id,
                 StateInit::<_, _> {
                     code: Some(V::code()),
-                    data: Some(V::init_data(wallet_id, key_pair.pkey)),
+                    data: Some(V::init_data(wallet_id, pubkey)),
                     ..Default::default()
                 }
                 .normalize()?,
             )?,
             wallet_id,
-            key_pair,
+            pubkey,
+            skey: None,
             _phantom: PhantomData,
         })
     }
 
-    /// Shortcut for [`Wallet::derive()`] with default workchain and wallet id
+    /// Shortcut for [`Wallet::derive()`] with default workchain and
+    /// [default wallet id](WalletVersion::default_wallet_id) for given
+    /// network
+    pub fn derive_default_for(key_pair: Keypair, network: Network) -> anyhow::Result<Self> {
+        Self::derive(0, key_pair, V::default_wallet_id(network))
+    }
+
+    /// Same as [`Wallet::derive_default_for()`] with [`Network::Mainnet`]
+    #[deprecated(
+        note = "default wallet id depends on network, use Wallet::derive_default_for() instead"
+    )]
     pub fn derive_default(key_pair: Keypair) -> anyhow::Result<Self> {
-        Self::derive(0, key_pair, DEFAULT_WALLET_ID)
+        Self::derive_default_for(key_pair, Network::Mainnet)
     }
 
     /// Address of the wallet
@@ -84,15 +132,25 @@ where
         self.wallet_id
     }
 
+    /// Public key of the wallet
+    #[inline]
+    pub const fn pubkey(&self) -> [u8; PUBLIC_KEY_LENGTH] {
+        self.pubkey
+    }
+
     /// Shortcut to [create](Wallet::create_external_body),
     /// [sign](Wallet::sign_body) and [wrap](Wallet::wrap_signed) external
     /// message ready for sending to TON blockchain.
     ///
+    /// Returns an error if there are more than
+    /// [`MAX_MESSAGES`](WalletVersion::MAX_MESSAGES) messages.
+    ///
     /// ```rust
-    /// # use tlb_ton::{message::Message, currency::ONE_TON};
+    /// # use tlb_ton::{message::Message, currency::consts::TON};
     /// # use ton_contracts::wallet::{
     /// #   mnemonic::Mnemonic,
     /// #   v4r2::V4R2,
+    /// #   Network,
     /// #   Wallet,
     /// #   WalletOpSendMessage,
     /// # };
@@ -100,7 +158,7 @@ where
     /// #     .parse()
     /// #     .unwrap();
     /// # let keypair = mnemonic.generate_keypair(None).unwrap();
-    /// # let wallet = Wallet::<V4R2>::derive_default(keypair).unwrap();
+    /// # let wallet = Wallet::<V4R2>::derive_default_for(keypair, Network::Mainnet).unwrap();
     /// let msg = wallet.create_external_message(
     ///     Default::default(), // DateTime::UNIX_EPOCH means no deadline
     ///     0, // seqno
@@ -110,7 +168,7 @@ where
     ///             "EQAWezezpqKTbO6xjCussXDdIeJ7XxTcErjA6uD3T3r7AwTk"
     ///                 .parse()
     ///                 .unwrap(),
-    ///             ONE_TON.clone(),
+    ///             TON.into(),
     ///             false,
     ///         )
     ///             .normalize()
@@ -127,29 +185,49 @@ where
         msgs: impl IntoIterator<Item = WalletOpSendMessage>,
         state_init: bool,
     ) -> anyhow::Result<Message<SignedBody, Arc<Cell>, V::Data>> {
-        let body = self.create_external_body(expire_at, seqno, msgs);
+        let body = self.create_external_body(expire_at, seqno, msgs)?;
         let signed = self.sign_body(&body)?;
         let wrapped = self.wrap_signed(signed, state_init);
         Ok(wrapped)
     }
 
     /// Create external body for this wallet.
+    ///
+    /// Returns an error if there are more than
+    /// [`MAX_MESSAGES`](WalletVersion::MAX_MESSAGES) messages.
     #[inline]
     pub fn create_external_body(
         &self,
         expire_at: DateTime<Utc>,
         seqno: u32,
         msgs: impl IntoIterator<Item = WalletOpSendMessage>,
-    ) -> V::MessageBody {
-        V::create_external_body(self.wallet_id, expire_at, seqno, msgs)
+    ) -> anyhow::Result<V::MessageBody> {
+        let msgs: Vec<_> = msgs.into_iter().collect();
+        if msgs.len() > V::MAX_MESSAGES {
+            return Err(anyhow!(
+                "too many messages: {}, max: {}",
+                msgs.len(),
+                V::MAX_MESSAGES
+            ));
+        }
+        Ok(V::create_external_body(
+            self.wallet_id,
+            expire_at,
+            seqno,
+            msgs,
+        ))
     }
 
     /// Sign body from [`.create_external_body()`](Wallet::create_external_body)
     /// using this wallet's private key
     pub fn sign_body(&self, msg: &V::MessageBody) -> anyhow::Result<SignedBody> {
+        let skey = self
+            .skey
+            .as_ref()
+            .ok_or_else(|| anyhow!("private key is not available, sign externally"))?;
         let msg = msg.to_cell()?;
         Ok(SignedBody {
-            sig: signature(msg.hash().as_slice(), self.key_pair.skey.as_slice())
+            sig: signature(msg.hash().as_slice(), skey.as_slice())
                 .map_err(|e| anyhow!("{}", e.message))?
                 .try_into()
                 .map_err(|sig: Vec<_>| {
@@ -162,6 +240,113 @@ where
         })
     }
 
+    /// Hash of the body from [`.create_external_body()`](Wallet::create_external_body)
+    /// to be signed by external signer.
+    #[inline]
+    pub fn signing_hash(&self, msg: &V::MessageBody) -> anyhow::Result<[u8; 32]> {
+        Ok(msg.to_cell()?.hash())
+    }
+
+    /// Idempotency key of the external message with given body, see
+    /// [`DedupKey`].
+    ///
+    /// `seqno` must be the same as the one the body was
+    /// [created](Wallet::create_external_body) with.
+    #[inline]
+    pub fn dedup_key(&self, seqno: u32, msg: &V::MessageBody) -> anyhow::Result<DedupKey> {
+        Ok(DedupKey {
+            address: self.address,
+            seqno,
+            body_hash: self.signing_hash(msg)?,
+        })
+    }
+
+    /// Wrap body from [`.create_external_body()`](Wallet::create_external_body)
+    /// with signature of its [`.signing_hash()`](Wallet::signing_hash)
+    /// produced elsewhere into a message ready for sending to TON blockchain.
+    ///
+    /// The signature is verified against wallet's public key.
+    pub fn external_message_from_signature(
+        &self,
+        msg: &V::MessageBody,
+        sig: [u8; 64],
+        state_init: bool,
+    ) -> anyhow::Result<Message<SignedBody, Arc<Cell>, V::Data>> {
+        let msg = msg.to_cell()?;
+        if !verify(&sig, msg.hash().as_slice(), &self.pubkey)
+            .map_err(|e| anyhow!("{}", e.message))?
+        {
+            return Err(anyhow!("invalid signature"));
+        }
+        Ok(self.wrap_signed(SignedBody { sig, msg }, state_init))
+    }
+
+    /// Assemble signature of [`.signing_hash()`](Wallet::signing_hash)
+    /// from given parts using [`SignatureAssembler`] and wrap the body into
+    /// a message ready for sending to TON blockchain.
+    pub fn assemble_external_message<A>(
+        &self,
+        assembler: &A,
+        msg: &V::MessageBody,
+        parts: impl IntoIterator<Item = A::Part>,
+        state_init: bool,
+    ) -> anyhow::Result<Message<SignedBody, Arc<Cell>, V::Data>>
+    where
+        A: SignatureAssembler,
+    {
+        let sig = assembler.assemble(self.signing_hash(msg)?, parts)?;
+        self.external_message_from_signature(msg, sig, state_init)
+    }
+
+    /// Create operation that sends the whole balance of the wallet to
+    /// `beneficiary` and destroys the wallet contract, i.e. transfer with
+    /// zero value and mode `160`
+    /// ([`CARRY_ALL_BALANCE`](SendMsgAction::CARRY_ALL_BALANCE) |
+    /// [`DESTROY_IF_ZERO`](SendMsgAction::DESTROY_IF_ZERO)).
+    ///
+    /// The message is non-bounceable, so that funds are not bounced back
+    /// to the destroyed wallet. Sending it to the wallet itself is
+    /// rejected.
+    /// ```rust
+    /// # use ton_contracts::wallet::{
+    /// #     mnemonic::Mnemonic, v4r2::V4R2, DestroyConfirmation, Network, Wallet,
+    /// # };
+    /// # use chrono::DateTime;
+    /// # let mnemonic: Mnemonic = "jewel loop vast intact snack drip fatigue lunch erode green indoor balance together scrub hen monster hour narrow banner warfare increase panel sound spell"
+    /// #     .parse()
+    /// #     .unwrap();
+    /// # let wallet = Wallet::<V4R2>::derive_default_for(mnemonic.generate_keypair(None).unwrap(), Network::Mainnet).unwrap();
+    /// let op = wallet
+    ///     .create_destroy_message(
+    ///         "EQAWezezpqKTbO6xjCussXDdIeJ7XxTcErjA6uD3T3r7AwTk".parse().unwrap(),
+    ///         DestroyConfirmation::SendAllBalanceAndDestroyWallet,
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(op.mode, 160);
+    /// let msg = wallet.create_external_message(DateTime::UNIX_EPOCH, 0, [op], false);
+    /// ```
+    pub fn create_destroy_message(
+        &self,
+        beneficiary: MsgAddress,
+        confirm: DestroyConfirmation,
+    ) -> anyhow::Result<WalletOpSendMessage> {
+        let DestroyConfirmation::SendAllBalanceAndDestroyWallet = confirm;
+        if beneficiary.is_null() {
+            return Err(anyhow!("beneficiary address is not set"));
+        }
+        if beneficiary == self.address {
+            return Err(anyhow!("beneficiary must differ from the wallet itself"));
+        }
+        let action = SendMsgAction::builder()
+            .mode(SendMsgAction::CARRY_ALL_BALANCE | SendMsgAction::DESTROY_IF_ZERO)
+            .message(Message::<()>::transfer(beneficiary, BigUint::ZERO, false).normalize()?)
+            .build()?;
+        Ok(WalletOpSendMessage {
+            mode: action.mode,
+            message: action.message,
+        })
+    }
+
     /// Wrap signed body from [`.sign_body()`](Wallet::sign_body) in a message
     /// ready for sending to TON blockchain.
     #[inline]
@@ -178,7 +363,7 @@ where
             }),
             init: state_init.then(|| StateInit::<_, _> {
                 code: Some(V::code()),
-                data: Some(V::init_data(self.wallet_id, self.key_pair.pkey)),
+                data: Some(V::init_data(self.wallet_id, self.pubkey)),
                 ..Default::default()
             }),
             body,
@@ -186,6 +371,226 @@ where
     }
 }
 
+/// Explicit acknowledgement required by
+/// [`Wallet::create_destroy_message()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DestroyConfirmation {
+    /// The whole balance is sent and the wallet contract is destroyed
+    /// along with its code and data, so it has to be deployed again
+    /// before it can send anything.
+    SendAllBalanceAndDestroyWallet,
+}
+
+/// Hook for threshold-signature backends (e.g. FROST), which produce
+/// final Ed25519 signature from partial signatures of key holders.
+///
+/// See [`Wallet::assemble_external_message()`].
+pub trait SignatureAssembler {
+    /// Partial signature
+    type Part;
+
+    /// Assemble signature of given hash from its parts
+    fn assemble(
+        &self,
+        hash: [u8; 32],
+        parts: impl IntoIterator<Item = Self::Part>,
+    ) -> anyhow::Result<[u8; 64]>;
+}
+
+/// Inputs of an external message which was sent to the [`Wallet`] but
+/// not yet applied, so it can be safely re-signed on retries.
+///
+/// When an external message expires without being applied, it should be
+/// re-sent with the **same** `seqno` and new `expire_at`. Bumping `seqno`
+/// instead would leave the wallet waiting for a message that will never
+/// arrive. See [`.retry()`](PendingExternal::retry) for the retry policy.
+///
+/// ```rust
+/// # use chrono::{DateTime, Duration, Utc};
+/// # use tlb_ton::{action::SendMsgAction, message::Message, currency::consts::TON};
+/// # use ton_contracts::wallet::{
+/// #   mnemonic::Mnemonic,
+/// #   v4r2::V4R2,
+/// #   Clock,
+/// #   MockClock,
+/// #   Network,
+/// #   PendingExternal,
+/// #   Wallet,
+/// #   WalletOpSendMessage,
+/// # };
+/// # let mnemonic: Mnemonic = "jewel loop vast intact snack drip fatigue lunch erode green indoor balance together scrub hen monster hour narrow banner warfare increase panel sound spell"
+/// #     .parse()
+/// #     .unwrap();
+/// # let keypair = mnemonic.generate_keypair(None).unwrap();
+/// # let wallet = Wallet::<V4R2>::derive_default_for(keypair, Network::Mainnet).unwrap();
+/// // use SystemClock in production
+/// let clock = MockClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+/// let pending = PendingExternal::new(
+///     &clock,
+///     Duration::minutes(1),
+///     5, // seqno
+///     vec![WalletOpSendMessage {
+///         mode: SendMsgAction::PAY_FEES_SEPARATELY | SendMsgAction::IGNORE_ERRORS,
+///         message: Message::<()>::transfer(
+///             "EQAWezezpqKTbO6xjCussXDdIeJ7XxTcErjA6uD3T3r7AwTk"
+///                 .parse()
+///                 .unwrap(),
+///             TON.into(),
+///             false,
+///         )
+///             .normalize()
+///             .unwrap(),
+///     }],
+///     false, // do not deploy wallet
+/// );
+/// let msg = pending.sign(&wallet).unwrap();
+/// // ... send msg
+///
+/// // later, the message has expired and wallet's seqno is still 5
+/// clock.advance(Duration::minutes(2));
+/// assert!(pending.is_expired(clock.now()));
+/// let retry = pending.retry_in(5, &clock, Duration::minutes(1)).unwrap();
+/// assert_eq!(retry.seqno, 5);
+/// let msg = retry.sign(&wallet).unwrap();
+/// // ... send msg again
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingExternal {
+    /// Expiration time, [`DateTime::UNIX_EPOCH`] means no deadline
+    pub expire_at: DateTime<Utc>,
+    pub seqno: u32,
+    pub msgs: Vec<WalletOpSendMessage>,
+    /// Whether to include [`StateInit`] to deploy the wallet
+    pub state_init: bool,
+}
+
+impl PendingExternal {
+    /// Create pending message, which expires after given `ttl` from current
+    /// time of the `clock`
+    #[inline]
+    pub fn new(
+        clock: impl Clock,
+        ttl: Duration,
+        seqno: u32,
+        msgs: impl IntoIterator<Item = WalletOpSendMessage>,
+        state_init: bool,
+    ) -> Self {
+        Self {
+            expire_at: clock.expire_in(ttl),
+            seqno,
+            msgs: msgs.into_iter().collect(),
+            state_init,
+        }
+    }
+
+    /// Build and sign external message for given wallet
+    #[inline]
+    pub fn sign<V>(
+        &self,
+        wallet: &Wallet<V>,
+    ) -> anyhow::Result<Message<SignedBody, Arc<Cell>, V::Data>>
+    where
+        V: WalletVersion,
+    {
+        wallet.create_external_message(
+            self.expire_at,
+            self.seqno,
+            self.msgs.iter().cloned(),
+            self.state_init,
+        )
+    }
+
+    /// Idempotency key of the message built for given wallet, see
+    /// [`DedupKey`]
+    #[inline]
+    pub fn dedup_key<V>(&self, wallet: &Wallet<V>) -> anyhow::Result<DedupKey>
+    where
+        V: WalletVersion,
+    {
+        wallet.dedup_key(
+            self.seqno,
+            &wallet.create_external_body(self.expire_at, self.seqno, self.msgs.iter().cloned())?,
+        )
+    }
+
+    /// Returns whether the message can no longer be accepted by the wallet
+    /// at given time.
+    #[inline]
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expire_at != DateTime::UNIX_EPOCH && self.expire_at <= now
+    }
+
+    /// Same message with new expiration time
+    #[inline]
+    #[must_use]
+    pub fn with_expire_at(self, expire_at: DateTime<Utc>) -> Self {
+        Self { expire_at, ..self }
+    }
+
+    /// Same message rebuilt for another seqno
+    #[inline]
+    #[must_use]
+    pub fn with_seqno(self, seqno: u32) -> Self {
+        Self { seqno, ..self }
+    }
+
+    /// Decide how to retry this message given current on-chain `seqno` of
+    /// the wallet:
+    /// * `seqno` is the same: the message was not applied, so it is re-built
+    ///   with the same `seqno` and new `expire_at`.
+    /// * `seqno` is greater: the `seqno` was already consumed, either by
+    ///   this message or by another one. It's not safe to retry without
+    ///   checking wallet's transactions, so `None` is returned. Use
+    ///   [`.with_seqno()`](PendingExternal::with_seqno) explicitly once
+    ///   it's known that this message was not applied.
+    /// * `seqno` is lower: wallet's `seqno` never moves backwards, so the
+    ///   state it was read from is stale and `None` is returned as well.
+    #[inline]
+    pub fn retry(&self, seqno: u32, expire_at: DateTime<Utc>) -> Option<Self> {
+        if seqno != self.seqno {
+            return None;
+        }
+        Some(self.clone().with_expire_at(expire_at))
+    }
+
+    /// Same as [`.retry()`](PendingExternal::retry), but new expiration
+    /// time is given `ttl` from current time of the `clock`
+    #[inline]
+    pub fn retry_in(&self, seqno: u32, clock: impl Clock, ttl: Duration) -> Option<Self> {
+        self.retry(seqno, clock.expire_in(ttl))
+    }
+}
+
+/// Idempotency key of an outgoing external message, which is known before
+/// the message is broadcasted.
+///
+/// Two messages with equal keys are the same transfer, so retry layers can
+/// use it to detect duplicates. Note that re-building the message with new
+/// `expire_at` (see [`PendingExternal::retry()`]) changes the body and
+/// produces a new key, while wallet's `seqno` still guarantees that at
+/// most one of them is applied.
+///
+/// Displayed as `{address}:{seqno}:{body_hash}`, where `body_hash` is in
+/// lower hex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DedupKey {
+    /// Address of the wallet
+    pub address: MsgAddress,
+    pub seqno: u32,
+    /// [Signing hash](Wallet::signing_hash) of the body
+    pub body_hash: [u8; 32],
+}
+
+impl Display for DedupKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:", self.address.display_raw(), self.seqno)?;
+        for b in self.body_hash {
+            write!(f, "{b:02x}")?;
+        }
+        Ok(())
+    }
+}
+
 /// Signed body retuned from [`Wallet::sign_body()`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SignedBody<T = Cell> {
@@ -222,6 +627,27 @@ pub trait WalletVersion {
     type Data: CellSerialize;
     type MessageBody: CellSerialize;
 
+    /// Default wallet id on [mainnet](Network::Mainnet) for wallets in
+    /// basechain
+    const DEFAULT_WALLET_ID_MAINNET: u32;
+
+    /// Default wallet id on [testnet](Network::Testnet) for wallets in
+    /// basechain
+    const DEFAULT_WALLET_ID_TESTNET: u32;
+
+    /// Maximum number of messages the wallet can send in a single
+    /// external message
+    const MAX_MESSAGES: usize;
+
+    /// Default wallet id for given network
+    #[inline]
+    fn default_wallet_id(network: Network) -> u32 {
+        match network {
+            Network::Mainnet => Self::DEFAULT_WALLET_ID_MAINNET,
+            Network::Testnet => Self::DEFAULT_WALLET_ID_TESTNET,
+        }
+    }
+
     /// Code of the wallet for use with [`StateInit`]
     fn code() -> Arc<Cell>;
 
@@ -238,6 +664,7 @@ pub trait WalletVersion {
 }
 
 /// Operation for [`Wallet`] to send message
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WalletOpSendMessage<T = Cell, IC = Cell, ID = Cell> {
     /// See <https://docs.ton.org/develop/func/stdlib#send_raw_message>
     pub mode: u8,
@@ -271,3 +698,290 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{mnemonic::Mnemonic, v4r2::V4R2, *};
+
+    fn key_pair() -> Keypair {
+        let mnemonic: Mnemonic = "jewel loop vast intact snack drip fatigue lunch erode green indoor balance together scrub hen monster hour narrow banner warfare increase panel sound spell"
+            .parse()
+            .unwrap();
+        mnemonic.generate_keypair(None).unwrap()
+    }
+
+    /// Assembler for single-party "threshold" scheme
+    struct SingleParty;
+
+    impl SignatureAssembler for SingleParty {
+        type Part = [u8; 64];
+
+        fn assemble(
+            &self,
+            _hash: [u8; 32],
+            parts: impl IntoIterator<Item = Self::Part>,
+        ) -> anyhow::Result<[u8; 64]> {
+            parts
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("no signature parts"))
+        }
+    }
+
+    #[test]
+    fn external_message_from_signature() {
+        let signer = Wallet::<V4R2>::derive_default_for(key_pair(), Network::Mainnet).unwrap();
+        let wallet =
+            Wallet::<V4R2>::derive_with_pubkey(0, signer.pubkey(), V4R2::DEFAULT_WALLET_ID_MAINNET)
+                .unwrap();
+        assert_eq!(wallet.address(), signer.address());
+
+        let body = wallet
+            .create_external_body(DateTime::UNIX_EPOCH, 0, [])
+            .unwrap();
+        assert!(wallet.sign_body(&body).is_err());
+
+        let signed = signer.sign_body(&body).unwrap();
+        assert_eq!(wallet.signing_hash(&body).unwrap(), signed.msg.hash());
+
+        let expected = signer.wrap_signed(signed.clone(), true);
+        assert_eq!(
+            wallet
+                .external_message_from_signature(&body, signed.sig, true)
+                .unwrap(),
+            expected,
+        );
+        assert_eq!(
+            wallet
+                .assemble_external_message(&SingleParty, &body, [signed.sig], true)
+                .unwrap(),
+            expected,
+        );
+        assert!(wallet
+            .external_message_from_signature(&body, [0; 64], true)
+            .is_err());
+    }
+
+    /// [`V4R2`] with distinct default wallet id on testnet, as V4R2 itself
+    /// uses the same one on both networks
+    struct NetworkAware;
+
+    impl WalletVersion for NetworkAware {
+        type Data = <V4R2 as WalletVersion>::Data;
+        type MessageBody = <V4R2 as WalletVersion>::MessageBody;
+
+        const DEFAULT_WALLET_ID_MAINNET: u32 = V4R2::DEFAULT_WALLET_ID_MAINNET;
+        const DEFAULT_WALLET_ID_TESTNET: u32 = 0x7fffff11;
+        const MAX_MESSAGES: usize = V4R2::MAX_MESSAGES;
+
+        fn code() -> Arc<Cell> {
+            V4R2::code()
+        }
+
+        fn init_data(wallet_id: u32, pubkey: [u8; PUBLIC_KEY_LENGTH]) -> Self::Data {
+            V4R2::init_data(wallet_id, pubkey)
+        }
+
+        fn create_external_body(
+            wallet_id: u32,
+            expire_at: DateTime<Utc>,
+            seqno: u32,
+            msgs: impl IntoIterator<Item = WalletOpSendMessage>,
+        ) -> Self::MessageBody {
+            V4R2::create_external_body(wallet_id, expire_at, seqno, msgs)
+        }
+    }
+
+    #[test]
+    fn derive_default_network() {
+        let mainnet =
+            Wallet::<NetworkAware>::derive_default_for(key_pair(), Network::Mainnet).unwrap();
+        let testnet =
+            Wallet::<NetworkAware>::derive_default_for(key_pair(), Network::Testnet).unwrap();
+        assert_eq!(mainnet.wallet_id(), NetworkAware::DEFAULT_WALLET_ID_MAINNET);
+        assert_eq!(testnet.wallet_id(), NetworkAware::DEFAULT_WALLET_ID_TESTNET);
+        assert_ne!(mainnet.address(), testnet.address());
+        assert_eq!(
+            testnet.address(),
+            Wallet::<NetworkAware>::derive(0, key_pair(), 0x7fffff11)
+                .unwrap()
+                .address(),
+        );
+
+        #[allow(deprecated)]
+        let default = Wallet::<NetworkAware>::derive_default(key_pair()).unwrap();
+        assert_eq!(default.wallet_id(), mainnet.wallet_id());
+        assert_eq!(default.address(), mainnet.address());
+    }
+
+    #[test]
+    fn derive_default_network_v4r2() {
+        assert_eq!(
+            Wallet::<V4R2>::derive_default_for(key_pair(), Network::Mainnet)
+                .unwrap()
+                .address(),
+            Wallet::<V4R2>::derive_default_for(key_pair(), Network::Testnet)
+                .unwrap()
+                .address(),
+        );
+    }
+
+    #[test]
+    fn create_external_message_max_messages() {
+        let wallet = Wallet::<V4R2>::derive_default_for(key_pair(), Network::Mainnet).unwrap();
+        let op = wallet
+            .create_destroy_message(
+                MsgAddress {
+                    workchain_id: 0,
+                    address: [1; 32],
+                },
+                DestroyConfirmation::SendAllBalanceAndDestroyWallet,
+            )
+            .unwrap();
+        for (n, ok) in [(V4R2::MAX_MESSAGES, true), (V4R2::MAX_MESSAGES + 1, false)] {
+            assert_eq!(
+                wallet
+                    .create_external_body(DateTime::UNIX_EPOCH, 0, vec![op.clone(); n])
+                    .is_ok(),
+                ok,
+                "{n}"
+            );
+            assert_eq!(
+                wallet
+                    .create_external_message(DateTime::UNIX_EPOCH, 0, vec![op.clone(); n], false)
+                    .is_ok(),
+                ok,
+                "{n}"
+            );
+        }
+    }
+
+    #[test]
+    fn create_destroy_message() {
+        let wallet = Wallet::<V4R2>::derive_default_for(key_pair(), Network::Mainnet).unwrap();
+        let beneficiary = MsgAddress {
+            workchain_id: 0,
+            address: [1; 32],
+        };
+        let op = wallet
+            .create_destroy_message(
+                beneficiary,
+                DestroyConfirmation::SendAllBalanceAndDestroyWallet,
+            )
+            .unwrap();
+        assert_eq!(op.mode, 160);
+        let CommonMsgInfo::Internal(info) = &op.message.info else {
+            panic!("internal message expected");
+        };
+        assert_eq!(info.dst, beneficiary);
+        assert_eq!(info.value.grams, BigUint::ZERO);
+        assert!(!info.bounce);
+
+        for beneficiary in [wallet.address(), MsgAddress::NULL] {
+            assert!(wallet
+                .create_destroy_message(
+                    beneficiary,
+                    DestroyConfirmation::SendAllBalanceAndDestroyWallet,
+                )
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn pending_external_retry() {
+        let now = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let pending = PendingExternal {
+            expire_at: now,
+            seqno: 5,
+            msgs: Vec::new(),
+            state_init: false,
+        };
+        assert!(pending.is_expired(now));
+        assert!(!pending
+            .clone()
+            .with_expire_at(DateTime::UNIX_EPOCH)
+            .is_expired(now));
+
+        let expire_at = now + Duration::minutes(1);
+        assert_eq!(
+            pending.retry(5, expire_at),
+            Some(PendingExternal {
+                expire_at,
+                ..pending.clone()
+            }),
+        );
+        assert_eq!(pending.retry(4, expire_at), None);
+        assert_eq!(pending.retry(6, expire_at), None);
+    }
+
+    #[test]
+    fn pending_external_clock() {
+        let clock = MockClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let ttl = Duration::minutes(1);
+        let pending = PendingExternal::new(&clock, ttl, 5, [], false);
+        assert_eq!(pending.expire_at, clock.now() + ttl);
+
+        clock.advance(Duration::seconds(59));
+        assert!(!pending.is_expired(clock.now()));
+        clock.advance(Duration::seconds(1));
+        assert!(pending.is_expired(clock.now()));
+
+        let retry = pending.retry_in(5, &clock, ttl).unwrap();
+        assert_eq!(retry.seqno, 5);
+        assert_eq!(retry.expire_at, clock.now() + ttl);
+        assert!(!retry.is_expired(clock.now()));
+    }
+
+    #[test]
+    fn pending_external_block_clock() {
+        let local = MockClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let clock = BlockClock::new(&local, Duration::zero());
+        assert_eq!(clock.now(), local.now());
+
+        // local clock is 5 minutes behind the chain
+        let block = local.now() + Duration::minutes(5);
+        clock.observe_block(block);
+        clock.observe_block(block - Duration::seconds(1));
+        assert_eq!(clock.latest_block(), Some(block));
+
+        local.advance(Duration::seconds(3));
+        let ttl = Duration::minutes(1);
+        let pending = PendingExternal::new(&clock, ttl, 5, [], false);
+        assert_eq!(pending.expire_at, block + Duration::seconds(3) + ttl);
+        assert!(!pending.is_expired(clock.now()));
+    }
+
+    #[test]
+    fn dedup_key() {
+        let wallet = Wallet::<V4R2>::derive_default_for(key_pair(), Network::Mainnet).unwrap();
+        let clock = MockClock::new(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+        let ttl = Duration::minutes(1);
+        let pending = PendingExternal::new(&clock, ttl, 5, [], false);
+
+        let key = pending.dedup_key(&wallet).unwrap();
+        assert_eq!(key.address, wallet.address());
+        assert_eq!(key.seqno, 5);
+        assert_eq!(
+            key.body_hash,
+            pending.sign(&wallet).unwrap().body.msg.hash()
+        );
+        assert_eq!(pending.dedup_key(&wallet).unwrap(), key);
+        assert!(key
+            .to_string()
+            .starts_with(&format!("{}:5:", wallet.address().display_raw())));
+
+        assert_ne!(
+            pending.clone().with_seqno(6).dedup_key(&wallet).unwrap(),
+            key
+        );
+        clock.advance(ttl);
+        assert_ne!(
+            pending
+                .retry_in(5, &clock, ttl)
+                .unwrap()
+                .dedup_key(&wallet)
+                .unwrap(),
+            key
+        );
+    }
+}
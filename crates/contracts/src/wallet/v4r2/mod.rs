@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::BTreeSet, sync::Arc};
 
 use chrono::{DateTime, Utc};
 use lazy_static::lazy_static;
@@ -6,26 +6,30 @@ use nacl::sign::PUBLIC_KEY_LENGTH;
 use num_bigint::BigUint;
 use tlb::{
     bits::{de::BitReaderExt, ser::BitWriterExt},
-    de::{CellDeserialize, CellParser, CellParserError},
-    r#as::{NoArgs, Ref},
-    ser::{CellBuilder, CellBuilderError, CellSerialize},
-    Cell,
+    de::{r#as::CellDeserializeAs, CellDeserialize, CellParser, CellParserError},
+    r#as::Ref,
+    ser::{r#as::CellSerializeAs, CellBuilder, CellBuilderError, CellSerialize},
+    Cell, Error, ResultExt,
 };
 use tlb_ton::{
-    boc::BagOfCells, currency::Grams, hashmap::HashmapE, state_init::StateInit, MsgAddress,
+    boc::CodeCellCache, currency::Grams, hashmap::Dict, state_init::StateInit, MsgAddress,
     UnixTimestamp,
 };
 
-use super::{WalletOpSendMessage, WalletVersion};
+use crate::opcodes::{
+    WALLET_V4_DEPLOY_AND_INSTALL_PLUGIN, WALLET_V4_INSTALL_PLUGIN, WALLET_V4_REMOVE_PLUGIN,
+    WALLET_V4_SEND,
+};
+
+use super::{WalletOpSendMessage, WalletVersion, WalletVersionKind};
 
 lazy_static! {
-    static ref WALLET_V4R2_CODE_CELL: Arc<Cell> = {
-        BagOfCells::parse_base64(include_str!("./wallet_v4r2.code"))
-            .unwrap()
-            .single_root()
-            .expect("code BoC must be single root")
-            .clone()
-    };
+    static ref WALLET_V4R2_CODE_CELL: Arc<Cell> = CodeCellCache::global()
+        .get_or_parse_base64(
+            WalletVersionKind::V4R2.code_hash(),
+            include_str!("./wallet_v4r2.code"),
+        )
+        .expect("invalid code BoC");
 }
 
 /// Wallet [v4r2](https://github.com/ton-blockchain/wallet-contract/blob/4111fd9e3313ec17d99ca9b5b1656445b5b49d8f/README.md).
@@ -35,6 +39,13 @@ impl WalletVersion for V4R2 {
     type Data = WalletV4R2Data;
     type MessageBody = WalletV4R2Message;
 
+    /// `698983191 + workchain`, which is the same for all networks
+    const DEFAULT_WALLET_ID_MAINNET: u32 = 0x29a9a317;
+    const DEFAULT_WALLET_ID_TESTNET: u32 = Self::DEFAULT_WALLET_ID_MAINNET;
+    /// Each message takes a reference, while external body is signed with
+    /// no other references
+    const MAX_MESSAGES: usize = 4;
+
     fn code() -> Arc<Cell> {
         WALLET_V4R2_CODE_CELL.clone()
     }
@@ -44,7 +55,7 @@ impl WalletVersion for V4R2 {
             seqno: 0,
             wallet_id,
             pubkey,
-            plugins: HashmapE::Empty,
+            plugins: BTreeSet::new(),
         }
     }
 
@@ -68,8 +79,8 @@ pub struct WalletV4R2Data {
     pub seqno: u32,
     pub wallet_id: u32,
     pub pubkey: [u8; PUBLIC_KEY_LENGTH],
-    /// plugin address -> ()
-    pub plugins: HashmapE<()>,
+    /// Addresses of installed plugins
+    pub plugins: BTreeSet<MsgAddress>,
 }
 
 impl CellSerialize for WalletV4R2Data {
@@ -78,10 +89,8 @@ impl CellSerialize for WalletV4R2Data {
             .pack(self.seqno)?
             .pack(self.wallet_id)?
             .pack(self.pubkey)?
-            .store_as_with::<_, &HashmapE<NoArgs<_>, NoArgs<_>>>(
-                &self.plugins,
-                (8 + 256, (), ()),
-            )?;
+            .store_as::<_, &Plugins>(&self.plugins)
+            .context("plugins")?;
         Ok(())
     }
 }
@@ -92,16 +101,58 @@ impl<'de> CellDeserialize<'de> for WalletV4R2Data {
             seqno: parser.unpack()?,
             wallet_id: parser.unpack()?,
             pubkey: parser.unpack()?,
-            plugins: parser.parse_as_with::<_, HashmapE<NoArgs<_>, NoArgs<_>>>((
-                8 + 256,
-                (),
-                (),
-            ))?,
+            plugins: parser.parse_as::<_, Plugins>().context("plugins")?,
         };
         Ok(d)
     }
 }
 
+/// Adapter for plugins dictionary keyed by workchain and address hash:
+/// ```tlb
+/// plugins:(HashmapE (8 + 256) Unit)
+/// ```
+struct Plugins;
+
+/// `wc:int8 addr_hash:bits256`
+type PluginKey = (i8, [u8; 32]);
+
+impl CellSerializeAs<BTreeSet<MsgAddress>> for Plugins {
+    fn store_as(
+        source: &BTreeSet<MsgAddress>,
+        builder: &mut CellBuilder,
+    ) -> Result<(), CellBuilderError> {
+        let plugins: Dict<{ 8 + 256 }, PluginKey, ()> = source
+            .iter()
+            .map(|addr| {
+                let wc = i8::try_from(addr.workchain_id).map_err(|_| {
+                    Error::custom(format!(
+                        "workchain_id {} does not fit into int8",
+                        addr.workchain_id
+                    ))
+                })?;
+                Ok(((wc, addr.address), ()))
+            })
+            .collect::<Result<_, CellBuilderError>>()?;
+        builder.store(plugins)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserializeAs<'de, BTreeSet<MsgAddress>> for Plugins {
+    fn parse_as(
+        parser: &mut CellParser<'de>,
+    ) -> Result<BTreeSet<MsgAddress>, CellParserError<'de>> {
+        let plugins: Dict<{ 8 + 256 }, PluginKey, ()> = parser.parse()?;
+        Ok(plugins
+            .into_iter()
+            .map(|((wc, address), ())| MsgAddress {
+                workchain_id: wc.into(),
+                address,
+            })
+            .collect())
+    }
+}
+
 pub struct WalletV4R2Message {
     pub wallet_id: u32,
     pub expire_at: DateTime<Utc>,
@@ -120,6 +171,7 @@ impl CellSerialize for WalletV4R2Message {
     }
 }
 
+#[allow(clippy::large_enum_variant)]
 pub enum WalletV4R2Op {
     Send(Vec<WalletOpSendMessage>),
     DeployAndInstall(WalletV4R2OpDeployAndInstallPlugin),
@@ -130,10 +182,12 @@ pub enum WalletV4R2Op {
 impl CellSerialize for WalletV4R2Op {
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
         match self {
-            Self::Send(msgs) => builder.pack(0u8)?.store_many(msgs)?,
-            Self::DeployAndInstall(msg) => builder.pack(1u8)?.store(msg)?,
-            Self::Install(msg) => builder.pack(2u8)?.store(msg)?,
-            Self::Remove(msg) => builder.pack(3u8)?.store(msg)?,
+            Self::Send(msgs) => builder.pack(WALLET_V4_SEND)?.store_many(msgs)?,
+            Self::DeployAndInstall(msg) => builder
+                .pack(WALLET_V4_DEPLOY_AND_INSTALL_PLUGIN)?
+                .store(msg)?,
+            Self::Install(msg) => builder.pack(WALLET_V4_INSTALL_PLUGIN)?.store(msg)?,
+            Self::Remove(msg) => builder.pack(WALLET_V4_REMOVE_PLUGIN)?.store(msg)?,
         };
         Ok(())
     }
@@ -210,25 +264,77 @@ impl<'de> CellDeserialize<'de> for WalletV4R2OpPlugin {
 
 #[cfg(test)]
 mod tests {
-    use tlb::bits::{de::unpack_fully, ser::pack_with};
-    use tlb_ton::boc::{BagOfCellsArgs, BoC};
+    use tlb::{
+        bits::{de::unpack_fully, ser::pack_with},
+        r#as::NoArgs,
+        ser::CellSerializeExt,
+    };
+    use tlb_ton::{
+        boc::{BagOfCellsArgs, BoC, CellOrder},
+        hashmap::HashmapE,
+    };
 
     use super::*;
 
     #[test]
     fn check_code() {
         let packed = pack_with(
-            BoC::from_root(WALLET_V4R2_CODE_CELL.clone()),
-            BagOfCellsArgs {
-                has_idx: false,
-                has_crc32c: true,
-            },
+            BoC::from_root(V4R2::code()),
+            BagOfCellsArgs::new(false, true),
         )
         .unwrap();
 
         let unpacked: BoC = unpack_fully(packed).unwrap();
 
         let got: Cell = unpacked.single_root().unwrap().parse_fully().unwrap();
-        assert_eq!(&got, WALLET_V4R2_CODE_CELL.as_ref());
+        assert_eq!(&got, V4R2::code().as_ref());
+
+        // parsed once and shared with global cache
+        assert!(Arc::ptr_eq(&V4R2::code(), &V4R2::code()));
+        assert!(Arc::ptr_eq(
+            &V4R2::code(),
+            &CodeCellCache::global()
+                .get(&WalletVersionKind::V4R2.code_hash())
+                .unwrap(),
+        ));
+    }
+
+    #[test]
+    fn data_plugins() {
+        let mut data = V4R2::init_data(698983191, [1; PUBLIC_KEY_LENGTH]);
+        data.plugins.insert(MsgAddress {
+            workchain_id: -1,
+            address: [0xff; 32],
+        });
+        data.plugins.insert(MsgAddress {
+            workchain_id: 0,
+            address: [0x42; 32],
+        });
+        let cell = data.to_cell().unwrap();
+        let got: WalletV4R2Data = cell.parse_fully().unwrap();
+        assert_eq!(got, data);
+
+        // keys are exactly 8 + 256 bits long
+        let mut parser = cell.parser();
+        let _: (u32, u32, [u8; PUBLIC_KEY_LENGTH]) = parser.unpack().unwrap();
+        let raw: HashmapE<()> = parser
+            .parse_as_with::<_, HashmapE<NoArgs<_>, NoArgs<_>>>((8 + 256, (), ()))
+            .unwrap();
+        assert_eq!(raw.len(), 2);
+
+        data.plugins.insert(MsgAddress {
+            workchain_id: 1 << 8,
+            address: [0; 32],
+        });
+        assert!(data.to_cell().is_err());
+    }
+
+    #[test]
+    fn code_dfs_preorder_byte_for_byte() {
+        let boc = BoC::from_root(V4R2::code());
+        let got = boc
+            .to_base64(BagOfCellsArgs::new(false, true).with_cell_order(CellOrder::DfsPreorder))
+            .unwrap();
+        assert_eq!(got, include_str!("./wallet_v4r2.code").trim());
     }
 }
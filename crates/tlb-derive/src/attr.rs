@@ -0,0 +1,94 @@
+use syn::{spanned::Spanned, Attribute, Error, LitStr, Path, Result, Type};
+
+/// Attributes of the struct itself
+#[derive(Default)]
+pub struct ContainerAttrs {
+    /// `#[tlb(crate = "...")]`
+    pub krate: Option<Path>,
+    /// `#[tlb(tag = "...")]`
+    pub tag: Option<(u64, u32)>,
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut output = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("tlb")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("crate") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    output.krate = Some(s.parse()?);
+                } else if meta.path.is_ident("tag") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    output.tag =
+                        Some(parse_tag(&s.value()).map_err(|msg| Error::new(s.span(), msg))?);
+                } else {
+                    return Err(meta.error("unknown container attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(output)
+    }
+}
+
+/// Attributes of fields
+#[derive(Default)]
+pub struct FieldAttrs {
+    /// `#[tlb(as = "...")]`
+    pub r#as: Option<Type>,
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut output = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("tlb")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("as") {
+                    let s: LitStr = meta.value()?.parse()?;
+                    output.r#as = Some(s.parse()?);
+                } else if meta.path.is_ident("tag") {
+                    return Err(Error::new(
+                        meta.path.span(),
+                        "tag is an attribute of the struct, not of its fields",
+                    ));
+                } else {
+                    return Err(meta.error("unknown field attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(output)
+    }
+}
+
+/// Parse constructor tag into `(value, len)`, either in TL-B notation,
+/// i.e. `#0f8a7ea5` or `$10`, or as Rust integer literal, i.e.
+/// `0x0f8a7ea5` or `0b10`, where each digit stands for 4 or 1 bits
+/// respectively.
+fn parse_tag(s: &str) -> core::result::Result<(u64, u32), &'static str> {
+    let (digits, radix) = if let Some(digits) = s.strip_prefix('#').or(s.strip_prefix("0x")) {
+        (digits, 16)
+    } else if let Some(digits) = s.strip_prefix('$').or(s.strip_prefix("0b")) {
+        (digits, 2)
+    } else {
+        return Err("tag must start with one of: '#', '$', '0x' or '0b'");
+    };
+    if digits == "_" {
+        return Ok((0, 0));
+    }
+    let digit_bits = if radix == 16 { 4 } else { 1 };
+    let mut value: u64 = 0;
+    let mut len: u32 = 0;
+    for c in digits.chars().filter(|&c| c != '_') {
+        let digit = c.to_digit(radix).ok_or("invalid tag digit")?;
+        len += digit_bits;
+        if len > 64 {
+            return Err("tag must not be longer than 64 bits");
+        }
+        value = (value << digit_bits) | digit as u64;
+    }
+    if len == 0 {
+        return Err("empty tag, use '$_' instead");
+    }
+    Ok((value, len))
+}
@@ -0,0 +1,228 @@
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{
+    parse_quote, Data, DeriveInput, Error, Fields, GenericParam, Generics, Ident, Index, Lifetime,
+    LifetimeParam, LitStr, Member, Path, Result, Type, WherePredicate,
+};
+
+use crate::attr::{ContainerAttrs, FieldAttrs};
+
+/// Parsed struct to derive implementations for
+struct Input<'a> {
+    ident: &'a Ident,
+    generics: &'a Generics,
+    krate: Path,
+    tag: Option<TokenStream>,
+    fields: Vec<Field<'a>>,
+    style: &'a Fields,
+}
+
+struct Field<'a> {
+    member: Member,
+    ty: &'a Type,
+    adapter: Option<Type>,
+}
+
+impl<'a> Input<'a> {
+    fn parse(input: &'a DeriveInput) -> Result<Self> {
+        let Data::Struct(data) = &input.data else {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "only structs are supported, use `tlb::tag_dispatch!` for enums",
+            ));
+        };
+        let attrs = ContainerAttrs::parse(&input.attrs)?;
+        let krate = attrs.krate.unwrap_or_else(|| parse_quote!(::tlb));
+        let tag = attrs
+            .tag
+            // empty tag is a no-op
+            .filter(|&(_, len)| len > 0)
+            .map(|(value, len)| quote!(const { #krate::tag::Tag::new(#value, #len) }));
+        let fields = data
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                Ok(Field {
+                    member: field
+                        .ident
+                        .clone()
+                        .map_or_else(|| Member::Unnamed(Index::from(i)), Member::Named),
+                    ty: &field.ty,
+                    adapter: FieldAttrs::parse(&field.attrs)?.r#as,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            ident: &input.ident,
+            generics: &input.generics,
+            krate,
+            tag,
+            fields,
+            style: &data.fields,
+        })
+    }
+
+    /// Generic type parameters of the struct
+    fn type_params(&self) -> Vec<&Ident> {
+        self.generics
+            .type_params()
+            .map(|param| &param.ident)
+            .collect()
+    }
+
+    /// Fields, whose types depend on generic type parameters, so that
+    /// bounds are added only for them
+    fn generic_fields(&self) -> impl Iterator<Item = &Field<'a>> {
+        let params = self.type_params();
+        self.fields.iter().filter(move |field| {
+            mentions_any(field.ty.to_token_stream(), &params)
+                || field
+                    .adapter
+                    .as_ref()
+                    .is_some_and(|adapter| mentions_any(adapter.to_token_stream(), &params))
+        })
+    }
+}
+
+/// Returns whether given tokens mention any of given identifiers
+fn mentions_any(tokens: TokenStream, idents: &[&Ident]) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        TokenTree::Ident(ident) => idents.contains(&&ident),
+        TokenTree::Group(group) => mentions_any(group.stream(), idents),
+        _ => false,
+    })
+}
+
+fn context(member: &Member) -> LitStr {
+    LitStr::new(
+        &match member {
+            Member::Named(ident) => ident.to_string(),
+            Member::Unnamed(index) => index.index.to_string(),
+        },
+        Span::call_site(),
+    )
+}
+
+pub fn derive_cell_serialize(input: &DeriveInput) -> Result<TokenStream> {
+    let input = Input::parse(input)?;
+    let Input {
+        ident, krate, tag, ..
+    } = &input;
+
+    let mut generics = input.generics.clone();
+    let predicates: Vec<WherePredicate> = input
+        .generic_fields()
+        .map(|Field { ty, adapter, .. }| match adapter {
+            Some(adapter) => parse_quote!(#adapter: #krate::ser::r#as::CellSerializeAs<#ty>),
+            None => parse_quote!(#ty: #krate::ser::CellSerialize),
+        })
+        .collect();
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let tag = tag.as_ref().map(|tag| {
+        quote! {
+            #krate::bits::ser::BitWriterExt::pack(&mut *builder, #tag)?;
+        }
+    });
+    let fields = input.fields.iter().map(|field| {
+        let Field {
+            member, adapter, ..
+        } = field;
+        let context = context(member);
+        let store = match adapter {
+            Some(adapter) => quote!(builder.store_as::<_, &#adapter>(&self.#member)),
+            None => quote!(builder.store(&self.#member)),
+        };
+        quote! {
+            #krate::ResultExt::context(#store, #context)?;
+        }
+    });
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #krate::ser::CellSerialize for #ident #ty_generics #where_clause {
+            fn store(
+                &self,
+                builder: &mut #krate::ser::CellBuilder,
+            ) -> ::core::result::Result<(), #krate::ser::CellBuilderError> {
+                #tag
+                #(#fields)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    })
+}
+
+pub fn derive_cell_deserialize(input: &DeriveInput) -> Result<TokenStream> {
+    let input = Input::parse(input)?;
+    let Input {
+        ident,
+        krate,
+        tag,
+        style,
+        ..
+    } = &input;
+
+    let de = Lifetime::new("'de", Span::call_site());
+    let mut generics = input.generics.clone();
+    // values can borrow from cells only if they outlive them
+    let mut de_param = LifetimeParam::new(de.clone());
+    de_param.bounds = generics
+        .lifetimes()
+        .map(|param| param.lifetime.clone())
+        .collect();
+    let predicates: Vec<WherePredicate> = input
+        .generic_fields()
+        .map(|Field { ty, adapter, .. }| match adapter {
+            Some(adapter) => parse_quote!(#adapter: #krate::de::r#as::CellDeserializeAs<#de, #ty>),
+            None => parse_quote!(#ty: #krate::de::CellDeserialize<#de>),
+        })
+        .collect();
+    generics.make_where_clause().predicates.extend(predicates);
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
+    let mut impl_generics = generics.clone();
+    impl_generics
+        .params
+        .insert(0, GenericParam::Lifetime(de_param));
+    let (impl_generics, _, _) = impl_generics.split_for_impl();
+
+    let tag = tag.as_ref().map(|tag| {
+        quote! {
+            #krate::tag::Tag::unpack_one_of(&mut *parser, &[#tag])?;
+        }
+    });
+    let fields = input.fields.iter().map(|field| {
+        let Field {
+            member, adapter, ..
+        } = field;
+        let context = context(member);
+        let parse = match adapter {
+            Some(adapter) => quote!(parser.parse_as::<_, #adapter>()),
+            None => quote!(parser.parse()),
+        };
+        let value = quote!(#krate::ResultExt::context(#parse, #context)?);
+        match member {
+            Member::Named(ident) => quote!(#ident: #value),
+            Member::Unnamed(_) => value,
+        }
+    });
+    let value = match style {
+        Fields::Named(_) => quote!(Self { #(#fields),* }),
+        Fields::Unnamed(_) => quote!(Self(#(#fields),*)),
+        Fields::Unit => quote!(Self),
+    };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics #krate::de::CellDeserialize<#de> for #ident #ty_generics #where_clause {
+            fn parse(
+                parser: &mut #krate::de::CellParser<#de>,
+            ) -> ::core::result::Result<Self, #krate::de::CellParserError<#de>> {
+                #tag
+                ::core::result::Result::Ok(#value)
+            }
+        }
+    })
+}
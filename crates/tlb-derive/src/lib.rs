@@ -0,0 +1,76 @@
+//! Derive macros for [`tlb`](https://docs.rs/tlb), use them via its
+//! `derive` feature.
+//!
+//! Structs are **de**/**ser**ialized field by field in the order of their
+//! declaration, optionally prefixed with the constructor tag:
+//! ```rust
+//! # use tlb::{
+//! #     bits::de::BitReaderExt,
+//! #     de::CellDeserialize,
+//! #     r#as::{Data, Ref},
+//! #     ser::{CellSerialize, CellSerializeExt},
+//! #     Cell,
+//! # };
+//! /// ```tlb
+//! /// transfer#0f8a7ea5 query_id:uint64 payload:^Cell = Msg;
+//! /// ```
+//! #[derive(Debug, PartialEq, CellSerialize, CellDeserialize)]
+//! #[tlb(tag = "#0f8a7ea5")]
+//! struct Transfer {
+//!     #[tlb(as = "Data")]
+//!     query_id: u64,
+//!     #[tlb(as = "Ref")]
+//!     payload: Cell,
+//! }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let msg = Transfer {
+//!     query_id: 1,
+//!     payload: Cell::new(),
+//! };
+//! let cell = msg.to_cell()?;
+//! assert_eq!(cell.parser().unpack::<u32>()?, 0x0f8a7ea5);
+//! assert_eq!(cell.parse_fully::<Transfer>()?, msg);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Container attributes
+//! * `#[tlb(tag = "...")]`: constructor tag in TL-B notation, i.e.
+//!   `#0f8a7ea5` or `$10`, or as `0x0f8a7ea5` or `0b10`, where each digit
+//!   stands for 4 or 1 bits respectively. It is checked on parsing, so that
+//!   [`UnknownTag`](https://docs.rs/tlb/latest/tlb/tag/struct.UnknownTag.html)
+//!   error is returned on mismatch.
+//! * `#[tlb(crate = "...")]`: path to `tlb` crate, if it's not available
+//!   as `::tlb`, e.g. `toner::tlb`.
+//!
+//! ## Field attributes
+//! * `#[tlb(as = "...")]`: adapter to **de**/**ser**ialize the field with
+//!   instead of its own implementations, e.g. `Ref<Data>`.
+//!
+//! Bounds are added for fields, whose types mention generic type
+//! parameters, while deserialized values can borrow from cells as long as
+//! lifetime parameters of the struct are outlived by them.
+mod attr;
+mod cell;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Derive `tlb::ser::CellSerialize`, see [crate-level](crate) docs
+#[proc_macro_derive(CellSerialize, attributes(tlb))]
+pub fn derive_cell_serialize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    cell::derive_cell_serialize(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive `tlb::de::CellDeserialize`, see [crate-level](crate) docs
+#[proc_macro_derive(CellDeserialize, attributes(tlb))]
+pub fn derive_cell_deserialize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    cell::derive_cell_deserialize(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,136 @@
+use tlb::{
+    bits::{de::BitReaderExt, ser::BitWriterExt},
+    de::CellDeserialize,
+    r#as::{Data, Ref},
+    ser::{CellSerialize, CellSerializeExt},
+    Cell,
+};
+
+/// ```tlb
+/// transfer#0f8a7ea5 query_id:uint64 amount:uint32 payload:(Maybe ^Cell) = Msg;
+/// ```
+#[derive(Debug, PartialEq, CellSerialize, CellDeserialize)]
+#[tlb(tag = "0x0f8a7ea5")]
+struct Transfer {
+    #[tlb(as = "Data")]
+    query_id: u64,
+    #[tlb(as = "Data")]
+    amount: u32,
+    #[tlb(as = "Option<Ref>")]
+    payload: Option<Cell>,
+}
+
+#[derive(Debug, PartialEq, CellSerialize, CellDeserialize)]
+struct Tuple(#[tlb(as = "Data")] u8, Transfer);
+
+#[derive(Debug, PartialEq, CellSerialize, CellDeserialize)]
+#[tlb(tag = "$10")]
+struct Unit;
+
+#[derive(Debug, PartialEq, CellSerialize, CellDeserialize)]
+struct Generic<T, P> {
+    #[tlb(as = "Ref<Data>")]
+    value: T,
+    payload: P,
+}
+
+#[derive(Debug, PartialEq, CellSerialize, CellDeserialize)]
+struct Borrowed<'a> {
+    #[tlb(as = "Ref")]
+    cell: &'a Cell,
+}
+
+mod renamed {
+    pub use tlb as my_tlb;
+}
+
+#[derive(Debug, PartialEq, CellSerialize, CellDeserialize)]
+#[tlb(crate = "renamed::my_tlb", tag = "#_")]
+struct Renamed(#[tlb(as = "Data")] bool);
+
+fn transfer() -> Transfer {
+    Transfer {
+        query_id: 1,
+        amount: 2,
+        payload: Some(Cell::from_hex_data("cafe", 16).unwrap()),
+    }
+}
+
+#[test]
+fn named() {
+    let msg = transfer();
+    let cell = msg.to_cell().unwrap();
+
+    let mut expected = Cell::builder();
+    expected
+        .pack(0x0f8a7ea5u32)
+        .unwrap()
+        .pack(1u64)
+        .unwrap()
+        .pack(2u32)
+        .unwrap()
+        .store_as::<_, Option<Ref>>(msg.payload.clone())
+        .unwrap();
+    assert_eq!(cell, expected.into_cell());
+    assert_eq!(cell.parse_fully::<Transfer>().unwrap(), msg);
+}
+
+#[test]
+fn tuple() {
+    let v = Tuple(7, transfer());
+    let cell = v.to_cell().unwrap();
+    assert_eq!(cell.parser().unpack::<u8>().unwrap(), 7);
+    assert_eq!(cell.parse_fully::<Tuple>().unwrap(), v);
+}
+
+#[test]
+fn unit() {
+    let cell = Unit.to_cell().unwrap();
+    assert_eq!(cell.data.len(), 2);
+    assert_eq!(cell.parse_fully::<Unit>().unwrap(), Unit);
+}
+
+#[test]
+fn generic() {
+    let v = Generic {
+        value: 0xdeadbeef_u32,
+        payload: Unit,
+    };
+    let cell = v.to_cell().unwrap();
+    assert_eq!(cell.references.len(), 1);
+    assert_eq!(cell.parse_fully::<Generic<u32, Unit>>().unwrap(), v);
+}
+
+#[test]
+fn borrowed() {
+    let mut builder = Cell::builder();
+    builder.store_as::<_, Ref>(Cell::new()).unwrap();
+    let cell = builder.into_cell();
+
+    let got: Borrowed = cell.parse_fully().unwrap();
+    assert!(core::ptr::eq(got.cell, &*cell.references[0]));
+    assert_eq!(got.to_cell().unwrap(), cell);
+}
+
+#[test]
+fn renamed_crate() {
+    let cell = Renamed(true).to_cell().unwrap();
+    assert_eq!(cell.data.len(), 1);
+    assert_eq!(cell.parse_fully::<Renamed>().unwrap(), Renamed(true));
+}
+
+#[test]
+fn unknown_tag() {
+    let mut builder = Cell::builder();
+    builder.pack(true).unwrap().pack(true).unwrap();
+    let err = builder.into_cell().parse_fully::<Unit>().unwrap_err();
+    assert_eq!(err.to_string(), "unknown tag: $11, expected one of: $10");
+}
+
+#[test]
+fn error_context() {
+    let mut builder = Cell::builder();
+    builder.pack(0x0f8a7ea5u32).unwrap().pack(1u64).unwrap();
+    let err = builder.into_cell().parse_fully::<Transfer>().unwrap_err();
+    assert!(err.to_string().starts_with("amount: "), "{err}");
+}
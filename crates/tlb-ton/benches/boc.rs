@@ -0,0 +1,65 @@
+use std::{hint::black_box, sync::Arc};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use tlb::{bits::ser::BitWriterExt, Cell};
+use tlb_ton::boc::{BagOfCells, BagOfCellsArgs, CellOrder};
+
+/// Chain of cells, each referencing the previous one twice, so the tree
+/// has `2^depth` cells, but only `depth + 1` of them are unique
+fn shared_tree(depth: u32) -> Cell {
+    let mut cell = Arc::new(Cell::new());
+    for i in 0..depth {
+        let mut builder = Cell::builder();
+        builder.pack(i).unwrap();
+        let mut c = builder.into_cell();
+        c.references = vec![cell.clone(), cell];
+        cell = Arc::new(c);
+    }
+    Arc::unwrap_or_clone(cell)
+}
+
+/// Chain of cells, each referencing the previous one 4 times, so that
+/// there are `4^depth` paths from the root to the leaf
+fn shared_dag(depth: u32) -> Cell {
+    let mut cell = Arc::new(Cell::new());
+    for i in 0..depth {
+        let mut builder = Cell::builder();
+        builder.pack(i).unwrap();
+        let mut c = builder.into_cell();
+        c.references = vec![cell; 4];
+        cell = Arc::new(c);
+    }
+    Arc::unwrap_or_clone(cell)
+}
+
+/// Full 4-ary tree of unique cells
+fn wide_tree(depth: u32, id: &mut u32) -> Cell {
+    let mut builder = Cell::builder();
+    builder.pack(*id).unwrap();
+    *id += 1;
+    let mut cell = builder.into_cell();
+    if depth > 0 {
+        cell.references = (0..4).map(|_| wide_tree(depth - 1, id).into()).collect();
+    }
+    cell
+}
+
+fn pack_boc(c: &mut Criterion) {
+    let trees = [
+        ("shared tree of depth 16", shared_tree(16)),
+        ("shared DAG of depth 256", shared_dag(256)),
+        ("wide tree of depth 6", wide_tree(6, &mut 0)),
+    ];
+    for (name, root) in trees {
+        let boc = BagOfCells::from_root(root);
+        for order in [CellOrder::Topological, CellOrder::DfsPreorder] {
+            let args = BagOfCellsArgs::default().with_cell_order(order);
+            c.bench_function(&format!("pack {name} in {order:?} order"), |b| {
+                b.iter(|| black_box(&boc).to_bytes(args).unwrap())
+            });
+        }
+    }
+}
+
+criterion_group!(benches, pack_boc);
+criterion_main!(benches);
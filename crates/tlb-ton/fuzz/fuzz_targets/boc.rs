@@ -0,0 +1,30 @@
+//! Parse arbitrary bytes as BoC, both with and without index
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tlb_ton::boc::{BagOfCells, BagOfCellsArgs, IndexedBagOfCells, ParseMode};
+
+/// Limit on number of cells loaded from indexed BoC, since each one is
+/// loaded with its subtree
+const MAX_LOADED_CELLS: u32 = 64;
+
+fuzz_target!(|bytes: &[u8]| {
+    if let Ok(parsed) = BagOfCells::parse(bytes, ParseMode::Lenient) {
+        // everything parsed must be packed back
+        let packed = parsed
+            .boc
+            .to_bytes(BagOfCellsArgs::default())
+            .expect("parsed BoC is packed back");
+        let got = BagOfCells::parse(packed, ParseMode::Strict)
+            .expect("packed BoC is parsed")
+            .boc;
+        assert_eq!(got.single_root(), parsed.boc.single_root());
+    }
+
+    if let Ok(indexed) = IndexedBagOfCells::parse(bytes) {
+        // including out of bounds
+        for i in 0..=(indexed.len() as u32).min(MAX_LOADED_CELLS) {
+            let _ = indexed.load_cell(i);
+        }
+    }
+});
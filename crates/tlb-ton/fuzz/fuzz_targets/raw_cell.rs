@@ -0,0 +1,12 @@
+//! Parse arbitrary bytes as a single serialized cell
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|bytes: &[u8]| {
+    let Some((size_bytes, bytes)) = bytes.split_first() else {
+        return;
+    };
+    // size:(## 3) { size <= 4 }
+    tlb_ton::boc::fuzz_raw_cell(bytes, (*size_bytes % 5).into());
+});
@@ -0,0 +1,369 @@
+//! Collection of types related to [Account](https://docs.ton.org/develop/data-formats/tl-b-types#account)
+use num_bigint::BigUint;
+use tlb::{
+    aliases::VarUInteger7,
+    bits::{
+        de::{BitReader, BitReaderExt, BitUnpack},
+        r#as::NBits,
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    tag::Tag,
+    ResultExt,
+};
+
+use crate::{currency::Grams, Timestamp, UnixTimestamp};
+
+/// ```tlb
+/// acc_state_uninit$00 = AccountStatus;
+/// acc_state_frozen$01 = AccountStatus;
+/// acc_state_active$10 = AccountStatus;
+/// acc_state_nonexist$11 = AccountStatus;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum AccountStatus {
+    Uninit = 0b00,
+    Frozen = 0b01,
+    Active = 0b10,
+    NonExist = 0b11,
+}
+
+impl AccountStatus {
+    /// Returns whether messages to the account with this status should be
+    /// sent as bounceable, which is the case only if it has code, so that
+    /// funds sent to not yet deployed wallet are not bounced back.
+    #[inline]
+    pub const fn is_bounceable(self) -> bool {
+        matches!(self, Self::Active | Self::Frozen)
+    }
+}
+
+impl BitPack for AccountStatus {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer.pack_as::<_, NBits<2>>(*self as u8)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for AccountStatus {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(match reader.unpack_as::<u8, NBits<2>>()? {
+            0b00 => Self::Uninit,
+            0b01 => Self::Frozen,
+            0b10 => Self::Active,
+            0b11 => Self::NonExist,
+            _ => unreachable!(),
+        })
+    }
+}
+
+/// ```tlb
+/// storage_used$_ cells:(VarUInteger 7) bits:(VarUInteger 7)
+///   public_cells:(VarUInteger 7) = StorageUsed;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct StorageUsed {
+    pub cells: u64,
+    pub bits: u64,
+    pub public_cells: u64,
+}
+
+impl StorageUsed {
+    /// Estimate fee for storing this amount of data during given number of
+    /// seconds with given prices, see
+    /// [Storage fee](https://docs.ton.org/develop/smart-contracts/fees#storage-fee)
+    /// ```rust
+    /// # use tlb_ton::account::{StoragePrice, StorageUsed};
+    /// let used = StorageUsed {
+    ///     cells: 1,
+    ///     bits: 1_000,
+    ///     public_cells: 0,
+    /// };
+    /// // basechain prices
+    /// let price = StoragePrice {
+    ///     bit_price_ps: 1,
+    ///     cell_price_ps: 500,
+    /// };
+    /// // a year
+    /// assert_eq!(used.estimate_storage_fee(price, 365 * 24 * 60 * 60), 721_802u32.into());
+    /// ```
+    #[inline]
+    pub fn estimate_storage_fee(&self, price: StoragePrice, seconds: u64) -> BigUint {
+        let per_second = BigUint::from(self.bits) * price.bit_price_ps
+            + BigUint::from(self.cells) * price.cell_price_ps;
+        // prices are given in 2^-16 nanotons per second, round up
+        (per_second * seconds + 0xffffu32) >> 16
+    }
+}
+
+impl BitPack for StorageUsed {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            .pack_as::<_, VarUInteger7>(self.cells)
+            .context("cells")?
+            .pack_as::<_, VarUInteger7>(self.bits)
+            .context("bits")?
+            .pack_as::<_, VarUInteger7>(self.public_cells)
+            .context("public_cells")?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for StorageUsed {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(Self {
+            cells: reader.unpack_as::<_, VarUInteger7>().context("cells")?,
+            bits: reader.unpack_as::<_, VarUInteger7>().context("bits")?,
+            public_cells: reader
+                .unpack_as::<_, VarUInteger7>()
+                .context("public_cells")?,
+        })
+    }
+}
+
+/// ```tlb
+/// storage_info$_ used:StorageUsed last_paid:uint32
+///   due_payment:(Maybe Grams) = StorageInfo;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageInfo {
+    pub used: StorageUsed,
+    pub last_paid: Timestamp,
+    pub due_payment: Option<BigUint>,
+}
+
+impl StorageInfo {
+    /// Estimate storage fee accumulated since
+    /// [`last_paid`](StorageInfo::last_paid) until given time, including
+    /// [`due_payment`](StorageInfo::due_payment)
+    #[inline]
+    pub fn estimate_storage_fee(&self, price: StoragePrice, now: Timestamp) -> BigUint {
+        #[cfg(feature = "chrono")]
+        let seconds = (now - self.last_paid).num_seconds().max(0) as u64;
+        #[cfg(not(feature = "chrono"))]
+        let seconds = now
+            .duration_since(self.last_paid)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        self.used.estimate_storage_fee(price, seconds)
+            + self.due_payment.as_ref().unwrap_or(&BigUint::ZERO)
+    }
+}
+
+impl BitPack for StorageInfo {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            .pack(self.used)
+            .context("used")?
+            .pack_as::<_, UnixTimestamp>(self.last_paid)
+            .context("last_paid")?
+            .pack_as::<_, Option<&Grams>>(self.due_payment.as_ref())
+            .context("due_payment")?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for StorageInfo {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(Self {
+            used: reader.unpack().context("used")?,
+            last_paid: reader
+                .unpack_as::<_, UnixTimestamp>()
+                .context("last_paid")?,
+            due_payment: reader
+                .unpack_as::<_, Option<Grams>>()
+                .context("due_payment")?,
+        })
+    }
+}
+
+/// Storage prices for a workchain in `2^-16` nanotons per second, see
+/// [`StoragePrices`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct StoragePrice {
+    pub bit_price_ps: u64,
+    pub cell_price_ps: u64,
+}
+
+/// Storage prices from config param 18:
+/// ```tlb
+/// _#cc utime_since:uint32 bit_price_ps:uint64 cell_price_ps:uint64
+///   mc_bit_price_ps:uint64 mc_cell_price_ps:uint64 = StoragePrices;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StoragePrices {
+    pub utime_since: Timestamp,
+    pub bit_price_ps: u64,
+    pub cell_price_ps: u64,
+    pub mc_bit_price_ps: u64,
+    pub mc_cell_price_ps: u64,
+}
+
+impl StoragePrices {
+    const TAG: Tag = Tag::parse("#cc");
+
+    /// Prices for given workchain
+    #[inline]
+    pub const fn for_workchain(&self, workchain_id: i32) -> StoragePrice {
+        if workchain_id == -1 {
+            StoragePrice {
+                bit_price_ps: self.mc_bit_price_ps,
+                cell_price_ps: self.mc_cell_price_ps,
+            }
+        } else {
+            StoragePrice {
+                bit_price_ps: self.bit_price_ps,
+                cell_price_ps: self.cell_price_ps,
+            }
+        }
+    }
+}
+
+impl BitPack for StoragePrices {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            .pack(Self::TAG)?
+            .pack_as::<_, UnixTimestamp>(self.utime_since)?
+            .pack(self.bit_price_ps)?
+            .pack(self.cell_price_ps)?
+            .pack(self.mc_bit_price_ps)?
+            .pack(self.mc_cell_price_ps)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for StoragePrices {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Tag::unpack_one_of(&mut reader, &[Self::TAG])?;
+        Ok(Self {
+            utime_since: reader.unpack_as::<_, UnixTimestamp>()?,
+            bit_price_ps: reader.unpack()?,
+            cell_price_ps: reader.unpack()?,
+            mc_bit_price_ps: reader.unpack()?,
+            mc_cell_price_ps: reader.unpack()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::bits::{de::unpack_fully, ser::pack};
+
+    use super::*;
+
+    #[cfg(feature = "chrono")]
+    fn timestamp(secs: i64) -> Timestamp {
+        Timestamp::from_timestamp(secs, 0).unwrap()
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    fn timestamp(secs: u64) -> Timestamp {
+        Timestamp::UNIX_EPOCH + std::time::Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn account_status_pack_unpack() {
+        for status in [
+            AccountStatus::Uninit,
+            AccountStatus::Frozen,
+            AccountStatus::Active,
+            AccountStatus::NonExist,
+        ] {
+            let packed = pack(status).unwrap();
+            assert_eq!(packed.len(), 2);
+            assert_eq!(unpack_fully::<AccountStatus>(&packed).unwrap(), status);
+        }
+    }
+
+    #[test]
+    fn storage_info_pack_unpack() {
+        let info = StorageInfo {
+            used: StorageUsed {
+                cells: 3,
+                bits: 1_234,
+                public_cells: 0,
+            },
+            last_paid: timestamp(1_700_000_000),
+            due_payment: Some(5u32.into()),
+        };
+        assert_eq!(
+            unpack_fully::<StorageInfo>(pack(&info).unwrap()).unwrap(),
+            info
+        );
+    }
+
+    #[test]
+    fn storage_fee() {
+        let prices = StoragePrices {
+            utime_since: Timestamp::UNIX_EPOCH,
+            bit_price_ps: 1,
+            cell_price_ps: 500,
+            mc_bit_price_ps: 1000,
+            mc_cell_price_ps: 500_000,
+        };
+        assert_eq!(
+            unpack_fully::<StoragePrices>(pack(prices).unwrap()).unwrap(),
+            prices
+        );
+
+        let info = StorageInfo {
+            used: StorageUsed {
+                cells: 1,
+                bits: 0,
+                public_cells: 0,
+            },
+            last_paid: Timestamp::UNIX_EPOCH,
+            due_payment: Some(10u32.into()),
+        };
+        let now = timestamp(1 << 16);
+        assert_eq!(
+            info.estimate_storage_fee(prices.for_workchain(0), now),
+            510u32.into()
+        );
+        assert_eq!(
+            info.estimate_storage_fee(prices.for_workchain(-1), now),
+            500_010u32.into()
+        );
+        // rounded up
+        assert_eq!(
+            info.used.estimate_storage_fee(prices.for_workchain(0), 1),
+            1u32.into()
+        );
+        // last_paid in the future
+        assert_eq!(
+            info.estimate_storage_fee(prices.for_workchain(0), Timestamp::UNIX_EPOCH),
+            10u32.into()
+        );
+    }
+}
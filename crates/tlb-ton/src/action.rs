@@ -0,0 +1,574 @@
+//! Collection of types related to [out actions](https://docs.ton.org/develop/data-formats/transaction-layout#outaction)
+use core::ops::{BitOr, BitOrAssign};
+
+use num_bigint::BigUint;
+use tlb::{
+    bits::{
+        de::{BitReader, BitReaderExt, BitUnpack},
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    de::{CellDeserialize, CellParser, CellParserError},
+    r#as::Ref,
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    tag_dispatch, Cell, Error, ResultExt, StringError,
+};
+
+use crate::{
+    currency::{consts, CurrencyCollection},
+    message::{CommonMsgInfo, Message},
+};
+
+/// Out action to be performed in action phase of the transaction
+/// ```tlb
+/// action_send_msg#0ec3c86d mode:(## 8) out_msg:^(MessageRelaxed Any) = OutAction;
+/// action_set_code#ad4de08e new_code:^Cell = OutAction;
+/// action_reserve_currency#36e6b809 mode:(## 8) currency:CurrencyCollection = OutAction;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
+pub enum OutAction {
+    /// ```tlb
+    /// action_send_msg#0ec3c86d
+    /// ```
+    SendMsg(SendMsgAction),
+
+    /// ```tlb
+    /// action_set_code#ad4de08e
+    /// ```
+    SetCode(Cell),
+
+    /// ```tlb
+    /// action_reserve_currency#36e6b809
+    /// ```
+    ReserveCurrency(ReserveCurrencyAction),
+}
+
+impl OutAction {
+    /// Reserve exactly given amount of nanoTON from the remaining balance,
+    /// fail if the balance is not enough
+    #[inline]
+    pub fn reserve_exact(coins: impl Into<BigUint>) -> Self {
+        Self::reserve(ReserveMode::EXACT, coins)
+    }
+
+    /// Reserve all remaining balance except given amount of nanoTON,
+    /// fail if the balance is not enough
+    #[inline]
+    pub fn reserve_all_but(coins: impl Into<BigUint>) -> Self {
+        Self::reserve(ReserveMode::ALL_BUT, coins)
+    }
+
+    #[inline]
+    fn reserve(mode: ReserveMode, coins: impl Into<BigUint>) -> Self {
+        Self::ReserveCurrency(ReserveCurrencyAction {
+            mode,
+            currency: CurrencyCollection {
+                grams: coins.into(),
+                ..Default::default()
+            },
+        })
+    }
+}
+
+tag_dispatch! {
+    OutAction {
+        // action_send_msg#0ec3c86d
+        "#0ec3c86d" => SendMsg,
+        // action_set_code#ad4de08e new_code:^Cell
+        "#ad4de08e" => SetCode as Ref,
+        // action_reserve_currency#36e6b809
+        "#36e6b809" => ReserveCurrency,
+    }
+}
+
+/// Send message action, i.e. [`action_send_msg`](OutAction::SendMsg)
+/// without the tag:
+/// ```tlb
+/// mode:(## 8) out_msg:^(MessageRelaxed Any)
+/// ```
+///
+/// Use [`SendMsgAction::builder()`] to check that `mode` is consistent
+/// with the message before sending it on-chain.
+///
+/// Body and init of the message are generic, so that typed bodies can be
+/// introspected until the action is serialized or
+/// [normalized](SendMsgAction::normalize).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendMsgAction<T = Cell, IC = Cell, ID = Cell> {
+    /// See <https://docs.ton.org/develop/smart-contracts/messages#message-modes>
+    pub mode: u8,
+    pub message: Message<T, IC, ID>,
+}
+
+impl SendMsgAction {
+    /// Ordinary message, sender pays fees from message value
+    pub const ORDINARY: u8 = 0;
+    /// Pay transfer fees separately from the message value
+    pub const PAY_FEES_SEPARATELY: u8 = 1;
+    /// Ignore errors arising while processing this message during the
+    /// action phase
+    pub const IGNORE_ERRORS: u8 = 2;
+    /// Bounce transaction in case of any errors during action phase
+    pub const BOUNCE_ON_ACTION_FAIL: u8 = 16;
+    /// Destroy current account if its resulting balance is zero
+    pub const DESTROY_IF_ZERO: u8 = 32;
+    /// Carry all the remaining value of the inbound message in addition
+    /// to the value initially indicated in the new message
+    pub const CARRY_REMAINING_VALUE: u8 = 64;
+    /// Carry all the remaining balance of the current smart contract
+    /// instead of the value originally indicated in the message
+    pub const CARRY_ALL_BALANCE: u8 = 128;
+
+    const KNOWN_FLAGS: u8 = Self::PAY_FEES_SEPARATELY
+        | Self::IGNORE_ERRORS
+        | Self::BOUNCE_ON_ACTION_FAIL
+        | Self::DESTROY_IF_ZERO
+        | Self::CARRY_REMAINING_VALUE
+        | Self::CARRY_ALL_BALANCE;
+}
+
+impl<T, IC, ID> SendMsgAction<T, IC, ID> {
+    /// Create [`SendMsgActionBuilder`]
+    /// ```rust
+    /// # use num_bigint::BigUint;
+    /// # use tlb_ton::{action::SendMsgAction, message::Message, MsgAddress};
+    /// let msg = Message::<()>::transfer(MsgAddress::NULL, BigUint::from(1u8), false)
+    ///     .normalize()
+    ///     .unwrap();
+    ///
+    /// let err = SendMsgAction::builder()
+    ///     .mode(SendMsgAction::CARRY_ALL_BALANCE)
+    ///     .message(msg.clone())
+    ///     .build()
+    ///     .unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "mode 128 (carry all balance) sends the whole balance, \
+    ///     so value must be zero, got: 1",
+    /// );
+    ///
+    /// let action = SendMsgAction::builder()
+    ///     .mode(SendMsgAction::CARRY_REMAINING_VALUE)
+    ///     .message(msg)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    #[inline]
+    pub fn builder() -> SendMsgActionBuilder<T, IC, ID> {
+        SendMsgActionBuilder::default()
+    }
+
+    /// Check that [`mode`](SendMsgAction::mode) is consistent with the
+    /// [`message`](SendMsgAction::message), so that the action would not
+    /// fail in action phase:
+    /// * only known mode flags are set
+    /// * [`CARRY_REMAINING_VALUE`](SendMsgAction::CARRY_REMAINING_VALUE)
+    ///   and [`CARRY_ALL_BALANCE`](SendMsgAction::CARRY_ALL_BALANCE) are
+    ///   mutually exclusive
+    /// * [`CARRY_ALL_BALANCE`](SendMsgAction::CARRY_ALL_BALANCE) is used
+    ///   with zero value, since the value is replaced by the whole balance
+    /// * message is not an inbound external one
+    /// * value does not exceed [`MAX_COINS`](crate::currency::consts::MAX_COINS)
+    ///
+    /// Note that [`CARRY_REMAINING_VALUE`](SendMsgAction::CARRY_REMAINING_VALUE)
+    /// adds the remaining value of the inbound message to the value of the
+    /// message, so non-zero value is allowed with it.
+    pub fn validate(&self) -> Result<(), StringError> {
+        let unknown = self.mode & !SendMsgAction::KNOWN_FLAGS;
+        if unknown != 0 {
+            return Err(Error::custom(format!(
+                "unknown mode flags: {unknown:#010b}"
+            )));
+        }
+        if self.mode & SendMsgAction::CARRY_REMAINING_VALUE != 0
+            && self.mode & SendMsgAction::CARRY_ALL_BALANCE != 0
+        {
+            return Err(Error::custom(
+                "modes 64 (carry remaining value) and 128 (carry all balance) \
+                are mutually exclusive",
+            ));
+        }
+        if let CommonMsgInfo::Internal(info) = &self.message.info {
+            if info.value.grams > consts::MAX_COINS.into() {
+                return Err(Error::custom(format!(
+                    "value exceeds max amount of coins: {}",
+                    info.value.grams
+                )));
+            }
+        }
+        match &self.message.info {
+            CommonMsgInfo::Internal(info) if self.mode & SendMsgAction::CARRY_ALL_BALANCE != 0 => {
+                if info.value.grams != 0u8.into() {
+                    return Err(Error::custom(format!(
+                        "mode 128 (carry all balance) sends the whole balance, \
+                        so value must be zero, got: {}",
+                        info.value.grams
+                    )));
+                }
+                if !info.value.other.0.is_empty() {
+                    return Err(Error::custom(
+                        "mode 128 (carry all balance) sends the whole balance, \
+                        so extra currencies must be empty",
+                    ));
+                }
+            }
+            CommonMsgInfo::ExternalIn(_) => {
+                return Err(Error::custom("inbound external message can not be sent"))
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl<T, IC, ID> SendMsgAction<T, IC, ID>
+where
+    T: CellSerialize,
+    IC: CellSerialize,
+    ID: CellSerialize,
+{
+    /// Serialize body and init of the message into cells, see
+    /// [`Message::normalize()`]
+    #[inline]
+    pub fn normalize(&self) -> Result<SendMsgAction, CellBuilderError> {
+        Ok(SendMsgAction {
+            mode: self.mode,
+            message: self.message.normalize()?,
+        })
+    }
+}
+
+impl<T, IC, ID> CellSerialize for SendMsgAction<T, IC, ID>
+where
+    T: CellSerialize,
+    IC: CellSerialize,
+    ID: CellSerialize,
+{
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // mode:(## 8)
+            .pack(self.mode)?
+            // out_msg:^(MessageRelaxed Any)
+            .store_as::<_, Ref>(&self.message)
+            .context("out_msg")?;
+        Ok(())
+    }
+}
+
+impl<'de, T, IC, ID> CellDeserialize<'de> for SendMsgAction<T, IC, ID>
+where
+    T: CellDeserialize<'de>,
+    IC: CellDeserialize<'de>,
+    ID: CellDeserialize<'de>,
+{
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            mode: parser.unpack().context("mode")?,
+            message: parser.parse_as::<_, Ref>().context("out_msg")?,
+        })
+    }
+}
+
+/// Builder for [`SendMsgAction`], which [validates](SendMsgAction::validate)
+/// it on [`.build()`](SendMsgActionBuilder::build)
+#[derive(Debug, Clone)]
+pub struct SendMsgActionBuilder<T = Cell, IC = Cell, ID = Cell> {
+    mode: u8,
+    message: Option<Message<T, IC, ID>>,
+}
+
+impl<T, IC, ID> Default for SendMsgActionBuilder<T, IC, ID> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            mode: SendMsgAction::ORDINARY,
+            message: None,
+        }
+    }
+}
+
+impl<T, IC, ID> SendMsgActionBuilder<T, IC, ID> {
+    /// Set mode flags, [`ORDINARY`](SendMsgAction::ORDINARY) by default
+    #[inline]
+    #[must_use]
+    pub fn mode(mut self, mode: u8) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set message to send
+    #[inline]
+    #[must_use]
+    pub fn message(mut self, message: Message<T, IC, ID>) -> Self {
+        self.message = Some(message);
+        self
+    }
+
+    /// Build and [validate](SendMsgAction::validate) the action
+    #[inline]
+    pub fn build(self) -> Result<SendMsgAction<T, IC, ID>, StringError> {
+        let action = SendMsgAction {
+            mode: self.mode,
+            message: self
+                .message
+                .ok_or_else(|| Error::custom("message is not set"))?,
+        };
+        action.validate()?;
+        Ok(action)
+    }
+}
+
+/// Reserve currency action, i.e.
+/// [`action_reserve_currency`](OutAction::ReserveCurrency) without the
+/// tag:
+/// ```tlb
+/// mode:(## 8) currency:CurrencyCollection
+/// ```
+///
+/// See [`OutAction::reserve_exact()`] and [`OutAction::reserve_all_but()`]
+/// for common cases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReserveCurrencyAction {
+    pub mode: ReserveMode,
+    pub currency: CurrencyCollection,
+}
+
+impl CellSerialize for ReserveCurrencyAction {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // mode:(## 8)
+            .pack(self.mode)?
+            // currency:CurrencyCollection
+            .store(&self.currency)
+            .context("currency")?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for ReserveCurrencyAction {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            mode: parser.unpack().context("mode")?,
+            currency: parser.parse().context("currency")?,
+        })
+    }
+}
+
+/// Flags of [`ReserveCurrencyAction`], see
+/// [`raw_reserve`](https://docs.ton.org/develop/func/stdlib#raw_reserve):
+/// ```rust
+/// # use tlb_ton::action::ReserveMode;
+/// let mode = ReserveMode::ALL_BUT | ReserveMode::BOUNCE_ON_ACTION_FAIL;
+/// assert_eq!(mode.bits(), 17);
+/// assert!(mode.contains(ReserveMode::ALL_BUT));
+/// assert_eq!(ReserveMode::from_bits(mode.bits()), Some(mode));
+/// assert_eq!(ReserveMode::from_bits(32), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ReserveMode(u8);
+
+impl ReserveMode {
+    /// Reserve exactly given amount, fail if the balance is not enough
+    pub const EXACT: Self = Self(0);
+    /// Reserve all remaining balance except given amount
+    pub const ALL_BUT: Self = Self(1);
+    /// Reserve at most given amount, i.e. do not fail if the balance is
+    /// not enough, but reserve all of it
+    pub const AT_MOST: Self = Self(2);
+    /// Increase the amount by the original balance of the account before
+    /// the compute phase
+    pub const ADD_ORIGINAL_BALANCE: Self = Self(4);
+    /// Negate the amount before performing the reservation, only valid
+    /// with [`ADD_ORIGINAL_BALANCE`](ReserveMode::ADD_ORIGINAL_BALANCE)
+    pub const NEGATE: Self = Self(8);
+    /// Bounce transaction in case of reservation failure
+    pub const BOUNCE_ON_ACTION_FAIL: Self = Self(16);
+
+    const ALL: Self = Self(0b11111);
+
+    /// Create from raw bits, returns `None` if unknown flags are set
+    #[inline]
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        if bits & !Self::ALL.0 != 0 {
+            return None;
+        }
+        Some(Self(bits))
+    }
+
+    /// Raw bits
+    #[inline]
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Returns whether all flags from `other` are set
+    #[inline]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for ReserveMode {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for ReserveMode {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitPack for ReserveMode {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer.pack(self.0)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for ReserveMode {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let bits = reader.unpack()?;
+        Self::from_bits(bits)
+            .ok_or_else(|| Error::custom(format!("unknown reserve mode flags: {bits:#010b}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::{
+        r#as::Data,
+        ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt},
+    };
+
+    use crate::{message::ExternalInMsgInfo, MsgAddress};
+
+    use super::*;
+
+    fn transfer(grams: u64) -> Message<Cell> {
+        Message::<()>::transfer(MsgAddress::NULL, grams.into(), false)
+            .normalize()
+            .unwrap()
+    }
+
+    fn build(mode: u8, message: Message<Cell>) -> Result<SendMsgAction, StringError> {
+        SendMsgAction::builder().mode(mode).message(message).build()
+    }
+
+    #[test]
+    fn send_msg_action_modes() {
+        for mode in [
+            SendMsgAction::ORDINARY,
+            SendMsgAction::PAY_FEES_SEPARATELY | SendMsgAction::IGNORE_ERRORS,
+            SendMsgAction::CARRY_REMAINING_VALUE | SendMsgAction::BOUNCE_ON_ACTION_FAIL,
+        ] {
+            assert!(build(mode, transfer(1)).is_ok());
+        }
+        assert!(build(
+            SendMsgAction::CARRY_ALL_BALANCE | SendMsgAction::DESTROY_IF_ZERO,
+            transfer(0)
+        )
+        .is_ok());
+
+        assert_eq!(
+            build(4, transfer(1)).unwrap_err().to_string(),
+            "unknown mode flags: 0b00000100"
+        );
+        assert!(build(
+            SendMsgAction::CARRY_REMAINING_VALUE | SendMsgAction::CARRY_ALL_BALANCE,
+            transfer(0)
+        )
+        .is_err());
+        assert!(build(SendMsgAction::CARRY_ALL_BALANCE, transfer(1)).is_err());
+        assert!(build(
+            SendMsgAction::ORDINARY,
+            Message {
+                info: CommonMsgInfo::ExternalIn(ExternalInMsgInfo {
+                    src: MsgAddress::NULL,
+                    dst: MsgAddress::NULL,
+                    import_fee: BigUint::ZERO,
+                }),
+                init: None,
+                body: Cell::new(),
+            }
+        )
+        .is_err());
+        assert!(build(
+            SendMsgAction::ORDINARY,
+            Message::<()>::transfer(
+                MsgAddress::NULL,
+                BigUint::from(consts::MAX_COINS) + 1u8,
+                false
+            )
+            .normalize()
+            .unwrap()
+        )
+        .is_err());
+        assert!(SendMsgAction::<Cell>::builder().build().is_err());
+    }
+
+    #[test]
+    fn out_action_serde() {
+        for action in [
+            OutAction::SendMsg(build(SendMsgAction::PAY_FEES_SEPARATELY, transfer(5)).unwrap()),
+            OutAction::SetCode(Cell::new()),
+            OutAction::reserve_exact(1u8),
+            OutAction::reserve_all_but(consts::TON),
+        ] {
+            let cell = action.to_cell().unwrap();
+            let got: OutAction = cell.parse_fully().unwrap();
+            assert_eq!(got, action);
+        }
+    }
+
+    #[test]
+    fn reserve_currency() {
+        let OutAction::ReserveCurrency(action) = OutAction::reserve_all_but(5u8) else {
+            unreachable!()
+        };
+        assert_eq!(action.mode, ReserveMode::ALL_BUT);
+        assert_eq!(action.currency.grams, 5u8.into());
+
+        let cell = ReserveCurrencyAction {
+            mode: ReserveMode::EXACT,
+            currency: Default::default(),
+        }
+        .to_cell()
+        .unwrap();
+        let mut invalid = cell.clone();
+        invalid.data.set(0, true);
+        assert_eq!(
+            invalid
+                .parse_fully::<ReserveCurrencyAction>()
+                .unwrap_err()
+                .to_string(),
+            "mode: unknown reserve mode flags: 0b10000000"
+        );
+    }
+
+    #[test]
+    fn unknown_out_action() {
+        let cell = 0xdeadbeefu32.wrap_as::<Data>().to_cell().unwrap();
+        assert_eq!(
+            cell.parse_fully::<OutAction>().unwrap_err().to_string(),
+            "unknown tag: 0xdeadbeef, expected one of: 0x0ec3c86d, 0xad4de08e, 0x36e6b809"
+        );
+    }
+}
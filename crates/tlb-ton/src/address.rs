@@ -1,25 +1,34 @@
 use core::{
+    cell::Cell as StdCell,
     fmt::{self, Debug, Display},
     str::FromStr,
 };
 
+#[cfg(feature = "base64")]
 use base64::{
     engine::general_purpose::STANDARD_NO_PAD, engine::general_purpose::URL_SAFE_NO_PAD, Engine,
 };
+#[cfg(feature = "base64")]
 use crc::Crc;
 use strum::Display;
 use tlb::{
     bits::{
+        bitvec::{
+            order::Msb0,
+            vec::BitVec,
+            view::{AsBits, AsMutBits},
+        },
         de::{BitReader, BitReaderExt, BitUnpack},
-        r#as::NBits,
+        r#as::{NBits, VarNBits},
         ser::{BitPack, BitWriter, BitWriterExt},
     },
     ser::{CellBuilderError, CellSerializeExt},
     Error, ResultExt, StringError,
 };
 
-use crate::state_init::StateInit;
+use crate::{account::AccountStatus, state_init::StateInit};
 
+#[cfg(feature = "base64")]
 const CRC_16_XMODEM: Crc<u16> = Crc::<u16>::new(&crc::CRC_16_XMODEM);
 
 /// [MsgAddress](https://docs.ton.org/develop/data-formats/msg-tlb#msgaddressext-tl-b)
@@ -83,6 +92,8 @@ impl MsgAddress {
     }
 
     /// Shortcut for [`.from_base64_url_flags()?.0`](MsgAddress::from_base64_url_flags)
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
     #[inline]
     pub fn from_base64_url(s: impl AsRef<str>) -> Result<Self, StringError> {
         Self::from_base64_url_flags(s).map(|(addr, _, _)| addr)
@@ -91,12 +102,16 @@ impl MsgAddress {
     /// Parse address from URL-base64
     /// [user-friendly](https://docs.ton.org/learn/overviews/addresses#user-friendly-address)
     /// representation and its flags: `(address, non_bouncible, non_production)`
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
     #[inline]
     pub fn from_base64_url_flags(s: impl AsRef<str>) -> Result<(Self, bool, bool), StringError> {
         Self::from_base64_repr(URL_SAFE_NO_PAD, s)
     }
 
     /// Shortcut for [`.from_base64_std_flags()?.0`](MsgAddress::from_base64_std_flags)
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
     #[inline]
     pub fn from_base64_std(s: impl AsRef<str>) -> Result<Self, StringError> {
         Self::from_base64_std_flags(s).map(|(addr, _, _)| addr)
@@ -105,30 +120,40 @@ impl MsgAddress {
     /// Parse address from standard base64
     /// [user-friendly](https://docs.ton.org/learn/overviews/addresses#user-friendly-address)
     /// representation and its flags: `(address, non_bouncible, non_production)`
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
     #[inline]
     pub fn from_base64_std_flags(s: impl AsRef<str>) -> Result<(Self, bool, bool), StringError> {
         Self::from_base64_repr(STANDARD_NO_PAD, s)
     }
 
     /// Shortcut for [`.to_base64_url_flags(false, false)`](MsgAddress::to_base64_url_flags)
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
     #[inline]
     pub fn to_base64_url(self) -> String {
         self.to_base64_url_flags(false, false)
     }
 
     /// Encode address as URL base64
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
     #[inline]
     pub fn to_base64_url_flags(self, non_bounceable: bool, non_production: bool) -> String {
         self.to_base64_flags(non_bounceable, non_production, URL_SAFE_NO_PAD)
     }
 
     /// Shortcut for [`.to_base64_std_flags(false, false)`](MsgAddress::to_base64_std_flags)
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
     #[inline]
     pub fn to_base64_std(self) -> String {
         self.to_base64_std_flags(false, false)
     }
 
     /// Encode address as standard base64
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
     #[inline]
     pub fn to_base64_std_flags(self, non_bounceable: bool, non_production: bool) -> String {
         self.to_base64_flags(non_bounceable, non_production, STANDARD_NO_PAD)
@@ -138,6 +163,7 @@ impl MsgAddress {
     ///
     /// # Returns
     /// the address, non-bounceable flag, non-production flag.
+    #[cfg(feature = "base64")]
     fn from_base64_repr(
         engine: impl Engine,
         s: impl AsRef<str>,
@@ -176,6 +202,7 @@ impl MsgAddress {
         ))
     }
 
+    #[cfg(feature = "base64")]
     fn to_base64_flags(
         self,
         non_bounceable: bool,
@@ -203,6 +230,239 @@ impl MsgAddress {
     pub fn is_null(&self) -> bool {
         *self == Self::NULL
     }
+
+    /// Rewrite first [`depth`](Anycast::depth) bits of the address with
+    /// [`rewrite_pfx`](Anycast::rewrite_pfx), which results in the
+    /// effective address used for routing.
+    ///
+    /// [`MsgAddress`] can not hold [`Anycast`] itself, so addresses with it
+    /// are parsed as [`ExtendedMsgAddress`] and rewritten with
+    /// [`ExtendedMsgAddress::effective_address()`].
+    /// ```rust
+    /// # use tlb_ton::{Anycast, MsgAddress};
+    /// let addr = MsgAddress {
+    ///     workchain_id: 0,
+    ///     address: [0xff; 32],
+    /// };
+    /// let got = addr.apply_anycast(Anycast::new(4, 0b0101).unwrap());
+    /// assert_eq!(got.address[0], 0x5f);
+    /// assert_eq!(got.address[1..], [0xff; 31]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn apply_anycast(mut self, anycast: Anycast) -> Self {
+        let depth = anycast.depth() as usize;
+        let pfx = anycast.rewrite_pfx().to_be_bytes();
+        self.address.as_mut_bits::<Msb0>()[..depth]
+            .copy_from_bitslice(&pfx.as_bits::<Msb0>()[32 - depth..]);
+        self
+    }
+
+    /// Effective address with optional [`Anycast`] applied, see
+    /// [`.apply_anycast()`](MsgAddress::apply_anycast)
+    #[inline]
+    #[must_use]
+    pub fn effective_address(self, anycast: Option<Anycast>) -> Self {
+        match anycast {
+            Some(anycast) => self.apply_anycast(anycast),
+            None => self,
+        }
+    }
+
+    /// Display address in given format
+    #[inline]
+    pub const fn display(&self, format: AddressFormat) -> DisplayAddress {
+        DisplayAddress {
+            address: *self,
+            format,
+        }
+    }
+
+    /// Display address in [raw](https://docs.ton.org/learn/overviews/addresses#raw-address)
+    /// format, see [`.to_hex()`](MsgAddress::to_hex)
+    #[inline]
+    pub const fn display_raw(&self) -> DisplayAddress {
+        self.display(AddressFormat::Raw)
+    }
+
+    /// Display address in [user-friendly](https://docs.ton.org/learn/overviews/addresses#user-friendly-address)
+    /// format with given flags, which falls back to raw format if `base64`
+    /// feature is disabled
+    #[inline]
+    pub const fn display_friendly(&self, flags: FriendlyFlags) -> DisplayAddress {
+        self.display(AddressFormat::Friendly(flags))
+    }
+
+    /// Encode address in [user-friendly](https://docs.ton.org/learn/overviews/addresses#user-friendly-address)
+    /// format following wallet UX convention: non-bounceable for accounts
+    /// which are not deployed yet, bounceable otherwise.
+    /// See [`AccountStatus::is_bounceable()`].
+    ///
+    /// Other flags are taken from [`MsgAddress::default_format()`] if it's
+    /// [`AddressFormat::Friendly`].
+    /// ```rust
+    /// # use tlb_ton::{account::AccountStatus, MsgAddress};
+    /// # #[cfg(feature = "base64")]
+    /// # fn main() {
+    /// let addr: MsgAddress = "EQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0Z9e".parse().unwrap();
+    /// assert_eq!(
+    ///     addr.to_string_for(AccountStatus::Active),
+    ///     "EQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0Z9e",
+    /// );
+    /// assert_eq!(
+    ///     addr.to_string_for(AccountStatus::Uninit),
+    ///     "UQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0cKb",
+    /// );
+    /// # }
+    /// # #[cfg(not(feature = "base64"))]
+    /// # fn main() {}
+    /// ```
+    #[inline]
+    pub fn to_string_for(&self, account_status: AccountStatus) -> String {
+        let flags = match Self::default_format() {
+            AddressFormat::Friendly(flags) => flags,
+            AddressFormat::Raw => FriendlyFlags::default(),
+        };
+        self.display_friendly(FriendlyFlags {
+            non_bounceable: !account_status.is_bounceable(),
+            ..flags
+        })
+        .to_string()
+    }
+
+    /// Format used by [`Display`] implementation of [`MsgAddress`] on
+    /// current thread, which is [`AddressFormat::default()`] unless
+    /// overridden by [`MsgAddress::with_default_format()`]
+    #[inline]
+    pub fn default_format() -> AddressFormat {
+        DEFAULT_ADDRESS_FORMAT.get()
+    }
+
+    /// Run `f` with [`Display`] implementation of [`MsgAddress`] using given
+    /// format on current thread, so that all addresses logged or serialized
+    /// within `f` have the same canonical form. Previous format is restored
+    /// afterwards, even if `f` panics, so calls can be nested.
+    /// ```rust
+    /// # use tlb_ton::{AddressFormat, MsgAddress};
+    /// let addr = MsgAddress::NULL;
+    /// let raw = MsgAddress::with_default_format(AddressFormat::Raw, || addr.to_string());
+    /// assert_eq!(raw, addr.to_hex());
+    /// assert_eq!(MsgAddress::default_format(), AddressFormat::default());
+    /// ```
+    #[inline]
+    pub fn with_default_format<R>(format: AddressFormat, f: impl FnOnce() -> R) -> R {
+        struct Restore(AddressFormat);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                DEFAULT_ADDRESS_FORMAT.set(self.0);
+            }
+        }
+
+        let _restore = Restore(DEFAULT_ADDRESS_FORMAT.replace(format));
+        f()
+    }
+}
+
+thread_local! {
+    static DEFAULT_ADDRESS_FORMAT: StdCell<AddressFormat> =
+        const { StdCell::new(AddressFormat::DEFAULT) };
+}
+
+/// Flags of [user-friendly](https://docs.ton.org/learn/overviews/addresses#user-friendly-address)
+/// address representation. Default is bounceable production address
+/// encoded as URL-safe base64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FriendlyFlags {
+    pub non_bounceable: bool,
+    pub non_production: bool,
+    /// Use standard base64 alphabet instead of URL-safe one
+    pub base64_std: bool,
+}
+
+/// Text format of [`MsgAddress`], see [`MsgAddress::display()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressFormat {
+    /// [Raw](https://docs.ton.org/learn/overviews/addresses#raw-address)
+    /// format: `workchain:hex`
+    Raw,
+    /// [User-friendly](https://docs.ton.org/learn/overviews/addresses#user-friendly-address)
+    /// format
+    Friendly(FriendlyFlags),
+}
+
+impl AddressFormat {
+    const DEFAULT: Self = Self::Friendly(FriendlyFlags {
+        non_bounceable: false,
+        non_production: false,
+        base64_std: false,
+    });
+}
+
+/// User-friendly format with default [`FriendlyFlags`]
+impl Default for AddressFormat {
+    #[inline]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Adapter to [`Display`] [`MsgAddress`] in given [`AddressFormat`],
+/// see [`MsgAddress::display()`]
+/// ```rust
+/// # use tlb_ton::{FriendlyFlags, MsgAddress};
+/// let addr: MsgAddress = "0:465d9f5d759796ca9c7c124262787257ef972dd1ba649aed18e18a18af734cd1"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(
+///     addr.display_raw().to_string(),
+///     "0:465d9f5d759796ca9c7c124262787257ef972dd1ba649aed18e18a18af734cd1",
+/// );
+/// # #[cfg(feature = "base64")]
+/// assert_eq!(
+///     addr.display_friendly(FriendlyFlags {
+///         non_bounceable: true,
+///         ..Default::default()
+///     })
+///     .to_string(),
+///     "UQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0cKb",
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayAddress {
+    address: MsgAddress,
+    format: AddressFormat,
+}
+
+impl Display for DisplayAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.format {
+            AddressFormat::Raw => f.write_str(&self.address.to_hex()),
+            #[cfg(feature = "base64")]
+            AddressFormat::Friendly(FriendlyFlags {
+                non_bounceable,
+                non_production,
+                base64_std: false,
+            }) => f.write_str(
+                &self
+                    .address
+                    .to_base64_url_flags(non_bounceable, non_production),
+            ),
+            #[cfg(feature = "base64")]
+            AddressFormat::Friendly(FriendlyFlags {
+                non_bounceable,
+                non_production,
+                base64_std: true,
+            }) => f.write_str(
+                &self
+                    .address
+                    .to_base64_std_flags(non_bounceable, non_production),
+            ),
+            // user-friendly format is only available with `base64` feature
+            #[cfg(not(feature = "base64"))]
+            AddressFormat::Friendly(_) => f.write_str(&self.address.to_hex()),
+        }
+    }
 }
 
 impl Debug for MsgAddress {
@@ -211,9 +471,58 @@ impl Debug for MsgAddress {
     }
 }
 
+/// Uses [`MsgAddress::default_format()`], see [`MsgAddress::display()`]
+/// for explicit formats
 impl Display for MsgAddress {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(self.to_base64_url().as_str())
+        Display::fmt(&self.display(Self::default_format()), f)
+    }
+}
+
+/// Adapter to **de**/**ser**ialize [`MsgAddress`] with [`serde_with`] in
+/// [raw](AddressFormat::Raw) format instead of the default one, while any
+/// format is accepted on **de**serialization:
+/// ```rust
+/// # use serde::{Deserialize, Serialize};
+/// # use serde_json::json;
+/// # use serde_with::serde_as;
+/// # use tlb_ton::{MsgAddress, RawAddress};
+/// #[serde_as]
+/// #[derive(Serialize, Deserialize)]
+/// struct Transfer {
+///     #[serde_as(as = "RawAddress")]
+///     dst: MsgAddress,
+/// }
+///
+/// let dst = MsgAddress::NULL;
+/// assert_eq!(
+///     serde_json::to_value(Transfer { dst }).unwrap(),
+///     json!({ "dst": dst.to_hex() }),
+/// );
+/// ```
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub struct RawAddress;
+
+#[cfg(feature = "serde")]
+impl ::serde_with::SerializeAs<MsgAddress> for RawAddress {
+    #[inline]
+    fn serialize_as<S>(source: &MsgAddress, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ::serde_with::serde::Serializer,
+    {
+        serializer.collect_str(&source.display_raw())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> ::serde_with::DeserializeAs<'de, MsgAddress> for RawAddress {
+    #[inline]
+    fn deserialize_as<D>(deserializer: D) -> Result<MsgAddress, D::Error>
+    where
+        D: ::serde_with::serde::Deserializer<'de>,
+    {
+        ::serde_with::DisplayFromStr::deserialize_as(deserializer)
     }
 }
 
@@ -222,14 +531,18 @@ impl FromStr for MsgAddress {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.len() == 48 {
-            if s.contains(['-', '_']) {
+            #[cfg(feature = "base64")]
+            return if s.contains(['-', '_']) {
                 Self::from_base64_url(s)
             } else {
                 Self::from_base64_std(s)
-            }
-        } else {
-            Self::from_hex(s)
+            };
+            #[cfg(not(feature = "base64"))]
+            return Err(Error::custom(
+                "user-friendly address: `base64` feature is disabled",
+            ));
         }
+        Self::from_hex(s)
     }
 }
 
@@ -264,7 +577,16 @@ impl BitUnpack for MsgAddress {
         match reader.unpack()? {
             MsgAddressTag::Null => Ok(Self::NULL),
             MsgAddressTag::Std => {
-                reader.skip(1)?; // anycast:(Maybe Anycast)
+                // anycast:(Maybe Anycast)
+                if reader
+                    .unpack::<Option<Anycast>>()
+                    .context("anycast")?
+                    .is_some()
+                {
+                    return Err(Error::custom(
+                        "anycast is not supported, use ExtendedMsgAddress",
+                    ));
+                }
                 Ok(Self {
                     // workchain_id:int8
                     workchain_id: reader.unpack::<i8>()? as i32,
@@ -277,9 +599,312 @@ impl BitUnpack for MsgAddress {
     }
 }
 
+/// ```tlb
+/// anycast_info$_ depth:(#<= 30) { depth >= 1 } rewrite_pfx:(bits depth) = Anycast;
+/// ```
+///
+/// `rewrite_pfx` is stored in `depth` least significant bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Anycast {
+    depth: u8,
+    rewrite_pfx: u32,
+}
+
+impl Anycast {
+    /// Maximum depth of the prefix
+    pub const MAX_DEPTH: u8 = 30;
+
+    /// Create anycast info with `rewrite_pfx` given as `depth` least
+    /// significant bits
+    #[inline]
+    pub fn new(depth: u8, rewrite_pfx: u32) -> Result<Self, StringError> {
+        if !(1..=Self::MAX_DEPTH).contains(&depth) {
+            return Err(Error::custom(format!(
+                "depth must be in range 1..={}, got {depth}",
+                Self::MAX_DEPTH
+            )));
+        }
+        if rewrite_pfx >> depth != 0 {
+            return Err(Error::custom(format!(
+                "rewrite_pfx {rewrite_pfx:#b} does not fit into {depth} bits"
+            )));
+        }
+        Ok(Self { depth, rewrite_pfx })
+    }
+
+    /// Number of bits in the prefix
+    #[inline]
+    pub const fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Prefix stored in [`depth`](Anycast::depth) least significant bits
+    #[inline]
+    pub const fn rewrite_pfx(&self) -> u32 {
+        self.rewrite_pfx
+    }
+}
+
+impl BitPack for Anycast {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // depth:(#<= 30)
+            .pack_as::<_, NBits<5>>(self.depth)?
+            // rewrite_pfx:(bits depth)
+            .pack_as_with::<_, VarNBits>(self.rewrite_pfx, self.depth as u32)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for Anycast {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        // depth:(#<= 30)
+        let depth: u8 = reader.unpack_as::<_, NBits<5>>().context("depth")?;
+        if !(1..=Self::MAX_DEPTH).contains(&depth) {
+            return Err(Error::custom(format!("invalid depth: {depth}")));
+        }
+        Ok(Self {
+            depth,
+            // rewrite_pfx:(bits depth)
+            rewrite_pfx: reader
+                .unpack_as_with::<_, VarNBits>(depth as u32)
+                .context("rewrite_pfx")?,
+        })
+    }
+}
+
+/// [MsgAddress](https://docs.ton.org/develop/data-formats/msg-tlb#msgaddressext-tl-b)
+/// with all constructors, which is **de**/**ser**ialized bit-exactly, i.e.
+/// [`Anycast`] is kept as-is, while [`MsgAddress`] rejects it:
+/// ```rust
+/// # use tlb::bits::{de::unpack_fully, ser::pack};
+/// # use tlb_ton::{Anycast, ExtendedMsgAddress, MsgAddress};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let addr = ExtendedMsgAddress::Std {
+///     anycast: Some(Anycast::new(4, 0b0101)?),
+///     workchain_id: 0,
+///     address: [0xff; 32],
+/// };
+/// let packed = pack(&addr)?;
+///
+/// let got: ExtendedMsgAddress = unpack_fully(&packed)?;
+/// assert_eq!(got, addr);
+/// assert_eq!(pack(&got)?, packed);
+///
+/// assert!(unpack_fully::<MsgAddress>(&packed).is_err());
+///
+/// // anycast is applied explicitly
+/// let effective = got.effective_address()?;
+/// assert_eq!(effective.address[0], 0x5f);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ExtendedMsgAddress {
+    /// ```tlb
+    /// addr_none$00 = MsgAddressExt;
+    /// ```
+    #[default]
+    None,
+    /// ```tlb
+    /// addr_extern$01 len:(## 9) external_address:(bits len) = MsgAddressExt;
+    /// ```
+    Extern(BitVec<u8, Msb0>),
+    /// ```tlb
+    /// addr_std$10 anycast:(Maybe Anycast)
+    /// workchain_id:int8 address:bits256  = MsgAddressInt;
+    /// ```
+    Std {
+        anycast: Option<Anycast>,
+        workchain_id: i8,
+        /// Address before [`Anycast`] is applied
+        address: [u8; 32],
+    },
+    /// ```tlb
+    /// addr_var$11 anycast:(Maybe Anycast) addr_len:(## 9)
+    /// workchain_id:int32 address:(bits addr_len) = MsgAddressInt;
+    /// ```
+    Var {
+        anycast: Option<Anycast>,
+        workchain_id: i32,
+        /// Address before [`Anycast`] is applied
+        address: BitVec<u8, Msb0>,
+    },
+}
+
+impl ExtendedMsgAddress {
+    /// Maximum length of `addr_extern` and `addr_var` addresses in bits
+    pub const MAX_LEN: usize = (1 << 9) - 1;
+
+    /// Effective [`MsgAddress`] with [`Anycast`] applied, which is only
+    /// defined for `addr_none`, `addr_std` and 256-bit long `addr_var`
+    pub fn effective_address(&self) -> Result<MsgAddress, StringError> {
+        Ok(match self {
+            Self::None => MsgAddress::NULL,
+            Self::Extern(_) => return Err(Error::custom("external address")),
+            Self::Std {
+                anycast,
+                workchain_id,
+                address,
+            } => MsgAddress {
+                workchain_id: (*workchain_id).into(),
+                address: *address,
+            }
+            .effective_address(*anycast),
+            Self::Var {
+                anycast,
+                workchain_id,
+                address,
+            } => {
+                if address.len() != 256 {
+                    return Err(Error::custom(format!(
+                        "address must be 256 bits long, got {}",
+                        address.len()
+                    )));
+                }
+                MsgAddress {
+                    workchain_id: *workchain_id,
+                    address: address.as_raw_slice().try_into().map_err(Error::custom)?,
+                }
+                .effective_address(*anycast)
+            }
+        })
+    }
+}
+
+/// [`MsgAddress::NULL`] is converted to `addr_none`, others are converted
+/// to `addr_std` without [`Anycast`], the same way they are serialized.
+impl From<MsgAddress> for ExtendedMsgAddress {
+    #[inline]
+    fn from(addr: MsgAddress) -> Self {
+        if addr.is_null() {
+            return Self::None;
+        }
+        Self::Std {
+            anycast: None,
+            workchain_id: addr.workchain_id as i8,
+            address: addr.address,
+        }
+    }
+}
+
+impl BitPack for ExtendedMsgAddress {
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        match self {
+            Self::None => {
+                writer.pack(MsgAddressTag::Null)?;
+            }
+            Self::Extern(address) => {
+                if address.len() > Self::MAX_LEN {
+                    return Err(Error::custom(format!(
+                        "external address is too long: {} bits",
+                        address.len()
+                    )));
+                }
+                writer
+                    .pack(MsgAddressTag::Extern)?
+                    // len:(## 9)
+                    .pack_as::<_, NBits<9>>(address.len() as u16)?
+                    // external_address:(bits len)
+                    .pack(address.as_bitslice())?;
+            }
+            Self::Std {
+                anycast,
+                workchain_id,
+                address,
+            } => {
+                writer
+                    .pack(MsgAddressTag::Std)?
+                    // anycast:(Maybe Anycast)
+                    .pack(anycast)?
+                    // workchain_id:int8
+                    .pack(workchain_id)?
+                    // address:bits256
+                    .pack(address)?;
+            }
+            Self::Var {
+                anycast,
+                workchain_id,
+                address,
+            } => {
+                if address.len() > Self::MAX_LEN {
+                    return Err(Error::custom(format!(
+                        "address is too long: {} bits",
+                        address.len()
+                    )));
+                }
+                writer
+                    .pack(MsgAddressTag::Var)?
+                    // anycast:(Maybe Anycast)
+                    .pack(anycast)?
+                    // addr_len:(## 9)
+                    .pack_as::<_, NBits<9>>(address.len() as u16)?
+                    // workchain_id:int32
+                    .pack(workchain_id)?
+                    // address:(bits addr_len)
+                    .pack(address.as_bitslice())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BitUnpack for ExtendedMsgAddress {
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(match reader.unpack()? {
+            MsgAddressTag::Null => Self::None,
+            MsgAddressTag::Extern => {
+                // len:(## 9)
+                let len: u16 = reader.unpack_as::<_, NBits<9>>().context("len")?;
+                // external_address:(bits len)
+                Self::Extern(
+                    reader
+                        .unpack_with(len as usize)
+                        .context("external_address")?,
+                )
+            }
+            MsgAddressTag::Std => Self::Std {
+                // anycast:(Maybe Anycast)
+                anycast: reader.unpack().context("anycast")?,
+                // workchain_id:int8
+                workchain_id: reader.unpack()?,
+                // address:bits256
+                address: reader.unpack()?,
+            },
+            MsgAddressTag::Var => {
+                // anycast:(Maybe Anycast)
+                let anycast = reader.unpack().context("anycast")?;
+                // addr_len:(## 9)
+                let len: u16 = reader.unpack_as::<_, NBits<9>>().context("addr_len")?;
+                Self::Var {
+                    anycast,
+                    // workchain_id:int32
+                    workchain_id: reader.unpack()?,
+                    // address:(bits addr_len)
+                    address: reader.unpack_with(len as usize).context("address")?,
+                }
+            }
+        })
+    }
+}
+
 #[derive(Clone, Copy, Display)]
 #[repr(u8)]
-enum MsgAddressTag {
+pub(crate) enum MsgAddressTag {
     #[strum(serialize = "addr_none$00")]
     Null,
     #[strum(serialize = "addr_extern$01")]
@@ -319,8 +944,11 @@ impl BitUnpack for MsgAddressTag {
 
 #[cfg(test)]
 mod tests {
+    use tlb::bits::{bitvec::vec::BitVec, de::unpack_fully, ser::pack};
+
     use super::*;
 
+    #[cfg(feature = "base64")]
     #[test]
     fn parse_address() {
         let _: MsgAddress = "EQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0Z9e"
@@ -328,6 +956,243 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn anycast() {
+        let anycast = Anycast::new(30, (1 << 30) - 1).unwrap();
+        assert_eq!(
+            unpack_fully::<Anycast>(pack(anycast).unwrap()).unwrap(),
+            anycast
+        );
+        assert!(Anycast::new(0, 0).is_err());
+        assert!(Anycast::new(31, 0).is_err());
+        assert!(Anycast::new(2, 0b100).is_err());
+
+        let addr = MsgAddress {
+            workchain_id: -1,
+            address: [0; 32],
+        };
+        let packed = pack((
+            MsgAddressTag::Std,
+            Some(anycast),
+            addr.workchain_id as i8,
+            addr.address,
+        ))
+        .unwrap();
+        assert!(unpack_fully::<MsgAddress>(&packed).is_err());
+
+        let got: ExtendedMsgAddress = unpack_fully(&packed).unwrap();
+        assert_eq!(pack(&got).unwrap(), packed);
+        let effective = got.effective_address().unwrap();
+        assert_eq!(effective, addr.apply_anycast(anycast));
+        assert_eq!(effective.address[..4], [0xff, 0xff, 0xff, 0xfc]);
+    }
+
+    /// Deterministic pseudo-random bytes for property tests
+    fn bytes(seed: u64) -> impl Iterator<Item = u8> {
+        let mut x = seed.wrapping_mul(0x9e3779b97f4a7c15) | 1;
+        core::iter::repeat_with(move || {
+            // xorshift64
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x as u8
+        })
+    }
+
+    fn assert_exact_round_trip(addr: ExtendedMsgAddress) {
+        let packed = pack(&addr).unwrap();
+        let got: ExtendedMsgAddress = unpack_fully(&packed).unwrap();
+        assert_eq!(got, addr);
+        assert_eq!(pack(&got).unwrap(), packed, "{addr:?}");
+
+        match &addr {
+            ExtendedMsgAddress::Var {
+                anycast,
+                workchain_id,
+                address,
+            } => match addr.effective_address() {
+                Ok(effective) => {
+                    assert_eq!(address.len(), 256);
+                    assert_eq!(
+                        effective,
+                        MsgAddress {
+                            workchain_id: *workchain_id,
+                            address: address.as_raw_slice().try_into().unwrap(),
+                        }
+                        .effective_address(*anycast)
+                    );
+                }
+                Err(_) => assert_ne!(address.len(), 256),
+            },
+            ExtendedMsgAddress::Std {
+                anycast: Some(_), ..
+            } => assert!(unpack_fully::<MsgAddress>(&packed).is_err()),
+            _ => {
+                if let Ok(effective) = addr.effective_address() {
+                    assert_eq!(unpack_fully::<MsgAddress>(&packed).unwrap(), effective);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn extended_std_anycast_round_trip() {
+        let mut seed = 0;
+        for depth in 1..=Anycast::MAX_DEPTH {
+            let max = (1u32 << depth) - 1;
+            for rewrite_pfx in [0, max, max & 0x2aaaaaaa, max & 0x15555555] {
+                for workchain_id in [-128, -1, 0, 127] {
+                    seed += 1;
+                    let mut address = [0; 32];
+                    address
+                        .iter_mut()
+                        .zip(bytes(seed))
+                        .for_each(|(b, r)| *b = r);
+                    let anycast = Anycast::new(depth, rewrite_pfx).unwrap();
+                    assert_exact_round_trip(ExtendedMsgAddress::Std {
+                        anycast: Some(anycast),
+                        workchain_id,
+                        address,
+                    });
+
+                    let effective = ExtendedMsgAddress::Std {
+                        anycast: Some(anycast),
+                        workchain_id,
+                        address,
+                    }
+                    .effective_address()
+                    .unwrap();
+                    assert_eq!(
+                        effective,
+                        MsgAddress {
+                            workchain_id: workchain_id.into(),
+                            address
+                        }
+                        .apply_anycast(anycast)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn extended_var_extern_round_trip() {
+        for (seed, len) in [0, 1, 8, 249, 255, 256, 257, ExtendedMsgAddress::MAX_LEN]
+            .into_iter()
+            .enumerate()
+        {
+            let address: BitVec<u8, Msb0> = bytes(seed as u64)
+                .take(len.div_ceil(8))
+                .collect::<Vec<_>>()
+                .as_bits::<Msb0>()[..len]
+                .to_bitvec();
+            for anycast in [None, Some(Anycast::new(5, 0b10101).unwrap())] {
+                assert_exact_round_trip(ExtendedMsgAddress::Var {
+                    anycast,
+                    workchain_id: -1_000_000,
+                    address: address.clone(),
+                });
+            }
+            assert_exact_round_trip(ExtendedMsgAddress::Extern(address));
+        }
+
+        let too_long = BitVec::repeat(false, ExtendedMsgAddress::MAX_LEN + 1);
+        assert!(pack(ExtendedMsgAddress::Extern(too_long.clone())).is_err());
+        assert!(ExtendedMsgAddress::Var {
+            anycast: None,
+            workchain_id: 0,
+            address: too_long,
+        }
+        .effective_address()
+        .is_err());
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn extended_same_as_msg_address() {
+        for s in [
+            "EQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0Z9e",
+            "EQAWezezpqKTbO6xjCussXDdIeJ7XxTcErjA6uD3T3r7AwTk",
+            // elector
+            "-1:3333333333333333333333333333333333333333333333333333333333333333",
+        ] {
+            let addr: MsgAddress = s.parse().unwrap();
+            let extended = ExtendedMsgAddress::from(addr);
+            assert_eq!(pack(&extended).unwrap(), pack(addr).unwrap());
+            assert_eq!(extended.effective_address().unwrap(), addr);
+            assert_exact_round_trip(extended);
+        }
+        assert_eq!(
+            ExtendedMsgAddress::from(MsgAddress::NULL),
+            ExtendedMsgAddress::None
+        );
+        assert_exact_round_trip(ExtendedMsgAddress::None);
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn display_friendly_round_trip() {
+        let addr: MsgAddress = "EQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0Z9e"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            addr.display(AddressFormat::default()).to_string(),
+            addr.to_base64_url()
+        );
+        for i in 0..8 {
+            let flags = FriendlyFlags {
+                non_bounceable: i & 1 != 0,
+                non_production: i & 2 != 0,
+                base64_std: i & 4 != 0,
+            };
+            let s = addr.display_friendly(flags).to_string();
+            assert_eq!(s.parse::<MsgAddress>().unwrap(), addr);
+        }
+        assert_eq!(
+            addr.display_raw()
+                .to_string()
+                .parse::<MsgAddress>()
+                .unwrap(),
+            addr
+        );
+    }
+
+    #[test]
+    fn with_default_format() {
+        let addr: MsgAddress = "0:465d9f5d759796ca9c7c124262787257ef972dd1ba649aed18e18a18af734cd1"
+            .parse()
+            .unwrap();
+        let nested = MsgAddress::with_default_format(AddressFormat::Raw, || {
+            assert_eq!(addr.to_string(), addr.to_hex());
+            let friendly = FriendlyFlags {
+                non_production: true,
+                ..Default::default()
+            };
+            MsgAddress::with_default_format(AddressFormat::Friendly(friendly), || {
+                assert_eq!(
+                    addr.to_string(),
+                    addr.display_friendly(friendly).to_string()
+                );
+            });
+            addr.to_string()
+        });
+        assert_eq!(nested, addr.to_hex());
+        assert_eq!(MsgAddress::default_format(), AddressFormat::default());
+
+        // restored after panic and not shared with other threads
+        let _ = std::panic::catch_unwind(|| {
+            MsgAddress::with_default_format(AddressFormat::Raw, || {
+                std::thread::spawn(|| {
+                    assert_eq!(MsgAddress::default_format(), AddressFormat::default());
+                })
+                .join()
+                .unwrap();
+                panic!()
+            })
+        });
+        assert_eq!(MsgAddress::default_format(), AddressFormat::default());
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn serde() {
@@ -337,4 +1202,24 @@ mod tests {
             serde_json::from_value(json!("EQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0Z9e"))
                 .unwrap();
     }
+
+    #[cfg(all(feature = "serde", feature = "base64"))]
+    #[test]
+    fn serde_raw_address() {
+        use serde_json::json;
+        use serde_with::{de::DeserializeAsWrap, ser::SerializeAsWrap};
+
+        let addr: MsgAddress = "EQBGXZ9ddZeWypx8EkJieHJX75ct0bpkmu0Y4YoYr3NM0Z9e"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(SerializeAsWrap::<_, RawAddress>::new(&addr)).unwrap(),
+            json!(addr.to_hex())
+        );
+        for s in [addr.to_hex(), addr.to_string()] {
+            let got: DeserializeAsWrap<MsgAddress, RawAddress> =
+                serde_json::from_value(json!(s)).unwrap();
+            assert_eq!(got.into_inner(), addr);
+        }
+    }
 }
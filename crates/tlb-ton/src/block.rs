@@ -0,0 +1,566 @@
+//! Collection of types related to [blocks](https://docs.ton.org/develop/data-formats/block-layout)
+use core::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use tlb::{
+    bits::{
+        de::{BitReader, BitReaderExt, BitUnpack},
+        r#as::NBits,
+        ser::{BitPack, BitWriter, BitWriterExt},
+    },
+    tag::Tag,
+    Error, ResultExt, StringError,
+};
+
+use crate::MsgAddress;
+
+/// Identifier of a [shard](https://docs.ton.org/learn/overviews/addresses#workchain-id-and-account-id),
+/// which contains all accounts of the workchain with addresses starting
+/// with its prefix.
+///
+/// `shard` is stored in the form used across TON: prefix bits followed by
+/// a single `1` bit and zeros, so that full workchain is
+/// `0x8000000000000000`.
+/// ```tlb
+/// shard_ident$00 shard_pfx_bits:(#<= 60)
+///   workchain_id:int32 shard_prefix:uint64 = ShardIdent;
+/// ```
+/// ```rust
+/// # use tlb_ton::block::ShardIdent;
+/// let shard = ShardIdent::full(0);
+/// assert_eq!(shard.to_string(), "0,8000000000000000");
+///
+/// let (left, right) = shard.split().unwrap();
+/// assert_eq!(left.to_string(), "0,4000000000000000");
+/// assert_eq!(right, "0,c000000000000000".parse().unwrap());
+/// assert_eq!(left.merge(), Some(shard));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShardIdent {
+    workchain_id: i32,
+    shard: u64,
+}
+
+impl ShardIdent {
+    /// Maximum length of the shard prefix
+    pub const MAX_PREFIX_LEN: u8 = 60;
+
+    const FULL: u64 = 1 << 63;
+
+    const TAG: Tag = Tag::parse("$00");
+
+    /// Create shard identifier, `shard` must contain the tag bit
+    #[inline]
+    pub fn new(workchain_id: i32, shard: u64) -> Result<Self, StringError> {
+        if shard == 0 || shard.trailing_zeros() < 63 - Self::MAX_PREFIX_LEN as u32 {
+            return Err(Error::custom(format!("invalid shard: {shard:016x}")));
+        }
+        Ok(Self {
+            workchain_id,
+            shard,
+        })
+    }
+
+    /// Shard covering the whole workchain
+    #[inline]
+    pub const fn full(workchain_id: i32) -> Self {
+        Self {
+            workchain_id,
+            shard: Self::FULL,
+        }
+    }
+
+    #[inline]
+    pub const fn workchain_id(&self) -> i32 {
+        self.workchain_id
+    }
+
+    /// Shard with tag bit
+    #[inline]
+    pub const fn shard(&self) -> u64 {
+        self.shard
+    }
+
+    /// Number of bits in the shard prefix
+    #[inline]
+    pub const fn prefix_len(&self) -> u8 {
+        63 - self.shard.trailing_zeros() as u8
+    }
+
+    /// Shard prefix without tag bit
+    #[inline]
+    pub const fn prefix(&self) -> u64 {
+        self.shard - self.tag_bit()
+    }
+
+    /// Returns whether this shard covers the whole workchain
+    #[inline]
+    pub const fn is_full(&self) -> bool {
+        self.shard == Self::FULL
+    }
+
+    #[inline]
+    const fn tag_bit(&self) -> u64 {
+        self.shard & self.shard.wrapping_neg()
+    }
+
+    /// Returns whether given account belongs to this shard
+    #[inline]
+    pub fn contains_address(&self, addr: &MsgAddress) -> bool {
+        let account_prefix = u64::from_be_bytes(addr.address[..8].try_into().unwrap());
+        addr.workchain_id == self.workchain_id
+            && (account_prefix ^ self.shard) & (self.tag_bit().wrapping_neg() << 1) == 0
+    }
+
+    /// Returns whether this shard is an ancestor of given one or the same
+    #[inline]
+    pub const fn contains(&self, other: &Self) -> bool {
+        self.workchain_id == other.workchain_id
+            && self.tag_bit() >= other.tag_bit()
+            && (other.shard ^ self.shard) & (self.tag_bit().wrapping_neg() << 1) == 0
+    }
+
+    /// Split into two child shards: `(left, right)`, or `None` if the
+    /// prefix is already of [maximum length](ShardIdent::MAX_PREFIX_LEN)
+    #[inline]
+    pub const fn split(&self) -> Option<(Self, Self)> {
+        if self.prefix_len() >= Self::MAX_PREFIX_LEN {
+            return None;
+        }
+        let half = self.tag_bit() >> 1;
+        Some((
+            Self {
+                workchain_id: self.workchain_id,
+                shard: self.shard - half,
+            },
+            Self {
+                workchain_id: self.workchain_id,
+                shard: self.shard + half,
+            },
+        ))
+    }
+
+    /// Parent shard resulting from merging this shard with its sibling, or
+    /// `None` if this shard [covers the whole workchain](ShardIdent::is_full)
+    #[inline]
+    pub const fn merge(&self) -> Option<Self> {
+        if self.is_full() {
+            return None;
+        }
+        let tag_bit = self.tag_bit();
+        Some(Self {
+            workchain_id: self.workchain_id,
+            shard: (self.shard & !(tag_bit | tag_bit << 1)) | tag_bit << 1,
+        })
+    }
+}
+
+/// `workchain_id,shard` with `shard` in hex, e.g. `0,8000000000000000`
+impl Display for ShardIdent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{:016x}", self.workchain_id, self.shard)
+    }
+}
+
+impl FromStr for ShardIdent {
+    type Err = StringError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (workchain_id, shard) = s
+            .split_once(',')
+            .ok_or_else(|| Error::custom("wrong format"))?;
+        Self::new(
+            workchain_id
+                .parse()
+                .map_err(Error::custom)
+                .context("workchain_id")?,
+            u64::from_str_radix(shard, 16)
+                .map_err(Error::custom)
+                .context("shard")?,
+        )
+    }
+}
+
+impl BitPack for ShardIdent {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            // shard_ident$00
+            .pack(Self::TAG)?
+            // shard_pfx_bits:(#<= 60)
+            .pack_as::<_, NBits<6>>(self.prefix_len())?
+            // workchain_id:int32
+            .pack(self.workchain_id)?
+            // shard_prefix:uint64
+            .pack(self.prefix())?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for ShardIdent {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        // shard_ident$00
+        Tag::unpack_one_of(&mut reader, &[Self::TAG])?;
+        let prefix_len: u8 = reader.unpack_as::<_, NBits<6>>()?;
+        if prefix_len > Self::MAX_PREFIX_LEN {
+            return Err(Error::custom(format!(
+                "shard_pfx_bits: {prefix_len} exceeds {}",
+                Self::MAX_PREFIX_LEN
+            )));
+        }
+        let workchain_id: i32 = reader.unpack().context("workchain_id")?;
+        let prefix: u64 = reader.unpack().context("shard_prefix")?;
+        let tag_bit = 1 << (63 - prefix_len);
+        if prefix & (tag_bit | (tag_bit - 1)) != 0 {
+            return Err(Error::custom(format!(
+                "shard_prefix {prefix:016x} is longer than {prefix_len} bits"
+            )));
+        }
+        Ok(Self {
+            workchain_id,
+            shard: prefix | tag_bit,
+        })
+    }
+}
+
+/// Identifier of a block within its shard chain.
+///
+/// Displayed in canonical form: `(workchain_id,shard,seqno)`
+/// ```rust
+/// # use tlb_ton::block::{BlockId, ShardIdent};
+/// let id = BlockId {
+///     shard: ShardIdent::full(-1),
+///     seqno: 1,
+/// };
+/// assert_eq!(id.to_string(), "(-1,8000000000000000,1)");
+/// assert_eq!(id, "(-1,8000000000000000,1)".parse().unwrap());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockId {
+    pub shard: ShardIdent,
+    pub seqno: u32,
+}
+
+impl BlockId {
+    /// Size of [TL](https://docs.ton.org/develop/data-formats/tl) representation
+    pub const TL_SIZE: usize = 4 + 8 + 4;
+
+    /// Serialize as [TL](https://docs.ton.org/develop/data-formats/tl):
+    /// ```tl
+    /// tonNode.blockId workchain:int shard:long seqno:int = tonNode.BlockId;
+    /// ```
+    #[inline]
+    pub fn to_tl_bytes(&self) -> [u8; Self::TL_SIZE] {
+        let mut bytes = [0; Self::TL_SIZE];
+        bytes[..4].copy_from_slice(&self.shard.workchain_id().to_le_bytes());
+        bytes[4..12].copy_from_slice(&self.shard.shard().to_le_bytes());
+        bytes[12..].copy_from_slice(&self.seqno.to_le_bytes());
+        bytes
+    }
+
+    /// Deserialize from [TL](https://docs.ton.org/develop/data-formats/tl),
+    /// see [`.to_tl_bytes()`](BlockId::to_tl_bytes)
+    #[inline]
+    pub fn from_tl_bytes(bytes: &[u8]) -> Result<Self, StringError> {
+        let bytes: &[u8; Self::TL_SIZE] = bytes.try_into().map_err(|_| {
+            Error::custom(format!(
+                "expected {} bytes, got {}",
+                Self::TL_SIZE,
+                bytes.len()
+            ))
+        })?;
+        Ok(Self {
+            shard: ShardIdent::new(
+                i32::from_le_bytes(bytes[..4].try_into().unwrap()),
+                u64::from_le_bytes(bytes[4..12].try_into().unwrap()),
+            )?,
+            seqno: u32::from_le_bytes(bytes[12..].try_into().unwrap()),
+        })
+    }
+}
+
+impl Display for BlockId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({},{})", self.shard, self.seqno)
+    }
+}
+
+impl FromStr for BlockId {
+    type Err = StringError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .strip_prefix('(')
+            .and_then(|s| s.strip_suffix(')'))
+            .ok_or_else(|| Error::custom("wrong format"))?;
+        let (shard, seqno) = s
+            .rsplit_once(',')
+            .ok_or_else(|| Error::custom("wrong format"))?;
+        Ok(Self {
+            shard: shard.parse()?,
+            seqno: seqno.parse().map_err(Error::custom).context("seqno")?,
+        })
+    }
+}
+
+/// Full identifier of a block including its hashes.
+///
+/// Displayed in canonical form: `(workchain_id,shard,seqno):root_hash:file_hash`
+/// with hashes in upper hex.
+/// ```tlb
+/// block_id_ext$_ shard_id:ShardIdent seq_no:uint32
+///   root_hash:bits256 file_hash:bits256 = BlockIdExt;
+/// ```
+/// ```rust
+/// # use tlb_ton::block::BlockIdExt;
+/// let s = "(-1,8000000000000000,1):\
+///     D4A8D6C0B1E5B40F8D6A7F1C3B54E5D3C50D9A1F6A8B7C6D5E4F3A2B1C0D9E8F:\
+///     0F1E2D3C4B5A69788796A5B4C3D2E1F00F1E2D3C4B5A69788796A5B4C3D2E1F0";
+/// let id: BlockIdExt = s.parse().unwrap();
+/// assert_eq!(id.seqno, 1);
+/// assert_eq!(id.to_string(), s);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockIdExt {
+    pub shard: ShardIdent,
+    pub seqno: u32,
+    pub root_hash: [u8; 32],
+    pub file_hash: [u8; 32],
+}
+
+impl BlockIdExt {
+    /// Size of [TL](https://docs.ton.org/develop/data-formats/tl) representation
+    pub const TL_SIZE: usize = BlockId::TL_SIZE + 32 + 32;
+
+    /// Identifier without hashes
+    #[inline]
+    pub const fn id(&self) -> BlockId {
+        BlockId {
+            shard: self.shard,
+            seqno: self.seqno,
+        }
+    }
+
+    /// Serialize as [TL](https://docs.ton.org/develop/data-formats/tl):
+    /// ```tl
+    /// tonNode.blockIdExt workchain:int shard:long seqno:int
+    ///   root_hash:int256 file_hash:int256 = tonNode.BlockIdExt;
+    /// ```
+    #[inline]
+    pub fn to_tl_bytes(&self) -> [u8; Self::TL_SIZE] {
+        let mut bytes = [0; Self::TL_SIZE];
+        bytes[..BlockId::TL_SIZE].copy_from_slice(&self.id().to_tl_bytes());
+        bytes[BlockId::TL_SIZE..BlockId::TL_SIZE + 32].copy_from_slice(&self.root_hash);
+        bytes[BlockId::TL_SIZE + 32..].copy_from_slice(&self.file_hash);
+        bytes
+    }
+
+    /// Deserialize from [TL](https://docs.ton.org/develop/data-formats/tl),
+    /// see [`.to_tl_bytes()`](BlockIdExt::to_tl_bytes)
+    #[inline]
+    pub fn from_tl_bytes(bytes: &[u8]) -> Result<Self, StringError> {
+        if bytes.len() != Self::TL_SIZE {
+            return Err(Error::custom(format!(
+                "expected {} bytes, got {}",
+                Self::TL_SIZE,
+                bytes.len()
+            )));
+        }
+        let (id, hashes) = bytes.split_at(BlockId::TL_SIZE);
+        let BlockId { shard, seqno } = BlockId::from_tl_bytes(id)?;
+        let (root_hash, file_hash) = hashes.split_at(32);
+        Ok(Self {
+            shard,
+            seqno,
+            root_hash: root_hash.try_into().unwrap(),
+            file_hash: file_hash.try_into().unwrap(),
+        })
+    }
+}
+
+impl Display for BlockIdExt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.id(),
+            hex::encode_upper(self.root_hash),
+            hex::encode_upper(self.file_hash)
+        )
+    }
+}
+
+impl FromStr for BlockIdExt {
+    type Err = StringError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rest, file_hash) = s
+            .rsplit_once(':')
+            .ok_or_else(|| Error::custom("wrong format"))?;
+        let (id, root_hash) = rest
+            .rsplit_once(':')
+            .ok_or_else(|| Error::custom("wrong format"))?;
+        let BlockId { shard, seqno } = id.parse()?;
+        let mut block_id = Self {
+            shard,
+            seqno,
+            root_hash: [0; 32],
+            file_hash: [0; 32],
+        };
+        hex::decode_to_slice(root_hash, &mut block_id.root_hash)
+            .map_err(Error::custom)
+            .context("root_hash")?;
+        hex::decode_to_slice(file_hash, &mut block_id.file_hash)
+            .map_err(Error::custom)
+            .context("file_hash")?;
+        Ok(block_id)
+    }
+}
+
+impl BitPack for BlockIdExt {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer
+            .pack(self.shard)?
+            .pack(self.seqno)?
+            .pack(self.root_hash)?
+            .pack(self.file_hash)?;
+        Ok(())
+    }
+}
+
+impl BitUnpack for BlockIdExt {
+    #[inline]
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Ok(Self {
+            shard: reader.unpack().context("shard_id")?,
+            seqno: reader.unpack().context("seq_no")?,
+            root_hash: reader.unpack().context("root_hash")?,
+            file_hash: reader.unpack().context("file_hash")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::bits::{de::unpack_fully, ser::pack};
+
+    use super::*;
+
+    #[test]
+    fn shard_ident_pack_unpack() {
+        let full = ShardIdent::full(-1);
+        let (left, right) = full.split().unwrap();
+        for shard in [full, left, right, right.split().unwrap().0] {
+            let packed = pack(shard).unwrap();
+            assert_eq!(packed.len(), 2 + 6 + 32 + 64);
+            assert_eq!(unpack_fully::<ShardIdent>(&packed).unwrap(), shard);
+        }
+    }
+
+    #[test]
+    fn shard_ident_split_merge() {
+        let mut shard = ShardIdent::full(0);
+        assert_eq!(shard.merge(), None);
+        for _ in 0..ShardIdent::MAX_PREFIX_LEN {
+            let (left, right) = shard.split().unwrap();
+            assert_eq!(left.merge(), Some(shard));
+            assert_eq!(right.merge(), Some(shard));
+            assert!(shard.contains(&left) && shard.contains(&right));
+            assert!(!left.contains(&right) && !left.contains(&shard));
+            shard = right;
+        }
+        assert_eq!(shard.prefix_len(), ShardIdent::MAX_PREFIX_LEN);
+        assert_eq!(shard.split(), None);
+        assert!(ShardIdent::new(0, shard.shard() >> 1).is_err());
+        assert!(ShardIdent::new(0, 0).is_err());
+    }
+
+    #[test]
+    fn shard_ident_contains_address() {
+        let mut address = [0; 32];
+        address[0] = 0b1010_0000;
+        let addr = MsgAddress {
+            workchain_id: 0,
+            address,
+        };
+        let full = ShardIdent::full(0);
+        assert!(full.contains_address(&addr));
+        assert!(!ShardIdent::full(-1).contains_address(&addr));
+
+        let (left, right) = full.split().unwrap();
+        assert!(!left.contains_address(&addr));
+        assert!(right.contains_address(&addr));
+
+        let (right_left, right_right) = right.split().unwrap();
+        assert!(right_left.contains_address(&addr));
+        assert!(!right_right.contains_address(&addr));
+    }
+
+    #[test]
+    fn shard_ident_display() {
+        let shard: ShardIdent = "-1,8000000000000000".parse().unwrap();
+        assert_eq!(shard, ShardIdent::full(-1));
+        assert_eq!(shard.to_string(), "-1,8000000000000000");
+        assert!("0,0".parse::<ShardIdent>().is_err());
+        assert!("0:8000000000000000".parse::<ShardIdent>().is_err());
+    }
+
+    fn block_id_ext() -> BlockIdExt {
+        BlockIdExt {
+            shard: ShardIdent::full(0).split().unwrap().1,
+            seqno: 42,
+            root_hash: [0xab; 32],
+            file_hash: [0x01; 32],
+        }
+    }
+
+    #[test]
+    fn block_id_ext_string() {
+        let id = block_id_ext();
+        let s = id.to_string();
+        assert_eq!(
+            s,
+            format!(
+                "(0,c000000000000000,42):{}:{}",
+                "AB".repeat(32),
+                "01".repeat(32)
+            )
+        );
+        assert_eq!(s.parse::<BlockIdExt>().unwrap(), id);
+        assert_eq!(s.to_lowercase().parse::<BlockIdExt>().unwrap(), id);
+        assert!(id.id().to_string().parse::<BlockIdExt>().is_err());
+    }
+
+    #[test]
+    fn block_id_ext_tl() {
+        let id = block_id_ext();
+        let bytes = id.to_tl_bytes();
+        assert_eq!(bytes[..4], 0i32.to_le_bytes());
+        assert_eq!(bytes[4..12], 0xc000000000000000u64.to_le_bytes());
+        assert_eq!(bytes[12..16], 42u32.to_le_bytes());
+        assert_eq!(BlockIdExt::from_tl_bytes(&bytes).unwrap(), id);
+        assert!(BlockIdExt::from_tl_bytes(&bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn block_id_ext_pack_unpack() {
+        let id = block_id_ext();
+        assert_eq!(unpack_fully::<BlockIdExt>(pack(id).unwrap()).unwrap(), id);
+    }
+}
@@ -1,21 +1,41 @@
 //! Collection of types related to [Bag Of Cells](https://docs.ton.org/develop/data-formats/cell-boc#bag-of-cells)
+use core::{
+    fmt::{self, Display},
+    ops::ControlFlow,
+};
 use std::{
     collections::{HashMap, HashSet},
-    sync::Arc,
+    sync::{Arc, RwLock},
 };
 
-use base64::{engine::general_purpose::STANDARD, Engine};
+#[cfg(feature = "base64")]
+use base64::{
+    alphabet,
+    engine::{
+        general_purpose::{GeneralPurpose, GeneralPurposeConfig, STANDARD, URL_SAFE},
+        DecodePaddingMode,
+    },
+    Engine,
+};
+#[cfg(feature = "crc")]
 use crc::Crc;
+use lazy_static::lazy_static;
 use tlb::{
     bits::{
-        bitvec::{order::Msb0, vec::BitVec, view::AsBits},
+        bitfield,
+        bitvec::{order::Msb0, slice::BitSlice, vec::BitVec, view::AsBits},
         de::{args::BitUnpackWithArgs, BitReader, BitReaderExt, BitUnpack},
+        integer::Tag32,
         r#as::{NBits, VarNBytes},
-        ser::{args::BitPackWithArgs, BitWriter, BitWriterExt},
+        ser::{args::BitPackWithArgs, pack_with, BitWriter, BitWriterExt},
     },
-    Cell, Error, ResultExt, StringError,
+    hash::CellHashCache,
+    ser::{CellBuilderError, CellSerialize, CellSerializeExt},
+    Cell, CellType, Error, ResultExt, StringError,
 };
 
+use crate::message::Message;
+
 /// Alias to [`BagOfCells`]
 pub type BoC = BagOfCells;
 
@@ -28,20 +48,16 @@ pub type BoC = BagOfCells;
 /// #     bits::{de::unpack_fully, ser::{BitWriterExt, pack_with}},
 /// #     Cell,
 /// #     ser::CellSerializeExt,
-/// #     StringError,
 /// # };
 /// # use tlb_ton::{boc::{BagOfCells, BagOfCellsArgs}, MsgAddress};
-/// # fn main() -> Result<(), StringError> {
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// let addr = MsgAddress::NULL;
 /// let mut builder = Cell::builder();
 /// builder.pack(addr)?;
 /// let root = builder.into_cell();
 ///
 /// let boc = BagOfCells::from_root(root);
-/// let packed = pack_with(boc, BagOfCellsArgs {
-///     has_idx: false,
-///     has_crc32c: true,
-/// })?;
+/// let packed = pack_with(boc, BagOfCellsArgs::new(false, false))?;
 ///
 /// let unpacked: BagOfCells = unpack_fully(packed)?;
 /// let got: MsgAddress = unpacked
@@ -73,6 +89,68 @@ impl BagOfCells {
         self.roots.push(root.into())
     }
 
+    /// Create from roots of given messages, e.g. to broadcast several
+    /// external messages at once:
+    /// ```rust
+    /// # use tlb_ton::{
+    /// #     boc::{BagOfCells, BagOfCellsArgs},
+    /// #     message::{CommonMsgInfo, ExternalInMsgInfo, Message},
+    /// #     MsgAddress,
+    /// # };
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let msg = |dst| Message::<()> {
+    ///     info: CommonMsgInfo::ExternalIn(ExternalInMsgInfo {
+    ///         src: MsgAddress::NULL,
+    ///         dst,
+    ///         import_fee: 0u32.into(),
+    ///     }),
+    ///     init: None,
+    ///     body: (),
+    /// };
+    /// let boc = BagOfCells::from_messages([
+    ///     msg(MsgAddress::NULL),
+    ///     msg(MsgAddress { workchain_id: 0, address: [1; 32] }),
+    /// ])?;
+    /// let bytes = boc.to_bytes(BagOfCellsArgs::default())?;
+    ///
+    /// let boc = BagOfCells::parse_hex(hex::encode(bytes))?;
+    /// assert!(boc.single_root().is_none());
+    /// for boc in boc.split_roots() {
+    ///     let root = boc.single_root().unwrap();
+    ///     let _: Message = root.parse_fully()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_messages<T, IC, ID>(
+        msgs: impl IntoIterator<Item = Message<T, IC, ID>>,
+    ) -> Result<Self, CellBuilderError>
+    where
+        T: CellSerialize,
+        IC: CellSerialize,
+        ID: CellSerialize,
+    {
+        let roots = msgs
+            .into_iter()
+            .enumerate()
+            .map(|(i, msg)| {
+                msg.to_cell()
+                    .map(Arc::new)
+                    .with_context(|| format!("[{i}]"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if roots.is_empty() {
+            return Err(Error::custom("no messages"));
+        }
+        Ok(Self { roots })
+    }
+
+    /// Split into bags with single root each, preserving order
+    #[inline]
+    pub fn split_roots(self) -> impl Iterator<Item = Self> {
+        self.roots.into_iter().map(Self::from_root)
+    }
+
     /// Return single root or `None` otherwise
     #[inline]
     pub fn single_root(&self) -> Option<&Arc<Cell>> {
@@ -80,42 +158,933 @@ impl BagOfCells {
         Some(root)
     }
 
-    /// Traverses all cells, fills all_cells set and inbound references map.
+    /// Return statistics on cells in this bag
+    pub fn stats(&self) -> BagOfCellsStats {
+        let mut stats = BagOfCellsStats::default();
+        let mut counts = HashMap::new();
+        for root in &self.roots {
+            stats.cells =
+                stats
+                    .cells
+                    .saturating_add(Self::collect_stats(root, &mut counts, &mut stats));
+        }
+        stats
+    }
+
+    /// Fills stats for unique cells and returns total number of cells
+    /// in the tree with given root.
+    fn collect_stats(
+        cell: &Arc<Cell>,
+        counts: &mut HashMap<*const Cell, u64>,
+        stats: &mut BagOfCellsStats,
+    ) -> u64 {
+        if let Some(count) = counts.get(&Arc::as_ptr(cell)) {
+            return *count;
+        }
+        stats.unique_cells += 1;
+        stats.bits += cell.data.len();
+        stats.refs += cell.references.len();
+        stats.memory_usage += cell.shallow_memory_usage();
+
+        let count = cell
+            .references
+            .iter()
+            .map(|r| Self::collect_stats(r, counts, stats))
+            .fold(1u64, u64::saturating_add);
+        counts.insert(Arc::as_ptr(cell), count);
+        count
+    }
+
+    /// Traverses all cells, fills all_cells map and inbound references map,
+    /// both keyed by hashes of cells.
     fn traverse_cell_tree(
         cell: &Arc<Cell>,
-        all_cells: &mut HashSet<Arc<Cell>>,
-        in_refs: &mut HashMap<Arc<Cell>, HashSet<Arc<Cell>>>,
+        hashes: &mut CellHashCache,
+        all_cells: &mut HashMap<[u8; 32], Arc<Cell>>,
+        in_refs: &mut HashMap<[u8; 32], HashSet<[u8; 32]>>,
+        progress: &mut ProgressFn<'_>,
     ) -> Result<(), StringError> {
-        if all_cells.insert(cell.clone()) {
+        let hash = hashes.hash(cell);
+        if all_cells.insert(hash, cell.clone()).is_none() {
+            report_progress(
+                progress,
+                Progress::new(ProgressStage::Ordering, all_cells.len(), None),
+            )?;
             for r in &cell.references {
-                if r == cell {
+                let r_hash = hashes.hash(r);
+                if r_hash == hash {
                     return Err(Error::custom("cell must not reference itself"));
                 }
-                in_refs.entry(r.clone()).or_default().insert(cell.clone());
-                Self::traverse_cell_tree(r, all_cells, in_refs)?;
+                in_refs.entry(r_hash).or_default().insert(hash);
+                Self::traverse_cell_tree(r, hashes, all_cells, in_refs, progress)?;
             }
         }
         Ok(())
     }
 
-    /// Parse hexadecimal string
+    /// See [`CellOrder::Topological`]
+    fn topological_order(
+        &self,
+        hashes: &mut CellHashCache,
+        progress: &mut ProgressFn<'_>,
+    ) -> Result<Vec<Arc<Cell>>, StringError> {
+        let mut all_cells: HashMap<[u8; 32], Arc<Cell>> = HashMap::new();
+        let mut in_refs: HashMap<[u8; 32], HashSet<[u8; 32]>> = HashMap::new();
+        for r in &self.roots {
+            Self::traverse_cell_tree(r, hashes, &mut all_cells, &mut in_refs, progress)?;
+        }
+        let mut no_in_refs: HashSet<[u8; 32]> = all_cells
+            .keys()
+            .filter(|hash| !in_refs.contains_key(*hash))
+            .copied()
+            .collect();
+        let mut ordered_cells: Vec<Arc<Cell>> = Vec::with_capacity(all_cells.len());
+        while let Some(hash) = no_in_refs.iter().next().copied() {
+            let cell = &all_cells[&hash];
+            ordered_cells.push(cell.clone());
+            for child in &cell.references {
+                let child = hashes.hash(child);
+                if let Some(refs) = in_refs.get_mut(&child) {
+                    refs.remove(&hash);
+                    if refs.is_empty() {
+                        no_in_refs.insert(child);
+                        in_refs.remove(&child);
+                    }
+                }
+            }
+            no_in_refs.remove(&hash);
+        }
+        if !in_refs.is_empty() {
+            return Err(Error::custom("reference cycle detected"));
+        }
+        Ok(ordered_cells)
+    }
+
+    /// See [`CellOrder::DfsPreorder`]
+    fn dfs_preorder(
+        &self,
+        hashes: &mut CellHashCache,
+        progress: &mut ProgressFn<'_>,
+    ) -> Result<Vec<Arc<Cell>>, StringError> {
+        DfsOrder::new(&self.roots, hashes, progress)?.into_ordered_cells()
+    }
+
+    /// Parse serialized bytes, handling trailing data after the BoC
+    /// according to given [`ParseMode`]:
+    /// ```rust
+    /// # use tlb_ton::boc::{BagOfCells, BagOfCellsWarning, ParseMode};
+    /// let mut bytes = hex::decode("b5ee9c72010101010002000000").unwrap();
+    /// bytes.extend_from_slice(b"garbage");
+    ///
+    /// assert!(BagOfCells::parse(&bytes, ParseMode::Strict).is_err());
+    ///
+    /// let parsed = BagOfCells::parse(&bytes, ParseMode::Lenient).unwrap();
+    /// assert_eq!(
+    ///     parsed.warnings,
+    ///     [BagOfCellsWarning::TrailingData { bytes: 7 }],
+    /// );
+    /// ```
+    #[inline]
+    pub fn parse(
+        bytes: impl AsRef<[u8]>,
+        mode: ParseMode,
+    ) -> Result<ParsedBagOfCells, StringError> {
+        Self::parse_reporting(bytes.as_ref(), mode, &mut |_| ControlFlow::Continue(()))
+    }
+
+    /// Same as [`BagOfCells::parse()`], but calls `progress` with the
+    /// number of cells read and built so far, so that parsing of large
+    /// BoCs can be tracked and cancelled by returning
+    /// [`ControlFlow::Break`]:
+    /// ```rust
+    /// # use core::ops::ControlFlow;
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// # use tlb::Cell;
+    /// # use tlb_ton::boc::{
+    /// #     BagOfCells, BagOfCellsArgs, ParseMode, Progress, ProgressError, ProgressStage,
+    /// # };
+    /// # let mut root = Cell::builder();
+    /// # root.store_reference(Cell::new()).unwrap();
+    /// # let bytes = BagOfCells::from_root(root.into_cell())
+    /// #     .to_bytes(BagOfCellsArgs::default())
+    /// #     .unwrap();
+    /// let mut last = None;
+    /// let parsed = BagOfCells::parse_with_progress(&bytes, ParseMode::Strict, |progress| {
+    ///     // render progress bar
+    ///     last = Some(progress);
+    ///     ControlFlow::Continue(())
+    /// });
+    /// assert!(parsed.is_ok());
+    /// assert_eq!(
+    ///     last,
+    ///     Some(Progress {
+    ///         stage: ProgressStage::Building,
+    ///         done: 2,
+    ///         total: Some(2),
+    ///     }),
+    /// );
+    ///
+    /// // e.g. set by Ctrl+C handler
+    /// let cancelled = AtomicBool::new(true);
+    /// let err = BagOfCells::parse_with_progress(&bytes, ParseMode::Strict, |_| {
+    ///     if cancelled.load(Ordering::Relaxed) {
+    ///         return ControlFlow::Break(());
+    ///     }
+    ///     ControlFlow::Continue(())
+    /// })
+    /// .unwrap_err();
+    /// assert!(matches!(err, ProgressError::Cancelled(_)));
+    /// assert_eq!(err.to_string(), "cancelled while reading 0 of 2 cells");
+    /// ```
+    pub fn parse_with_progress(
+        bytes: impl AsRef<[u8]>,
+        mode: ParseMode,
+        progress: impl FnMut(Progress) -> ControlFlow<()>,
+    ) -> Result<ParsedBagOfCells, ProgressError> {
+        with_progress(progress, |progress| {
+            Self::parse_reporting(bytes.as_ref(), mode, progress)
+        })
+    }
+
+    fn parse_reporting(
+        bytes: &[u8],
+        mode: ParseMode,
+        progress: &mut ProgressFn<'_>,
+    ) -> Result<ParsedBagOfCells, StringError> {
+        let mut reader = bytes.as_bits();
+        let boc = Self::unpack_with_progress(&mut reader, progress)?;
+        let mut warnings = Vec::new();
+        if !reader.is_empty() {
+            let trailing = BagOfCellsWarning::TrailingData {
+                bytes: reader.len().div_ceil(8),
+            };
+            match mode {
+                ParseMode::Strict => return Err(Error::custom(trailing)),
+                ParseMode::Lenient => warnings.push(trailing),
+            }
+        }
+        Ok(ParsedBagOfCells { boc, warnings })
+    }
+
+    /// Parse hexadecimal string in [`ParseMode::Strict`] mode
     pub fn parse_hex(s: impl AsRef<[u8]>) -> Result<Self, StringError> {
         let bytes = hex::decode(s).map_err(Error::custom)?;
-        Self::unpack(bytes.as_bits())
+        Self::parse(bytes, ParseMode::Strict).map(|parsed| parsed.boc)
     }
 
-    /// Parse base64-encoded string
+    /// Parse base64-encoded string in [`ParseMode::Strict`] mode
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
     pub fn parse_base64(s: impl AsRef<[u8]>) -> Result<Self, StringError> {
         let bytes = STANDARD.decode(s).map_err(Error::custom)?;
-        Self::unpack(bytes.as_bits())
+        Self::parse(bytes, ParseMode::Strict).map(|parsed| parsed.boc)
+    }
+
+    /// Parse URL-safe base64-encoded string in [`ParseMode::Strict`] mode.
+    /// Padding is optional.
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+    pub fn parse_base64_url(s: impl AsRef<[u8]>) -> Result<Self, StringError> {
+        /// [`URL_SAFE`] which accepts both padded and unpadded input
+        const URL_SAFE_INDIFFERENT: GeneralPurpose = GeneralPurpose::new(
+            &alphabet::URL_SAFE,
+            GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+        );
+
+        let bytes = URL_SAFE_INDIFFERENT.decode(s).map_err(Error::custom)?;
+        Self::parse(bytes, ParseMode::Strict).map(|parsed| parsed.boc)
+    }
+
+    /// Serialize into bytes with given [`BagOfCellsArgs`]
+    #[inline]
+    pub fn to_bytes(&self, args: BagOfCellsArgs) -> Result<Vec<u8>, StringError> {
+        pack_with(self, args).map(BitVec::into_vec)
+    }
+
+    /// Same as [`BagOfCells::to_bytes()`], but calls `progress` with the
+    /// number of cells ordered and written so far, so that serialization
+    /// of large BoCs can be tracked and cancelled by returning
+    /// [`ControlFlow::Break`], see [`BagOfCells::parse_with_progress()`].
+    ///
+    /// Total number of cells is not known until all of them are
+    /// [ordered](ProgressStage::Ordering).
+    pub fn to_bytes_with_progress(
+        &self,
+        args: BagOfCellsArgs,
+        progress: impl FnMut(Progress) -> ControlFlow<()>,
+    ) -> Result<Vec<u8>, ProgressError> {
+        with_progress(progress, |progress| {
+            let mut writer = BitVec::<u8, Msb0>::new();
+            self.pack_with_progress(&mut writer, args, progress)?;
+            Ok(writer.into_vec())
+        })
+    }
+
+    /// Serialize into lowercase hexadecimal string, the reverse of
+    /// [`BagOfCells::parse_hex()`]
+    /// ```rust
+    /// # use tlb::Cell;
+    /// # use tlb_ton::boc::{BagOfCells, BagOfCellsArgs};
+    /// let boc = BagOfCells::from_root(Cell::new());
+    /// let args = BagOfCellsArgs::new(false, false);
+    /// assert_eq!(boc.to_hex(args).unwrap(), "b5ee9c72010101010002000000");
+    /// ```
+    #[inline]
+    pub fn to_hex(&self, args: BagOfCellsArgs) -> Result<String, StringError> {
+        self.to_bytes(args).map(hex::encode)
+    }
+
+    /// Serialize into standard base64-encoded string, the reverse of
+    /// [`BagOfCells::parse_base64()`]
+    /// ```rust
+    /// # use tlb::Cell;
+    /// # use tlb_ton::boc::{BagOfCells, BagOfCellsArgs};
+    /// let boc = BagOfCells::from_root(Cell::new());
+    /// let args = BagOfCellsArgs::new(false, true);
+    /// assert_eq!(boc.to_base64(args).unwrap(), "te6cckEBAQEAAgAAAEysuc0=");
+    /// ```
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+    #[inline]
+    pub fn to_base64(&self, args: BagOfCellsArgs) -> Result<String, StringError> {
+        self.to_bytes(args).map(|bytes| STANDARD.encode(bytes))
+    }
+
+    /// Serialize into URL-safe base64-encoded string with padding, the
+    /// reverse of [`BagOfCells::parse_base64_url()`]
+    /// ```rust
+    /// # use tlb::Cell;
+    /// # use tlb_ton::boc::{BagOfCells, BagOfCellsArgs};
+    /// let boc = BagOfCells::from_root(Cell::new());
+    /// let args = BagOfCellsArgs::new(false, false);
+    /// assert_eq!(boc.to_base64_url(args).unwrap(), "te6ccgEBAQEAAgAAAA==");
+    /// ```
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+    #[inline]
+    pub fn to_base64_url(&self, args: BagOfCellsArgs) -> Result<String, StringError> {
+        self.to_bytes(args).map(|bytes| URL_SAFE.encode(bytes))
     }
 }
 
-/// [`BitPackWithArgs::Args`] for [`BagOfCells`]
+/// Cell ordering of the reference implementation, see
+/// [`CellOrder::DfsPreorder`]
+struct DfsOrder {
+    cells: Vec<DfsCellInfo>,
+    roots: Vec<usize>,
+    /// indices of allocated cells in reversed order
+    allocated: Vec<usize>,
+}
+
+struct DfsCellInfo {
+    cell: Arc<Cell>,
+    references: Vec<usize>,
+    /// weight of the cell, `0` means that the cell is special
+    wt: u8,
+    state: DfsCellState,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DfsCellState {
+    New,
+    Previsited,
+    Visited,
+    Allocated,
+}
+
+impl DfsOrder {
+    const MAX_CELL_WEIGHT: usize = 64;
+
+    fn new(
+        roots: &[Arc<Cell>],
+        hashes: &mut CellHashCache,
+        progress: &mut ProgressFn<'_>,
+    ) -> Result<Self, StringError> {
+        let mut order = Self {
+            cells: Vec::new(),
+            roots: Vec::with_capacity(roots.len()),
+            allocated: Vec::new(),
+        };
+        let mut indices = HashMap::new();
+        for root in roots {
+            let i = order.import(root, hashes, &mut indices, progress)?;
+            order.roots.push(i);
+        }
+        order.reweight();
+        Ok(order)
+    }
+
+    /// Add cells in post-order, so that children go before parents.
+    /// Cells are deduplicated by their hashes.
+    fn import(
+        &mut self,
+        cell: &Arc<Cell>,
+        hashes: &mut CellHashCache,
+        indices: &mut HashMap<[u8; 32], usize>,
+        progress: &mut ProgressFn<'_>,
+    ) -> Result<usize, StringError> {
+        let hash = hashes.hash(cell);
+        if let Some(i) = indices.get(&hash) {
+            return Ok(*i);
+        }
+        let mut references = Vec::with_capacity(cell.references.len());
+        let mut wt = 1usize;
+        for r in &cell.references {
+            if hashes.hash(r) == hash {
+                return Err(Error::custom("cell must not reference itself"));
+            }
+            let i = self.import(r, hashes, indices, progress)?;
+            wt += self.cells[i].wt as usize;
+            references.push(i);
+        }
+        self.cells.push(DfsCellInfo {
+            cell: cell.clone(),
+            references,
+            wt: wt.min(u8::MAX as usize) as u8,
+            state: DfsCellState::New,
+        });
+        let i = self.cells.len() - 1;
+        indices.insert(hash, i);
+        report_progress(
+            progress,
+            Progress::new(ProgressStage::Ordering, self.cells.len(), None),
+        )?;
+        Ok(i)
+    }
+
+    /// Limit weights of children by their parents, so that heavy
+    /// subtrees become special
+    fn reweight(&mut self) {
+        for i in (0..self.cells.len()).rev() {
+            let refs = self.cells[i].references.clone();
+            let s = refs.len();
+            let (mut c, mut sum, mut mask) = (s, Self::MAX_CELL_WEIGHT - 1, 0u8);
+            for (j, r) in refs.iter().enumerate() {
+                let limit = (Self::MAX_CELL_WEIGHT - 1 + j) / s;
+                let wt = self.cells[*r].wt as usize;
+                if wt <= limit {
+                    sum -= wt;
+                    c -= 1;
+                    mask |= 1 << j;
+                }
+            }
+            if c == 0 {
+                continue;
+            }
+            for (j, r) in refs.iter().enumerate() {
+                if mask & (1 << j) != 0 {
+                    continue;
+                }
+                let limit = sum / c;
+                sum += 1;
+                let wt = &mut self.cells[*r].wt;
+                if *wt as usize > limit {
+                    *wt = limit as u8;
+                }
+            }
+        }
+        for i in 0..self.cells.len() {
+            let sum = 1 + self.cells[i]
+                .references
+                .iter()
+                .map(|r| self.cells[*r].wt as usize)
+                .sum::<usize>();
+            let cell = &mut self.cells[i];
+            cell.wt = if sum <= cell.wt as usize {
+                sum as u8
+            } else {
+                0
+            };
+        }
+    }
+
+    fn is_special(&self, i: usize) -> bool {
+        self.cells[i].wt == 0
+    }
+
+    fn previsit(&mut self, i: usize) {
+        if self.cells[i].state != DfsCellState::New {
+            return;
+        }
+        for j in (0..self.cells[i].references.len()).rev() {
+            let r = self.cells[i].references[j];
+            if self.is_special(r) {
+                self.visit(r);
+            } else {
+                self.previsit(r);
+            }
+        }
+        self.cells[i].state = DfsCellState::Previsited;
+    }
+
+    fn visit(&mut self, i: usize) {
+        if matches!(
+            self.cells[i].state,
+            DfsCellState::Visited | DfsCellState::Allocated
+        ) {
+            return;
+        }
+        if self.is_special(i) {
+            // prevent special cell from being allocated before its children
+            self.previsit(i);
+        }
+        let references = self.cells[i].references.clone();
+        for r in references.iter().rev() {
+            self.visit(*r);
+        }
+        for r in references.iter().rev() {
+            self.allocate(*r);
+        }
+        self.cells[i].state = DfsCellState::Visited;
+    }
+
+    fn allocate(&mut self, i: usize) {
+        if self.cells[i].state == DfsCellState::Allocated {
+            return;
+        }
+        self.allocated.push(i);
+        self.cells[i].state = DfsCellState::Allocated;
+    }
+
+    fn into_ordered_cells(mut self) -> Result<Vec<Arc<Cell>>, StringError> {
+        for i in self.roots.clone() {
+            self.previsit(i);
+            self.visit(i);
+            self.allocate(i);
+        }
+        if self.allocated.len() != self.cells.len() {
+            return Err(Error::custom("reference cycle detected"));
+        }
+        Ok(self
+            .allocated
+            .into_iter()
+            .rev()
+            .map(|i| self.cells[i].cell.clone())
+            .collect())
+    }
+}
+
+/// Mode for [`BagOfCells::parse()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Return an error if there is any data after the BoC
+    #[default]
+    Strict,
+    /// Skip any data after the BoC and report it in
+    /// [`ParsedBagOfCells::warnings`]
+    Lenient,
+}
+
+/// Result of [`BagOfCells::parse()`]
+#[derive(Debug, Clone)]
+pub struct ParsedBagOfCells {
+    pub boc: BagOfCells,
+    /// Non-fatal issues skipped in [`ParseMode::Lenient`] mode
+    pub warnings: Vec<BagOfCellsWarning>,
+}
+
+/// Non-fatal issue found by [`BagOfCells::parse()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BagOfCellsWarning {
+    /// There is data after the BoC (including its CRC, if any)
+    TrailingData { bytes: usize },
+}
+
+impl Display for BagOfCellsWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TrailingData { bytes } => write!(f, "{bytes} bytes of trailing data"),
+        }
+    }
+}
+
+/// Stage of [`Progress`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressStage {
+    /// Reading serialized cells
+    Reading,
+    /// Building cells from serialized ones
+    Building,
+    /// Hashing and ordering cells before serialization
+    Ordering,
+    /// Writing serialized cells
+    Writing,
+}
+
+impl Display for ProgressStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Reading => "reading",
+            Self::Building => "building",
+            Self::Ordering => "ordering",
+            Self::Writing => "writing",
+        })
+    }
+}
+
+/// Progress of long-running operations on [`BagOfCells`], see
+/// [`BagOfCells::parse_with_progress()`] and
+/// [`BagOfCells::to_bytes_with_progress()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub stage: ProgressStage,
+    /// Number of cells processed so far at current stage
+    pub done: usize,
+    /// Total number of cells, unknown while
+    /// [ordering](ProgressStage::Ordering) them
+    pub total: Option<usize>,
+}
+
+impl Progress {
+    #[inline]
+    const fn new(stage: ProgressStage, done: usize, total: Option<usize>) -> Self {
+        Self { stage, done, total }
+    }
+}
+
+impl Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.stage, self.done)?;
+        if let Some(total) = self.total {
+            write!(f, " of {total}")?;
+        }
+        f.write_str(" cells")
+    }
+}
+
+/// Error of operations on [`BagOfCells`] reporting [`Progress`]
+#[derive(Debug)]
+pub enum ProgressError {
+    /// Operation was cancelled by the callback at given progress
+    Cancelled(Progress),
+    /// Operation failed on its own
+    Failed(StringError),
+}
+
+impl Display for ProgressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cancelled(progress) => write!(f, "cancelled while {progress}"),
+            Self::Failed(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl std::error::Error for ProgressError {}
+
+/// Serialized [`BagOfCells`] with index (i.e. `has_idx` is set), which
+/// allows to load individual cells with their subtrees lazily, without
+/// decoding all other cells.
+/// ```rust
+/// # use tlb::{bits::ser::{pack_with, BitWriterExt}, r#as::Ref, Cell};
+/// # use tlb_ton::boc::{BagOfCells, BagOfCellsArgs, IndexedBagOfCells};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut leaf = Cell::builder();
+/// leaf.pack(0xdeadbeefu32)?;
+/// let leaf = leaf.into_cell();
+/// let mut root = Cell::builder();
+/// root.store_as::<_, Ref>(&leaf)?;
+///
+/// let bytes = pack_with(
+///     BagOfCells::from_root(root.into_cell()),
+///     BagOfCellsArgs::new(true, false),
+/// )?
+/// .into_vec();
+///
+/// let boc = IndexedBagOfCells::parse(&bytes)?;
+/// assert_eq!(boc.len(), 2);
+/// assert_eq!(boc.roots(), [0]);
+/// assert_eq!(boc.load_cell(1)?.as_ref(), &leaf);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct IndexedBagOfCells<'a> {
+    cell_data: &'a [u8],
+    size_bytes: u32,
+    roots: Vec<u32>,
+    /// End offsets of cells in `cell_data`
+    index: Vec<usize>,
+}
+
+impl<'a> IndexedBagOfCells<'a> {
+    /// Parse header and index of serialized BoC and verify its CRC, if any.
+    /// Cells are decoded only when loaded.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, StringError> {
+        let mut reader = BitSlice::<u8, Msb0>::from_slice(bytes);
+        let header: BagOfCellsHeader = reader.unpack()?;
+        let index = header
+            .index
+            .ok_or_else(|| Error::custom("BoC has no index"))?;
+        let header_len = bytes.len() - reader.len() / 8;
+        let data_end = header_len
+            .checked_add(header.tot_cells_size)
+            .filter(|end| *end <= bytes.len())
+            .ok_or_else(|| Error::custom("cell_data out of bounds"))?;
+        if header.has_crc32c {
+            // crc32c:has_crc32c?uint32
+            let cs = bytes
+                .get(data_end..data_end + 4)
+                .ok_or_else(|| Error::custom("crc32c: EOF"))?;
+            if cs != crc32c::<StringError>(&bytes[..data_end])?.to_le_bytes() {
+                return Err(Error::custom("CRC mismatch"));
+            }
+        }
+        if !index.is_sorted() || index.last().is_some_and(|end| *end > header.tot_cells_size) {
+            return Err(Error::custom("invalid index"));
+        }
+        if let Some(r) = header.roots.iter().find(|r| **r >= header.cells) {
+            return Err(Error::custom(format!("root out of bounds: [{r}]")));
+        }
+        Ok(Self {
+            cell_data: &bytes[header_len..data_end],
+            size_bytes: header.size_bytes,
+            roots: header.roots,
+            index,
+        })
+    }
+
+    /// Returns total number of cells
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns whether there are no cells
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Returns indices of root cells
+    #[inline]
+    pub fn roots(&self) -> &[u32] {
+        &self.roots
+    }
+
+    /// Decode cell at given index in `cell_data` with all cells reachable
+    /// from it, where shared cells are decoded only once
+    pub fn load_cell(&self, i: u32) -> Result<Arc<Cell>, StringError> {
+        let mut raw_cells: HashMap<usize, RawCell> = HashMap::new();
+        let order = RawBagOfCells::children_first_order([i as usize], |i| {
+            let raw_cell = self.load_raw_cell(i).with_context(|| format!("[{i}]"))?;
+            let references = raw_cell.references.clone();
+            raw_cells.insert(i, raw_cell);
+            Ok(references)
+        })?;
+
+        let mut cells: HashMap<usize, Arc<Cell>> = HashMap::with_capacity(order.len());
+        let mut levels = CellHashCache::new();
+        for i in order {
+            let raw_cell = raw_cells.remove(&i).expect("each cell is visited once");
+            let references = raw_cell
+                .references
+                .iter()
+                .map(|r| cells[&(*r as usize)].clone())
+                .collect();
+            let cell = raw_cell
+                .into_cell(references, &mut levels)
+                .with_context(|| format!("[{i}]"))?;
+            cells.insert(i, cell.into());
+        }
+        Ok(cells.remove(&(i as usize)).expect("start cell is visited"))
+    }
+
+    fn load_raw_cell(&self, i: usize) -> Result<RawCell, StringError> {
+        let end = *self
+            .index
+            .get(i)
+            .ok_or_else(|| Error::custom("cell out of bounds"))?;
+        let start = i.checked_sub(1).map_or(0, |prev| self.index[prev]);
+        let mut reader = BitSlice::<u8, Msb0>::from_slice(&self.cell_data[start..end]);
+        let raw_cell: RawCell = reader.unpack_with(self.size_bytes)?;
+        if !reader.is_empty() {
+            return Err(Error::custom("more data left"));
+        }
+        if let Some(r) = raw_cell
+            .references
+            .iter()
+            .find(|r| **r as usize >= self.len())
+        {
+            return Err(Error::custom(format!("reference out of bounds: [{r}]")));
+        }
+        Ok(raw_cell)
+    }
+}
+
+lazy_static! {
+    static ref GLOBAL_CODE_CELL_CACHE: CodeCellCache = CodeCellCache::new();
+}
+
+/// Cache of single-root cells (usually, contract code) keyed by their
+/// representation hash, so that well-known code embedded as BoC is
+/// parsed only once.
+///
+/// ```rust
+/// # use std::sync::Arc;
+/// # use hex_literal::hex;
+/// # use tlb_ton::boc::CodeCellCache;
+/// # #[cfg(feature = "base64")]
+/// # fn main() -> Result<(), tlb::StringError> {
+/// const CODE: &str = "te6cckEBAQEAAgAAAEysuc0=";
+/// const CODE_HASH: [u8; 32] =
+///     hex!("96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7");
+///
+/// let cell = CodeCellCache::global().get_or_parse_base64(CODE_HASH, CODE)?;
+/// assert_eq!(cell.hash(), CODE_HASH);
+///
+/// // cached cell is returned without parsing
+/// let cached = CodeCellCache::global().get_or_parse_base64(CODE_HASH, CODE)?;
+/// assert!(Arc::ptr_eq(&cell, &cached));
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "base64"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug, Default)]
+pub struct CodeCellCache {
+    cells: RwLock<HashMap<[u8; 32], Arc<Cell>>>,
+}
+
+impl CodeCellCache {
+    /// Create empty cache
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process-wide cache shared by all users, including wallet bindings
+    /// from `ton-contracts`
+    #[inline]
+    pub fn global() -> &'static Self {
+        &GLOBAL_CODE_CELL_CACHE
+    }
+
+    /// Return cached cell with given representation hash
+    #[inline]
+    pub fn get(&self, hash: &[u8; 32]) -> Option<Arc<Cell>> {
+        self.cells
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(hash)
+            .cloned()
+    }
+
+    /// Return cached cell with given representation hash or parse it
+    /// from base64-encoded single-root BoC and cache it.
+    ///
+    /// Returns an error if the parsed root has different hash.
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+    pub fn get_or_parse_base64(
+        &self,
+        hash: [u8; 32],
+        s: impl AsRef<[u8]>,
+    ) -> Result<Arc<Cell>, StringError> {
+        if let Some(cell) = self.get(&hash) {
+            return Ok(cell);
+        }
+        let root = BagOfCells::parse_base64(s)?
+            .single_root()
+            .ok_or_else(|| Error::custom("BoC must be single root"))?
+            .clone();
+        let got = root.hash();
+        if got != hash {
+            return Err(Error::custom(format!(
+                "hash mismatch: expected {}, got {}",
+                hex::encode(hash),
+                hex::encode(got),
+            )));
+        }
+        Ok(self
+            .cells
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .entry(hash)
+            .or_insert(root)
+            .clone())
+    }
+}
+
+/// Statistics on [`BagOfCells`] returned by [`BagOfCells::stats()`].
+///
+/// Cells are considered unique if they are stored in distinct
+/// allocations, i.e. cells from the same BoC index are shared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BagOfCellsStats {
+    /// Total number of cells reachable from roots, where shared cells are
+    /// counted each time they are referenced (saturates at [`u64::MAX`]).
+    pub cells: u64,
+    /// Number of unique cells
+    pub unique_cells: usize,
+    /// Total number of data bits in unique cells
+    pub bits: usize,
+    /// Total number of references in unique cells
+    pub refs: usize,
+    /// Estimated number of bytes occupied in memory by unique cells,
+    /// see [`Cell::memory_usage()`]
+    pub memory_usage: usize,
+}
+
+/// [`BitPackWithArgs::Args`] for [`BagOfCells`].
+///
+/// More options might be added later, so it can only be created with
+/// [`BagOfCellsArgs::new()`] or [`Default`]:
+/// ```rust
+/// # use tlb_ton::boc::{BagOfCellsArgs, CellOrder};
+/// let args = BagOfCellsArgs::new(false, true).with_cell_order(CellOrder::DfsPreorder);
+/// assert!(!args.has_idx);
+/// assert!(args.has_crc32c);
+/// assert_eq!(args.cell_order, CellOrder::DfsPreorder);
+/// ```
 #[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
 pub struct BagOfCellsArgs {
     pub has_idx: bool,
     pub has_crc32c: bool,
+    /// Order of cells in serialized BoC
+    pub cell_order: CellOrder,
+}
+
+impl BagOfCellsArgs {
+    /// Args with given flags and default [`CellOrder`]
+    #[inline]
+    pub const fn new(has_idx: bool, has_crc32c: bool) -> Self {
+        Self {
+            has_idx,
+            has_crc32c,
+            cell_order: CellOrder::Topological,
+        }
+    }
+
+    /// Set [`cell_order`](BagOfCellsArgs::cell_order)
+    #[inline]
+    #[must_use]
+    pub const fn with_cell_order(mut self, cell_order: CellOrder) -> Self {
+        self.cell_order = cell_order;
+        self
+    }
+}
+
+/// Order of cells in serialized [`BagOfCells`], see [`BagOfCellsArgs`].
+///
+/// Any order is valid as long as each cell goes before all cells it
+/// references, but some consumers (e.g. liteserver proof checks) compare
+/// serialized BoCs byte-for-byte with the ones produced by reference
+/// implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CellOrder {
+    /// Any topological order, which is not guaranteed to be stable
+    /// between runs
+    #[default]
+    Topological,
+    /// Depth-first order starting from roots, which is the same as in
+    /// [reference implementation](https://github.com/ton-blockchain/ton/blob/master/crypto/vm/boc.cpp),
+    /// so that serialized BoCs match it byte-for-byte.
+    ///
+    /// References of each cell are placed right after each other, while
+    /// heavy subtrees are placed closer to their parents.
+    DfsPreorder,
 }
 
 /// ```tlb
@@ -127,8 +1096,8 @@ pub struct BagOfCellsArgs {
 ///   tot_cells_size:(##(off_bytes * 8))
 ///   index:(cells * ##(off_bytes * 8))
 ///   cell_data:(tot_cells_size * [ uint8 ])
-///   = BagOfCells;///
-
+///   = BagOfCells;
+///
 /// serialized_boc_idx_crc32c#acc3a728 size:(## 8) { size <= 4 }
 ///   off_bytes:(## 8) { off_bytes <= 8 }
 ///   cells:(##(size * 8))
@@ -137,8 +1106,8 @@ pub struct BagOfCellsArgs {
 ///   tot_cells_size:(##(off_bytes * 8))
 ///   index:(cells * ##(off_bytes * 8))
 ///   cell_data:(tot_cells_size * [ uint8 ])
-///   crc32c:uint32 = BagOfCells;///
-
+///   crc32c:uint32 = BagOfCells;
+///
 /// serialized_boc#b5ee9c72 has_idx:(## 1) has_crc32c:(## 1)
 ///   has_cache_bits:(## 1) flags:(## 2) { flags = 0 }
 ///   size:(## 3) { size <= 4 }
@@ -156,61 +1125,161 @@ pub struct BagOfCellsArgs {
 impl BitPackWithArgs for BagOfCells {
     type Args = BagOfCellsArgs;
 
+    #[inline]
     fn pack_with<W>(&self, writer: W, args: Self::Args) -> Result<(), W::Error>
     where
         W: BitWriter,
     {
-        let mut all_cells: HashSet<Arc<Cell>> = HashSet::new();
-        let mut in_refs: HashMap<Arc<Cell>, HashSet<Arc<Cell>>> = HashMap::new();
-        for r in &self.roots {
-            Self::traverse_cell_tree(r, &mut all_cells, &mut in_refs).map_err(Error::custom)?;
-        }
-        let mut no_in_refs: HashSet<Arc<Cell>> = HashSet::new();
-        for c in &all_cells {
-            if !in_refs.contains_key(c) {
-                no_in_refs.insert(c.clone());
-            }
-        }
-        let mut ordered_cells: Vec<Arc<Cell>> = Vec::new();
-        let mut indices: HashMap<Arc<Cell>, u32> = HashMap::new();
-        while let Some(cell) = no_in_refs.iter().next().cloned() {
-            ordered_cells.push(cell.clone());
-            indices.insert(cell.clone(), indices.len() as u32);
-            for child in &cell.references {
-                if let Some(refs) = in_refs.get_mut(child) {
-                    refs.remove(&cell);
-                    if refs.is_empty() {
-                        no_in_refs.insert(child.clone());
-                        in_refs.remove(child);
-                    }
-                }
-            }
-            no_in_refs.remove(&cell);
+        self.pack_with_progress(writer, args, &mut |_| ControlFlow::Continue(()))
+    }
+}
+
+/// Callback to report [`Progress`] of long-running operations on
+/// [`BagOfCells`]
+type ProgressFn<'a> = dyn FnMut(Progress) -> ControlFlow<()> + 'a;
+
+/// Report progress and return an error if it was cancelled
+#[inline]
+fn report_progress<E>(progress: &mut ProgressFn<'_>, current: Progress) -> Result<(), E>
+where
+    E: Error,
+{
+    match progress(current) {
+        ControlFlow::Continue(()) => Ok(()),
+        ControlFlow::Break(()) => Err(E::custom(format!("cancelled while {current}"))),
+    }
+}
+
+/// Run `f` with `progress` and distinguish its cancellation from other
+/// errors
+fn with_progress<T>(
+    mut progress: impl FnMut(Progress) -> ControlFlow<()>,
+    f: impl FnOnce(&mut ProgressFn<'_>) -> Result<T, StringError>,
+) -> Result<T, ProgressError> {
+    let mut cancelled = None;
+    f(&mut |current| {
+        let flow = progress(current);
+        if flow.is_break() {
+            cancelled = Some(current);
         }
-        if !in_refs.is_empty() {
-            return Err(Error::custom("reference cycle detected"));
+        flow
+    })
+    .map_err(|err| cancelled.map_or(ProgressError::Failed(err), ProgressError::Cancelled))
+}
+
+impl BagOfCells {
+    fn pack_with_progress<W>(
+        &self,
+        writer: W,
+        args: BagOfCellsArgs,
+        progress: &mut ProgressFn<'_>,
+    ) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        let mut hashes = CellHashCache::new();
+        let ordered_cells = match args.cell_order {
+            CellOrder::Topological => self.topological_order(&mut hashes, progress),
+            CellOrder::DfsPreorder => self.dfs_preorder(&mut hashes, progress),
         }
+        .map_err(Error::custom)?;
+        let indices: HashMap<[u8; 32], u32> = ordered_cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| (hashes.hash(cell), i as u32))
+            .collect();
+        let index_of = |hashes: &mut CellHashCache, cell: &Arc<Cell>| indices[&hashes.hash(cell)];
 
         RawBagOfCells {
             cells: ordered_cells
-                .into_iter()
+                .iter()
                 .map(|cell| RawCell {
+                    r#type: cell.r#type,
                     data: cell.data.clone(),
                     references: cell
                         .references
                         .iter()
-                        .map(|c| *indices.get(c).unwrap())
+                        .map(|r| index_of(&mut hashes, r))
                         .collect(),
-                    level: cell.level(),
+                    level_mask: hashes.level_mask(cell),
                 })
                 .collect(),
             roots: self
                 .roots
                 .iter()
-                .map(|c| *indices.get(c).unwrap())
+                .map(|r| index_of(&mut hashes, r))
                 .collect(),
         }
-        .pack_with(writer, args)
+        .pack_with_progress(writer, args, progress)
+    }
+
+    fn unpack_with_progress<R>(reader: R, progress: &mut ProgressFn<'_>) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let raw = RawBagOfCells::unpack_with_progress(reader, progress)?;
+        let num_cells = raw.cells.len();
+        let building = |done| Progress::new(ProgressStage::Building, done, Some(num_cells));
+        report_progress(progress, building(0))?;
+        for (i, raw_cell) in raw.cells.iter().enumerate() {
+            if let Some(r) = raw_cell
+                .references
+                .iter()
+                .find(|r| **r as usize >= num_cells)
+            {
+                return Err(Error::custom(format!(
+                    "reference out of bounds: [{i}] -> [{r}]"
+                )));
+            }
+        }
+
+        // first pass: order cells so that each one goes after all cells it
+        // references, since some serializers do not follow the direction
+        // of references in the standard one
+        let order = RawBagOfCells::children_first_order(0..num_cells, |i| {
+            Ok(raw.cells[i].references.clone())
+        })
+        .map_err(Error::custom)?;
+
+        // second pass: build cells in that order
+        let mut raw_cells: Vec<Option<RawCell>> = raw.cells.into_iter().map(Some).collect();
+        let mut cells: Vec<Option<Arc<Cell>>> = vec![None; num_cells];
+        let mut levels = CellHashCache::new();
+        let mut done = 0;
+        for i in order {
+            let raw_cell = raw_cells[i].take().expect("each cell is visited once");
+            let references = raw_cell
+                .references
+                .iter()
+                .map(|r| {
+                    cells[*r as usize]
+                        .clone()
+                        .expect("references are built before")
+                })
+                .collect();
+            cells[i] = Some(
+                raw_cell
+                    .into_cell(references, &mut levels)
+                    .with_context(|| format!("[{i}]"))
+                    .map_err(Error::custom)?
+                    .into(),
+            );
+            done += 1;
+            report_progress(progress, building(done))?;
+        }
+        Ok(BagOfCells {
+            roots: raw
+                .roots
+                .into_iter()
+                .map(|r| {
+                    cells
+                        .get(r as usize)
+                        .cloned()
+                        .flatten()
+                        .ok_or_else(|| Error::custom(format!("root out of bounds: [{r}]")))
+                })
+                .collect::<Result<_, _>>()?,
+        })
     }
 }
 
@@ -223,8 +1292,8 @@ impl BitPackWithArgs for BagOfCells {
 ///   tot_cells_size:(##(off_bytes * 8))
 ///   index:(cells * ##(off_bytes * 8))
 ///   cell_data:(tot_cells_size * [ uint8 ])
-///   = BagOfCells;///
-
+///   = BagOfCells;
+///
 /// serialized_boc_idx_crc32c#acc3a728 size:(## 8) { size <= 4 }
 ///   off_bytes:(## 8) { off_bytes <= 8 }
 ///   cells:(##(size * 8))
@@ -233,8 +1302,8 @@ impl BitPackWithArgs for BagOfCells {
 ///   tot_cells_size:(##(off_bytes * 8))
 ///   index:(cells * ##(off_bytes * 8))
 ///   cell_data:(tot_cells_size * [ uint8 ])
-///   crc32c:uint32 = BagOfCells;///
-
+///   crc32c:uint32 = BagOfCells;
+///
 /// serialized_boc#b5ee9c72 has_idx:(## 1) has_crc32c:(## 1)
 ///   has_cache_bits:(## 1) flags:(## 2) { flags = 0 }
 ///   size:(## 3) { size <= 4 }
@@ -250,45 +1319,34 @@ impl BitPackWithArgs for BagOfCells {
 ///   = BagOfCells;
 /// ```
 impl BitUnpack for BagOfCells {
+    #[inline]
     fn unpack<R>(reader: R) -> Result<Self, R::Error>
     where
         R: BitReader,
     {
-        let raw = RawBagOfCells::unpack(reader)?;
-        let num_cells = raw.cells.len();
-        let mut cells: Vec<Arc<Cell>> = Vec::new();
-        for (i, raw_cell) in raw.cells.into_iter().enumerate().rev() {
-            cells.push(
-                Cell {
-                    data: raw_cell.data,
-                    references: raw_cell
-                        .references
-                        .into_iter()
-                        .map(|r| {
-                            if r <= i as u32 {
-                                return Err(Error::custom(format!(
-                                    "references to previous cells are not supported: [{i}] -> [{r}]"
-                                )));
-                            }
-                            Ok(cells[num_cells - 1 - r as usize].clone())
-                        })
-                        .collect::<Result<_, _>>()?,
-                }
-                .into(),
-            );
-        }
-        Ok(BagOfCells {
-            roots: raw
-                .roots
-                .into_iter()
-                .map(|r| cells[num_cells - 1 - r as usize].clone())
-                .collect(),
-        })
+        Self::unpack_with_progress(reader, &mut |_| ControlFlow::Continue(()))
     }
 }
 
+#[cfg(feature = "crc")]
 const CRC_32_ISCSI: Crc<u32> = Crc::<u32>::new(&crc::CRC_32_ISCSI);
 
+/// CRC32-C checksum of serialized BoC, which can only be computed with
+/// `crc` feature enabled
+#[inline]
+fn crc32c<E>(bytes: &[u8]) -> Result<u32, E>
+where
+    E: Error,
+{
+    #[cfg(feature = "crc")]
+    return Ok(CRC_32_ISCSI.checksum(bytes));
+    #[cfg(not(feature = "crc"))]
+    {
+        let _ = bytes;
+        Err(E::custom("crc32c: `crc` feature is disabled"))
+    }
+}
+
 #[derive(PartialEq, Eq, Debug, Clone, Hash)]
 struct RawBagOfCells {
     pub cells: Vec<RawCell>,
@@ -310,30 +1368,97 @@ impl RawBagOfCells {
     /// serialized_boc#b5ee9c72
     /// ```
     const GENERIC_BOC_TAG: u32 = 0xb5ee9c72;
+
+    /// Returns indices of all cells reachable from `starts` in post-order,
+    /// i.e. each cell goes after all cells it references, or an error if
+    /// there is a cycle.
+    /// All references returned by `references` must be in bounds.
+    fn children_first_order(
+        starts: impl IntoIterator<Item = usize>,
+        mut references: impl FnMut(usize) -> Result<Vec<u32>, StringError>,
+    ) -> Result<Vec<usize>, StringError> {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        enum State {
+            InProgress,
+            Done,
+        }
+
+        let mut states: HashMap<usize, State> = HashMap::new();
+        let mut order = Vec::new();
+        // (cell index, its references, number of references already visited)
+        let mut stack: Vec<(usize, Vec<u32>, usize)> = Vec::new();
+        for start in starts {
+            if states.contains_key(&start) {
+                continue;
+            }
+            states.insert(start, State::InProgress);
+            stack.push((start, references(start)?, 0));
+            while let Some((i, refs, next)) = stack.last_mut() {
+                let i = *i;
+                let Some(&r) = refs.get(*next) else {
+                    states.insert(i, State::Done);
+                    order.push(i);
+                    stack.pop();
+                    continue;
+                };
+                *next += 1;
+                let r = r as usize;
+                match states.get(&r) {
+                    None => {
+                        states.insert(r, State::InProgress);
+                        stack.push((r, references(r)?, 0));
+                    }
+                    Some(State::InProgress) => {
+                        return Err(Error::custom(format!(
+                            "reference cycle detected: [{i}] -> [{r}]"
+                        )))
+                    }
+                    Some(State::Done) => {}
+                }
+            }
+        }
+        Ok(order)
+    }
 }
 
 impl BitPackWithArgs for RawBagOfCells {
     type Args = BagOfCellsArgs;
 
-    fn pack_with<W>(&self, mut writer: W, args: Self::Args) -> Result<(), W::Error>
+    #[inline]
+    fn pack_with<W>(&self, writer: W, args: Self::Args) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        self.pack_with_progress(writer, args, &mut |_| ControlFlow::Continue(()))
+    }
+}
+
+impl RawBagOfCells {
+    fn pack_with_progress<W>(
+        &self,
+        mut writer: W,
+        args: BagOfCellsArgs,
+        progress: &mut ProgressFn<'_>,
+    ) -> Result<(), W::Error>
     where
         W: BitWriter,
     {
-        if self.roots.len() > 1 {
-            return Err(Error::custom("only single root cell supported"));
+        if self.roots.is_empty() {
+            return Err(Error::custom("no roots"));
         }
         let size_bits: u32 = 32 - (self.cells.len() as u32).leading_zeros();
-        let size_bytes: u32 = (size_bits + 7) / 8;
+        let size_bytes: u32 = size_bits.div_ceil(8);
 
         let mut tot_cells_size: u32 = 0;
         let mut index = Vec::<u32>::with_capacity(self.cells.len());
         for cell in &self.cells {
-            index.push(tot_cells_size);
             tot_cells_size += cell.size(size_bytes);
+            // index contains end offsets of cells
+            index.push(tot_cells_size);
         }
 
         let off_bits: u32 = 32 - tot_cells_size.leading_zeros();
-        let off_bytes: u32 = (off_bits + 7) / 8;
+        let off_bytes: u32 = off_bits.div_ceil(8);
 
         let mut buffered = writer.as_mut().tee(BitVec::<u8, Msb0>::new());
         buffered
@@ -354,30 +1479,33 @@ impl BitPackWithArgs for RawBagOfCells {
             // cells:(##(size * 8))
             .pack_as_with::<_, VarNBytes>(self.cells.len() as u32, size_bytes)?
             // roots:(##(size * 8)) { roots >= 1 }
-            .pack_as_with::<_, VarNBytes>(1u32, size_bytes)? // single root
+            .pack_as_with::<_, VarNBytes>(self.roots.len() as u32, size_bytes)?
             // absent:(##(size * 8)) { roots + absent <= cells }
             .pack_as_with::<_, VarNBytes>(0u32, size_bytes)? // complete BoCs only
             // tot_cells_size:(##(off_bytes * 8))
             .pack_as_with::<_, VarNBytes>(tot_cells_size, off_bytes)?
             // root_list:(roots * ##(size * 8))
-            .pack_as_with::<_, VarNBytes>(0u32, size_bytes)?; // root should have index 0
+            .pack_many_as_with::<_, VarNBytes>(self.roots.iter().copied(), size_bytes)?;
         if args.has_idx {
             // index:has_idx?(cells * ##(off_bytes * 8))
             buffered.pack_many_as_with::<_, VarNBytes>(index, off_bytes)?;
         }
         // cell_data:(tot_cells_size * [ uint8 ])
+        let writing = |done| Progress::new(ProgressStage::Writing, done, Some(self.cells.len()));
+        report_progress(progress, writing(0))?;
         for (i, cell) in self.cells.iter().enumerate() {
             cell.pack_with(&mut buffered, size_bytes)
                 .with_context(|| format!("[{i}]"))?;
+            report_progress(progress, writing(i + 1))?;
         }
 
         let buf = buffered.into_writer();
-        if buf.len() % 8 != 0 {
+        if !buf.len().is_multiple_of(8) {
             return Err(Error::custom("produced stream is not byte-aligned"));
         }
         // crc32c:has_crc32c?uint32
         if args.has_crc32c {
-            let cs = CRC_32_ISCSI.checksum(buf.as_raw_slice());
+            let cs = crc32c::<W::Error>(buf.as_raw_slice())?;
             writer.write_bitslice(cs.to_le_bytes().as_bits())?;
         }
         Ok(())
@@ -385,95 +1513,170 @@ impl BitPackWithArgs for RawBagOfCells {
 }
 
 impl BitUnpack for RawBagOfCells {
-    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    #[inline]
+    fn unpack<R>(reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        Self::unpack_with_progress(reader, &mut |_| ControlFlow::Continue(()))
+    }
+}
+
+impl RawBagOfCells {
+    fn unpack_with_progress<R>(
+        mut reader: R,
+        progress: &mut ProgressFn<'_>,
+    ) -> Result<Self, R::Error>
     where
         R: BitReader,
     {
         let mut buffered = reader.as_mut().tee(BitVec::<u8, Msb0>::new());
 
-        let tag = buffered.unpack::<u32>()?;
-        let (has_idx, has_crc32c) = match tag {
-            Self::INDEXED_BOC_TAG => (true, false),
-            Self::INDEXED_CRC32_TAG => (true, true),
-            Self::GENERIC_BOC_TAG => {
+        let header: BagOfCellsHeader = buffered.unpack()?;
+        let num_cells = header.cells as usize;
+        let reading = |done| Progress::new(ProgressStage::Reading, done, Some(num_cells));
+        report_progress(progress, reading(0))?;
+        // cell_data:(tot_cells_size * [ uint8 ])
+        let mut cell_data: Vec<RawCell> = Vec::new();
+        for cell in buffered.unpack_iter_with(header.size_bytes).take(num_cells) {
+            cell_data.push(cell.context("cell_data")?);
+            report_progress(progress, reading(cell_data.len()))?;
+        }
+
+        let buf = buffered.into_writer();
+        if !buf.len().is_multiple_of(8) {
+            return Err(Error::custom("produced stream is not byte-aligned"));
+        }
+        if header.has_crc32c {
+            // crc32c:has_crc32c?uint32
+            let cs = u32::from_le_bytes(reader.unpack()?);
+            if cs != crc32c::<R::Error>(buf.as_raw_slice())? {
+                return Err(Error::custom("CRC mismatch"));
+            }
+        }
+
+        Ok(RawBagOfCells {
+            cells: cell_data,
+            roots: header.roots,
+        })
+    }
+}
+
+/// Header of serialized [`BagOfCells`] up to `cell_data`
+struct BagOfCellsHeader {
+    has_crc32c: bool,
+    size_bytes: u32,
+    cells: u32,
+    roots: Vec<u32>,
+    tot_cells_size: usize,
+    /// End offsets of cells in `cell_data`
+    index: Option<Vec<usize>>,
+}
+
+impl BitUnpack for BagOfCellsHeader {
+    fn unpack<R>(mut reader: R) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let tag = reader.unpack::<u32>()?;
+        let (has_idx, has_crc32c, has_cache_bits) = match tag {
+            RawBagOfCells::INDEXED_BOC_TAG => (true, false, false),
+            RawBagOfCells::INDEXED_CRC32_TAG => (true, true, false),
+            RawBagOfCells::GENERIC_BOC_TAG => {
                 // has_idx:(## 1) has_crc32c:(## 1)
-                let (has_idx, has_crc32c) = buffered.unpack()?;
+                let (has_idx, has_crc32c) = reader.unpack()?;
                 // has_cache_bits:(## 1)
-                let _has_cache_bits: bool = buffered.unpack()?;
+                let has_cache_bits: bool = reader.unpack()?;
                 // flags:(## 2) { flags = 0 }
-                let _flags: u8 = buffered.unpack_as::<_, NBits<2>>()?;
-                (has_idx, has_crc32c)
+                let _flags: u8 = reader.unpack_as::<_, NBits<2>>()?;
+                (has_idx, has_crc32c, has_cache_bits)
             }
-            _ => return Err(Error::custom(format!("invalid BoC tag: {tag:#x}"))),
+            _ => return Err(Error::custom(format!("invalid BoC tag: {}", Tag32(tag)))),
         };
         // size:(## 3) { size <= 4 }
-        let size_bytes: u32 = buffered.unpack_as::<_, NBits<3>>()?;
+        let size_bytes: u32 = reader.unpack_as::<_, NBits<3>>()?;
         if size_bytes > 4 {
             return Err(Error::custom(format!("invalid size: {size_bytes}")));
         }
         // off_bytes:(## 8) { off_bytes <= 8 }
-        let off_bytes: u32 = buffered.unpack_as::<_, NBits<8>>()?;
-        if size_bytes > 8 {
+        let off_bytes: u32 = reader.unpack_as::<_, NBits<8>>()?;
+        if off_bytes > 8 {
             return Err(Error::custom(format!("invalid off_bytes: {off_bytes}")));
         }
         // cells:(##(size * 8))
-        let cells: u32 = buffered.unpack_as_with::<_, VarNBytes>(size_bytes)?;
+        let cells: u32 = reader.unpack_as_with::<_, VarNBytes>(size_bytes)?;
         // roots:(##(size * 8)) { roots >= 1 }
-        let roots: u32 = buffered.unpack_as_with::<_, VarNBytes>(size_bytes)?;
+        let roots: u32 = reader.unpack_as_with::<_, VarNBytes>(size_bytes)?;
         // absent:(##(size * 8)) { roots + absent <= cells }
-        let absent: u32 = buffered.unpack_as_with::<_, VarNBytes>(size_bytes)?;
-        if roots + absent > cells {
+        let absent: u32 = reader.unpack_as_with::<_, VarNBytes>(size_bytes)?;
+        if roots == 0 {
+            return Err(Error::custom("no roots"));
+        }
+        if roots.checked_add(absent).is_none_or(|n| n > cells) {
             return Err(Error::custom("roots + absent > cells"));
         }
         // tot_cells_size:(##(off_bytes * 8))
-        let _tot_cells_size: usize = buffered.unpack_as_with::<_, VarNBytes>(off_bytes)?;
-        let root_list = if tag == Self::GENERIC_BOC_TAG {
+        let tot_cells_size: usize = reader.unpack_as_with::<_, VarNBytes>(off_bytes)?;
+        let root_list = if tag == RawBagOfCells::GENERIC_BOC_TAG {
             // root_list:(roots * ##(size * 8))
-            buffered
+            reader
                 .unpack_iter_as_with::<_, VarNBytes>(size_bytes)
                 .take(roots as usize)
                 .collect::<Result<_, _>>()?
         } else {
-            Vec::new()
+            // { roots = 1 }, the root is the first cell
+            [0].into()
         };
-        if has_idx {
+        let index = if has_idx {
             // index:has_idx?(cells * ##(off_bytes * 8))
-            let _index: Vec<u32> = buffered
-                .unpack_iter_as_with::<_, VarNBytes>(off_bytes)
-                .take(cells as usize)
-                .collect::<Result<_, _>>()?;
-        }
-        // cell_data:(tot_cells_size * [ uint8 ])
-        let cell_data: Vec<RawCell> = buffered
-            .unpack_iter_with(size_bytes)
-            .take(cells as usize)
-            .collect::<Result<_, _>>()
-            .context("cell_data")?;
-
-        let buf = buffered.into_writer();
-        if buf.len() % 8 != 0 {
-            return Err(Error::custom("produced stream is not byte-aligned"));
-        }
-        if has_crc32c {
-            // crc32c:has_crc32c?uint32
-            let cs = u32::from_le_bytes(reader.unpack()?);
-            if cs != CRC_32_ISCSI.checksum(buf.as_raw_slice()) {
-                return Err(Error::custom("CRC mismatch"));
-            }
-        }
-
-        Ok(RawBagOfCells {
-            cells: cell_data,
+            Some(
+                reader
+                    .unpack_iter_as_with::<usize, VarNBytes>(off_bytes)
+                    .take(cells as usize)
+                    // lowest bit is a cache flag if has_cache_bits
+                    .map(|offset| offset.map(|o| if has_cache_bits { o >> 1 } else { o }))
+                    .collect::<Result<_, _>>()?,
+            )
+        } else {
+            None
+        };
+        Ok(Self {
+            has_crc32c,
+            size_bytes,
+            cells,
             roots: root_list,
+            tot_cells_size,
+            index,
         })
     }
 }
 
+bitfield! {
+    /// ```tlb
+    /// d1 = refs + 8 * is_exotic + 16 * with_hashes + 32 * level
+    /// ```
+    struct RefsDescriptor {
+        level: u8 = 3,
+        with_hashes: bool = 1,
+        is_exotic: bool = 1,
+        refs: u8 = 3,
+    }
+
+    /// ```tlb
+    /// d2 = floor(bits / 8) + ceil(bits / 8)
+    /// ```
+    struct BitsDescriptor {
+        full_bytes: u8 = 7,
+        has_partial_byte: bool = 1,
+    }
+}
+
 #[derive(PartialEq, Eq, Debug, Clone, Hash)]
 pub(crate) struct RawCell {
+    pub r#type: CellType,
     pub data: BitVec<u8, Msb0>,
     pub references: Vec<u32>,
-    pub level: u8,
+    pub level_mask: u8,
 }
 
 impl BitUnpackWithArgs for RawCell {
@@ -484,33 +1687,59 @@ impl BitUnpackWithArgs for RawCell {
     where
         R: BitReader,
     {
-        let refs_descriptor: u8 = reader.unpack()?;
-        let level: u8 = refs_descriptor >> 5;
-        let _is_exotic: bool = refs_descriptor >> 3 & 0b1 == 1;
-        let ref_num: usize = refs_descriptor as usize & 0b111;
-
-        let bits_descriptor: u8 = reader.unpack()?;
-        let num_bytes: usize = ((bits_descriptor >> 1) + (bits_descriptor & 1)) as usize;
-        let full_bytes = (bits_descriptor & 1) == 0;
-
-        let mut data = reader.read_bitvec(num_bytes * 8)?;
-        if !data.is_empty() && !full_bytes {
-            let trailing_zeros = data.trailing_zeros();
-            if trailing_zeros >= 8 {
-                return Err(Error::custom("last byte must be non zero"));
-            }
+        let RefsDescriptor {
+            level: level_mask,
+            is_exotic,
+            refs: ref_num,
+            ..
+        } = reader.unpack().context("refs_descriptor")?;
+        if ref_num > 4 {
+            return Err(Error::custom(format!(
+                "invalid number of references: {ref_num}"
+            )));
+        }
+
+        let BitsDescriptor {
+            full_bytes: num_full_bytes,
+            has_partial_byte,
+        } = reader.unpack().context("bits_descriptor")?;
+        let num_bytes = num_full_bytes as usize + has_partial_byte as usize;
+        let full_bytes = !has_partial_byte;
+
+        let mut bytes = vec![0; num_bytes];
+        reader.read_bytes_into(&mut bytes)?;
+        let last_byte = bytes.last().copied();
+        let mut data = BitVec::from_vec(bytes);
+        if !full_bytes {
+            // remove stop-bit with trailing zeros
+            let trailing_zeros = last_byte
+                .filter(|b| *b != 0)
+                .ok_or_else(|| Error::custom("last byte must be non zero"))?
+                .trailing_zeros() as usize;
             data.truncate(data.len() - trailing_zeros - 1);
         }
 
+        let r#type = if is_exotic {
+            let tag: u8 = data
+                .as_bitslice()
+                .unpack()
+                .context("exotic cell type")
+                .map_err(Error::custom)?;
+            CellType::from_exotic_tag(tag).map_err(Error::custom)?
+        } else {
+            CellType::Ordinary
+        };
+
         let references: Vec<u32> = reader
             .unpack_iter_as_with::<_, VarNBytes>(size_bytes)
-            .take(ref_num)
+            .take(ref_num as usize)
             .collect::<Result<_, _>>()?;
 
         Ok(RawCell {
+            r#type,
             data,
             references,
-            level,
+            level_mask,
         })
     }
 }
@@ -523,16 +1752,23 @@ impl BitPackWithArgs for RawCell {
     where
         W: BitWriter,
     {
-        let level: u8 = 0;
-        let is_exotic: u8 = 0;
-        let refs_descriptor: u8 = self.references.len() as u8 + is_exotic * 8 + level * 32;
-        writer.pack(refs_descriptor)?;
+        writer
+            .pack(RefsDescriptor {
+                level: self.level_mask,
+                with_hashes: false,
+                is_exotic: self.r#type.is_exotic(),
+                refs: self.references.len() as u8,
+            })
+            .context("refs_descriptor")?;
 
         let padding_bits = self.data.len() % 8;
         let full_bytes = padding_bits == 0;
-        let data_bytes = (self.data.len() + 7) / 8;
-        let bits_descriptor: u8 = data_bytes as u8 * 2 - if full_bytes { 0 } else { 1 }; // subtract 1 if the last byte is not full
-        writer.pack(bits_descriptor)?;
+        writer
+            .pack(BitsDescriptor {
+                full_bytes: (self.data.len() / 8) as u8,
+                has_partial_byte: !full_bytes,
+            })
+            .context("bits_descriptor")?;
 
         writer.pack(self.data.as_bitslice())?;
         if !full_bytes {
@@ -547,8 +1783,600 @@ impl BitPackWithArgs for RawCell {
 }
 
 impl RawCell {
+    /// Build [`Cell`] with given references, validating its type and
+    /// level mask, see [`Cell::try_new()`].
+    ///
+    /// Level masks and hashes of references are looked up in given cache,
+    /// which should be shared across cells of the same BoC.
+    fn into_cell(
+        self,
+        references: Vec<Arc<Cell>>,
+        levels: &mut CellHashCache,
+    ) -> Result<Cell, StringError> {
+        Cell::try_new_with_cache(self.data, references, self.r#type, self.level_mask, levels)
+    }
+
     fn size(&self, ref_size_bytes: u32) -> u32 {
-        let data_len: u32 = (self.data.len() as u32 + 7) / 8;
+        let data_len: u32 = (self.data.len() as u32).div_ceil(8);
         2 + data_len + self.references.len() as u32 * ref_size_bytes
     }
 }
+
+/// Entrypoint for `raw_cell` fuzz target, since [`RawCell`] is private
+#[cfg(fuzzing)]
+#[doc(hidden)]
+pub fn fuzz_raw_cell(bytes: &[u8], size_bytes: u32) {
+    let Ok(raw_cell) = bytes.as_bits().unpack_with::<RawCell>(size_bytes) else {
+        return;
+    };
+    let packed = pack_with(&raw_cell, size_bytes).expect("parsed cell is packed back");
+    assert_eq!(
+        packed.as_bitslice().unpack_with::<RawCell>(size_bytes).ok(),
+        Some(raw_cell),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::bits::de::unpack_fully;
+
+    use crate::{message::CommonMsgInfo, MsgAddress};
+
+    use super::*;
+
+    fn cell(tag: u8, references: impl IntoIterator<Item = Arc<Cell>>) -> Arc<Cell> {
+        Arc::new(Cell {
+            r#type: CellType::Ordinary,
+            data: tag.to_be_bytes().as_bits().to_bitvec(),
+            references: references.into_iter().collect(),
+        })
+    }
+
+    #[test]
+    fn dfs_preorder() {
+        let g = cell(6, []);
+        let f = cell(5, []);
+        let d = cell(3, [g]);
+        let c = cell(2, [f]);
+        let b = cell(1, [d]);
+        let a = cell(0, [b, c.clone()]);
+        // shared cell goes after all its parents
+        let root = cell(0xff, [a, c]);
+
+        let boc = BagOfCells::from_root(root.clone());
+        let ordered: Vec<u8> = boc
+            .dfs_preorder(
+                &mut CellHashCache::new(),
+                &mut |_| ControlFlow::Continue(()),
+            )
+            .unwrap()
+            .iter()
+            .map(|c| c.data.as_raw_slice()[0])
+            .collect();
+        assert_eq!(ordered, [0xff, 0, 1, 2, 3, 6, 5]);
+
+        let packed = boc
+            .to_bytes(BagOfCellsArgs {
+                cell_order: CellOrder::DfsPreorder,
+                ..Default::default()
+            })
+            .unwrap();
+        let got: BagOfCells = unpack_fully(packed.as_bits()).unwrap();
+        assert_eq!(got.single_root().unwrap(), &root);
+    }
+
+    #[test]
+    fn progress() {
+        let shared = cell(0xaa, []);
+        let root = cell(0, [cell(1, [shared.clone()]), cell(2, [shared])]);
+        let boc = BagOfCells::from_root(root.clone());
+        let stage = |stage, total: Option<usize>| {
+            (0..=4)
+                .filter(move |done| total.is_some() || *done > 0)
+                .map(move |done| Progress::new(stage, done, total))
+        };
+
+        for cell_order in [CellOrder::Topological, CellOrder::DfsPreorder] {
+            let args = BagOfCellsArgs {
+                cell_order,
+                ..Default::default()
+            };
+            let mut reported = Vec::new();
+            let bytes = boc
+                .to_bytes_with_progress(args, |progress| {
+                    reported.push(progress);
+                    ControlFlow::Continue(())
+                })
+                .unwrap();
+            assert_eq!(bytes.len(), boc.to_bytes(args).unwrap().len());
+            assert_eq!(
+                reported,
+                stage(ProgressStage::Ordering, None)
+                    .chain(stage(ProgressStage::Writing, Some(4)))
+                    .collect::<Vec<_>>()
+            );
+
+            reported.clear();
+            let parsed = BagOfCells::parse_with_progress(&bytes, ParseMode::Strict, |progress| {
+                reported.push(progress);
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+            assert_eq!(parsed.boc.single_root().unwrap(), &root);
+            assert_eq!(
+                reported,
+                stage(ProgressStage::Reading, Some(4))
+                    .chain(stage(ProgressStage::Building, Some(4)))
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn progress_cancelled() {
+        let root = cell(0, [cell(1, []), cell(2, [])]);
+        let boc = BagOfCells::from_root(root);
+        let cancel_at = |at| {
+            move |progress: Progress| {
+                if progress.stage == at && progress.done >= 2 {
+                    return ControlFlow::Break(());
+                }
+                ControlFlow::Continue(())
+            }
+        };
+
+        for (stage, total) in [
+            (ProgressStage::Ordering, None),
+            (ProgressStage::Writing, Some(3)),
+        ] {
+            let err = boc
+                .to_bytes_with_progress(BagOfCellsArgs::default(), cancel_at(stage))
+                .unwrap_err();
+            assert!(
+                matches!(err, ProgressError::Cancelled(progress) if progress == Progress::new(stage, 2, total))
+            );
+        }
+
+        let bytes = boc.to_bytes(BagOfCellsArgs::default()).unwrap();
+        for stage in [ProgressStage::Reading, ProgressStage::Building] {
+            let err = BagOfCells::parse_with_progress(&bytes, ParseMode::Strict, cancel_at(stage))
+                .unwrap_err();
+            assert!(
+                matches!(err, ProgressError::Cancelled(progress) if progress == Progress::new(stage, 2, Some(3)))
+            );
+        }
+        let err = BagOfCells::parse_with_progress(
+            &bytes,
+            ParseMode::Strict,
+            cancel_at(ProgressStage::Building),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "cancelled while building 2 of 3 cells");
+
+        let err = BagOfCells::parse_with_progress(&bytes[..4], ParseMode::Strict, |_| {
+            ControlFlow::Break(())
+        })
+        .unwrap_err();
+        assert!(matches!(err, ProgressError::Failed(_)));
+    }
+
+    #[test]
+    fn shared_dag_round_trip() {
+        // 2^256 paths from root, so each shared cell must be visited once
+        let mut root = cell(0, []);
+        for i in 1..=256 {
+            root = cell(i as u8, [root.clone(), root]);
+        }
+        let boc = BagOfCells::from_root(root.clone());
+        let bytes = boc
+            .to_bytes(BagOfCellsArgs {
+                has_idx: true,
+                ..Default::default()
+            })
+            .unwrap();
+
+        let got: BagOfCells = unpack_fully(bytes.as_bits()).unwrap();
+        assert!(got.single_root().unwrap().eq_by_hash(&root));
+
+        let indexed = IndexedBagOfCells::parse(&bytes).unwrap();
+        assert!(indexed
+            .load_cell(indexed.roots()[0])
+            .unwrap()
+            .eq_by_hash(&root));
+    }
+
+    #[test]
+    fn multiple_roots_round_trip() {
+        let shared = cell(0xaa, []);
+        let roots = [cell(0, [shared.clone()]), cell(1, []), cell(2, [shared])];
+        let mut boc = BagOfCells::from_root(roots[0].clone());
+        boc.add_root(roots[1].clone());
+        boc.add_root(roots[2].clone());
+        for cell_order in [CellOrder::Topological, CellOrder::DfsPreorder] {
+            let packed = boc
+                .to_bytes(BagOfCellsArgs {
+                    cell_order,
+                    ..Default::default()
+                })
+                .unwrap();
+            let got: BagOfCells = unpack_fully(packed.as_bits()).unwrap();
+            assert!(got.single_root().is_none());
+            let got: Vec<_> = got
+                .split_roots()
+                .map(|boc| boc.single_root().unwrap().clone())
+                .collect();
+            assert_eq!(got, roots);
+        }
+    }
+
+    #[test]
+    fn from_messages() {
+        let msgs = [0u32, 1].map(|v| Message::<_, (), ()> {
+            info: CommonMsgInfo::transfer(MsgAddress::NULL, v.into(), false),
+            init: None,
+            body: (),
+        });
+        let boc = BagOfCells::from_messages(msgs.clone()).unwrap();
+        let got: Vec<Message<(), (), ()>> = boc
+            .split_roots()
+            .map(|boc| boc.single_root().unwrap().parse_fully().unwrap())
+            .collect();
+        assert_eq!(got, msgs);
+
+        assert!(BagOfCells::from_messages::<(), (), ()>([]).is_err());
+    }
+
+    #[test]
+    fn to_hex_base64_round_trip() {
+        let mut builder = Cell::builder();
+        builder.pack(0xdeadbeefu32).unwrap();
+        let root = builder.into_cell();
+        let boc = BagOfCells::from_root(root.clone());
+        for has_idx in [false, true] {
+            for has_crc32c in [false, cfg!(feature = "crc")] {
+                let args = BagOfCellsArgs {
+                    has_idx,
+                    has_crc32c,
+                    ..Default::default()
+                };
+                let hex = boc.to_hex(args).unwrap();
+                assert_eq!(hex, hex::encode(boc.to_bytes(args).unwrap()));
+                let got = BagOfCells::parse_hex(hex).unwrap();
+                assert_eq!(**got.single_root().unwrap(), root);
+
+                #[cfg(feature = "base64")]
+                {
+                    let got = BagOfCells::parse_base64(boc.to_base64(args).unwrap()).unwrap();
+                    assert_eq!(**got.single_root().unwrap(), root);
+
+                    let url = boc.to_base64_url(args).unwrap();
+                    assert!(!url.contains(['+', '/']));
+                    let got = BagOfCells::parse_base64_url(&url).unwrap();
+                    assert_eq!(**got.single_root().unwrap(), root);
+                    let got = BagOfCells::parse_base64_url(url.trim_end_matches('=')).unwrap();
+                    assert_eq!(**got.single_root().unwrap(), root);
+                }
+            }
+        }
+    }
+
+    fn assert_unpack_err(hex: &str, contains: &str) {
+        let err = BagOfCells::parse_hex(hex).unwrap_err();
+        assert!(err.to_string().contains(contains), "{err}");
+    }
+
+    #[test]
+    fn unpack_merkle_update() {
+        assert_unpack_err("b5ee9c7201010101000300080204", "MerkleUpdate");
+    }
+
+    #[test]
+    fn merkle_update_round_trip() {
+        let leaf = Cell::from_hex_data("deadbeef", 32).unwrap();
+        // pruned branch of level 1 in place of the leaf
+        let mut pruned = BitVec::<u8, Msb0>::from_slice(&[1, 0b001]);
+        pruned.extend_from_raw_slice(&leaf.hash());
+        pruned.extend_from_raw_slice(&0u16.to_be_bytes());
+        let pruned =
+            Arc::new(Cell::try_new(pruned, Vec::new(), CellType::PrunedBranch, 0b001).unwrap());
+
+        let old = cell(1, [leaf.clone().into()]);
+        let new = cell(2, [leaf.into(), cell(3, [])]);
+        let mut data = BitVec::<u8, Msb0>::from_slice(&[4]);
+        data.extend_from_raw_slice(&old.hash());
+        data.extend_from_raw_slice(&new.hash());
+        data.extend_from_raw_slice(&1u16.to_be_bytes());
+        data.extend_from_raw_slice(&1u16.to_be_bytes());
+        let update = Cell::try_new(
+            data,
+            vec![
+                Arc::new(
+                    Cell::try_new(
+                        old.data.clone(),
+                        vec![pruned.clone()],
+                        CellType::Ordinary,
+                        0b001,
+                    )
+                    .unwrap(),
+                ),
+                Arc::new(
+                    Cell::try_new(
+                        new.data.clone(),
+                        vec![pruned, new.references[1].clone()],
+                        CellType::Ordinary,
+                        0b001,
+                    )
+                    .unwrap(),
+                ),
+            ],
+            CellType::MerkleUpdate,
+            0,
+        )
+        .unwrap();
+        let root = cell(0, [Arc::new(update)]);
+
+        let bytes = BagOfCells::from_root(root.clone())
+            .to_bytes(BagOfCellsArgs {
+                has_idx: true,
+                ..Default::default()
+            })
+            .unwrap();
+        let got: BagOfCells = unpack_fully(bytes.as_bits()).unwrap();
+        let got = got.single_root().unwrap();
+        assert_eq!(got, &root);
+        assert_eq!(got.references[0].r#type, CellType::MerkleUpdate);
+        assert_eq!(got.hash(), root.hash());
+
+        let indexed = IndexedBagOfCells::parse(&bytes).unwrap();
+        assert_eq!(&indexed.load_cell(indexed.roots()[0]).unwrap(), &root);
+    }
+
+    #[test]
+    fn unpack_unknown_exotic_cell() {
+        assert_unpack_err("b5ee9c720101010100030008020a", "unknown exotic cell type");
+    }
+
+    #[test]
+    fn unpack_invalid_references_number() {
+        assert_unpack_err("b5ee9c72010101010002000700", "invalid number of references");
+    }
+
+    #[test]
+    fn unpack_reference_out_of_bounds() {
+        assert_unpack_err("b5ee9c7201010101000300010005", "reference out of bounds");
+    }
+
+    #[test]
+    fn unpack_root_out_of_bounds() {
+        assert_unpack_err("b5ee9c72010101010002050000", "root out of bounds");
+    }
+
+    #[test]
+    fn unpack_no_roots() {
+        assert_unpack_err("b5ee9c72010101000000", "no roots");
+    }
+
+    #[test]
+    fn unpack_roots_absent_overflow() {
+        assert_unpack_err(
+            "b5ee9c720401000000ffffffffff00000001",
+            "roots + absent > cells",
+        );
+    }
+
+    #[test]
+    fn unpack_zero_last_byte() {
+        assert_unpack_err("b5ee9c7201010101000300000100", "last byte must be non zero");
+    }
+
+    #[test]
+    fn stats() {
+        let leaf = Arc::new(Cell {
+            r#type: CellType::Ordinary,
+            data: [0xFFu8].as_bits().to_bitvec(),
+            references: Vec::new(),
+        });
+        let root = Cell {
+            r#type: CellType::Ordinary,
+            data: BitVec::new(),
+            references: vec![leaf.clone(), leaf.clone()],
+        };
+        let stats = BagOfCells::from_root(root).stats();
+        assert_eq!(stats.cells, 3);
+        assert_eq!(stats.unique_cells, 2);
+        assert_eq!(stats.bits, 8);
+        assert_eq!(stats.refs, 2);
+        assert!(stats.memory_usage > 0);
+    }
+
+    #[test]
+    fn stats_exponential_tree() {
+        let root = (0..100).fold(Arc::new(Cell::new()), |child, _| {
+            Arc::new(Cell {
+                r#type: CellType::Ordinary,
+                data: BitVec::new(),
+                references: vec![child.clone(), child],
+            })
+        });
+        let stats = BagOfCells::from_root(root).stats();
+        assert_eq!(stats.cells, u64::MAX);
+        assert_eq!(stats.unique_cells, 101);
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn code_cell_cache() {
+        let mut builder = Cell::builder();
+        builder.pack(0xdeadbeefu32).unwrap();
+        let cell = builder.into_cell();
+        let hash = cell.hash();
+        let packed = pack_with(BagOfCells::from_root(cell), BagOfCellsArgs::default()).unwrap();
+        let code = STANDARD.encode(packed.as_raw_slice());
+
+        let cache = CodeCellCache::new();
+        assert!(cache.get(&hash).is_none());
+        assert!(cache.get_or_parse_base64([0; 32], &code).is_err());
+
+        let got = cache.get_or_parse_base64(hash, &code).unwrap();
+        assert_eq!(got.hash(), hash);
+        assert!(Arc::ptr_eq(
+            &got,
+            &cache.get_or_parse_base64(hash, "").unwrap()
+        ));
+    }
+
+    /// Pack cells with given data and references in given order with
+    /// root at index 0
+    fn pack_raw_cells(
+        cells: impl IntoIterator<Item = (u8, Vec<u32>)>,
+        args: BagOfCellsArgs,
+    ) -> BitVec<u8, Msb0> {
+        let raw = RawBagOfCells {
+            cells: cells
+                .into_iter()
+                .map(|(data, references)| RawCell {
+                    r#type: CellType::Ordinary,
+                    data: BitVec::from_element(data),
+                    references,
+                    level_mask: 0,
+                })
+                .collect(),
+            roots: vec![0],
+        };
+        pack_with(raw, args).unwrap()
+    }
+
+    #[test]
+    fn drop_deep_chain() {
+        const N: u32 = 100_000;
+        let bytes = pack_raw_cells(
+            (0..N).map(|i| (0, if i + 1 < N { vec![i + 1] } else { vec![] })),
+            BagOfCellsArgs::default(),
+        );
+        let parsed = BagOfCells::parse(bytes.as_raw_slice(), ParseMode::Strict).unwrap();
+        drop(parsed);
+    }
+
+    #[test]
+    fn unpack_invalid_level() {
+        let raw = RawBagOfCells {
+            cells: vec![RawCell {
+                r#type: CellType::Ordinary,
+                data: BitVec::new(),
+                references: Vec::new(),
+                level_mask: 1,
+            }],
+            roots: vec![0],
+        };
+        let err = unpack_fully::<BagOfCells>(pack_with(raw, BagOfCellsArgs::default()).unwrap())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "[0]: invalid level mask: 0b001, expected: 0b000"
+        );
+    }
+
+    #[test]
+    fn unpack_references_to_previous_cells() {
+        // [0] -> [2] -> [1]
+        //    \-> [3] -/
+        let boc: BagOfCells = unpack_fully(pack_raw_cells(
+            [(0, vec![2, 3]), (1, vec![]), (2, vec![1]), (3, vec![1])],
+            BagOfCellsArgs::default(),
+        ))
+        .unwrap();
+        let root = boc.single_root().unwrap();
+        assert_eq!(root.data, BitVec::<u8, Msb0>::from_element(0));
+        let [l, r] = root.references.as_slice() else {
+            panic!("expected 2 references");
+        };
+        assert_eq!(l.data, BitVec::<u8, Msb0>::from_element(2));
+        assert_eq!(r.data, BitVec::<u8, Msb0>::from_element(3));
+        assert!(Arc::ptr_eq(&l.references[0], &r.references[0]));
+
+        // same tree in standard order
+        let standard: BagOfCells = unpack_fully(pack_raw_cells(
+            [(0, vec![1, 2]), (2, vec![3]), (3, vec![3]), (1, vec![])],
+            BagOfCellsArgs::default(),
+        ))
+        .unwrap();
+        assert_eq!(standard.single_root(), boc.single_root());
+    }
+
+    #[test]
+    fn unpack_reference_cycle() {
+        for cells in [
+            vec![(0, vec![0])],
+            vec![(0, vec![1]), (1, vec![2]), (2, vec![0])],
+        ] {
+            let err = unpack_fully::<BagOfCells>(pack_raw_cells(cells, BagOfCellsArgs::default()))
+                .unwrap_err();
+            assert!(err.to_string().contains("reference cycle"), "{err}");
+        }
+    }
+
+    #[test]
+    fn parse_trailing_data() {
+        let mut bytes = pack_with(
+            BagOfCells::from_root(Cell::new()),
+            BagOfCellsArgs::default(),
+        )
+        .unwrap()
+        .into_vec();
+        assert!(BagOfCells::parse(&bytes, ParseMode::Strict)
+            .unwrap()
+            .warnings
+            .is_empty());
+
+        bytes.extend_from_slice(&[0; 3]);
+        let err = BagOfCells::parse(&bytes, ParseMode::Strict).unwrap_err();
+        assert_eq!(err.to_string(), "3 bytes of trailing data");
+        let parsed = BagOfCells::parse(&bytes, ParseMode::Lenient).unwrap();
+        assert_eq!(
+            parsed.warnings,
+            [BagOfCellsWarning::TrailingData { bytes: 3 }]
+        );
+        assert_eq!(parsed.boc.single_root().unwrap().as_ref(), &Cell::new());
+    }
+
+    #[test]
+    fn indexed_load_cell() {
+        // [0] -> [2] -> [1]
+        //    \-> [3] -/
+        let bytes = pack_raw_cells(
+            [(0, vec![2, 3]), (1, vec![]), (2, vec![1]), (3, vec![1])],
+            BagOfCellsArgs {
+                has_idx: true,
+                has_crc32c: cfg!(feature = "crc"),
+                ..Default::default()
+            },
+        )
+        .into_vec();
+
+        let full: BagOfCells = unpack_fully(bytes.as_bits()).unwrap();
+        let root = full.single_root().unwrap();
+
+        let indexed = IndexedBagOfCells::parse(&bytes).unwrap();
+        assert_eq!(indexed.len(), 4);
+        assert_eq!(indexed.roots(), [0]);
+        assert_eq!(&indexed.load_cell(0).unwrap(), root);
+        assert_eq!(&indexed.load_cell(2).unwrap(), &root.references[0]);
+        assert_eq!(
+            &indexed.load_cell(1).unwrap(),
+            &root.references[0].references[0]
+        );
+        assert!(indexed.load_cell(4).is_err());
+    }
+
+    #[test]
+    fn indexed_requires_index() {
+        let bytes = pack_with(
+            BagOfCells::from_root(Cell::new()),
+            BagOfCellsArgs::default(),
+        )
+        .unwrap()
+        .into_vec();
+        let err = IndexedBagOfCells::parse(&bytes).unwrap_err();
+        assert_eq!(err.to_string(), "BoC has no index");
+    }
+}
@@ -0,0 +1,198 @@
+//! Blockchain configuration stored in masterchain state
+use std::collections::BTreeMap;
+
+use impl_tools::autoimpl;
+use tlb::{
+    bits::{de::BitReaderExt, ser::BitWriterExt},
+    de::{r#as::CellDeserializeAs, CellDeserialize, CellParser, CellParserError},
+    r#as::Ref,
+    ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Cell, ResultExt,
+};
+
+use crate::hashmap::NonEmptyDict;
+
+/// Configuration parameters of the blockchain:
+/// ```tlb
+/// _ config_addr:bits256 config:^(Hashmap 32 ^Cell) = ConfigParams;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigParams {
+    /// Address of config smart contract in masterchain
+    pub config_addr: [u8; 32],
+    pub config: ConfigDict,
+}
+
+impl CellSerialize for ConfigParams {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // config_addr:bits256
+            .pack(self.config_addr)?
+            // config:^(Hashmap 32 ^Cell)
+            .store_as::<_, Ref>(&self.config)
+            .context("config")?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for ConfigParams {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            // config_addr:bits256
+            config_addr: parser.unpack().context("config_addr")?,
+            // config:^(Hashmap 32 ^Cell)
+            config: parser.parse_as::<_, Ref>().context("config")?,
+        })
+    }
+}
+
+/// Non-empty dictionary of configuration parameters keyed by their index,
+/// where each value is stored in its own cell:
+/// ```tlb
+/// config:(Hashmap 32 ^Cell)
+/// ```
+///
+/// Raw cells of parameters can be accessed via [`Deref`](core::ops::Deref)
+/// to the inner map, while [`.get_param()`](ConfigDict::get_param) parses
+/// them into typed structures:
+/// ```rust
+/// # use tlb::{r#as::Data, ser::CellSerializeExt, Cell};
+/// # use tlb_ton::config::ConfigDict;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let config: ConfigDict = [
+///     // config_addr:bits256
+///     (0, Cell::from_hex_data("55".repeat(32), 256)?),
+///     (-999, Cell::new()),
+/// ]
+/// .into_iter()
+/// .collect();
+///
+/// let config: ConfigDict = config.to_cell()?.parse_fully()?;
+/// assert!(config.get(&-999).is_some());
+/// assert_eq!(config.get_param_as::<[u8; 32], Data>(0)?, Some([0x55; 32]));
+/// assert_eq!(config.get_param_as::<[u8; 32], Data>(1)?, None);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[autoimpl(Deref using self.params)]
+#[autoimpl(DerefMut using self.params)]
+pub struct ConfigDict {
+    pub params: BTreeMap<i32, Cell>,
+}
+
+impl ConfigDict {
+    /// Length of keys in bits
+    pub const KEY_BITS: u32 = 32;
+
+    /// Parse parameter with given index, if present
+    pub fn get_param<'a, T>(&'a self, index: i32) -> Result<Option<T>, CellParserError<'a>>
+    where
+        T: CellDeserialize<'a>,
+    {
+        self.params
+            .get(&index)
+            .map(Cell::parse_fully)
+            .transpose()
+            .with_context(|| format!("param {index}"))
+    }
+
+    /// Parse parameter with given index using an adapter, if present
+    pub fn get_param_as<'a, T, As>(&'a self, index: i32) -> Result<Option<T>, CellParserError<'a>>
+    where
+        As: CellDeserializeAs<'a, T> + ?Sized,
+    {
+        self.params
+            .get(&index)
+            .map(Cell::parse_fully_as::<T, As>)
+            .transpose()
+            .with_context(|| format!("param {index}"))
+    }
+
+    /// Unwrap inner [`BTreeMap`]
+    #[inline]
+    pub fn into_inner(self) -> BTreeMap<i32, Cell> {
+        self.params
+    }
+}
+
+impl From<BTreeMap<i32, Cell>> for ConfigDict {
+    #[inline]
+    fn from(params: BTreeMap<i32, Cell>) -> Self {
+        Self { params }
+    }
+}
+
+impl FromIterator<(i32, Cell)> for ConfigDict {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (i32, Cell)>>(iter: I) -> Self {
+        Self {
+            params: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl CellSerialize for ConfigDict {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder.store_as::<_, &NonEmptyDict<{ Self::KEY_BITS }, Ref>>(&self.params)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for ConfigDict {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        parser
+            .parse_as::<_, NonEmptyDict<{ Self::KEY_BITS }, Ref>>()
+            .map(Self::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::{r#as::Data, ser::CellSerializeExt};
+
+    use super::*;
+
+    fn param(v: u32) -> Cell {
+        let mut builder = Cell::builder();
+        builder.pack(v).unwrap();
+        builder.into_cell()
+    }
+
+    #[test]
+    fn config_params_serde() {
+        let params = ConfigParams {
+            config_addr: [0x55; 32],
+            config: [
+                (0, Cell::from_hex_data("55".repeat(32), 256).unwrap()),
+                (7, param(7)),
+                (-1024, param(1024)),
+                (i32::MIN, Cell::new()),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let cell = params.to_cell().unwrap();
+        let got: ConfigParams = cell.parse_fully().unwrap();
+        assert_eq!(got, params);
+
+        assert_eq!(got.config.get_param_as::<u32, Data>(7).unwrap(), Some(7));
+        assert_eq!(
+            got.config.get_param_as::<u32, Data>(-1024).unwrap(),
+            Some(1024)
+        );
+        assert_eq!(got.config.get_param_as::<u32, Data>(8).unwrap(), None);
+        assert!(got.config.get_param_as::<u64, Data>(7).is_err());
+        assert_eq!(
+            got.config.get_param::<Cell>(i32::MIN).unwrap(),
+            Some(Cell::new())
+        );
+    }
+
+    #[test]
+    fn config_dict_empty() {
+        assert!(ConfigDict::default().to_cell().is_err());
+    }
+}
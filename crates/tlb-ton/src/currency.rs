@@ -1,28 +1,223 @@
 //! Collection of types to work with currencies
 use lazy_static::lazy_static;
 use num_bigint::BigUint;
-use num_traits::One;
+use num_traits::{CheckedSub, One, ToPrimitive};
 use tlb::{
-    bits::{de::BitReaderExt, r#as::VarInt, ser::BitWriterExt},
+    aliases::VarUInteger16,
+    bits::{
+        bitvec::{order::Msb0, view::AsBits},
+        de::BitReaderExt,
+        r#as::VarInt,
+        ser::BitWriterExt,
+    },
     de::{CellDeserialize, CellParser, CellParserError},
     r#as::{Data, NoArgs},
     ser::{CellBuilder, CellBuilderError, CellSerialize},
+    Error, ResultExt, StringError,
 };
 
 use crate::hashmap::HashmapE;
 
 lazy_static! {
-    /// 1 gram (nano-TON)
+    /// 1 gram (nano-TON), see [`consts::NANOTON`]
     pub static ref ONE_GRAM: BigUint = BigUint::one();
+    /// 1 TON, see [`consts::TON`]
+    pub static ref ONE_TON: BigUint = &*ONE_GRAM * consts::TON;
+}
+
+/// Denominations of TON expressed in nano-TONs, which is the unit of
+/// [`Coins`]:
+/// ```rust
+/// # use tlb_ton::currency::consts::{MILLITON, TON};
+/// let fee = 50 * MILLITON;
+/// assert_eq!(20 * fee, TON);
+/// ```
+pub mod consts {
+    /// Number of decimal places in TON
+    pub const TON_DECIMALS: u32 = 9;
+
+    /// 1 nano-TON, i.e. 1 gram
+    pub const NANOTON: u64 = 1;
+    /// 1 micro-TON
+    pub const MICROTON: u64 = 1_000 * NANOTON;
+    /// 1 milli-TON
+    pub const MILLITON: u64 = 1_000 * MICROTON;
     /// 1 TON
-    pub static ref ONE_TON: BigUint = &*ONE_GRAM * 1_000_000_000u64;
+    pub const TON: u64 = 1_000 * MILLITON;
+
+    /// Maximum amount representable as [`Coins`](super::Coins), i.e.
+    /// `VarUInteger 16`: `2^120 - 1` nano-TONs
+    pub const MAX_COINS: u128 = (1 << 120) - 1;
+}
+
+/// Conversions and arithmetic for amounts of nano-TONs, which keep them
+/// representable as [`Coins`], i.e. within [`MAX_COINS`](consts::MAX_COINS).
+/// ```rust
+/// # use tlb_ton::currency::{consts::MILLITON, CoinsExt};
+/// let amount = u64::from_ton_str("1.05").unwrap();
+/// assert_eq!(amount.checked_sub_coins(&(50 * MILLITON)), Some(1_000_000_000));
+/// assert_eq!(amount.to_ton_string(), "1.05");
+///
+/// // precision is never lost silently
+/// assert!(u64::from_ton_str("0.0000000001").is_err());
+/// assert_eq!(u64::from_ton_str("0.0000000010").unwrap(), 1);
+/// ```
+pub trait CoinsExt: Sized {
+    /// Parse amount of TONs given as decimal string, i.e. `"1.5"`, into
+    /// nano-TONs.
+    ///
+    /// Parsing is exact: there is no rounding, so amounts with non-zero
+    /// digits beyond [`TON_DECIMALS`](consts::TON_DECIMALS) are rejected
+    /// along with the ones exceeding [`MAX_COINS`](consts::MAX_COINS) or
+    /// the capacity of `Self`.
+    fn from_ton_str(s: &str) -> Result<Self, StringError>;
+
+    /// Format nano-TONs as decimal amount of TONs without trailing zeros
+    fn to_ton_string(&self) -> String;
+
+    /// Checked addition, returns `None` if the result exceeds
+    /// [`MAX_COINS`](consts::MAX_COINS) or the capacity of `Self`
+    fn checked_add_coins(&self, rhs: &Self) -> Option<Self>;
+
+    /// Checked subtraction, returns `None` on underflow
+    fn checked_sub_coins(&self, rhs: &Self) -> Option<Self>;
+
+    /// Saturating addition, which is bounded by
+    /// [`MAX_COINS`](consts::MAX_COINS) or the capacity of `Self`
+    fn saturating_add_coins(&self, rhs: &Self) -> Self;
+
+    /// Saturating subtraction, which is bounded by zero
+    fn saturating_sub_coins(&self, rhs: &Self) -> Self;
+}
+
+/// Parse decimal amount of TONs into nano-TONs, see
+/// [`CoinsExt::from_ton_str()`]
+fn parse_ton_str(s: &str) -> Result<u128, StringError> {
+    let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+    if int.is_empty() || !int.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Error::custom(format!("invalid amount of TON: {s:?}")));
+    }
+    if !frac.bytes().all(|b| b.is_ascii_digit()) || (frac.is_empty() && s.ends_with('.')) {
+        return Err(Error::custom(format!("invalid amount of TON: {s:?}")));
+    }
+    let decimals = consts::TON_DECIMALS as usize;
+    let (frac, rest) = frac.split_at(frac.len().min(decimals));
+    if rest.bytes().any(|b| b != b'0') {
+        return Err(Error::custom(format!(
+            "too many decimal places in {s:?}, max: {decimals}"
+        )));
+    }
+    let nano = int
+        .parse::<u128>()
+        .ok()
+        .and_then(|int| int.checked_mul(consts::TON.into()))
+        .and_then(|nano| {
+            let frac: u128 = format!("{frac:0<decimals$}").parse().ok()?;
+            nano.checked_add(frac)
+        })
+        .filter(|&nano| nano <= consts::MAX_COINS)
+        .ok_or_else(|| Error::custom(format!("amount of TON is too big: {s}")))?;
+    Ok(nano)
+}
+
+/// Format TONs and fractional part in nano-TONs, see
+/// [`CoinsExt::to_ton_string()`]
+fn format_ton(int: impl core::fmt::Display, frac: u64) -> String {
+    if frac == 0 {
+        return int.to_string();
+    }
+    let frac = format!("{frac:0width$}", width = consts::TON_DECIMALS as usize);
+    format!("{int}.{}", frac.trim_end_matches('0'))
+}
+
+macro_rules! impl_coins_ext_for_uint {
+    ($($t:ty),+) => {$(
+        impl CoinsExt for $t {
+            #[inline]
+            fn from_ton_str(s: &str) -> Result<Self, StringError> {
+                parse_ton_str(s)?
+                    .try_into()
+                    .map_err(|_| Error::custom(format!(
+                        "amount of TON is too big for {}: {s}",
+                        stringify!($t),
+                    )))
+            }
+
+            #[inline]
+            fn to_ton_string(&self) -> String {
+                format_ton(self / consts::TON as $t, (self % consts::TON as $t) as u64)
+            }
+
+            #[inline]
+            fn checked_add_coins(&self, rhs: &Self) -> Option<Self> {
+                <$t>::checked_add(*self, *rhs)
+                    .filter(|&v| v as u128 <= consts::MAX_COINS)
+            }
+
+            #[inline]
+            fn checked_sub_coins(&self, rhs: &Self) -> Option<Self> {
+                <$t>::checked_sub(*self, *rhs)
+            }
+
+            #[inline]
+            fn saturating_add_coins(&self, rhs: &Self) -> Self {
+                self.checked_add_coins(rhs).unwrap_or_else(|| {
+                    consts::MAX_COINS.try_into().unwrap_or(Self::MAX)
+                })
+            }
+
+            #[inline]
+            fn saturating_sub_coins(&self, rhs: &Self) -> Self {
+                <$t>::saturating_sub(*self, *rhs)
+            }
+        }
+    )+};
+}
+impl_coins_ext_for_uint!(u64, u128);
+
+impl CoinsExt for BigUint {
+    #[inline]
+    fn from_ton_str(s: &str) -> Result<Self, StringError> {
+        parse_ton_str(s).map(Into::into)
+    }
+
+    #[inline]
+    fn to_ton_string(&self) -> String {
+        let frac = (self % consts::TON)
+            .to_u64()
+            .unwrap_or_else(|| unreachable!());
+        format_ton(self / consts::TON, frac)
+    }
+
+    #[inline]
+    fn checked_add_coins(&self, rhs: &Self) -> Option<Self> {
+        Some(self + rhs).filter(|v| *v <= consts::MAX_COINS.into())
+    }
+
+    #[inline]
+    fn checked_sub_coins(&self, rhs: &Self) -> Option<Self> {
+        self.checked_sub(rhs)
+    }
+
+    #[inline]
+    fn saturating_add_coins(&self, rhs: &Self) -> Self {
+        self.checked_add_coins(rhs)
+            .unwrap_or_else(|| consts::MAX_COINS.into())
+    }
+
+    #[inline]
+    fn saturating_sub_coins(&self, rhs: &Self) -> Self {
+        self.checked_sub_coins(rhs).unwrap_or_default()
+    }
 }
 
 /// Alias for `VarUInteger 16`
 /// ```tlb
 /// nanograms$_ amount:(VarUInteger 16) = Grams;
 /// ```
-pub type Coins = VarInt<4>;
+/// Can be used with [`BigUint`], as well as with `u64` or `u128` to avoid
+/// heap allocations.
+pub type Coins = VarUInteger16;
 
 /// Alias for `VarUInteger 16`
 /// ```tlb
@@ -63,15 +258,53 @@ impl<'de> CellDeserialize<'de> for CurrencyCollection {
 /// ```tlb
 /// extra_currencies$_ dict:(HashmapE 32 (VarUInteger 32)) = ExtraCurrencyCollection;
 /// ```
+///
+/// Dictionaries don't store width of their keys, so they are always
+/// parsed with [`CURRENCY_ID_BITS`](Self::CURRENCY_ID_BITS)-long keys and
+/// the ones built for other widths fail to parse. Serialization of
+/// in-memory dictionary with keys of other width is rejected with an
+/// error rather than producing a malformed one.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct ExtraCurrencyCollection(pub HashmapE<BigUint>);
 
+impl ExtraCurrencyCollection {
+    /// Width of currency ids, i.e. keys of the dictionary
+    pub const CURRENCY_ID_BITS: u32 = 32;
+
+    /// Return amount of extra currency with given id
+    #[inline]
+    pub fn get(&self, id: u32) -> Option<&BigUint> {
+        self.0.get(id.to_be_bytes().as_bits::<Msb0>())
+    }
+
+    /// Ensure all keys are exactly [`CURRENCY_ID_BITS`](Self::CURRENCY_ID_BITS)
+    /// long before storing them
+    fn check_key_width<E>(&self) -> Result<(), E>
+    where
+        E: Error,
+    {
+        if let Some((key, _)) = self
+            .0
+            .iter()
+            .find(|(key, _)| key.len() != Self::CURRENCY_ID_BITS as usize)
+        {
+            return Err(E::custom(format!(
+                "currency id must be {} bits long, got {}",
+                Self::CURRENCY_ID_BITS,
+                key.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
 impl CellSerialize for ExtraCurrencyCollection {
     #[inline]
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        self.check_key_width()?;
         builder.store_as_with::<_, &HashmapE<NoArgs<_, Data<VarInt<32>>>, NoArgs<_>>>(
             &self.0,
-            (32, (), ()),
+            (Self::CURRENCY_ID_BITS, (), ()),
         )?;
         Ok(())
     }
@@ -81,18 +314,27 @@ impl<'de> CellDeserialize<'de> for ExtraCurrencyCollection {
     #[inline]
     fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
         Ok(Self(
-            parser.parse_as_with::<_, HashmapE<NoArgs<_, Data<VarInt<32>>>, NoArgs<_>>>((
-                32,
-                (),
-                (),
-            ))?,
+            parser
+                .parse_as_with::<_, HashmapE<NoArgs<_, Data<VarInt<32>>>, NoArgs<_>>>((
+                    Self::CURRENCY_ID_BITS,
+                    (),
+                    (),
+                ))
+                .with_context(|| {
+                    format!(
+                        "dictionary with {}-bit currency ids",
+                        Self::CURRENCY_ID_BITS
+                    )
+                })?,
         ))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use tlb::ser::CellSerializeExt;
+    use tlb::{bits::bitvec::vec::BitVec, ser::CellSerializeExt, Cell};
+
+    use crate::hashmap::{aug::HashmapAugNode, Hashmap, HashmapNode};
 
     use super::*;
 
@@ -105,4 +347,105 @@ mod tests {
 
         assert_eq!(got, v);
     }
+
+    fn single_entry(key: BitVec<u8, Msb0>, value: u8) -> Cell {
+        let hm = HashmapE::Root(Hashmap::new(
+            key.clone(),
+            HashmapAugNode::new(HashmapNode::Leaf(BigUint::from(value)), ()),
+        ));
+        let mut builder = Cell::builder();
+        builder
+            .store_as_with::<_, HashmapE<NoArgs<_, Data<VarInt<32>>>, NoArgs<_>>>(
+                hm,
+                (key.len() as u32, (), ()),
+            )
+            .unwrap();
+        builder.into_cell()
+    }
+
+    #[test]
+    fn extra_currency_collection_serde() {
+        let cell = single_entry(1u32.to_be_bytes().as_bits().to_bitvec(), 100);
+
+        let got: ExtraCurrencyCollection = cell.parse_fully().unwrap();
+        assert_eq!(got.get(1), Some(&100u8.into()));
+        assert_eq!(got.get(2), None);
+        assert_eq!(got.to_cell().unwrap(), cell);
+    }
+
+    #[test]
+    fn extra_currency_collection_key_width() {
+        for bits in [16, 64] {
+            let key = BitVec::repeat(true, bits);
+            let err = single_entry(key.clone(), 100)
+                .parse_fully::<ExtraCurrencyCollection>()
+                .unwrap_err();
+            assert!(err.to_string().contains("32-bit currency ids"), "{err}");
+
+            let v = ExtraCurrencyCollection(HashmapE::Root(Hashmap::new(
+                key,
+                HashmapAugNode::new(HashmapNode::Leaf(BigUint::from(100u8)), ()),
+            )));
+            let err = v.to_cell().unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains(&format!("must be 32 bits long, got {bits}")),
+                "{err}"
+            );
+        }
+    }
+
+    #[test]
+    fn from_ton_str() {
+        for (s, nano) in [
+            ("0", 0u64),
+            ("1", consts::TON),
+            ("1.5", 1_500_000_000),
+            ("0.000000001", 1),
+            ("0.0000000010", 1),
+            ("007.050", 7_050_000_000),
+        ] {
+            assert_eq!(u64::from_ton_str(s).unwrap(), nano, "{s}");
+            assert_eq!(BigUint::from_ton_str(s).unwrap(), nano.into(), "{s}");
+        }
+        for s in ["", ".5", "1.", "-1", "1.2.3", "1e9", "0.0000000001", " 1"] {
+            assert!(u64::from_ton_str(s).is_err(), "{s:?}");
+        }
+        assert!(u64::from_ton_str("18446744074").is_err());
+        assert!(u128::from_ton_str("1329227995784915872903807060.280344575").is_ok());
+        assert!(u128::from_ton_str("1329227995784915872903807060.280344576").is_err());
+    }
+
+    #[test]
+    fn to_ton_string() {
+        for (nano, s) in [
+            (0u64, "0"),
+            (consts::TON, "1"),
+            (1_500_000_000, "1.5"),
+            (1, "0.000000001"),
+            (123 * consts::TON + 45 * consts::MILLITON, "123.045"),
+        ] {
+            assert_eq!(nano.to_ton_string(), s);
+            assert_eq!(BigUint::from(nano).to_ton_string(), s);
+            assert_eq!(u64::from_ton_str(s).unwrap(), nano);
+        }
+    }
+
+    #[test]
+    fn coins_arithmetic() {
+        let max = consts::MAX_COINS;
+        assert_eq!(max.checked_add_coins(&1), None);
+        assert_eq!(max.saturating_add_coins(&1), max);
+        assert_eq!(1u128.saturating_sub_coins(&2), 0);
+        assert_eq!(u64::MAX.saturating_add_coins(&1), u64::MAX);
+
+        let max = BigUint::from(max);
+        assert_eq!(max.checked_add_coins(&BigUint::one()), None);
+        assert_eq!(max.saturating_add_coins(&BigUint::one()), max);
+        assert_eq!(BigUint::one().checked_sub_coins(&BigUint::from(2u8)), None);
+        assert_eq!(
+            BigUint::one().saturating_sub_coins(&BigUint::from(2u8)),
+            BigUint::ZERO
+        );
+    }
 }
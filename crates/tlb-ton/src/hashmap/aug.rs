@@ -1,3 +1,4 @@
+use core::fmt::{self, Debug, Display};
 use std::iter::once;
 
 use impl_tools::autoimpl;
@@ -16,7 +17,7 @@ use tlb::{
         args::{r#as::CellSerializeAsWithArgs, CellSerializeWithArgs},
         CellBuilder, CellBuilderError,
     },
-    Error, ResultExt,
+    Error, HexWithCompletionTag, ResultExt,
 };
 
 use super::hm_label::HmLabel;
@@ -156,6 +157,34 @@ impl<T, E> HashmapE<T, E> {
             Self::Root(root) => root.get_mut(key),
         }
     }
+
+    /// Returns an iterator over full keys and leaf values sorted by keys
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T, E> {
+        match self {
+            Self::Empty => Iter { stack: Vec::new() },
+            Self::Root(root) => root.iter(),
+        }
+    }
+
+    /// Returns a helper to [`Display`] this hashmap as a logical map,
+    /// one `key_hex -> value` line per entry sorted by keys:
+    /// ```rust
+    /// # use tlb::bits::bitvec::{bitvec, order::Msb0};
+    /// # use tlb_ton::hashmap::{aug::HashmapAugNode, Hashmap, HashmapE, HashmapNode};
+    /// let hm: HashmapE<u32> = HashmapE::Root(Hashmap::new(
+    ///     bitvec![u8, Msb0; 1,0,1,0, 1,1,0,0, 1],
+    ///     HashmapAugNode::new(HashmapNode::Leaf(777), ()),
+    /// ));
+    /// assert_eq!(hm.display_entries().to_string(), "acc_ -> 777\n");
+    /// ```
+    ///
+    /// Keys are rendered as hex digits, and keys with length not
+    /// divisible by 4 have a completion tag `_` appended, as in `fift`.
+    #[inline]
+    pub fn display_entries(&self) -> DisplayEntries<'_, T, E> {
+        DisplayEntries(self.iter())
+    }
 }
 
 impl<T, AsT, E, AsE> CellSerializeAsWithArgs<HashmapE<T, E>> for HashmapE<AsT, AsE>
@@ -315,7 +344,7 @@ impl<T, E> Hashmap<T, E> {
     pub fn contains_key(&self, key: impl AsRef<BitSlice<u8, Msb0>>) -> bool {
         key.as_ref()
             .strip_prefix(&self.prefix)
-            .map_or(false, |key| self.node.contains_key(key))
+            .is_some_and(|key| self.node.contains_key(key))
     }
 
     #[inline]
@@ -327,6 +356,63 @@ impl<T, E> Hashmap<T, E> {
     pub fn get_mut(&mut self, key: impl AsRef<BitSlice<u8, Msb0>>) -> Option<&mut T> {
         self.node.get_mut(key.as_ref().strip_prefix(&self.prefix)?)
     }
+
+    /// Returns an iterator over full keys and leaf values sorted by keys
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T, E> {
+        Iter {
+            stack: [(Key::new(), self)].into(),
+        }
+    }
+
+    /// See [`HashmapE::display_entries()`]
+    #[inline]
+    pub fn display_entries(&self) -> DisplayEntries<'_, T, E> {
+        DisplayEntries(self.iter())
+    }
+}
+
+/// Iterator over entries of [`Hashmap`] and [`HashmapE`] in the order of
+/// their keys
+#[autoimpl(Clone)]
+pub struct Iter<'a, T, E = ()> {
+    stack: Vec<(Key, &'a Hashmap<T, E>)>,
+}
+
+impl<'a, T, E> Iterator for Iter<'a, T, E> {
+    type Item = (Key, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (mut key, hm) = self.stack.pop()?;
+        key.extend_from_bitslice(&hm.prefix);
+        match &hm.node.node {
+            HashmapNode::Leaf(v) => Some((key, v)),
+            HashmapNode::Fork([left, right]) => {
+                // push right first, so that left is visited first
+                for (bit, child) in [(true, right), (false, left)] {
+                    let mut key = key.clone();
+                    key.push(bit);
+                    self.stack.push((key, child));
+                }
+                self.next()
+            }
+        }
+    }
+}
+
+/// [`Display`] helper returned by [`HashmapE::display_entries()`]
+pub struct DisplayEntries<'a, T, E = ()>(Iter<'a, T, E>);
+
+impl<T, E> Display for DisplayEntries<'_, T, E>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in self.0.clone() {
+            writeln!(f, "{:x} -> {value:?}", HexWithCompletionTag(&key))?;
+        }
+        Ok(())
+    }
 }
 
 impl<T, AsT, E, AsE> CellSerializeAsWithArgs<Hashmap<T, E>> for Hashmap<AsT, AsE>
@@ -693,8 +779,8 @@ where
 mod tests {
     use std::collections::{BTreeMap, HashMap};
     use tlb::{
-        bits::bitvec::{bits, order::Msb0, view::AsBits},
-        r#as::{Data, NoArgs},
+        bits::bitvec::{bits, field::BitField, order::Msb0, view::AsBits},
+        r#as::{ArgsContext, Data, NoArgs},
         ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt},
         Cell,
     };
@@ -725,6 +811,24 @@ mod tests {
         assert_eq!(got, cell);
     }
 
+    #[test]
+    fn iter_display_entries() {
+        let hm: HashmapE<u16> = given_cell_from_example()
+            .parse_fully_as_with::<_, HashmapE<Data<NoArgs<_>>, NoArgs<_>>>((8, (), ()))
+            .unwrap();
+
+        assert_eq!(
+            hm.iter()
+                .map(|(k, v)| (k.load_be::<u8>(), *v))
+                .collect::<Vec<_>>(),
+            [(1, 777), (17, 111), (128, 777)],
+        );
+        assert_eq!(
+            hm.display_entries().to_string(),
+            "01 -> 777\n11 -> 111\n80 -> 777\n",
+        );
+    }
+
     #[test]
     fn hashmape_parse_as_std_hashmap() {
         let cell = given_cell_from_example();
@@ -759,6 +863,18 @@ mod tests {
         assert_eq!(hm.get(128u8.to_be_bytes().as_bits()), Some(&777));
     }
 
+    #[test]
+    fn parse_args_context() {
+        let cell = given_cell_from_example();
+
+        let err =
+            cell.parse_fully_as_with::<BTreeMap<Key, u16>, ArgsContext<HashmapE<Data<NoArgs<_>>>>>(
+                (16, ()),
+            )
+            .unwrap_err();
+        assert!(err.to_string().starts_with("args (16, ()): "), "{err}");
+    }
+
     /// See <https://docs.ton.org/develop/data-formats/tl-b-types#hashmap-parsing-example>
     fn given_cell_from_example() -> Cell {
         (
@@ -0,0 +1,360 @@
+use core::marker::PhantomData;
+use std::collections::BTreeMap;
+
+use impl_tools::autoimpl;
+use tlb::{
+    bits::{
+        bitvec::{order::Msb0, slice::BitSlice},
+        de::r#as::{unpack_fully_as, BitUnpackAs},
+        ser::{
+            r#as::{pack_as, BitPackAs},
+            BitWriterExt,
+        },
+    },
+    de::{r#as::CellDeserializeAs, CellDeserialize, CellParser, CellParserError},
+    r#as::{NoArgs, Ref, Same},
+    ser::{r#as::CellSerializeAs, CellBuilder, CellBuilderError, CellSerialize},
+    Cell, Error, ResultExt,
+};
+
+use super::{aug::Key, hm_label::HmLabel, Hashmap, HashmapE};
+
+/// [`HashmapE N X`](https://docs.ton.org/develop/data-formats/tl-b-types#hashmap)
+/// with typed keys and values, which does not require any args for
+/// **de**/**ser**ialization, so it can be used as a regular field:
+/// ```rust
+/// # use tlb::{
+/// #     r#as::Data,
+/// #     de::{CellDeserialize, CellParser, CellParserError},
+/// #     ser::{CellBuilder, CellBuilderError, CellSerialize, CellSerializeExt},
+/// # };
+/// # use tlb_ton::hashmap::Dict;
+/// #[derive(Debug, PartialEq)]
+/// struct Balances {
+///     balances: Dict<32, u32, u64, Data>,
+/// }
+///
+/// impl CellSerialize for Balances {
+///     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+///         builder.store(&self.balances)?;
+///         Ok(())
+///     }
+/// }
+///
+/// impl<'de> CellDeserialize<'de> for Balances {
+///     fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+///         Ok(Self {
+///             balances: parser.parse()?,
+///         })
+///     }
+/// }
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut b = Balances {
+///     balances: Dict::new(),
+/// };
+/// b.balances.insert(1, 100);
+/// b.balances.insert(7, 700);
+///
+/// let cell = b.to_cell()?;
+/// let got: Balances = cell.parse_fully()?;
+/// assert_eq!(got.balances.get(&7), Some(&700));
+/// assert_eq!(got, b);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Keys are **de**/**ser**ialized using `KeyAs` adapter and must occupy
+/// exactly `N` bits, while values are **de**/**ser**ialized using `As`
+/// adapter. For example, dictionaries keyed by addresses are
+/// `Dict<267, MsgAddress, V, As, MsgAddressKey>`, see
+/// [`MsgAddressKey`](super::MsgAddressKey).
+#[autoimpl(Debug, Clone, PartialEq, Eq, Hash, Default where K: trait, V: trait)]
+#[autoimpl(Deref using self.m)]
+#[autoimpl(DerefMut using self.m)]
+pub struct Dict<const N: u32, K, V, As: ?Sized = Same, KeyAs = Same> {
+    pub m: BTreeMap<K, V>,
+    _phantom: PhantomData<fn() -> As>,
+    _key: PhantomData<fn() -> KeyAs>,
+}
+
+impl<const N: u32, K, V, As, KeyAs> Dict<N, K, V, As, KeyAs>
+where
+    As: ?Sized,
+{
+    /// Create empty dictionary
+    #[inline]
+    pub const fn new() -> Self {
+        Self::from_map(BTreeMap::new())
+    }
+
+    #[inline]
+    const fn from_map(m: BTreeMap<K, V>) -> Self {
+        Self {
+            m,
+            _phantom: PhantomData,
+            _key: PhantomData,
+        }
+    }
+
+    /// Unwrap inner [`BTreeMap`]
+    #[inline]
+    pub fn into_inner(self) -> BTreeMap<K, V> {
+        self.m
+    }
+}
+
+impl<const N: u32, K, V, As, KeyAs> From<BTreeMap<K, V>> for Dict<N, K, V, As, KeyAs>
+where
+    As: ?Sized,
+{
+    #[inline]
+    fn from(m: BTreeMap<K, V>) -> Self {
+        Self::from_map(m)
+    }
+}
+
+impl<const N: u32, K, V, As, KeyAs> FromIterator<(K, V)> for Dict<N, K, V, As, KeyAs>
+where
+    K: Ord,
+    As: ?Sized,
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::from_map(iter.into_iter().collect())
+    }
+}
+
+impl<const N: u32, K, V, As, KeyAs> IntoIterator for Dict<N, K, V, As, KeyAs>
+where
+    As: ?Sized,
+{
+    type Item = (K, V);
+    type IntoIter = <BTreeMap<K, V> as IntoIterator>::IntoIter;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.m.into_iter()
+    }
+}
+
+impl<const N: u32, K, V, As, KeyAs> CellSerialize for Dict<N, K, V, As, KeyAs>
+where
+    As: CellSerializeAs<V> + ?Sized,
+    KeyAs: BitPackAs<K>,
+{
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        if self.is_empty() {
+            // hme_empty$0
+            builder.pack(false)?;
+            return Ok(());
+        }
+        let mut root = Cell::builder();
+        root.store_as::<_, &NonEmptyDict<N, As, KeyAs>>(&self.m)?;
+        builder
+            // hme_root$1
+            .pack(true)?
+            // root:^(Hashmap n X)
+            .store_as::<_, Ref>(root.into_cell())?;
+        Ok(())
+    }
+}
+
+/// Adapter to **de**/**ser**ialize [`BTreeMap`] as non-empty
+/// [`Hashmap N X`](https://docs.ton.org/develop/data-formats/tl-b-types#hashmap),
+/// i.e. the root of [`Dict`] stored inline without `HashmapE` wrapper.
+/// Keys and values are **de**/**ser**ialized the same way as in [`Dict`].
+/// ```rust
+/// # use std::collections::BTreeMap;
+/// # use tlb::{r#as::Data, Cell};
+/// # use tlb_ton::hashmap::NonEmptyDict;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let m: BTreeMap<u8, u32> = [(1, 100), (7, 700)].into();
+///
+/// let mut builder = Cell::builder();
+/// builder.store_as::<_, &NonEmptyDict<8, Data>>(&m)?;
+/// let cell = builder.into_cell();
+///
+/// let got: BTreeMap<u8, u32> = cell.parse_fully_as::<_, NonEmptyDict<8, Data>>()?;
+/// assert_eq!(got, m);
+///
+/// let mut builder = Cell::builder();
+/// assert!(builder
+///     .store_as::<_, &NonEmptyDict<8, Data>>(&BTreeMap::<u8, u32>::new())
+///     .is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub struct NonEmptyDict<const N: u32, As: ?Sized = Same, KeyAs = Same>(
+    PhantomData<KeyAs>,
+    PhantomData<As>,
+);
+
+impl<const N: u32, K, V, As, KeyAs> CellSerializeAs<BTreeMap<K, V>> for NonEmptyDict<N, As, KeyAs>
+where
+    As: CellSerializeAs<V> + ?Sized,
+    KeyAs: BitPackAs<K>,
+{
+    fn store_as(
+        source: &BTreeMap<K, V>,
+        builder: &mut CellBuilder,
+    ) -> Result<(), CellBuilderError> {
+        if source.is_empty() {
+            return Err(Error::custom("dictionary must not be empty"));
+        }
+        let mut entries = source
+            .iter()
+            .map(|(k, v)| {
+                let key = pack_as::<_, &KeyAs>(k).map_err(Error::custom)?;
+                if key.len() != N as usize {
+                    return Err(Error::custom(format!(
+                        "key must be {N} bits long, got {}",
+                        key.len()
+                    )));
+                }
+                Ok((key, v))
+            })
+            .collect::<Result<Vec<_>, CellBuilderError>>()
+            .context("key")?;
+        // keys might be ordered differently from their bit representation
+        entries.sort_unstable_by(|(l, _), (r, _)| l.cmp(r));
+        store_hashmap::<_, As>(builder, N, &entries, 0)
+    }
+}
+
+impl<'de, const N: u32, K, V, As, KeyAs> CellDeserializeAs<'de, BTreeMap<K, V>>
+    for NonEmptyDict<N, As, KeyAs>
+where
+    K: Ord,
+    As: CellDeserializeAs<'de, V> + ?Sized,
+    KeyAs: BitUnpackAs<K>,
+{
+    fn parse_as(parser: &mut CellParser<'de>) -> Result<BTreeMap<K, V>, CellParserError<'de>> {
+        let entries: Vec<(Key, V)> =
+            parser.parse_as_with::<_, Hashmap<NoArgs<(), As>, ()>>((N, ()))?;
+        parse_entries::<_, _, KeyAs>(entries)
+    }
+}
+
+fn parse_entries<'de, K, V, KeyAs>(
+    entries: Vec<(Key, V)>,
+) -> Result<BTreeMap<K, V>, CellParserError<'de>>
+where
+    K: Ord,
+    KeyAs: BitUnpackAs<K>,
+{
+    entries
+        .into_iter()
+        .map(|(k, v)| {
+            let k = unpack_fully_as::<_, KeyAs>(&k).map_err(Error::custom);
+            Ok((k.context("key")?, v))
+        })
+        .collect()
+}
+
+/// Store non-empty sorted `entries` as `Hashmap n X`, where all keys have
+/// the same first `offset` bits.
+fn store_hashmap<V, As>(
+    builder: &mut CellBuilder,
+    n: u32,
+    entries: &[(Key, &V)],
+    offset: usize,
+) -> Result<(), CellBuilderError>
+where
+    As: CellSerializeAs<V> + ?Sized,
+{
+    let (first, _) = &entries[0];
+    let (last, _) = &entries[entries.len() - 1];
+    let end = offset + n as usize;
+    // common prefix of sorted keys is the one of the first and last
+    let l = first[offset..end]
+        .iter()
+        .zip(&last[offset..end])
+        .take_while(|(f, l)| f == l)
+        .count();
+    builder
+        // label:(HmLabel ~l n)
+        .pack_as_with::<&BitSlice<u8, Msb0>, &HmLabel>(&first[offset..offset + l], n)
+        .context("label")?;
+    // {n = (~m) + l}
+    let m = n - l as u32;
+    if m == 0 {
+        // hmn_leaf#_ value:X
+        return builder.store_as::<_, &As>(entries[0].1).map(|_| ());
+    }
+
+    // hmn_fork#_ left:^(Hashmap n X) right:^(Hashmap n X)
+    let fork = offset + l;
+    let split = entries.partition_point(|(k, _)| !k[fork]);
+    for (i, entries) in [&entries[..split], &entries[split..]]
+        .into_iter()
+        .enumerate()
+    {
+        let mut child = Cell::builder();
+        store_hashmap::<_, As>(&mut child, m - 1, entries, fork + 1)
+            .with_context(|| format!("[{i}]"))?;
+        builder.store_as::<_, Ref>(child.into_cell())?;
+    }
+    Ok(())
+}
+
+impl<'de, const N: u32, K, V, As, KeyAs> CellDeserialize<'de> for Dict<N, K, V, As, KeyAs>
+where
+    K: Ord,
+    As: CellDeserializeAs<'de, V> + ?Sized,
+    KeyAs: BitUnpackAs<K>,
+{
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let entries: Vec<(Key, V)> =
+            parser.parse_as_with::<_, HashmapE<NoArgs<(), As>>>((N, ()))?;
+        parse_entries::<_, _, KeyAs>(entries).map(Self::from_map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::{bits::bitvec::view::AsBits, r#as::Data, ser::CellSerializeExt};
+
+    use crate::hashmap::HashmapE;
+
+    use super::*;
+
+    #[test]
+    fn dict_store_parse() {
+        for n in [0u8, 1, 2, 5, 100] {
+            let dict: Dict<8, u8, u32, Data> =
+                (0..n).map(|i| (i.reverse_bits(), i as u32)).collect();
+            let cell = dict.to_cell().unwrap();
+            let got: Dict<8, u8, u32, Data> = cell.parse_fully().unwrap();
+            assert_eq!(got, dict);
+        }
+    }
+
+    #[test]
+    fn dict_signed_keys() {
+        let dict: Dict<8, i8, ()> = [-128, -1, 0, 1, 127].map(|k| (k, ())).into_iter().collect();
+        let got: Dict<8, i8, ()> = dict.to_cell().unwrap().parse_fully().unwrap();
+        assert_eq!(got, dict);
+    }
+
+    #[test]
+    fn dict_same_as_hashmap_e() {
+        let dict: Dict<16, u16, u8, Data> = [(0x0001, 1), (0x00ff, 2), (0xff00, 3)]
+            .into_iter()
+            .collect();
+        let cell = dict.to_cell().unwrap();
+
+        let got: HashmapE<u8> = cell
+            .parser()
+            .parse_as_with::<_, HashmapE<NoArgs<_, Data>, NoArgs<_>>>((16, (), ()))
+            .unwrap();
+        assert_eq!(got.len(), 3);
+        assert_eq!(got.get(0x00ffu16.to_be_bytes().as_bits()), Some(&2));
+    }
+
+    #[test]
+    fn dict_key_size() {
+        let dict: Dict<7, u8, ()> = [(1, ())].into_iter().collect();
+        assert!(dict.to_cell().is_err());
+    }
+}
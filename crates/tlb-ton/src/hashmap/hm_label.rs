@@ -30,8 +30,9 @@ impl BitPackAsWithArgs<BitSlice<u8, Msb0>> for HmLabel {
     {
         let n = source.len() as u32;
         // {n <= m}
-        // here we check if strictly less as (Unary ~n) needs n+1 bits
-        if n < m {
+        // here we check if strictly less as (Unary ~n) needs n+1 bits,
+        // but empty label is always the shortest as hml_short
+        if n < m || n == 0 {
             writer
                 // hml_short$0
                 .pack(false)?
@@ -42,7 +43,8 @@ impl BitPackAsWithArgs<BitSlice<u8, Msb0>> for HmLabel {
             return Ok(());
         }
 
-        let n_bits = m.ilog2() + 1;
+        // #<= m
+        let n_bits = u32::BITS - m.leading_zeros();
         let v = if source.all() {
             true
         } else if source.not_any() {
@@ -92,7 +94,8 @@ impl BitUnpackAsWithArgs<BitVec<u8, Msb0>> for HmLabel {
                 // hml_long$10
                 false => {
                     // n:(#<= m)
-                    let n: u32 = reader.unpack_as_with::<_, VarNBits>(m.ilog2() + 1)?;
+                    let n: u32 =
+                        reader.unpack_as_with::<_, VarNBits>(u32::BITS - m.leading_zeros())?;
                     // s:(n * Bit)
                     reader.unpack_with(n as usize)
                 }
@@ -101,7 +104,8 @@ impl BitUnpackAsWithArgs<BitVec<u8, Msb0>> for HmLabel {
                     // v:Bit
                     let v: bool = reader.unpack()?;
                     // n:(#<= m)
-                    let n: u32 = reader.unpack_as_with::<_, VarNBits>(m.ilog2() + 1)?;
+                    let n: u32 =
+                        reader.unpack_as_with::<_, VarNBits>(u32::BITS - m.leading_zeros())?;
                     Ok(BitVec::repeat(v, n as usize))
                 }
             },
@@ -0,0 +1,225 @@
+use num_bigint::BigUint;
+use num_traits::{PrimInt, Unsigned};
+use tlb::{
+    bits::{
+        bitvec::{order::Msb0, slice::BitSlice, vec::BitVec},
+        de::{
+            r#as::{unpack_fully_as, BitUnpackAs},
+            BitReader, BitReaderExt,
+        },
+        r#as::VarNBits,
+        ser::{r#as::BitPackAs, BitWriter, BitWriterExt},
+    },
+    Error, ResultExt, StringError,
+};
+
+use crate::{MsgAddress, MsgAddressTag};
+
+/// Helpers to convert raw [`Key`](super::aug::Key)s of hashmaps parsed
+/// into collections like [`HashMap<Key, T>`](std::collections::HashMap)
+/// back to typed values.
+/// ```rust
+/// # use tlb::bits::bitvec::{bits, order::Msb0};
+/// # use tlb_ton::hashmap::KeyExt;
+/// let key = bits![u8, Msb0; 0, 0, 0, 1, 0, 0, 0, 1];
+/// assert_eq!(key.to_uint::<u64>().unwrap(), 17);
+/// assert!(key.to_uint::<u8>().is_ok());
+/// assert!(bits![u8, Msb0; 1; 9].to_uint::<u8>().is_err());
+/// ```
+pub trait KeyExt {
+    /// Interpret the key as big-endian unsigned integer, which must fit
+    /// into `T`
+    fn to_uint<T>(&self) -> Result<T, StringError>
+    where
+        T: PrimInt + Unsigned;
+
+    /// Interpret the key as big-endian unsigned integer of arbitrary length
+    fn to_ubig(&self) -> BigUint;
+
+    /// Interpret the key as `addr_std` [`MsgAddress`] without anycast, i.e.
+    /// as key of `HashmapE 267 X`
+    fn to_msg_address(&self) -> Result<MsgAddress, StringError>;
+}
+
+impl KeyExt for BitSlice<u8, Msb0> {
+    #[inline]
+    fn to_uint<T>(&self) -> Result<T, StringError>
+    where
+        T: PrimInt + Unsigned,
+    {
+        let bits = size_of::<T>() * 8;
+        if self.len() > bits {
+            return Err(Error::custom(format!(
+                "key of {} bits does not fit into {bits} bits",
+                self.len()
+            )));
+        }
+        let mut reader = self;
+        reader.unpack_as_with::<_, VarNBits>(self.len() as u32)
+    }
+
+    #[inline]
+    fn to_ubig(&self) -> BigUint {
+        // pad with leading zeros up to whole bytes
+        let mut bits = BitVec::<u8, Msb0>::repeat(false, (8 - self.len() % 8) % 8);
+        bits.extend_from_bitslice(self);
+        BigUint::from_bytes_be(bits.as_raw_slice())
+    }
+
+    #[inline]
+    fn to_msg_address(&self) -> Result<MsgAddress, StringError> {
+        if self.len() != MsgAddressKey::BITS as usize {
+            return Err(Error::custom(format!(
+                "address key must be {} bits long, got {}",
+                MsgAddressKey::BITS,
+                self.len()
+            )));
+        }
+        unpack_fully_as::<_, MsgAddressKey>(self)
+    }
+}
+
+/// Adapter to **de**/**ser**ialize [`MsgAddress`] as a key of hashmaps
+/// keyed by addresses, e.g. owners of jettons or NFTs, i.e. always in full
+/// `addr_std` form without anycast:
+/// ```tlb
+/// addr_std$10 anycast:(Maybe Anycast) workchain_id:int8 address:bits256
+/// ```
+///
+/// Unlike [`MsgAddress`] itself, [`MsgAddress::NULL`] is stored as
+/// `addr_std` as well, so that all keys have the same length:
+/// ```rust
+/// # use tlb::{r#as::Data, ser::CellSerializeExt};
+/// # use tlb_ton::{hashmap::{Dict, MsgAddressKey}, MsgAddress};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// type Balances = Dict<{ MsgAddressKey::BITS }, MsgAddress, u64, Data, MsgAddressKey>;
+///
+/// let owner: MsgAddress =
+///     "0:167b37b3a6a2936ceeb18c2bacb170dd21e27b5f14dc12b8c0eae0f74f7afb03".parse()?;
+/// let balances: Balances = [(owner, 100), (MsgAddress::NULL, 0)].into_iter().collect();
+///
+/// let got: Balances = balances.to_cell()?.parse_fully()?;
+/// assert_eq!(got.get(&owner), Some(&100));
+/// assert_eq!(got, balances);
+/// # Ok(())
+/// # }
+/// ```
+pub struct MsgAddressKey;
+
+impl MsgAddressKey {
+    /// Length of the key in bits
+    pub const BITS: u32 = 2 + 1 + 8 + 256;
+}
+
+impl BitPackAs<MsgAddress> for MsgAddressKey {
+    #[inline]
+    fn pack_as<W>(source: &MsgAddress, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        let workchain_id: i8 = source.workchain_id.try_into().map_err(|_| {
+            Error::custom(format!(
+                "workchain_id {} does not fit into int8",
+                source.workchain_id
+            ))
+        })?;
+        writer
+            // addr_std$10
+            .pack(MsgAddressTag::Std)?
+            // anycast:(Maybe Anycast)
+            .pack(false)?
+            // workchain_id:int8
+            .pack(workchain_id)?
+            // address:bits256
+            .pack(source.address)?;
+        Ok(())
+    }
+}
+
+impl BitUnpackAs<MsgAddress> for MsgAddressKey {
+    #[inline]
+    fn unpack_as<R>(mut reader: R) -> Result<MsgAddress, R::Error>
+    where
+        R: BitReader,
+    {
+        // addr_std$10
+        let tag: MsgAddressTag = reader.unpack()?;
+        if !matches!(tag, MsgAddressTag::Std) {
+            return Err(Error::custom(format!(
+                "address key must be addr_std$10, got {tag}"
+            )));
+        }
+        // anycast:(Maybe Anycast)
+        if reader.unpack::<bool>().context("anycast")? {
+            return Err(Error::custom("address key must not have anycast"));
+        }
+        Ok(MsgAddress {
+            // workchain_id:int8
+            workchain_id: reader.unpack::<i8>().context("workchain_id")?.into(),
+            // address:bits256
+            address: reader.unpack().context("address")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::bits::{
+        bitvec::view::AsBits,
+        ser::{pack, r#as::pack_as},
+    };
+
+    use super::*;
+
+    #[test]
+    fn key_to_uint() {
+        let key = 0x1234_5678_9abc_def0u64.to_be_bytes();
+        assert_eq!(
+            key.as_bits().to_uint::<u64>().unwrap(),
+            0x1234_5678_9abc_def0
+        );
+        assert_eq!(
+            key.as_bits().to_uint::<u128>().unwrap(),
+            0x1234_5678_9abc_def0
+        );
+        assert!(key.as_bits().to_uint::<u32>().is_err());
+        assert_eq!(key.as_bits()[60..].to_uint::<u8>().unwrap(), 0);
+    }
+
+    #[test]
+    fn key_to_ubig() {
+        let key = [0xffu8; 40];
+        assert_eq!(
+            key.as_bits()[3..].to_ubig(),
+            (BigUint::from(1u8) << 317) - 1u8
+        );
+        assert_eq!(BitSlice::<u8, Msb0>::empty().to_ubig(), BigUint::ZERO);
+    }
+
+    #[test]
+    fn key_to_msg_address() {
+        let addr = MsgAddress {
+            workchain_id: -1,
+            address: [0x42; 32],
+        };
+        let key = pack(addr).unwrap();
+        assert_eq!(key.to_msg_address().unwrap(), addr);
+        assert!(key[..266].to_msg_address().is_err());
+    }
+
+    #[test]
+    fn msg_address_key() {
+        let key = pack_as::<_, MsgAddressKey>(MsgAddress::NULL).unwrap();
+        assert_eq!(key.len(), MsgAddressKey::BITS as usize);
+        assert_eq!(key.to_msg_address().unwrap(), MsgAddress::NULL);
+
+        assert!(pack_as::<_, MsgAddressKey>(MsgAddress {
+            workchain_id: 128,
+            address: [0; 32],
+        })
+        .is_err());
+
+        // addr_none$00
+        assert!(pack(MsgAddress::NULL).unwrap().to_msg_address().is_err());
+    }
+}
@@ -1,5 +1,9 @@
 //! Collection of hashmap-like **de**/**ser**ializable data structures
 pub mod aug;
 pub use aug::{Hashmap, HashmapE, HashmapNode};
+mod dict;
+pub use dict::*;
 mod hm_label;
+mod key;
+pub use key::*;
 pub mod pfx;
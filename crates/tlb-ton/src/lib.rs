@@ -1,11 +1,17 @@
 #![doc = include_str!("../README.md")]
+pub mod account;
+pub mod action;
 mod address;
 pub mod bin_tree;
+pub mod block;
 pub mod boc;
+pub mod config;
 pub mod currency;
 pub mod hashmap;
+pub mod library;
 pub mod message;
 pub mod state_init;
 mod timestamp;
+pub mod trace;
 
 pub use self::{address::*, timestamp::*};
@@ -0,0 +1,149 @@
+//! Shared libraries published in masterchain, which are referred to by
+//! `LibraryReference` cells in code of contracts
+use std::collections::{BTreeMap, BTreeSet};
+
+use tlb::{
+    bits::ser::BitWriterExt,
+    de::{r#as::CellDeserializeAs, CellDeserialize, CellParser, CellParserError},
+    r#as::Ref,
+    ser::{r#as::CellSerializeAs, CellBuilder, CellBuilderError, CellSerialize},
+    tag::Tag,
+    Cell, ResultExt,
+};
+
+use crate::hashmap::{Dict, NonEmptyDict};
+
+/// Libraries dictionary from masterchain state keyed by hashes of their
+/// root cells:
+/// ```tlb
+/// libraries:(HashmapE 256 LibDescr)
+/// ```
+///
+/// `LibraryReference` cell only stores the hash of library root cell,
+/// which is resolved with [`LibraryProvider`]:
+/// ```rust
+/// # use tlb::{ser::CellSerializeExt, Cell};
+/// # use tlb_ton::library::{LibDescr, Libraries, LibraryProvider};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let lib = Cell::from_hex_data("deadbeef", 32)?;
+/// let libraries: Libraries = [(
+///     lib.hash(),
+///     LibDescr {
+///         lib: lib.clone(),
+///         publishers: [[1; 32]].into(),
+///     },
+/// )]
+/// .into_iter()
+/// .collect();
+///
+/// let libraries: Libraries = libraries.to_cell()?.parse_fully()?;
+/// assert_eq!(libraries.library(&lib.hash()), Some(&lib));
+/// # Ok(())
+/// # }
+/// ```
+pub type Libraries = Dict<256, [u8; 32], LibDescr>;
+
+/// Source of shared libraries to resolve `LibraryReference` cells by
+/// hash of library root cell
+pub trait LibraryProvider {
+    /// Returns root cell of the library with given hash, if known
+    fn library(&self, hash: &[u8; 32]) -> Option<&Cell>;
+}
+
+impl LibraryProvider for Libraries {
+    #[inline]
+    fn library(&self, hash: &[u8; 32]) -> Option<&Cell> {
+        self.get(hash).map(|descr| &descr.lib)
+    }
+}
+
+/// Description of shared library
+/// ```tlb
+/// shared_lib_descr$00 lib:^Cell publishers:(Hashmap 256 True) = LibDescr;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibDescr {
+    /// Root cell of the library
+    pub lib: Cell,
+    /// Address hashes of masterchain accounts that published the library,
+    /// must not be empty
+    pub publishers: BTreeSet<[u8; 32]>,
+}
+
+impl LibDescr {
+    const TAG: Tag = Tag::parse("$00");
+}
+
+impl CellSerialize for LibDescr {
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .pack(Self::TAG)?
+            .store_as::<_, Ref>(&self.lib)
+            .context("lib")?
+            .store_as::<_, &Publishers>(&self.publishers)
+            .context("publishers")?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for LibDescr {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Tag::unpack_one_of(&mut *parser, &[Self::TAG])?;
+        Ok(Self {
+            lib: parser.parse_as::<_, Ref>().context("lib")?,
+            publishers: parser.parse_as::<_, Publishers>().context("publishers")?,
+        })
+    }
+}
+
+/// Adapter for non-empty set of publishers:
+/// ```tlb
+/// publishers:(Hashmap 256 True)
+/// ```
+struct Publishers;
+
+impl CellSerializeAs<BTreeSet<[u8; 32]>> for Publishers {
+    fn store_as(
+        source: &BTreeSet<[u8; 32]>,
+        builder: &mut CellBuilder,
+    ) -> Result<(), CellBuilderError> {
+        let publishers: BTreeMap<_, _> = source.iter().map(|publisher| (*publisher, ())).collect();
+        builder.store_as::<_, &NonEmptyDict<256>>(&publishers)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserializeAs<'de, BTreeSet<[u8; 32]>> for Publishers {
+    #[inline]
+    fn parse_as(parser: &mut CellParser<'de>) -> Result<BTreeSet<[u8; 32]>, CellParserError<'de>> {
+        let publishers: BTreeMap<_, ()> = parser.parse_as::<_, NonEmptyDict<256>>()?;
+        Ok(publishers.into_keys().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlb::ser::CellSerializeExt;
+
+    use super::*;
+
+    #[test]
+    fn lib_descr_serde() {
+        let descr = LibDescr {
+            lib: Cell::from_hex_data("deadbeef", 32).unwrap(),
+            publishers: [[0; 32], [1; 32], [0xff; 32]].into(),
+        };
+        let cell = descr.to_cell().unwrap();
+        let got: LibDescr = cell.parse_fully().unwrap();
+        assert_eq!(got, descr);
+    }
+
+    #[test]
+    fn lib_descr_no_publishers() {
+        let descr = LibDescr {
+            lib: Cell::new(),
+            publishers: BTreeSet::new(),
+        };
+        assert!(descr.to_cell().is_err());
+    }
+}
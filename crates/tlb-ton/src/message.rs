@@ -1,24 +1,31 @@
 //! Collection of typs related to [Message](https://docs.ton.org/develop/data-formats/msg-tlb#message-tl-b)
-use chrono::{DateTime, Utc};
+use core::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+use std::sync::Arc;
+
+use impl_tools::autoimpl;
 use num_bigint::BigUint;
 use tlb::{
     bits::{
         de::{BitReader, BitReaderExt, BitUnpack},
-        r#as::NBits,
+        integer::ConstU32,
         ser::{BitPack, BitWriter, BitWriterExt},
     },
     de::{CellDeserialize, CellParser, CellParserError},
     either::Either,
-    r#as::{DefaultOnNone, Ref, Same},
+    r#as::{Data, Ref, Same},
     ser::{CellBuilder, CellBuilderError, CellSerialize, CellSerializeExt},
-    Cell,
+    tag_dispatch, Cell, CellType, Error, ResultExt, StringError,
 };
 
 use crate::{
+    boc::{BagOfCells, BagOfCellsArgs},
     currency::{CurrencyCollection, ExtraCurrencyCollection, Grams},
     hashmap::HashmapE,
     state_init::StateInit,
-    MsgAddress, UnixTimestamp,
+    MsgAddress, Timestamp, UnixTimestamp,
 };
 
 /// [Message](https://docs.ton.org/develop/data-formats/msg-tlb#message-tl-b)
@@ -27,8 +34,11 @@ use crate::{
 /// init:(Maybe (Either StateInit ^StateInit))
 /// body:(Either X ^X) = Message X;
 /// ```
+///
+/// Body is kept as [`RawBody`] by default, so that messages of unknown
+/// contracts can be decoded and forwarded untouched.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Message<T = Cell, IC = Cell, ID = Cell> {
+pub struct Message<T = RawBody, IC = Cell, ID = Cell> {
     pub info: CommonMsgInfo,
     pub init: Option<StateInit<IC, ID>>,
     pub body: T,
@@ -45,13 +55,235 @@ where
         self
     }
 
-    pub fn normalize(&self) -> Result<Message, CellBuilderError> {
+    /// Normalize the message with default [`NormalizeOptions`], i.e.
+    /// convert its body and [`init`](Message::init) to cells as-is
+    #[inline]
+    pub fn normalize(&self) -> Result<Message<Cell>, CellBuilderError> {
+        self.normalize_with(NormalizeOptions::default())
+    }
+
+    /// Normalize the message with given [`NormalizeOptions`], since
+    /// different APIs expect slightly different normalized forms, e.g.
+    /// when looking up messages by hash:
+    /// ```rust
+    /// # use tlb::ser::CellSerializeExt;
+    /// # use tlb_ton::{
+    /// #     message::{CommonMsgInfo, ExternalInMsgInfo, Message, NormalizeOptions},
+    /// #     state_init::StateInit,
+    /// #     MsgAddress,
+    /// # };
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let msg = Message::<(), (), ()> {
+    ///     info: CommonMsgInfo::ExternalIn(ExternalInMsgInfo {
+    ///         src: MsgAddress::NULL,
+    ///         dst: MsgAddress::NULL,
+    ///         import_fee: 1_000u64.into(),
+    ///     }),
+    ///     init: Some(StateInit::default()),
+    ///     body: (),
+    /// };
+    /// let options = NormalizeOptions {
+    ///     drop_init: true,
+    ///     zero_import_fee: true,
+    ///     ..Default::default()
+    /// };
+    /// assert!(msg.normalize_with(options)?.init.is_none());
+    ///
+    /// // refuse to lose present init and non-zero import fee
+    /// assert!(msg
+    ///     .normalize_with(NormalizeOptions {
+    ///         error_on_lossy: true,
+    ///         ..options
+    ///     })
+    ///     .is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn normalize_with(
+        &self,
+        options: NormalizeOptions,
+    ) -> Result<Message<Cell>, CellBuilderError> {
+        let mut info = self.info.clone();
+        if options.zero_import_fee {
+            if let CommonMsgInfo::ExternalIn(info) = &mut info {
+                if options.error_on_lossy && info.import_fee != BigUint::ZERO {
+                    return Err(Error::custom(format!(
+                        "import_fee: {} would be lost",
+                        info.import_fee
+                    )));
+                }
+                info.import_fee = BigUint::ZERO;
+            }
+        }
+        if options.drop_init && options.error_on_lossy && self.init.is_some() {
+            return Err(Error::custom("init would be lost"));
+        }
         Ok(Message {
-            info: self.info.clone(),
-            init: self.init.as_ref().map(StateInit::normalize).transpose()?,
+            info,
+            init: self
+                .init
+                .as_ref()
+                .filter(|_| !options.drop_init)
+                .map(StateInit::normalize)
+                .transpose()?,
             body: self.body.to_cell()?,
         })
     }
+
+    /// Serialize [normalized](Message::normalize_with) message, where
+    /// [`body_in_ref`](NormalizeOptions::body_in_ref) is also respected:
+    /// ```rust
+    /// # use tlb::ser::CellSerializeExt;
+    /// # use tlb_ton::{message::{Message, NormalizeOptions}, MsgAddress};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let msg = Message::<()>::transfer(MsgAddress::NULL, 1_000u64.into(), false);
+    /// // same as serializing the message itself
+    /// assert_eq!(msg.to_normalized_cell(NormalizeOptions::default())?, msg.to_cell()?);
+    ///
+    /// let cell = msg.to_normalized_cell(NormalizeOptions {
+    ///     body_in_ref: true,
+    ///     ..Default::default()
+    /// })?;
+    /// assert_eq!(cell.references.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_normalized_cell(&self, options: NormalizeOptions) -> Result<Cell, CellBuilderError> {
+        let msg = self.normalize_with(options)?;
+        let mut builder = Cell::builder();
+        Message::store_parts(
+            &mut builder,
+            &msg.info,
+            msg.init.as_ref(),
+            &msg.body,
+            options.body_in_ref,
+        )?;
+        Ok(builder.into_cell())
+    }
+
+    fn store_parts(
+        builder: &mut CellBuilder,
+        info: &CommonMsgInfo,
+        init: Option<&StateInit<IC, ID>>,
+        body: &T,
+        body_in_ref: bool,
+    ) -> Result<(), CellBuilderError> {
+        builder
+            .store(info)?
+            .store_as::<_, Option<Either<(), Ref>>>(init.map(Some))?
+            .store_as::<_, Either<(), Ref>>(
+                Some(body.to_cell()?)
+                    // store empty cell inline, unless asked otherwise
+                    .filter(|cell| body_in_ref || !cell.is_empty()),
+            )?;
+        Ok(())
+    }
+}
+
+/// Options for [`Message::normalize_with()`], where default ones produce
+/// the same cell as serializing the message itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NormalizeOptions {
+    /// Drop [`init`](Message::init)
+    pub drop_init: bool,
+    /// Store [`body`](Message::body) in a reference even if it's empty,
+    /// while non-empty bodies are always stored in a reference
+    pub body_in_ref: bool,
+    /// Set [`import_fee`](ExternalInMsgInfo::import_fee) of external-in
+    /// messages to zero
+    pub zero_import_fee: bool,
+    /// Return an error instead of dropping present [`init`](Message::init)
+    /// or zeroing non-zero [`import_fee`](ExternalInMsgInfo::import_fee)
+    pub error_on_lossy: bool,
+}
+
+impl<T, IC, ID> Message<T, IC, ID> {
+    /// Map [`body`](Message::body) by given closure, preserving
+    /// [`info`](Message::info) and [`init`](Message::init).
+    ///
+    /// ```rust
+    /// # use tlb::Cell;
+    /// # use tlb_ton::message::Message;
+    /// # let msg = Message::<()>::transfer(Default::default(), Default::default(), false);
+    /// let msg: Message<Cell> = msg.map_body(|()| Cell::new());
+    /// ```
+    #[inline]
+    pub fn map_body<U>(self, f: impl FnOnce(T) -> U) -> Message<U, IC, ID> {
+        Message {
+            info: self.info,
+            init: self.init,
+            body: f(self.body),
+        }
+    }
+
+    /// Fallible version of [`.map_body()`](Message::map_body), useful for
+    /// parsing raw bodies into typed ones:
+    ///
+    /// ```rust
+    /// # use tlb::{de::CellParserError, Cell};
+    /// # use tlb_ton::message::Message;
+    /// # fn main() -> Result<(), CellParserError<'static>> {
+    /// # let msg = Message::<()>::transfer(Default::default(), Default::default(), false)
+    /// #     .map_body(|()| Cell::new());
+    /// let msg: Message<()> = msg.try_map_body(|body| body.parse_fully())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn try_map_body<U, E>(
+        self,
+        f: impl FnOnce(T) -> Result<U, E>,
+    ) -> Result<Message<U, IC, ID>, E> {
+        Ok(Message {
+            info: self.info,
+            init: self.init,
+            body: f(self.body)?,
+        })
+    }
+
+    /// Map [`init`](Message::init) by given closure if present, preserving
+    /// [`info`](Message::info) and [`body`](Message::body).
+    #[inline]
+    pub fn map_init<IC2, ID2>(
+        self,
+        f: impl FnOnce(StateInit<IC, ID>) -> StateInit<IC2, ID2>,
+    ) -> Message<T, IC2, ID2> {
+        Message {
+            info: self.info,
+            init: self.init.map(f),
+            body: self.body,
+        }
+    }
+
+    /// Fallible version of [`.map_init()`](Message::map_init)
+    #[inline]
+    pub fn try_map_init<IC2, ID2, E>(
+        self,
+        f: impl FnOnce(StateInit<IC, ID>) -> Result<StateInit<IC2, ID2>, E>,
+    ) -> Result<Message<T, IC2, ID2>, E> {
+        Ok(Message {
+            info: self.info,
+            init: self.init.map(f).transpose()?,
+            body: self.body,
+        })
+    }
+}
+
+impl<IC, ID> Message<Cell, IC, ID> {
+    /// Leading 32-bit `op` of the [`body`](Message::body), if it has at
+    /// least 32 data bits
+    #[inline]
+    pub fn body_opcode(&self) -> Option<u32> {
+        self.body.peek_uint(32).map(|op| op as u32)
+    }
+}
+
+impl<IC, ID> Message<RawBody, IC, ID> {
+    /// See [`Message::body_opcode()`](Message<Cell, IC, ID>::body_opcode)
+    #[inline]
+    pub fn body_opcode(&self) -> Option<u32> {
+        self.body.peek_uint(32).map(|op| op as u32)
+    }
 }
 
 impl Message<()> {
@@ -73,15 +305,7 @@ where
     ID: CellSerialize,
 {
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
-        builder
-            .store(&self.info)?
-            .store_as::<_, Option<Either<(), Ref>>>(self.init.as_ref().map(Some))?
-            .store_as::<_, Either<(), Ref>>(
-                Some(self.body.to_cell()?)
-                    // store empty cell inline
-                    .filter(|cell| !cell.is_empty()),
-            )?;
-        Ok(())
+        Self::store_parts(builder, &self.info, self.init.as_ref(), &self.body, false)
     }
 }
 
@@ -104,6 +328,99 @@ where
     }
 }
 
+/// Body of [`Message`] passed through as-is, which is shared rather than
+/// copied when it's stored in a reference.
+///
+/// It's [displayed](Display) as BoC, which is base64-encoded if `base64`
+/// feature is enabled (default) or hex-encoded otherwise, and can be
+/// [parsed](FromStr) back:
+/// ```rust
+/// # use tlb::Cell;
+/// # use tlb_ton::message::RawBody;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let body = RawBody::from(Cell::from_hex_data("cafe", 16)?);
+/// # #[cfg(feature = "base64")]
+/// assert_eq!(body.to_string(), "te6cckEBAQEABAAABMr+KojUJA==");
+/// assert_eq!(body.to_string().parse::<RawBody>()?, body);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[autoimpl(Deref using self.0)]
+pub struct RawBody(pub Arc<Cell>);
+
+impl RawBody {
+    /// Arguments of BoC it's displayed as
+    const BOC_ARGS: BagOfCellsArgs = BagOfCellsArgs::new(false, cfg!(feature = "crc"));
+
+    /// Unwrap the cell
+    #[inline]
+    pub fn into_inner(self) -> Arc<Cell> {
+        self.0
+    }
+}
+
+impl From<Arc<Cell>> for RawBody {
+    #[inline]
+    fn from(cell: Arc<Cell>) -> Self {
+        Self(cell)
+    }
+}
+
+impl From<Cell> for RawBody {
+    #[inline]
+    fn from(cell: Cell) -> Self {
+        Self(cell.into())
+    }
+}
+
+impl CellSerialize for RawBody {
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder.store(&*self.0)?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserialize<'de> for RawBody {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        // borrow the whole cell if it's a reference, otherwise copy the rest
+        // of inline body
+        parser
+            .parse::<&Arc<Cell>>()
+            .cloned()
+            .or_else(|_| parser.parse::<Cell>().map(Arc::new))
+            .map(Self)
+    }
+}
+
+impl Display for RawBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let boc = BagOfCells::from_root(self.0.clone());
+        #[cfg(feature = "base64")]
+        let s = boc.to_base64(Self::BOC_ARGS);
+        #[cfg(not(feature = "base64"))]
+        let s = boc.to_hex(Self::BOC_ARGS);
+        f.write_str(&s.map_err(|_| fmt::Error)?)
+    }
+}
+
+impl FromStr for RawBody {
+    type Err = StringError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        #[cfg(feature = "base64")]
+        let boc = BagOfCells::parse_base64(s)?;
+        #[cfg(not(feature = "base64"))]
+        let boc = BagOfCells::parse_hex(s)?;
+        boc.single_root()
+            .cloned()
+            .map(Self)
+            .ok_or_else(|| Error::custom("single root expected"))
+    }
+}
+
 /// `info` field for [`Message`]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CommonMsgInfo {
@@ -130,38 +447,14 @@ impl CommonMsgInfo {
     }
 }
 
-impl CellSerialize for CommonMsgInfo {
-    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
-        match self {
-            Self::Internal(msg) => builder
-                // int_msg_info$0
-                .pack(false)?
-                .store(msg)?,
-            Self::ExternalIn(msg) => builder
-                // ext_in_msg_info$10
-                .pack_as::<_, NBits<2>>(0b10)?
-                .pack(msg)?,
-            Self::ExternalOut(msg) => builder
-                // ext_out_msg_info$11
-                .pack_as::<_, NBits<2>>(0b11)?
-                .pack(msg)?,
-        };
-        Ok(())
-    }
-}
-
-impl<'de> CellDeserialize<'de> for CommonMsgInfo {
-    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
-        match parser.unpack()? {
-            // int_msg_info$0
-            false => Ok(Self::Internal(parser.parse()?)),
-            true => match parser.unpack()? {
-                // ext_in_msg_info$10
-                false => Ok(Self::ExternalIn(parser.unpack()?)),
-                // ext_out_msg_info$11
-                true => Ok(Self::ExternalOut(parser.unpack()?)),
-            },
-        }
+tag_dispatch! {
+    CommonMsgInfo {
+        // int_msg_info$0
+        "$0" => Internal,
+        // ext_in_msg_info$10
+        "$10" => ExternalIn as Data,
+        // ext_out_msg_info$11
+        "$11" => ExternalOut as Data,
     }
 }
 
@@ -194,7 +487,7 @@ pub struct InternalMsgInfo {
     /// Logic time of sending message assigned by validator. Using for odering actions in smart contract.
     pub created_lt: u64,
     /// Unix time
-    pub created_at: Option<DateTime<Utc>>,
+    pub created_at: Option<Timestamp>,
 }
 
 impl InternalMsgInfo {
@@ -230,7 +523,7 @@ impl CellSerialize for InternalMsgInfo {
             .pack_as::<_, &Grams>(&self.ihr_fee)?
             .pack_as::<_, &Grams>(&self.fwd_fee)?
             .pack(self.created_lt)?
-            .pack_as::<_, DefaultOnNone<UnixTimestamp>>(self.created_at)?;
+            .pack_as::<_, UnixTimestamp>(self.created_at.unwrap_or(Timestamp::UNIX_EPOCH))?;
         Ok(())
     }
 }
@@ -248,7 +541,7 @@ impl<'de> CellDeserialize<'de> for InternalMsgInfo {
             fwd_fee: parser.unpack_as::<_, Grams>()?,
             created_lt: parser.unpack()?,
             created_at: Some(parser.unpack_as::<_, UnixTimestamp>()?)
-                .filter(|dt| *dt != DateTime::UNIX_EPOCH),
+                .filter(|t| *t != Timestamp::UNIX_EPOCH),
         })
     }
 }
@@ -301,7 +594,7 @@ pub struct ExternalOutMsgInfo {
     pub src: MsgAddress,
     pub dst: MsgAddress,
     pub created_lt: u64,
-    pub created_at: DateTime<Utc>,
+    pub created_at: Timestamp,
 }
 
 impl BitPack for ExternalOutMsgInfo {
@@ -332,12 +625,104 @@ impl BitUnpack for ExternalOutMsgInfo {
     }
 }
 
+/// Body of the [bounced](InternalMsgInfo::bounced) message.
+///
+/// When an internal message bounces, its body is replaced with `0xffffffff`
+/// prefix followed by the first 256 bits of the original body (without
+/// references):
+/// ```tlb
+/// bounced#ffffffff original:(truncated X) = BouncedBody X;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BouncedBody<T = Cell> {
+    /// Truncated body of the original message
+    pub original: T,
+}
+
+impl BouncedBody {
+    /// Number of bits of the original body kept in bounced message
+    pub const ORIGINAL_BITS: usize = 256;
+
+    /// Construct bounced body from the original body in the same way as
+    /// TVM does: keep only first [`ORIGINAL_BITS`](BouncedBody::ORIGINAL_BITS)
+    /// and no references.
+    #[inline]
+    pub fn from_original(body: &Cell) -> Self {
+        Self {
+            original: Cell {
+                r#type: CellType::Ordinary,
+                data: body.data[..body.data.len().min(Self::ORIGINAL_BITS)].to_bitvec(),
+                references: Vec::new(),
+            },
+        }
+    }
+
+    /// `op` of the original message, if present
+    #[inline]
+    pub fn op(&self) -> Option<u32> {
+        self.original.peek_uint(32).map(|op| op as u32)
+    }
+
+    /// `query_id` of the original message, if present
+    #[inline]
+    pub fn query_id(&self) -> Option<u64> {
+        self.original.data.get(32..96)?.unpack().ok()
+    }
+}
+
+const BOUNCED_BODY_TAG: u32 = 0xffffffff;
+
+impl<T> CellSerialize for BouncedBody<T>
+where
+    T: CellSerialize,
+{
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            // bounced#ffffffff
+            .pack(BOUNCED_BODY_TAG)?
+            .store(&self.original)?;
+        Ok(())
+    }
+}
+
+impl<'de, T> CellDeserialize<'de> for BouncedBody<T>
+where
+    T: CellDeserialize<'de>,
+{
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        // bounced#ffffffff
+        parser.unpack::<ConstU32<BOUNCED_BODY_TAG>>()?;
+        Ok(Self {
+            original: parser.parse().context("original")?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use tlb::ser::CellSerializeExt;
+    #[cfg(feature = "chrono")]
+    use chrono::DateTime;
+    use tlb::{error::ParseError, ser::CellSerializeExt};
 
     use super::*;
 
+    #[test]
+    fn body_opcode() {
+        let msg = Message::<()>::transfer(MsgAddress::NULL, BigUint::ZERO, false);
+        assert_eq!(msg.clone().map_body(|()| Cell::new()).body_opcode(), None);
+        assert_eq!(
+            msg.map_body(|()| Cell {
+                r#type: CellType::Ordinary,
+                data: tlb::bits::ser::pack(0x7362d09cu32).unwrap(),
+                references: Vec::new(),
+            })
+            .body_opcode(),
+            Some(0x7362d09c)
+        );
+    }
+
     #[test]
     fn message_serde() {
         let msg = Message::<(), (), ()> {
@@ -363,6 +748,200 @@ mod tests {
         assert_eq!(got, msg);
     }
 
+    #[test]
+    fn raw_body_pass_through() {
+        let msg = Message::<()>::transfer(MsgAddress::NULL, BigUint::ZERO, false);
+        let body = Cell::from_hex_data("cafe", 16).unwrap();
+        let cell = msg.clone().map_body(|()| body.clone()).to_cell().unwrap();
+
+        // body is stored in a reference and shared
+        let got: Message = cell.parse_fully().unwrap();
+        assert!(Arc::ptr_eq(&got.body, &cell.references[0]));
+        assert_eq!(got.to_cell().unwrap(), cell);
+
+        // inline body
+        let cell = msg.map_body(|()| Cell::new()).to_cell().unwrap();
+        let got: Message = cell.parse_fully().unwrap();
+        assert!(got.body.is_empty());
+        assert_eq!(got.to_cell().unwrap(), cell);
+    }
+
+    #[test]
+    fn to_normalized_cell() {
+        let msg = Message::<(), (), ()> {
+            info: CommonMsgInfo::ExternalIn(ExternalInMsgInfo {
+                src: MsgAddress::NULL,
+                dst: MsgAddress::NULL,
+                import_fee: 1_000u64.into(),
+            }),
+            init: Some(StateInit::default()),
+            body: (),
+        };
+        let normalized = |options| -> Message {
+            msg.to_normalized_cell(options)
+                .unwrap()
+                .parse_fully()
+                .unwrap()
+        };
+
+        let got = normalized(NormalizeOptions::default());
+        assert!(got.init.is_some());
+        assert_eq!(
+            msg.to_normalized_cell(NormalizeOptions::default()).unwrap(),
+            msg.to_cell().unwrap()
+        );
+
+        let got = normalized(NormalizeOptions {
+            drop_init: true,
+            ..Default::default()
+        });
+        assert!(got.init.is_none());
+
+        let cell = msg
+            .to_normalized_cell(NormalizeOptions {
+                drop_init: true,
+                body_in_ref: true,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(cell.references.len(), 1);
+        assert!(cell.references[0].is_empty());
+
+        let got = normalized(NormalizeOptions {
+            zero_import_fee: true,
+            ..Default::default()
+        });
+        let CommonMsgInfo::ExternalIn(info) = got.info else {
+            panic!("expected external-in");
+        };
+        assert_eq!(info.import_fee, BigUint::ZERO);
+    }
+
+    #[test]
+    fn normalize_error_on_lossy() {
+        let mut msg = Message::<(), (), ()> {
+            info: CommonMsgInfo::ExternalIn(ExternalInMsgInfo {
+                src: MsgAddress::NULL,
+                dst: MsgAddress::NULL,
+                import_fee: 1_000u64.into(),
+            }),
+            init: Some(StateInit::default()),
+            body: (),
+        };
+        for (options, lossy) in [
+            (NormalizeOptions::default(), false),
+            (
+                NormalizeOptions {
+                    drop_init: true,
+                    ..Default::default()
+                },
+                true,
+            ),
+            (
+                NormalizeOptions {
+                    zero_import_fee: true,
+                    ..Default::default()
+                },
+                true,
+            ),
+            (
+                NormalizeOptions {
+                    body_in_ref: true,
+                    ..Default::default()
+                },
+                false,
+            ),
+        ] {
+            let strict = NormalizeOptions {
+                error_on_lossy: true,
+                ..options
+            };
+            assert!(msg.normalize_with(options).is_ok(), "{options:?}");
+            assert_eq!(msg.normalize_with(strict).is_err(), lossy, "{options:?}");
+            assert_eq!(
+                msg.to_normalized_cell(strict).is_err(),
+                lossy,
+                "{options:?}"
+            );
+        }
+
+        // nothing is lost
+        msg.init = None;
+        let CommonMsgInfo::ExternalIn(info) = &mut msg.info else {
+            unreachable!()
+        };
+        info.import_fee = BigUint::ZERO;
+        msg.normalize_with(NormalizeOptions {
+            drop_init: true,
+            zero_import_fee: true,
+            error_on_lossy: true,
+            ..Default::default()
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn message_try_map_body() {
+        let msg = Message::<(), (), ()> {
+            info: CommonMsgInfo::transfer(MsgAddress::NULL, BigUint::ZERO, true),
+            init: Some(StateInit {
+                code: Some(()),
+                data: Some(()),
+                ..Default::default()
+            }),
+            body: (),
+        };
+
+        let got: Message<(), (), ()> = msg
+            .normalize()
+            .unwrap()
+            .try_map_body(|body| body.parse_fully())
+            .unwrap()
+            .try_map_init(|init| -> Result<_, ParseError> {
+                Ok(StateInit {
+                    split_depth: init.split_depth,
+                    special: init.special,
+                    code: init.code.map(|c| c.parse_fully()).transpose()?,
+                    data: init.data.map(|d| d.parse_fully()).transpose()?,
+                    library: init.library,
+                })
+            })
+            .unwrap();
+
+        assert_eq!(got, msg);
+    }
+
+    #[test]
+    fn bounced_body() {
+        let mut builder = Cell::builder();
+        builder
+            .pack(0x0f8a7ea5u32)
+            .unwrap()
+            .pack(42u64)
+            .unwrap()
+            .pack([0xffu8; 32])
+            .unwrap()
+            .store_as::<_, Ref>(())
+            .unwrap();
+        let original = builder.into_cell();
+
+        let bounced = BouncedBody::from_original(&original).to_cell().unwrap();
+        assert_eq!(bounced.data.len(), 32 + BouncedBody::ORIGINAL_BITS);
+        assert!(bounced.references.is_empty());
+
+        let got: BouncedBody = bounced.parse_fully().unwrap();
+        assert_eq!(got.op(), Some(0x0f8a7ea5));
+        assert_eq!(got.query_id(), Some(42));
+    }
+
+    #[test]
+    fn bounced_body_invalid_tag() {
+        let mut builder = Cell::builder();
+        builder.pack(0u32).unwrap();
+        let cell = builder.into_cell();
+        assert!(cell.parse_fully::<BouncedBody>().is_err());
+    }
+
     #[test]
     fn internal_msg_info_serde() {
         let info = CommonMsgInfo::Internal(InternalMsgInfo {
@@ -398,6 +977,7 @@ mod tests {
         assert_eq!(got, info);
     }
 
+    #[cfg(feature = "chrono")]
     #[test]
     fn external_out_msg_info_serde() {
         let info = CommonMsgInfo::ExternalOut(ExternalOutMsgInfo {
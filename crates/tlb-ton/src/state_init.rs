@@ -7,7 +7,7 @@ use tlb::{
         ser::{BitPack, BitWriter, BitWriterExt},
     },
     de::{CellDeserialize, CellParser, CellParserError},
-    r#as::{NoArgs, ParseFully, Ref},
+    r#as::{MaybeRef, NoArgs, ParseFully, Ref},
     ser::{CellBuilder, CellBuilderError, CellSerialize, CellSerializeExt},
     Cell,
 };
@@ -68,9 +68,9 @@ where
             // special:(Maybe TickTock)
             .pack(self.special)?
             // code:(Maybe ^Cell)
-            .store_as::<_, Option<Ref>>(self.code.as_ref())?
+            .store_as::<_, MaybeRef>(self.code.as_ref())?
             // data:(Maybe ^Cell)
-            .store_as::<_, Option<Ref>>(self.data.as_ref())?
+            .store_as::<_, MaybeRef>(self.data.as_ref())?
             // library:(HashmapE 256 SimpleLib)
             .store_as_with::<_, &HashmapE<NoArgs<_>, NoArgs<_>>>(&self.library, (256, (), ()))?;
         Ok(())
@@ -90,9 +90,9 @@ where
             // special:(Maybe TickTock)
             special: parser.unpack()?,
             // code:(Maybe ^Cell)
-            code: parser.parse_as::<_, Option<Ref<ParseFully>>>()?,
+            code: parser.parse_as::<_, MaybeRef<ParseFully>>()?,
             // data:(Maybe ^Cell)
-            data: parser.parse_as::<_, Option<Ref<ParseFully>>>()?,
+            data: parser.parse_as::<_, MaybeRef<ParseFully>>()?,
             // library:(HashmapE 256 SimpleLib)
             library: parser.parse_as_with::<_, HashmapE<NoArgs<_>, NoArgs<_>>>((256, (), ()))?,
         })
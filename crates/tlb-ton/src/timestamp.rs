@@ -1,4 +1,7 @@
-use chrono::{DateTime, Utc};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, NaiveDateTime, Utc};
 use tlb::{
     bits::{
         de::{r#as::BitUnpackAs, BitReader, BitReaderExt},
@@ -7,21 +10,72 @@ use tlb::{
     Error,
 };
 
-/// Adapter to **de**/**ser**ialize UNIX timestamp as `u32` from [`DateTime`]
+/// Point in time used by timestamp fields across this crate:
+/// `DateTime<Utc>` with `chrono` feature (default), [`SystemTime`]
+/// otherwise. Both are [`UnixTimestamp`]-compatible and have `UNIX_EPOCH`.
+#[cfg(feature = "chrono")]
+pub type Timestamp = DateTime<Utc>;
+
+/// Point in time used by timestamp fields across this crate:
+/// `DateTime<Utc>` with `chrono` feature (default), [`SystemTime`]
+/// otherwise. Both are [`UnixTimestamp`]-compatible and have `UNIX_EPOCH`.
+#[cfg(not(feature = "chrono"))]
+pub type Timestamp = SystemTime;
+
+/// Adapter to **de**/**ser**ialize UNIX timestamp as `u32` from
+/// [`SystemTime`] or, with `chrono` feature, from `DateTime<Utc>` and
+/// `NaiveDateTime` (treated as UTC).
+///
+/// Sub-second precision is truncated. Timestamps after
+/// `u32::MAX` seconds (year 2106) saturate to `u32::MAX`, while
+/// timestamps before UNIX epoch result in an error.
+/// ```rust
+/// # use std::time::{Duration, SystemTime, UNIX_EPOCH};
+/// # use tlb::bits::{de::{r#as::unpack_fully_as, unpack_fully}, ser::r#as::pack_as};
+/// # use tlb_ton::UnixTimestamp;
+/// let packed = pack_as::<_, UnixTimestamp>(SystemTime::now()).unwrap();
+/// let got: SystemTime = unpack_fully_as::<_, UnixTimestamp>(packed).unwrap();
+///
+/// let far_future = UNIX_EPOCH + Duration::from_secs(u64::from(u32::MAX) + 1);
+/// let packed = pack_as::<_, UnixTimestamp>(far_future).unwrap();
+/// assert_eq!(unpack_fully::<u32>(packed).unwrap(), u32::MAX);
+///
+/// assert!(pack_as::<_, UnixTimestamp>(UNIX_EPOCH - Duration::from_secs(1)).is_err());
+/// ```
 pub struct UnixTimestamp;
 
+#[cfg(feature = "chrono")]
+impl UnixTimestamp {
+    /// Convert seconds since UNIX epoch to `u32` saturating at `u32::MAX`
+    #[inline]
+    fn saturate<E>(secs: i64) -> Result<u32, E>
+    where
+        E: Error,
+    {
+        if secs < 0 {
+            return Err(Error::custom(format!(
+                "timestamp is before UNIX epoch: {secs}"
+            )));
+        }
+        Ok(secs.try_into().unwrap_or(u32::MAX))
+    }
+}
+
+#[cfg(feature = "chrono")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
 impl BitPackAs<DateTime<Utc>> for UnixTimestamp {
     #[inline]
     fn pack_as<W>(source: &DateTime<Utc>, mut writer: W) -> Result<(), W::Error>
     where
         W: BitWriter,
     {
-        let timestamp: u32 = source.timestamp().try_into().map_err(Error::custom)?;
-        writer.pack(timestamp)?;
+        writer.pack(Self::saturate::<W::Error>(source.timestamp())?)?;
         Ok(())
     }
 }
 
+#[cfg(feature = "chrono")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
 impl BitUnpackAs<DateTime<Utc>> for UnixTimestamp {
     #[inline]
     fn unpack_as<R>(mut reader: R) -> Result<DateTime<Utc>, R::Error>
@@ -33,12 +87,74 @@ impl BitUnpackAs<DateTime<Utc>> for UnixTimestamp {
     }
 }
 
+#[cfg(feature = "chrono")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+impl BitPackAs<NaiveDateTime> for UnixTimestamp {
+    #[inline]
+    fn pack_as<W>(source: &NaiveDateTime, writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        Self::pack_as(&source.and_utc(), writer)
+    }
+}
+
+#[cfg(feature = "chrono")]
+#[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+impl BitUnpackAs<NaiveDateTime> for UnixTimestamp {
+    #[inline]
+    fn unpack_as<R>(reader: R) -> Result<NaiveDateTime, R::Error>
+    where
+        R: BitReader,
+    {
+        <Self as BitUnpackAs<DateTime<Utc>>>::unpack_as(reader).map(|dt| dt.naive_utc())
+    }
+}
+
+impl BitPackAs<SystemTime> for UnixTimestamp {
+    #[inline]
+    fn pack_as<W>(source: &SystemTime, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        let secs = source
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| {
+                Error::custom(format!(
+                    "timestamp is before UNIX epoch by {:?}",
+                    err.duration()
+                ))
+            })?
+            .as_secs();
+        writer.pack(u32::try_from(secs).unwrap_or(u32::MAX))?;
+        Ok(())
+    }
+}
+
+impl BitUnpackAs<SystemTime> for UnixTimestamp {
+    #[inline]
+    fn unpack_as<R>(mut reader: R) -> Result<SystemTime, R::Error>
+    where
+        R: BitReader,
+    {
+        let timestamp: u32 = reader.unpack()?;
+        Ok(UNIX_EPOCH + std::time::Duration::from_secs(timestamp.into()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use tlb::bits::{de::r#as::unpack_fully_as, ser::r#as::pack_as};
+    use std::time::Duration;
+
+    use proptest::prelude::*;
+    use tlb::bits::{
+        de::{r#as::unpack_fully_as, unpack_fully},
+        ser::r#as::pack_as,
+    };
 
     use super::*;
 
+    #[cfg(feature = "chrono")]
     #[test]
     fn unix_timestamp_serde() {
         let ts = DateTime::UNIX_EPOCH;
@@ -48,4 +164,45 @@ mod tests {
 
         assert_eq!(got, ts);
     }
+
+    fn packed_secs<T>(v: T) -> Result<u32, tlb::StringError>
+    where
+        UnixTimestamp: BitPackAs<T>,
+    {
+        unpack_fully(pack_as::<_, UnixTimestamp>(v)?)
+    }
+
+    proptest! {
+        #[test]
+        fn unix_timestamp_fuzz(secs in prop_oneof![0..=i64::from(u32::MAX), -(1i64 << 40)..(1i64 << 40)], nanos in 0..1_000_000_000u32) {
+            let expected = match secs {
+                ..0 => None,
+                secs => Some(u32::try_from(secs).unwrap_or(u32::MAX)),
+            };
+            #[cfg(feature = "chrono")]
+            {
+                let dt = DateTime::from_timestamp(secs, nanos).unwrap();
+                prop_assert_eq!(packed_secs(dt).ok(), expected);
+                prop_assert_eq!(packed_secs(dt.naive_utc()).ok(), expected);
+            }
+
+            let st = if secs < 0 {
+                UNIX_EPOCH - Duration::new(secs.unsigned_abs(), 0) + Duration::from_nanos(nanos.into())
+            } else {
+                UNIX_EPOCH + Duration::new(secs as u64, nanos)
+            };
+            prop_assert_eq!(packed_secs(st).ok(), expected);
+
+            if let Some(expected) = expected.filter(|&s| s < u32::MAX) {
+                let packed = pack_as::<_, UnixTimestamp>(st).unwrap();
+                #[cfg(feature = "chrono")]
+                {
+                    let got: DateTime<Utc> = unpack_fully_as::<_, UnixTimestamp>(&packed).unwrap();
+                    prop_assert_eq!(got.timestamp(), i64::from(expected));
+                }
+                let got: SystemTime = unpack_fully_as::<_, UnixTimestamp>(&packed).unwrap();
+                prop_assert_eq!(got, UNIX_EPOCH + Duration::from_secs(expected.into()));
+            }
+        }
+    }
 }
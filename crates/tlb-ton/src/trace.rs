@@ -0,0 +1,317 @@
+//! Message execution traces, i.e. trees of transactions caused by a
+//! single message, as returned by emulators and indexers like tonapi
+use tlb::{Cell, Error, StringError};
+
+use crate::{
+    message::{CommonMsgInfo, Message},
+    MsgAddress,
+};
+
+/// Transaction in [`Trace`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceTransaction {
+    /// Account the transaction was executed on
+    pub account: MsgAddress,
+    /// Logical time of the transaction
+    pub lt: u64,
+    /// Inbound message, `None` for tick-tock transactions
+    pub in_msg: Option<Message<Cell>>,
+    /// Outbound messages in order they were sent
+    pub out_msgs: Vec<Message<Cell>>,
+    /// Exit code of compute phase, `None` if it was skipped
+    pub exit_code: Option<i32>,
+    /// Whether the transaction was aborted
+    pub aborted: bool,
+}
+
+impl TraceTransaction {
+    /// Returns whether compute phase succeeded with exit code `0` or `1`
+    /// and the transaction was not aborted
+    #[inline]
+    pub fn is_success(&self) -> bool {
+        !self.aborted && matches!(self.exit_code, Some(0 | 1))
+    }
+
+    /// Returns outbound external messages, i.e. events emitted by the
+    /// contract
+    #[inline]
+    pub fn events(&self) -> impl Iterator<Item = &Message<Cell>> {
+        self.out_msgs
+            .iter()
+            .filter(|msg| matches!(msg.info, CommonMsgInfo::ExternalOut(_)))
+    }
+}
+
+/// Tree of transactions: root [`transaction`](Trace::transaction) and
+/// traces of transactions caused by its outbound internal messages.
+///
+/// Use [`Trace::builder()`] to construct expected traces in tests:
+/// ```rust
+/// # use tlb_ton::{currency::consts::TON, message::Message, trace::Trace, MsgAddress};
+/// let wallet = MsgAddress { workchain_id: 0, address: [1; 32] };
+/// let dst = MsgAddress { workchain_id: 0, address: [2; 32] };
+/// let transfer = Message::<()>::transfer(dst, TON.into(), true)
+///     .normalize()
+///     .unwrap();
+///
+/// let trace = Trace::builder(wallet)
+///     .out_msg(transfer.clone())
+///     .child(Trace::builder(dst).in_msg(transfer).build().unwrap())
+///     .build()
+///     .unwrap();
+/// assert!(trace.is_success());
+/// assert_eq!(trace.iter().count(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    pub transaction: TraceTransaction,
+    /// Traces caused by internal [`out_msgs`](TraceTransaction::out_msgs)
+    /// of the [`transaction`](Trace::transaction)
+    pub children: Vec<Trace>,
+}
+
+impl Trace {
+    /// Create [`TraceBuilder`] for transaction on given account
+    #[inline]
+    pub fn builder(account: MsgAddress) -> TraceBuilder {
+        TraceBuilder::new(account)
+    }
+
+    /// Iterate over all transactions in the trace in depth-first pre-order
+    pub fn iter(&self) -> impl Iterator<Item = &TraceTransaction> {
+        let mut stack = vec![self];
+        core::iter::from_fn(move || {
+            let trace = stack.pop()?;
+            stack.extend(trace.children.iter().rev());
+            Some(&trace.transaction)
+        })
+    }
+
+    /// Returns whether all transactions in the trace
+    /// [succeeded](TraceTransaction::is_success)
+    #[inline]
+    pub fn is_success(&self) -> bool {
+        self.iter().all(TraceTransaction::is_success)
+    }
+
+    /// Returns the first transaction in the trace that did not
+    /// [succeed](TraceTransaction::is_success)
+    #[inline]
+    pub fn first_failure(&self) -> Option<&TraceTransaction> {
+        self.iter().find(|tx| !tx.is_success())
+    }
+
+    /// Check that each child was caused by one of internal
+    /// [`out_msgs`](TraceTransaction::out_msgs) of its parent, which was
+    /// sent to the account of the child
+    pub fn validate(&self) -> Result<(), StringError> {
+        let mut stack = vec![self];
+        while let Some(trace) = stack.pop() {
+            for (i, child) in trace.children.iter().enumerate() {
+                let tx = &child.transaction;
+                let Some(in_msg) = &tx.in_msg else {
+                    return Err(Error::custom(format!(
+                        "child [{i}]: inbound message missing"
+                    )));
+                };
+                let CommonMsgInfo::Internal(info) = &in_msg.info else {
+                    return Err(Error::custom(format!(
+                        "child [{i}]: inbound message must be internal"
+                    )));
+                };
+                if info.dst != tx.account {
+                    return Err(Error::custom(format!(
+                        "child [{i}]: inbound message was sent to {}, not {}",
+                        info.dst, tx.account
+                    )));
+                }
+                if !trace.transaction.out_msgs.contains(in_msg) {
+                    return Err(Error::custom(format!(
+                        "child [{i}]: inbound message was not sent by {}",
+                        trace.transaction.account
+                    )));
+                }
+            }
+            stack.extend(&trace.children);
+        }
+        Ok(())
+    }
+}
+
+/// Builder for [`Trace`], which [validates](Trace::validate) it on
+/// [`.build()`](TraceBuilder::build). By default the transaction is
+/// successful with exit code `0`.
+#[derive(Debug, Clone)]
+pub struct TraceBuilder {
+    transaction: TraceTransaction,
+    children: Vec<Trace>,
+}
+
+impl TraceBuilder {
+    #[inline]
+    fn new(account: MsgAddress) -> Self {
+        Self {
+            transaction: TraceTransaction {
+                account,
+                lt: 0,
+                in_msg: None,
+                out_msgs: Vec::new(),
+                exit_code: Some(0),
+                aborted: false,
+            },
+            children: Vec::new(),
+        }
+    }
+
+    /// Set logical time of the transaction
+    #[inline]
+    #[must_use]
+    pub fn lt(mut self, lt: u64) -> Self {
+        self.transaction.lt = lt;
+        self
+    }
+
+    /// Set inbound message
+    #[inline]
+    #[must_use]
+    pub fn in_msg(mut self, msg: Message<Cell>) -> Self {
+        self.transaction.in_msg = Some(msg);
+        self
+    }
+
+    /// Add outbound message
+    #[inline]
+    #[must_use]
+    pub fn out_msg(mut self, msg: Message<Cell>) -> Self {
+        self.transaction.out_msgs.push(msg);
+        self
+    }
+
+    /// Set exit code of compute phase, `None` if it was skipped
+    #[inline]
+    #[must_use]
+    pub fn exit_code(mut self, exit_code: impl Into<Option<i32>>) -> Self {
+        self.transaction.exit_code = exit_code.into();
+        self
+    }
+
+    /// Mark the transaction as aborted
+    #[inline]
+    #[must_use]
+    pub fn aborted(mut self, aborted: bool) -> Self {
+        self.transaction.aborted = aborted;
+        self
+    }
+
+    /// Add trace caused by one of [`out_msg`](TraceBuilder::out_msg)s
+    #[inline]
+    #[must_use]
+    pub fn child(mut self, child: Trace) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Build and [validate](Trace::validate) the trace
+    #[inline]
+    pub fn build(self) -> Result<Trace, StringError> {
+        let trace = Trace {
+            transaction: self.transaction,
+            children: self.children,
+        };
+        trace.validate()?;
+        Ok(trace)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{message::ExternalOutMsgInfo, Timestamp};
+
+    use super::*;
+
+    fn addr(b: u8) -> MsgAddress {
+        MsgAddress {
+            workchain_id: 0,
+            address: [b; 32],
+        }
+    }
+
+    fn transfer(dst: MsgAddress) -> Message<Cell> {
+        Message::<()>::transfer(dst, 1u8.into(), true)
+            .normalize()
+            .unwrap()
+    }
+
+    #[test]
+    fn trace() {
+        let event = Message {
+            info: CommonMsgInfo::ExternalOut(ExternalOutMsgInfo {
+                src: addr(2),
+                dst: MsgAddress::NULL,
+                created_lt: 0,
+                created_at: Timestamp::UNIX_EPOCH,
+            }),
+            init: None,
+            body: Default::default(),
+        };
+        let trace = Trace::builder(addr(1))
+            .lt(1)
+            .out_msg(transfer(addr(2)))
+            .out_msg(transfer(addr(3)))
+            .child(
+                Trace::builder(addr(2))
+                    .lt(2)
+                    .in_msg(transfer(addr(2)))
+                    .out_msg(event.clone())
+                    .build()
+                    .unwrap(),
+            )
+            .child(
+                Trace::builder(addr(3))
+                    .lt(3)
+                    .in_msg(transfer(addr(3)))
+                    .exit_code(9)
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(trace.iter().map(|tx| tx.lt).collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(!trace.is_success());
+        assert_eq!(trace.first_failure().unwrap().account, addr(3));
+        assert_eq!(
+            trace
+                .iter()
+                .flat_map(TraceTransaction::events)
+                .collect::<Vec<_>>(),
+            [&event]
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let child = Trace::builder(addr(2))
+            .in_msg(transfer(addr(2)))
+            .build()
+            .unwrap();
+        assert_eq!(
+            Trace::builder(addr(1))
+                .child(child.clone())
+                .build()
+                .unwrap_err()
+                .to_string(),
+            format!("child [0]: inbound message was not sent by {}", addr(1))
+        );
+
+        let misdirected = Trace::builder(addr(3))
+            .in_msg(transfer(addr(2)))
+            .build()
+            .unwrap();
+        assert!(Trace::builder(addr(1))
+            .out_msg(transfer(addr(2)))
+            .child(misdirected)
+            .build()
+            .is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Type aliases named after built-in types and common combinators of
+//! [TL-B](https://docs.ton.org/develop/data-formats/tl-b-language), so that
+//! implementations read like the schemas they implement.
+//!
+//! Most of them are adapters, so they can be used with `*_as` methods:
+//! ```tlb
+//! transfer#0f8a7ea5 query_id:uint64 amount:(VarUInteger 16)
+//!     custom_payload:(Maybe ^Cell) forward_payload:(Either Cell ^Cell)
+//!     = Transfer;
+//! ```
+//! ```rust
+//! # use tlb::{
+//! #     aliases::{EitherInline, Maybe, Uint, VarUInteger16},
+//! #     bits::{de::BitReaderExt, either::Either, ser::BitWriterExt},
+//! #     r#as::{ParseFully, Ref},
+//! #     de::{CellDeserialize, CellParser, CellParserError},
+//! #     ser::{CellBuilder, CellBuilderError, CellSerialize, CellSerializeExt},
+//! #     Cell,
+//! # };
+//! #[derive(Debug, PartialEq)]
+//! struct Transfer {
+//!     query_id: u64,
+//!     amount: u128,
+//!     custom_payload: Maybe<Cell>,
+//!     forward_payload: Either<Cell, Cell>,
+//! }
+//!
+//! impl CellSerialize for Transfer {
+//!     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+//!         builder
+//!             .pack_as::<_, Uint<32>>(0x0f8a7ea5u32)?
+//!             .pack_as::<_, Uint<64>>(self.query_id)?
+//!             .pack_as::<_, &VarUInteger16>(&self.amount)?
+//!             .store_as::<_, Maybe<Ref>>(self.custom_payload.as_ref())?
+//!             .store_as::<_, EitherInline>(self.forward_payload.as_ref())?;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! impl<'de> CellDeserialize<'de> for Transfer {
+//!     fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+//!         let _: u32 = parser.unpack_as::<_, Uint<32>>()?;
+//!         Ok(Self {
+//!             query_id: parser.unpack_as::<_, Uint<64>>()?,
+//!             amount: parser.unpack_as::<_, VarUInteger16>()?,
+//!             custom_payload: parser.parse_as::<_, Maybe<Ref<ParseFully>>>()?,
+//!             forward_payload: parser.parse_as::<_, EitherInline<ParseFully>>()?,
+//!         })
+//!     }
+//! }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let transfer = Transfer {
+//!     query_id: 1,
+//!     amount: 1_000,
+//!     custom_payload: None,
+//!     forward_payload: Either::Right(Cell::new()),
+//! };
+//! let got: Transfer = transfer.to_cell()?.parse_fully()?;
+//! assert_eq!(got, transfer);
+//! # Ok(())
+//! # }
+//! ```
+use crate::{
+    bits::{either::Either, r#as::NBits},
+    r#as::{Ref, Same},
+};
+
+pub use crate::bits::r#as::VarInt;
+
+/// `uint N`, which is the same as `## N`
+pub type Uint<const N: usize> = NBits<N>;
+
+/// `int N`
+pub type Int<const N: usize> = NBits<N>;
+
+/// `bits N`
+pub type Bits<const N: usize> = NBits<N>;
+
+/// ```tlb
+/// nothing$0 {X:Type} = Maybe X;
+/// just$1 {X:Type} value:X = Maybe X;
+/// ```
+pub type Maybe<T> = Option<T>;
+
+/// `Either X ^X`, where the value is stored either inline or in a
+/// reference. See also [`EitherInlineOrRef`](crate::r#as::EitherInlineOrRef)
+/// adapter, which chooses between them automatically when serializing.
+/// ```tlb
+/// left$0 {X:Type} {Y:Type} value:X = Either X Y;
+/// right$1 {X:Type} {Y:Type} value:Y = Either X Y;
+/// ```
+pub type EitherInline<T = Same> = Either<T, Ref<T>>;
+
+/// `VarUInteger 7`
+/// ```tlb
+/// var_uint$_ {n:#} len:(#< n) value:(uint (len * 8)) = VarUInteger n;
+/// ```
+pub type VarUInteger7 = VarInt<3>;
+
+/// `VarUInteger 16`
+/// ```tlb
+/// var_uint$_ {n:#} len:(#< n) value:(uint (len * 8)) = VarUInteger n;
+/// ```
+pub type VarUInteger16 = VarInt<4>;
+
+/// `VarUInteger 32`
+/// ```tlb
+/// var_uint$_ {n:#} len:(#< n) value:(uint (len * 8)) = VarUInteger n;
+/// ```
+pub type VarUInteger32 = VarInt<5>;
+
+/// `VarInteger 16`
+/// ```tlb
+/// var_int$_ {n:#} len:(#< n) value:(int (len * 8)) = VarInteger n;
+/// ```
+pub type VarInteger16 = VarInt<4>;
+
+/// `VarInteger 32`
+/// ```tlb
+/// var_int$_ {n:#} len:(#< n) value:(int (len * 8)) = VarInteger n;
+/// ```
+pub type VarInteger32 = VarInt<5>;
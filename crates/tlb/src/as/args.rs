@@ -1,4 +1,5 @@
 use crate::{
+    bits::ResultExt,
     de::{
         args::r#as::CellDeserializeAsWithArgs, r#as::CellDeserializeAs, CellParser, CellParserError,
     },
@@ -7,7 +8,7 @@ use crate::{
     },
 };
 
-pub use crate::bits::r#as::args::{DefaultArgs, NoArgs};
+pub use crate::bits::r#as::args::{ArgsContext, DefaultArgs, DescribeArgs, NoArgs};
 
 impl<T, As, Args> CellSerializeAsWithArgs<T> for NoArgs<Args, As>
 where
@@ -61,3 +62,39 @@ where
         As::parse_as_with(parser, <As::Args>::default())
     }
 }
+
+impl<T, As> CellSerializeAsWithArgs<T> for ArgsContext<As>
+where
+    T: ?Sized,
+    As: CellSerializeAsWithArgs<T> + ?Sized,
+    As::Args: DescribeArgs + Clone,
+{
+    type Args = As::Args;
+
+    #[inline]
+    fn store_as_with(
+        source: &T,
+        builder: &mut CellBuilder,
+        args: Self::Args,
+    ) -> Result<(), CellBuilderError> {
+        As::store_as_with(source, builder, args.clone())
+            .with_context(|| format!("args {}", args.describe_args()))
+    }
+}
+
+impl<'de, T, As> CellDeserializeAsWithArgs<'de, T> for ArgsContext<As>
+where
+    As: CellDeserializeAsWithArgs<'de, T> + ?Sized,
+    As::Args: DescribeArgs + Clone,
+{
+    type Args = As::Args;
+
+    #[inline]
+    fn parse_as_with(
+        parser: &mut CellParser<'de>,
+        args: Self::Args,
+    ) -> Result<T, CellParserError<'de>> {
+        As::parse_as_with(parser, args.clone())
+            .with_context(|| format!("args {}", args.describe_args()))
+    }
+}
@@ -24,7 +24,6 @@ use super::Same;
 /// #           ser::{BitPack, BitWriter, BitWriterExt},
 /// #       },
 /// #       Cell,
-/// #       StringError,
 /// # };
 /// # #[derive(Debug, Clone, Copy, PartialEq)]
 /// struct BinaryData {
@@ -50,14 +49,14 @@ use super::Same;
 ///     }
 /// }
 ///
-/// # fn main() -> Result<(), StringError> {
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// let v = BinaryData { field: 123 };
 /// # let mut builder = Cell::builder();
 /// // store as binary data
 /// builder.store_as::<_, Data>(v)?;
 /// # let cell = builder.into_cell();
 /// # let mut parser = cell.parser();
-/// # let got = 
+/// # let got =
 /// // parse as binary data
 /// parser.parse_as::<BinaryData, Data>()?;
 /// # assert_eq!(got, v);
@@ -1,16 +1,18 @@
 use core::marker::PhantomData;
+use std::sync::Arc;
 
 use crate::{
+    bits::de::BitReaderExt,
     de::{
         args::r#as::CellDeserializeAsWithArgs, r#as::CellDeserializeAs, CellParser, CellParserError,
     },
     ser::{
         args::r#as::CellSerializeAsWithArgs, r#as::CellSerializeAs, CellBuilder, CellBuilderError,
     },
-    ResultExt,
+    Cell, ResultExt,
 };
 
-use super::Same;
+use super::{Data, Same};
 
 /// Adapter to **de**/**ser**ialize value from/into reference to the child cell.
 pub struct Ref<As: ?Sized = Same>(PhantomData<As>);
@@ -68,3 +70,251 @@ where
         parser.parse_reference_as_with::<T, As>(args).context("^")
     }
 }
+
+/// Adapter to **de**/**ser**ialize already built [`Arc<Cell>`] as reference
+/// to the child cell without copying it, i.e. `^Cell`.
+///
+/// [`Ref`] stores the cell into a newly built one, while this adapter
+/// shares the same [`Arc`], so cached cells (e.g. contract code) can be
+/// embedded cheaply:
+/// ```rust
+/// # use std::sync::Arc;
+/// # use tlb::{r#as::SharedRef, Cell};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let code = Arc::new(Cell::from_hex_data("cafe", 16)?);
+/// let mut builder = Cell::builder();
+/// builder.store_as::<_, &SharedRef>(&code)?;
+/// let cell = builder.into_cell();
+/// assert!(Arc::ptr_eq(&cell.references[0], &code));
+///
+/// let got: Arc<Cell> = cell.parse_fully_as::<_, SharedRef>()?;
+/// assert!(Arc::ptr_eq(&got, &code));
+/// # Ok(())
+/// # }
+/// ```
+pub struct SharedRef;
+
+impl CellSerializeAs<Arc<Cell>> for SharedRef {
+    #[inline]
+    fn store_as(source: &Arc<Cell>, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder.store_reference(source.clone()).context("^")?;
+        Ok(())
+    }
+}
+
+impl<'de> CellDeserializeAs<'de, Arc<Cell>> for SharedRef {
+    #[inline]
+    fn parse_as(parser: &mut CellParser<'de>) -> Result<Arc<Cell>, CellParserError<'de>> {
+        parser.parse_reference().context("^")
+    }
+}
+
+/// Adapter to **de**/**ser**ialize `Option<T>` as `(Maybe ^X)`, i.e. the
+/// tag bit is stored in the current cell, followed by a reference to the
+/// child cell if present:
+/// ```tlb
+/// custom_payload:(Maybe ^Cell)
+/// ```
+/// Not to be confused with [`RefMaybe`]:
+/// ```rust
+/// # use tlb::{r#as::{Data, MaybeRef}, ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt}};
+/// let cell = Some(0xffu8).wrap_as::<MaybeRef<Data>>().to_cell().unwrap();
+/// assert_eq!(cell.data.len(), 1);
+/// assert_eq!(cell.references.len(), 1);
+/// assert_eq!(cell.parse_fully_as::<Option<u8>, MaybeRef<Data>>().unwrap(), Some(0xff));
+///
+/// let cell = None::<u8>.wrap_as::<MaybeRef<Data>>().to_cell().unwrap();
+/// assert_eq!(cell.data.len(), 1);
+/// assert!(cell.references.is_empty());
+/// ```
+pub type MaybeRef<As = Same> = Option<Ref<As>>;
+
+/// Adapter to **de**/**ser**ialize `Option<T>` as `^(Maybe X)`, i.e. a
+/// reference to the child cell is always stored, while the tag bit and the
+/// value itself are stored in the child cell:
+/// ```tlb
+/// inner:^(Maybe X)
+/// ```
+/// Not to be confused with [`MaybeRef`]:
+/// ```rust
+/// # use tlb::{r#as::{Data, RefMaybe}, ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt}};
+/// let cell = None::<u8>.wrap_as::<RefMaybe<Data>>().to_cell().unwrap();
+/// assert!(cell.data.is_empty());
+/// assert_eq!(cell.references.len(), 1);
+/// assert_eq!(cell.references[0].data.len(), 1);
+/// assert_eq!(cell.parse_fully_as::<Option<u8>, RefMaybe<Data>>().unwrap(), None);
+/// ```
+pub type RefMaybe<As = Same> = Ref<Option<As>>;
+
+/// Adapter to **de**/**ser**ialize value as raw bits stored in the child cell.
+/// Shortcut for `Ref<Data<As>>`:
+/// ```rust
+/// # use tlb::{
+/// #     bits::r#as::NBits,
+/// #     r#as::DataRef,
+/// #     ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt},
+/// # };
+/// let cell = 0x7fu8.wrap_as::<DataRef<NBits<7>>>().to_cell().unwrap();
+/// assert!(cell.data.is_empty());
+/// assert_eq!(cell.references[0].data.len(), 7);
+/// assert_eq!(cell.parse_fully_as::<u8, DataRef<NBits<7>>>().unwrap(), 0x7f);
+/// ```
+pub type DataRef<As = Same> = Ref<Data<As>>;
+
+/// Adapter to **de**/**ser**ialize value as `Either X ^X`, i.e. inline if it
+/// fits into current cell and as a reference to the child cell otherwise:
+/// ```tlb
+/// left$0 {X:Type} {Y:Type} value:X = Either X Y;
+/// right$1 {X:Type} {Y:Type} value:Y = Either X Y;
+/// ```
+///
+/// Serialization stores the value only once and then decides whether it
+/// fits into the rest of current cell.
+pub struct EitherInlineOrRef<As: ?Sized = Same>(PhantomData<As>);
+
+impl<T, As> CellSerializeAs<T> for EitherInlineOrRef<As>
+where
+    As: CellSerializeAs<T> + ?Sized,
+{
+    #[inline]
+    fn store_as(source: &T, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder.store_inline_or_ref(|builder| As::store_as(source, builder))?;
+        Ok(())
+    }
+}
+
+impl<T, As> CellSerializeAsWithArgs<T> for EitherInlineOrRef<As>
+where
+    As: CellSerializeAsWithArgs<T> + ?Sized,
+{
+    type Args = As::Args;
+
+    #[inline]
+    fn store_as_with(
+        source: &T,
+        builder: &mut CellBuilder,
+        args: Self::Args,
+    ) -> Result<(), CellBuilderError> {
+        builder.store_inline_or_ref(|builder| As::store_as_with(source, builder, args))?;
+        Ok(())
+    }
+}
+
+impl<'de, T, As> CellDeserializeAs<'de, T> for EitherInlineOrRef<As>
+where
+    As: CellDeserializeAs<'de, T> + ?Sized,
+{
+    #[inline]
+    fn parse_as(parser: &mut CellParser<'de>) -> Result<T, CellParserError<'de>> {
+        match parser.unpack().context("tag")? {
+            // left$0
+            false => As::parse_as(parser).context("left"),
+            // right$1
+            true => Ref::<As>::parse_as(parser).context("right"),
+        }
+    }
+}
+
+impl<'de, T, As> CellDeserializeAsWithArgs<'de, T> for EitherInlineOrRef<As>
+where
+    As: CellDeserializeAsWithArgs<'de, T> + ?Sized,
+{
+    type Args = As::Args;
+
+    #[inline]
+    fn parse_as_with(
+        parser: &mut CellParser<'de>,
+        args: Self::Args,
+    ) -> Result<T, CellParserError<'de>> {
+        match parser.unpack().context("tag")? {
+            // left$0
+            false => As::parse_as_with(parser, args).context("left"),
+            // right$1
+            true => Ref::<As>::parse_as_with(parser, args).context("right"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bits::ser::BitWriterExt,
+        r#as::Data,
+        ser::{r#as::CellSerializeWrapAsExt, CellSerialize, CellSerializeExt},
+        tests::assert_store_parse_as_eq,
+        Both, Cell, Error,
+    };
+
+    use super::*;
+
+    #[test]
+    fn either_inline_or_ref() {
+        assert_store_parse_as_eq::<_, EitherInlineOrRef<Data>>(0xdeadbeefu32);
+        assert_store_parse_as_eq::<_, EitherInlineOrRef<Data>>([0xffu8; 127]);
+    }
+
+    #[test]
+    fn either_inline_or_ref_falls_back_to_ref() {
+        let mut builder = Cell::builder();
+        builder.pack(0u8).unwrap();
+        builder
+            .store_as::<_, EitherInlineOrRef<Data>>([0xffu8; 127])
+            .unwrap();
+        let cell = builder.into_cell();
+
+        assert_eq!(cell.data.len(), 9);
+        assert_eq!(cell.references.len(), 1);
+
+        let mut parser = cell.parser();
+        assert_eq!(parser.unpack::<u8>().unwrap(), 0);
+        assert_eq!(
+            parser
+                .parse_as::<[u8; 127], EitherInlineOrRef<Data>>()
+                .unwrap(),
+            [0xffu8; 127]
+        );
+    }
+
+    #[test]
+    fn either_inline_or_ref_stores_once() {
+        struct Failing<'a>(&'a core::cell::Cell<usize>);
+
+        impl CellSerialize for Failing<'_> {
+            fn store(&self, _builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+                self.0.set(self.0.get() + 1);
+                Err(Error::custom("failed"))
+            }
+        }
+
+        let calls = core::cell::Cell::new(0);
+        let err = Failing(&calls)
+            .wrap_as::<EitherInlineOrRef>()
+            .to_cell()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "failed");
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn maybe_ref() {
+        assert_store_parse_as_eq::<_, MaybeRef<Data>>(Some(0xdeadbeefu32));
+        assert_store_parse_as_eq::<_, MaybeRef<Data>>(None::<u32>);
+        assert_store_parse_as_eq::<_, RefMaybe<Data>>(Some(0xdeadbeefu32));
+        assert_store_parse_as_eq::<_, RefMaybe<Data>>(None::<u32>);
+
+        // layouts are not interchangeable
+        let cell = Some(0xffu8).wrap_as::<MaybeRef<Data>>().to_cell().unwrap();
+        assert!(cell.parse_fully_as::<Option<u8>, RefMaybe<Data>>().is_err());
+    }
+
+    #[test]
+    fn both_ref() {
+        assert_store_parse_as_eq::<_, Both<Ref<Data>, Data>>(Both::new(0xdeadbeefu32, ()));
+
+        let cell = Cell::builder().into_cell();
+        let err = cell
+            .parse_fully_as::<Both<u32, ()>, Both<Ref<Data>, ()>>()
+            .unwrap_err();
+        assert_eq!(err.to_string(), "first: ^: no more references left");
+    }
+}
@@ -1,35 +1,272 @@
 use core::{
     fmt::{self, Debug},
     hash::Hash,
-    ops::Deref,
+    mem::{self, size_of},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
 };
-use std::sync::Arc;
 
-use bitvec::{order::Msb0, vec::BitVec};
-use sha2::{Digest, Sha256};
+#[cfg(feature = "base64")]
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bitvec::{field::BitField, order::Msb0, slice::BitSlice, vec::BitVec, view::BitView};
 
 use crate::{
     de::{
         args::{r#as::CellDeserializeAsWithArgs, CellDeserializeWithArgs},
         r#as::CellDeserializeAs,
-        CellDeserialize, CellParser, CellParserError,
+        CellDeserialize, CellParser, CellParserError, CellParserLimits,
     },
-    ser::CellBuilder,
+    hash::{CellDigest, CellHashCache, DefaultCellDigest},
+    ser::{CellBuilder, CellBuilderLimits},
+    Error, StringError,
 };
 
+/// Formatter of bits as hex digits with completion tag, see
+/// [`Cell::data_hex_with_completion_tag()`]:
+/// ```rust
+/// # use tlb::{bits::bitvec::{bits, order::Msb0}, HexWithCompletionTag};
+/// let bits = bits![u8, Msb0; 1, 0, 1, 0, 1, 1, 0, 0, 1];
+/// assert_eq!(format!("{:x}", HexWithCompletionTag(bits)), "acc_");
+/// assert_eq!(format!("{:X}", HexWithCompletionTag(bits)), "ACC_");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct HexWithCompletionTag<'a>(pub &'a BitSlice<u8, Msb0>);
+
+impl HexWithCompletionTag<'_> {
+    fn fmt_with(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        fmt_nibble: fn(u8, &mut fmt::Formatter<'_>) -> fmt::Result,
+    ) -> fmt::Result {
+        let chunks = self.0.chunks_exact(4);
+        let rem = chunks.remainder();
+        for nibble in chunks {
+            fmt_nibble(nibble.load_be(), f)?;
+        }
+        if !rem.is_empty() {
+            // append completion tag: 1 followed by zeros
+            let mut nibble = rem.to_bitvec();
+            nibble.push(true);
+            nibble.resize(4, false);
+            fmt_nibble(nibble.load_be(), f)?;
+            f.write_str("_")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::LowerHex for HexWithCompletionTag<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, |nibble, f| write!(f, "{nibble:x}"))
+    }
+}
+
+impl fmt::UpperHex for HexWithCompletionTag<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, |nibble, f| write!(f, "{nibble:X}"))
+    }
+}
+
+/// Type of the cell, see [Exotic cells](https://docs.ton.org/develop/data-formats/exotic-cells)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CellType {
+    #[default]
+    Ordinary,
+    PrunedBranch,
+    LibraryReference,
+    MerkleProof,
+    MerkleUpdate,
+}
+
+impl CellType {
+    /// Tag stored in the first byte of exotic cell's data, `None` for
+    /// [`CellType::Ordinary`]
+    #[inline]
+    pub const fn exotic_tag(self) -> Option<u8> {
+        Some(match self {
+            Self::Ordinary => return None,
+            Self::PrunedBranch => 1,
+            Self::LibraryReference => 2,
+            Self::MerkleProof => 3,
+            Self::MerkleUpdate => 4,
+        })
+    }
+
+    /// Determine type of exotic cell from the first byte of its data
+    #[inline]
+    pub fn from_exotic_tag(tag: u8) -> Result<Self, StringError> {
+        Ok(match tag {
+            1 => Self::PrunedBranch,
+            2 => Self::LibraryReference,
+            3 => Self::MerkleProof,
+            4 => Self::MerkleUpdate,
+            _ => return Err(Error::custom(format!("unknown exotic cell type: {tag}"))),
+        })
+    }
+
+    /// Returns whether this is an [exotic](https://docs.ton.org/develop/data-formats/exotic-cells)
+    /// cell type
+    #[inline]
+    pub const fn is_exotic(self) -> bool {
+        !matches!(self, Self::Ordinary)
+    }
+
+    /// Returns whether this is a Merkle proof or update, whose references
+    /// are one level deeper
+    #[inline]
+    pub const fn is_merkle(self) -> bool {
+        matches!(self, Self::MerkleProof | Self::MerkleUpdate)
+    }
+
+    /// [Level mask](https://docs.ton.org/develop/data-formats/exotic-cells#level-of-cells)
+    /// of the cell of this type with given data and bitwise OR of level
+    /// masks of its references.
+    ///
+    /// Invalid data of pruned branch results in the mask it would have
+    /// after truncating it to 3 bits, see [`.check()`](CellType::check).
+    pub(crate) fn level_mask(self, data: &BitSlice<u8, Msb0>, refs_mask: u8) -> u8 {
+        match self {
+            Self::Ordinary => refs_mask,
+            Self::PrunedBranch => data.get(8..16).map_or(0, BitField::load_be::<u8>) & 0b111,
+            Self::LibraryReference => 0,
+            Self::MerkleProof | Self::MerkleUpdate => refs_mask >> 1,
+        }
+    }
+
+    /// Check layout of data and number of references of the cell of this
+    /// type
+    pub(crate) fn check(self, data: &BitSlice<u8, Msb0>, refs: usize) -> Result<(), StringError> {
+        let Some(tag) = self.exotic_tag() else {
+            return Ok(());
+        };
+        if data.len() < 8 || data[..8].load_be::<u8>() != tag {
+            return Err(Error::custom(format!(
+                "{self:?} cell must start with tag {tag}"
+            )));
+        }
+        let (expected_bits, expected_refs) = match self {
+            Self::Ordinary => unreachable!(),
+            // _ tag:(## 8) { tag = 1 } level_mask:(## 8)
+            //   hashes:(level_mask.count_ones() * bits256)
+            //   depths:(level_mask.count_ones() * uint16)
+            Self::PrunedBranch => {
+                if data.len() < 16 {
+                    return Err(Error::custom("pruned branch cell must have level mask"));
+                }
+                let mask: u8 = data[8..16].load_be();
+                if mask == 0 || mask > 0b111 {
+                    return Err(Error::custom(format!(
+                        "invalid pruned branch level mask: {mask:#05b}"
+                    )));
+                }
+                (
+                    16 + mask.count_ones() as usize * (HASH_BITS + DEPTH_BITS),
+                    0,
+                )
+            }
+            // _ tag:(## 8) { tag = 2 } hash:bits256
+            Self::LibraryReference => (8 + HASH_BITS, 0),
+            // _ tag:(## 8) { tag = 3 } virtual_hash:bits256 depth:uint16 ^Cell
+            Self::MerkleProof => (8 + HASH_BITS + DEPTH_BITS, 1),
+            // _ tag:(## 8) { tag = 4 } old_hash:bits256 new_hash:bits256
+            //   old_depth:uint16 new_depth:uint16 ^Cell ^Cell
+            Self::MerkleUpdate => (8 + 2 * (HASH_BITS + DEPTH_BITS), 2),
+        };
+        if data.len() != expected_bits {
+            return Err(Error::custom(format!(
+                "{self:?} cell must have {expected_bits} bits of data, got {}",
+                data.len()
+            )));
+        }
+        if refs != expected_refs {
+            return Err(Error::custom(format!(
+                "{self:?} cell must have {expected_refs} references, got {refs}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that hashes and depths stored in data of Merkle proof or
+    /// update match the ones of its references at level 0, given that
+    /// layout of the cell was already [checked](CellType::check)
+    pub(crate) fn check_merkle_references<D>(
+        self,
+        data: &BitSlice<u8, Msb0>,
+        references: &[Arc<Cell>],
+        cache: &mut CellHashCache<D>,
+    ) -> Result<(), StringError>
+    where
+        D: CellDigest,
+    {
+        // hashes come first, then depths
+        let (hashes, depths) = data[8..].split_at(references.len() * HASH_BITS);
+        for (i, r) in references.iter().enumerate() {
+            let info = cache.info(r);
+            if hashes[i * HASH_BITS..][..HASH_BITS] != *info.hash(0).view_bits::<Msb0>() {
+                return Err(Error::custom(format!(
+                    "{self:?} hash mismatch in reference [{i}]"
+                )));
+            }
+            if depths[i * DEPTH_BITS..][..DEPTH_BITS].load_be::<u16>() != info.depth(0) {
+                return Err(Error::custom(format!(
+                    "{self:?} depth mismatch in reference [{i}]"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+const HASH_BITS: usize = 256;
+const DEPTH_BITS: usize = 16;
+
+/// Level of the cell with given level mask, i.e. the index of its highest
+/// bit
+#[inline]
+pub(crate) const fn level_of(level_mask: u8) -> u8 {
+    8 - level_mask.leading_zeros() as u8
+}
+
 /// A [Cell](https://docs.ton.org/develop/data-formats/cell-boc#cell).  
 #[derive(Clone, Default, PartialEq, Eq, Hash)]
 pub struct Cell {
+    /// Ordinary cells are created by [`CellBuilder`], while exotic ones
+    /// should be created with [`Cell::try_new()`], which validates them.
+    pub r#type: CellType,
     pub data: BitVec<u8, Msb0>,
     pub references: Vec<Arc<Self>>,
 }
 
 impl Cell {
+    /// Maximum number of data bits in a cell
+    pub const MAX_BITS_LEN: usize = 1023;
+
+    /// Maximum number of references in a cell
+    pub const MAX_REFS_COUNT: usize = 4;
+
     /// Create new [`CellBuilder`]
     #[inline]
     #[must_use]
     pub const fn builder() -> CellBuilder {
-        CellBuilder::new()
+        Self::builder_with_limits(CellBuilderLimits {
+            max_cells: CellBuilderLimits::DEFAULT_MAX_CELLS,
+        })
+    }
+
+    /// Create new [`CellBuilder`] with given [`CellBuilderLimits`]
+    /// ```rust
+    /// # use tlb::{r#as::Ref, ser::CellBuilderLimits, Cell};
+    /// let mut builder = Cell::builder_with_limits(CellBuilderLimits { max_cells: 1 });
+    /// assert!(builder.store_as::<_, Ref>(()).is_err());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn builder_with_limits(limits: CellBuilderLimits) -> CellBuilder {
+        CellBuilder::new(limits)
     }
 
     /// Create empty cell
@@ -37,16 +274,220 @@ impl Cell {
     #[must_use]
     pub const fn new() -> Self {
         Self {
+            r#type: CellType::Ordinary,
             data: BitVec::EMPTY,
             references: Vec::new(),
         }
     }
 
-    /// Return [`CellParser`] for this cell
+    /// Create cell from its raw parts, validating limits on data and
+    /// references along with invariants of given [`CellType`] and
+    /// [level mask](https://docs.ton.org/develop/data-formats/exotic-cells#level-of-cells),
+    /// which is what BoC stores as level of the cell.
+    ///
+    /// Hashes and depths stored in Merkle proofs and updates must match
+    /// the ones of their references.
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use tlb::{bits::bitvec::{bits, order::Msb0, vec::BitVec}, Cell, CellType};
+    /// let cell = Cell::try_new(bits![u8, Msb0; 1, 0, 1].into(), Vec::new(), CellType::Ordinary, 0)
+    ///     .unwrap();
+    /// assert_eq!(cell.data, bits![u8, Msb0; 1, 0, 1]);
+    ///
+    /// assert!(Cell::try_new(bits![u8, Msb0; 1; 1024].into(), Vec::new(), CellType::Ordinary, 0)
+    ///     .is_err());
+    ///
+    /// // _ tag:(## 8) { tag = 3 } virtual_hash:bits256 depth:uint16 ^Cell
+    /// let mut data = BitVec::from_slice(&[3]);
+    /// data.extend_from_raw_slice(&cell.hash());
+    /// data.extend_from_raw_slice(&0u16.to_be_bytes());
+    /// let proof = Cell::try_new(data, vec![Arc::new(cell)], CellType::MerkleProof, 0).unwrap();
+    /// assert_eq!(proof.r#type, CellType::MerkleProof);
+    /// ```
+    #[inline]
+    pub fn try_new(
+        data: BitVec<u8, Msb0>,
+        references: Vec<Arc<Self>>,
+        r#type: CellType,
+        level_mask: u8,
+    ) -> Result<Self, StringError> {
+        Self::try_new_with_cache(
+            data,
+            references,
+            r#type,
+            level_mask,
+            &mut CellHashCache::new(),
+        )
+    }
+
+    /// Same as [`Cell::try_new()`], but looks up level masks and hashes
+    /// of references in given cache, so that building a DAG bottom-up
+    /// with shared cache stays linear in number of its distinct cells.
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use tlb::{bits::bitvec::vec::BitVec, hash::CellHashCache, Cell, CellType};
+    /// let mut levels = CellHashCache::new();
+    /// let mut cell = Arc::new(Cell::new());
+    /// for _ in 0..1000 {
+    ///     cell = Arc::new(
+    ///         Cell::try_new_with_cache(
+    ///             BitVec::new(),
+    ///             vec![cell.clone(), cell],
+    ///             CellType::Ordinary,
+    ///             0,
+    ///             &mut levels,
+    ///         )
+    ///         .unwrap(),
+    ///     );
+    /// }
+    /// assert_eq!(levels.level(&cell), 0);
+    /// ```
+    pub fn try_new_with_cache<D>(
+        data: BitVec<u8, Msb0>,
+        references: Vec<Arc<Self>>,
+        r#type: CellType,
+        level_mask: u8,
+        cache: &mut CellHashCache<D>,
+    ) -> Result<Self, StringError>
+    where
+        D: CellDigest,
+    {
+        if data.len() > Self::MAX_BITS_LEN {
+            return Err(Error::custom(format!(
+                "too many data bits: {}, max: {}",
+                data.len(),
+                Self::MAX_BITS_LEN
+            )));
+        }
+        if references.len() > Self::MAX_REFS_COUNT {
+            return Err(Error::custom(format!(
+                "too many references: {}, max: {}",
+                references.len(),
+                Self::MAX_REFS_COUNT
+            )));
+        }
+        r#type.check(&data, references.len())?;
+        let refs_mask = references
+            .iter()
+            .fold(0, |mask, r| mask | cache.level_mask(r));
+        let expected_mask = r#type.level_mask(&data, refs_mask);
+        if level_mask != expected_mask {
+            return Err(Error::custom(format!(
+                "invalid level mask: {level_mask:#05b}, expected: {expected_mask:#05b}"
+            )));
+        }
+        if r#type.is_merkle() {
+            r#type.check_merkle_references(&data, &references, cache)?;
+        }
+        Ok(Self {
+            r#type,
+            data,
+            references,
+        })
+    }
+
+    /// Create cell without references from first `bits` bits of data given
+    /// as hex string, which must not contain more bytes than needed.
+    /// ```rust
+    /// # use tlb::{bits::bitvec::{bits, order::Msb0}, Cell};
+    /// let cell = Cell::from_hex_data("a5", 6).unwrap();
+    /// assert_eq!(cell.data, bits![u8, Msb0; 1, 0, 1, 0, 0, 1]);
+    /// ```
+    #[inline]
+    pub fn from_hex_data(s: impl AsRef<[u8]>, bits: usize) -> Result<Self, StringError> {
+        let bytes = hex::decode(s).map_err(Error::custom)?;
+        Self::from_bytes_data(bytes, bits)
+    }
+
+    /// Same as [`Cell::from_hex_data()`], but data is given as standard
+    /// base64 string.
+    /// ```rust
+    /// # use tlb::{bits::bitvec::{bits, order::Msb0}, Cell};
+    /// let cell = Cell::from_base64_data("pQ==", 6).unwrap();
+    /// assert_eq!(cell, Cell::from_hex_data("a5", 6).unwrap());
+    /// ```
+    #[cfg(feature = "base64")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "base64")))]
+    #[inline]
+    pub fn from_base64_data(s: impl AsRef<[u8]>, bits: usize) -> Result<Self, StringError> {
+        let bytes = STANDARD.decode(s).map_err(Error::custom)?;
+        Self::from_bytes_data(bytes, bits)
+    }
+
+    /// Create cell without references from hex string with optional
+    /// completion tag, as printed by Fift and
+    /// [`Cell::data_hex_with_completion_tag()`].
+    /// See [`Cell::data_hex_with_completion_tag()`] for the format.
+    /// ```rust
+    /// # use tlb::{bits::bitvec::{bits, order::Msb0}, Cell};
+    /// let cell = Cell::from_hex_with_completion_tag("A4_").unwrap();
+    /// assert_eq!(cell.data, bits![u8, Msb0; 1, 0, 1, 0, 0]);
+    /// ```
+    pub fn from_hex_with_completion_tag(s: impl AsRef<str>) -> Result<Self, StringError> {
+        let s = s.as_ref();
+        let (hex, tagged) = match s.strip_suffix('_') {
+            Some(hex) => (hex, true),
+            None => (s, false),
+        };
+        let mut data = BitVec::<u8, Msb0>::with_capacity(hex.len() * 4);
+        for c in hex.chars() {
+            let nibble = c
+                .to_digit(16)
+                .ok_or_else(|| Error::custom(format!("invalid hex digit: {c:?}")))?;
+            data.extend_from_bitslice(&(nibble as u8).view_bits::<Msb0>()[4..]);
+        }
+        if tagged {
+            let last = data
+                .iter()
+                .by_vals()
+                .rposition(|bit| bit)
+                .ok_or_else(|| Error::custom("completion tag is missing"))?;
+            data.truncate(last);
+        }
+        Self::try_new(data, Vec::new(), CellType::Ordinary, 0)
+    }
+
+    /// Format [`data`](Cell::data) as upper hex with completion tag, as
+    /// used by Fift and TON exporters.
+    ///
+    /// If data length is not a multiple of 4, it is padded with a single
+    /// `1` bit followed by `0` bits up to the whole hex digit and `_` is
+    /// appended to denote that. References are not included.
+    /// ```rust
+    /// # use tlb::{bits::bitvec::{bits, order::Msb0}, Cell};
+    /// let mut cell = Cell::new();
+    /// cell.data = bits![u8, Msb0; 1, 0, 1, 0, 0].to_bitvec();
+    /// assert_eq!(cell.data_hex_with_completion_tag(), "A4_");
+    /// ```
+    pub fn data_hex_with_completion_tag(&self) -> String {
+        format!("{:X}", HexWithCompletionTag(&self.data))
+    }
+
+    fn from_bytes_data(bytes: Vec<u8>, bits: usize) -> Result<Self, StringError> {
+        if bytes.len() != bits.div_ceil(8) {
+            return Err(Error::custom(format!(
+                "{bits} bits of data require {} bytes, got {}",
+                bits.div_ceil(8),
+                bytes.len()
+            )));
+        }
+        let mut data = BitVec::from_vec(bytes);
+        data.truncate(bits);
+        Self::try_new(data, Vec::new(), CellType::Ordinary, 0)
+    }
+
+    /// Return [`CellParser`] for this cell with default [`CellParserLimits`]
     #[inline]
     #[must_use]
     pub fn parser(&self) -> CellParser<'_> {
-        CellParser::new(&self.data, &self.references)
+        self.parser_with_limits(CellParserLimits::default())
+    }
+
+    /// Return [`CellParser`] for this cell with given [`CellParserLimits`]
+    #[inline]
+    #[must_use]
+    pub fn parser_with_limits(&self, limits: CellParserLimits) -> CellParser<'_> {
+        CellParser::new(self, limits)
     }
 
     /// Shortcut for [`.parser()`](Cell::parser)[`.parse()`](CellParser::parse)[`.ensure_empty()`](CellParser::ensure_empty).
@@ -106,56 +547,123 @@ impl Cell {
         self.data.is_empty() && self.references.is_empty()
     }
 
+    /// Read first `bits` of [`data`](Cell::data) as big-endian unsigned
+    /// integer without constructing a [parser](Cell::parser).
+    ///
+    /// Returns `None` if `bits` is greater than 64 or the cell has less
+    /// data bits.
+    /// ```rust
+    /// # use tlb::{bits::bitvec::{bits, order::Msb0}, Cell};
+    /// let mut cell = Cell::new();
+    /// cell.data = bits![u8, Msb0; 1, 0, 1, 1].to_bitvec();
+    /// assert_eq!(cell.peek_uint(3), Some(0b101));
+    /// assert_eq!(cell.peek_uint(5), None);
+    /// ```
+    #[inline]
+    pub fn peek_uint(&self, bits: usize) -> Option<u64> {
+        if bits > 64 {
+            return None;
+        }
+        let data = self.data.get(..bits)?;
+        if data.is_empty() {
+            return Some(0);
+        }
+        Some(data.load_be())
+    }
+
+    /// Estimated number of bytes occupied in memory by this cell and all
+    /// cells referenced by it, directly or indirectly.
+    /// Cells shared via the same [`Arc`] are counted only once.
+    #[inline]
+    pub fn memory_usage(&self) -> usize {
+        let mut visited = HashSet::new();
+        self.memory_usage_visited(&mut visited)
+    }
+
+    fn memory_usage_visited(&self, visited: &mut HashSet<*const Self>) -> usize {
+        if !visited.insert(self) {
+            return 0;
+        }
+        self.shallow_memory_usage()
+            + self
+                .references
+                .iter()
+                .map(|r| r.memory_usage_visited(visited))
+                .sum::<usize>()
+    }
+
+    /// Estimated number of bytes occupied in memory by this cell only,
+    /// without its references.
+    #[inline]
+    pub fn shallow_memory_usage(&self) -> usize {
+        size_of::<Self>()
+            + self.data.capacity().div_ceil(8)
+            + self.references.capacity() * size_of::<Arc<Self>>()
+    }
+
     #[inline]
     fn data_bytes(&self) -> (usize, &[u8]) {
         (self.data.len(), self.data.as_raw_slice())
     }
 
     /// See [Cell level](https://docs.ton.org/develop/data-formats/cell-boc#cell-level)
+    ///
+    /// Each cell shared by the same [`Arc`] is visited only once, so this
+    /// is linear in number of distinct cells, use
+    /// [`CellHashCache::level()`] to reuse levels across calls.
     #[inline]
     pub fn level(&self) -> u8 {
-        self.references
-            .iter()
-            .map(Deref::deref)
-            .map(Cell::level)
-            .max()
-            .unwrap_or(0)
+        level_of(self.level_mask())
+    }
+
+    /// See [Level of cells](https://docs.ton.org/develop/data-formats/exotic-cells#level-of-cells)
+    /// and [`.level()`](Cell::level)
+    #[inline]
+    pub fn level_mask(&self) -> u8 {
+        CellHashCache::new().compute_level_mask(self)
     }
 
     /// See [Cell serialization](https://docs.ton.org/develop/data-formats/cell-boc#cell-serialization)
     #[inline]
-    fn refs_descriptor(&self) -> u8 {
-        // TODO: exotic cells
-        self.references.len() as u8 | (self.level() << 5)
+    fn refs_descriptor(&self, level_mask: u8) -> u8 {
+        self.references.len() as u8 | ((self.r#type.is_exotic() as u8) << 3) | (level_mask << 5)
     }
 
     /// See [Cell serialization](https://docs.ton.org/develop/data-formats/cell-boc#cell-serialization)
     #[inline]
     fn bits_descriptor(&self) -> u8 {
         let b = self.data.len();
-        (b / 8) as u8 + ((b + 7) / 8) as u8
+        (b / 8) as u8 + b.div_ceil(8) as u8
     }
 
-    #[inline]
+    #[cfg(test)]
     fn max_depth(&self) -> u16 {
-        self.references
-            .iter()
-            .map(Deref::deref)
-            .map(Cell::max_depth)
-            .max()
-            .map(|d| d + 1)
-            .unwrap_or(0)
+        let mut hashes = CellHashCache::new();
+        let level_mask = hashes.compute_level_mask(self);
+        hashes.compute(self, level_mask).repr_depth()
     }
 
-    /// [Standard Cell representation hash](https://docs.ton.org/develop/data-formats/cell-boc#standard-cell-representation-hash-calculation)
-    fn repr(&self) -> Vec<u8> {
+    /// [Standard Cell representation](https://docs.ton.org/develop/data-formats/cell-boc#standard-cell-representation-hash-calculation)
+    /// with given level mask applied to the level being hashed and already
+    /// known depths and hashes of references at that level.
+    ///
+    /// Data is replaced with the hash of previous level, if any, see
+    /// [Hashes of exotic cells](https://docs.ton.org/develop/data-formats/exotic-cells#hashes-of-exotic-cells).
+    pub(crate) fn repr_with_refs(
+        &self,
+        level_mask: u8,
+        prev_hash: Option<&[u8; 32]>,
+        refs: impl IntoIterator<Item = (u16, [u8; 32])> + Clone,
+    ) -> Vec<u8> {
         let mut buf = Vec::new();
-        buf.push(self.refs_descriptor());
+        buf.push(self.refs_descriptor(level_mask));
         buf.push(self.bits_descriptor());
 
         let rest_bits = self.data.len() % 8;
 
-        if rest_bits == 0 {
+        if let Some(prev_hash) = prev_hash {
+            buf.extend(prev_hash);
+        } else if rest_bits == 0 {
             buf.extend(self.data.as_raw_slice());
         } else {
             let (last, data) = self.data.as_raw_slice().split_last().unwrap();
@@ -168,33 +676,158 @@ impl Cell {
 
         // refs depth
         buf.extend(
-            self.references
-                .iter()
-                .flat_map(|r| r.max_depth().to_be_bytes()),
+            refs.clone()
+                .into_iter()
+                .flat_map(|(depth, _)| depth.to_be_bytes()),
         );
 
         // refs hashes
-        buf.extend(
-            self.references
-                .iter()
-                .map(Deref::deref)
-                .flat_map(Cell::hash),
-        );
+        buf.extend(refs.into_iter().flat_map(|(_, hash)| hash));
 
         buf
     }
 
+    /// Render this cell with all cells reachable from it as a
+    /// [Graphviz](https://graphviz.org) graph in DOT language.
+    ///
+    /// Each node is labeled with a prefix of its [hash](Cell::hash) and
+    /// its bit-length, edges are labeled with indices of references.
+    /// Cells with the same hash are rendered once, and cells shared by
+    /// the same [`Arc`] are hashed once.
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use tlb::Cell;
+    /// let leaf = Arc::new(Cell::new());
+    /// let mut root = Cell::new();
+    /// root.references = vec![leaf.clone(), leaf];
+    /// let dot = root.to_dot();
+    /// assert_eq!(dot.matches("[label=\"96a296d2\\n0 bits\"]").count(), 1);
+    /// ```
+    pub fn to_dot(&self) -> String {
+        use fmt::Write;
+
+        // hash each shared cell only once
+        let mut hashes = CellHashCache::new();
+        let level_mask = hashes.compute_level_mask(self);
+        let root_hash = hashes.compute(self, level_mask).repr_hash();
+
+        let mut ids: HashMap<[u8; 32], usize> = HashMap::new();
+        let mut dot = String::from("digraph cells {\n    node [shape=box];\n");
+        let mut stack = vec![(self, root_hash)];
+        ids.insert(stack[0].1, 0);
+        while let Some((cell, hash)) = stack.pop() {
+            let id = ids[&hash];
+            writeln!(
+                dot,
+                "    c{id} [label=\"{}\\n{} bits\"];",
+                hex::encode(&hash[..4]),
+                cell.data.len(),
+            )
+            .unwrap();
+            for (i, r) in cell.references.iter().enumerate() {
+                let r_hash = hashes.hash(r);
+                let next_id = ids.len();
+                let r_id = *ids.entry(r_hash).or_insert_with(|| {
+                    stack.push((r.as_ref(), r_hash));
+                    next_id
+                });
+                writeln!(dot, "    c{id} -> c{r_id} [label=\"{i}\"];").unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
     /// Calculates [standard Cell representation hash](https://docs.ton.org/develop/data-formats/cell-boc#cell-hash)
+    /// using [`DefaultCellDigest`]
     #[inline]
     pub fn hash(&self) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update(self.repr());
-        hasher.finalize().into()
+        self.hash_with::<DefaultCellDigest>()
+    }
+
+    /// Same as [`.hash()`](Cell::hash), but with given SHA-256
+    /// implementation, see [`hash`](crate::hash) module
+    /// ```rust
+    /// # use tlb::{hash::Sha256, Cell};
+    /// let cell = Cell::new();
+    /// assert_eq!(cell.hash_with::<Sha256>(), cell.hash());
+    /// ```
+    #[inline]
+    pub fn hash_with<D>(&self) -> [u8; 32]
+    where
+        D: CellDigest,
+    {
+        let mut hashes = CellHashCache::<D>::default();
+        let level_mask = hashes.compute_level_mask(self);
+        hashes.compute(self, level_mask).repr_hash()
+    }
+
+    /// Hash of this cell at given level, see
+    /// [Hashes of exotic cells](https://docs.ton.org/develop/data-formats/exotic-cells#hashes-of-exotic-cells).
+    ///
+    /// Hash at level 0 of a cell with pruned branches is the hash of the
+    /// original cell, while at levels starting from [`.level()`](Cell::level)
+    /// it's the [representation hash](Cell::hash).
+    /// ```rust
+    /// # use tlb::Cell;
+    /// let cell = Cell::from_hex_data("deadbeef", 32).unwrap();
+    /// assert_eq!(cell.hash_at(0), cell.hash());
+    /// ```
+    #[inline]
+    pub fn hash_at(&self, level: u8) -> [u8; 32] {
+        let mut hashes = CellHashCache::new();
+        let level_mask = hashes.compute_level_mask(self);
+        hashes.compute(self, level_mask).hash(level)
+    }
+
+    /// Compare cells by their [hashes](Cell::hash) rather than by
+    /// contents of the whole trees, which [`PartialEq`] does.
+    ///
+    /// Note that calculating hash visits the whole tree as well, so use
+    /// [`CellHashCache`](crate::hash::CellHashCache) when comparing many
+    /// cells sharing subtrees.
+    /// ```rust
+    /// # use tlb::Cell;
+    /// let a = Cell::from_hex_data("deadbeef", 32).unwrap();
+    /// let b = Cell::from_hex_data("deadbeef", 32).unwrap();
+    /// assert!(a.eq_by_hash(&b));
+    /// assert!(!a.eq_by_hash(&Cell::new()));
+    /// ```
+    #[inline]
+    pub fn eq_by_hash(&self, other: &Self) -> bool {
+        core::ptr::eq(self, other) || self.hash() == other.hash()
+    }
+}
+
+/// Drop references iteratively, so that dropping very deep trees of cells
+/// (e.g. parsed from adversarial BoCs) does not overflow the stack:
+/// ```rust
+/// # use std::sync::Arc;
+/// # use tlb::Cell;
+/// let mut cell = Arc::new(Cell::new());
+/// for _ in 0..100_000 {
+///     let mut parent = Cell::new();
+///     parent.references.push(cell);
+///     cell = Arc::new(parent);
+/// }
+/// drop(cell);
+/// ```
+impl Drop for Cell {
+    fn drop(&mut self) {
+        let mut stack = mem::take(&mut self.references);
+        while let Some(r) = stack.pop() {
+            if let Some(mut cell) = Arc::into_inner(r) {
+                stack.append(&mut cell.references);
+            }
+        }
     }
 }
 
 impl Debug for Cell {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.r#type.is_exotic() {
+            write!(f, "{:?} ", self.r#type)?;
+        }
         if f.alternate() {
             write!(f, "{}[0b", self.data.len())?;
             for bit in &self.data {
@@ -220,13 +853,67 @@ mod tests {
 
     use crate::{
         bits::{r#as::NBits, ser::BitWriterExt},
-        r#as::{Data, Ref},
+        r#as::{Data, DataRef, Ref},
         ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt},
         tests::assert_store_parse_as_eq,
     };
 
     use super::*;
 
+    #[test]
+    fn hex_with_completion_tag() {
+        for (hex, bits) in [
+            ("", 0),
+            ("8_", 0),
+            ("A_", 2),
+            ("C_", 1),
+            ("0F8A7EA5", 32),
+            ("0f8a7ea58_", 32),
+            ("0F8A7EA5C_", 33),
+        ] {
+            let cell = Cell::from_hex_with_completion_tag(hex).unwrap();
+            assert_eq!(cell.data.len(), bits, "{hex}");
+            let s = cell.data_hex_with_completion_tag();
+            assert_eq!(Cell::from_hex_with_completion_tag(&s).unwrap(), cell);
+        }
+        assert_eq!(
+            Cell::from_hex_with_completion_tag("0F8A7EA5C_")
+                .unwrap()
+                .data_hex_with_completion_tag(),
+            "0F8A7EA5C_"
+        );
+
+        assert!(Cell::from_hex_with_completion_tag("0_").is_err());
+        assert!(Cell::from_hex_with_completion_tag("x").is_err());
+        assert!(Cell::from_hex_with_completion_tag("F".repeat(256)).is_err());
+    }
+
+    #[test]
+    fn drop_deep() {
+        let mut cell = Cell::new();
+        for _ in 0..1_000_000 {
+            cell = Cell {
+                r#type: CellType::Ordinary,
+                data: BitVec::new(),
+                references: vec![cell.into()],
+            };
+        }
+        drop(cell);
+    }
+
+    #[test]
+    fn peek_uint() {
+        let cell = (0x0f8a7ea5u32, 1u8)
+            .wrap_as::<(Data<NBits<32>>, Data<NBits<1>>)>()
+            .to_cell()
+            .unwrap();
+        assert_eq!(cell.peek_uint(0), Some(0));
+        assert_eq!(cell.peek_uint(32), Some(0x0f8a7ea5));
+        assert_eq!(cell.peek_uint(33), Some(0x0f8a7ea5 << 1 | 1));
+        assert_eq!(cell.peek_uint(34), None);
+        assert_eq!(Cell::new().peek_uint(65), None);
+    }
+
     #[test]
     fn zero_depth() {
         assert_eq!(().to_cell().unwrap().max_depth(), 0)
@@ -258,6 +945,18 @@ mod tests {
         >((0b1, 0x0AAAAA, (0x7F, 0x0AAAAA)));
     }
 
+    #[test]
+    fn cell_serde_data_ref() {
+        assert_store_parse_as_eq::<
+            _,
+            (
+                Data<NBits<1>>,
+                DataRef<NBits<24>>,
+                Ref<(Data<NBits<7>>, DataRef<NBits<24>>)>,
+            ),
+        >((0b1, 0x0AAAAA, (0x7F, 0x0AAAAA)));
+    }
+
     #[test]
     fn hash_no_refs() {
         let mut builder = Cell::builder();
@@ -287,4 +986,273 @@ mod tests {
             hex!("f345277cc6cfa747f001367e1e873dcfa8a936b8492431248b7a3eeafa8030e7")
         );
     }
+
+    #[test]
+    fn hash_with_ref_data() {
+        let mut builder = Cell::builder();
+        builder
+            .store_as::<_, Data<NBits<24>>>(0x00000B)
+            .unwrap()
+            .store_ref_data(0x0000000F_u32)
+            .unwrap()
+            .store_ref_data(0x0000000F_u32)
+            .unwrap();
+        let cell = builder.into_cell();
+
+        assert_eq!(
+            cell.hash(),
+            hex!("f345277cc6cfa747f001367e1e873dcfa8a936b8492431248b7a3eeafa8030e7")
+        );
+    }
+
+    #[test]
+    fn try_new() {
+        let leaf = Arc::new(Cell::new());
+        let cell = Cell::try_new(
+            BitVec::repeat(true, Cell::MAX_BITS_LEN),
+            vec![leaf.clone(); Cell::MAX_REFS_COUNT],
+            CellType::Ordinary,
+            0,
+        )
+        .unwrap();
+        assert_eq!(cell.references.len(), Cell::MAX_REFS_COUNT);
+
+        assert!(Cell::try_new(
+            BitVec::new(),
+            vec![leaf.clone(); Cell::MAX_REFS_COUNT + 1],
+            CellType::Ordinary,
+            0,
+        )
+        .is_err());
+        assert!(Cell::try_new(BitVec::new(), Vec::new(), CellType::Ordinary, 1).is_err());
+    }
+
+    #[test]
+    fn try_new_exotic() {
+        let mut library = BitVec::<u8, Msb0>::from_slice(&[2]);
+        library.extend_from_bitslice(BitSlice::<u8, Msb0>::from_slice(&[0; 32]));
+        let cell =
+            Cell::try_new(library.clone(), Vec::new(), CellType::LibraryReference, 0).unwrap();
+        assert_eq!(cell.r#type, CellType::LibraryReference);
+        assert_eq!(cell.level(), 0);
+
+        library.pop();
+        assert_eq!(
+            Cell::try_new(library.clone(), Vec::new(), CellType::LibraryReference, 0)
+                .unwrap_err()
+                .to_string(),
+            "LibraryReference cell must have 264 bits of data, got 263",
+        );
+        assert!(Cell::try_new(library, Vec::new(), CellType::MerkleProof, 0).is_err());
+
+        // pruned branch with level mask 0b010
+        let mut pruned = BitVec::<u8, Msb0>::from_slice(&[1, 0b010]);
+        pruned.resize(16 + 256 + 16, false);
+        assert_eq!(
+            Cell::try_new(pruned.clone(), Vec::new(), CellType::PrunedBranch, 1)
+                .unwrap_err()
+                .to_string(),
+            "invalid level mask: 0b001, expected: 0b010",
+        );
+        let cell = Cell::try_new(pruned, Vec::new(), CellType::PrunedBranch, 0b010).unwrap();
+        assert_eq!(cell.level(), 2);
+    }
+
+    /// Pruned branch of level 1 in place of given cell of level 0
+    fn pruned(cell: &Cell) -> Arc<Cell> {
+        let mut data = BitVec::<u8, Msb0>::from_slice(&[1, 0b001]);
+        data.extend_from_raw_slice(&cell.hash());
+        data.extend_from_raw_slice(&cell.max_depth().to_be_bytes());
+        Arc::new(Cell::try_new(data, Vec::new(), CellType::PrunedBranch, 0b001).unwrap())
+    }
+
+    fn merkle_update_data(old: &Cell, new: &Cell) -> BitVec<u8, Msb0> {
+        let mut data = BitVec::<u8, Msb0>::from_slice(&[4]);
+        data.extend_from_raw_slice(&old.hash());
+        data.extend_from_raw_slice(&new.hash());
+        data.extend_from_raw_slice(&old.max_depth().to_be_bytes());
+        data.extend_from_raw_slice(&new.max_depth().to_be_bytes());
+        data
+    }
+
+    #[test]
+    fn pruned_branch_hash() {
+        let leaf = Cell::from_hex_data("deadbeef", 32).unwrap();
+        let root = Cell {
+            r#type: CellType::Ordinary,
+            data: BitVec::from_element(0xAB),
+            references: vec![Arc::new(Cell::new()), Arc::new(leaf.clone())],
+        };
+
+        let pruned_root = Arc::new(
+            Cell::try_new(
+                root.data.clone(),
+                vec![root.references[0].clone(), pruned(&leaf)],
+                CellType::Ordinary,
+                0b001,
+            )
+            .unwrap(),
+        );
+        assert_eq!(pruned_root.level(), 1);
+        assert_ne!(Cell::hash(&pruned_root), root.hash());
+
+        // hash at level 0 is the one of original cell
+        let info = CellHashCache::new().info(&pruned_root);
+        assert_eq!(info.hash(0), root.hash());
+        assert_eq!(info.depth(0), root.max_depth());
+        assert_eq!(info.hash(1), Cell::hash(&pruned_root));
+    }
+
+    #[test]
+    fn merkle_update() {
+        let leaf = Cell::from_hex_data("deadbeef", 32).unwrap();
+        let old = Cell {
+            r#type: CellType::Ordinary,
+            data: BitVec::from_element(1),
+            references: vec![Arc::new(leaf.clone())],
+        };
+        let new = Cell {
+            r#type: CellType::Ordinary,
+            data: BitVec::from_element(2),
+            references: vec![Arc::new(leaf.clone()), Arc::new(Cell::new())],
+        };
+        // unchanged leaf is pruned in both
+        let refs = vec![
+            Arc::new(
+                Cell::try_new(
+                    old.data.clone(),
+                    vec![pruned(&leaf)],
+                    CellType::Ordinary,
+                    0b001,
+                )
+                .unwrap(),
+            ),
+            Arc::new(
+                Cell::try_new(
+                    new.data.clone(),
+                    vec![pruned(&leaf), Arc::new(Cell::new())],
+                    CellType::Ordinary,
+                    0b001,
+                )
+                .unwrap(),
+            ),
+        ];
+
+        let update = Cell::try_new(
+            merkle_update_data(&old, &new),
+            refs.clone(),
+            CellType::MerkleUpdate,
+            0,
+        )
+        .unwrap();
+        assert_eq!(update.level(), 0);
+        assert_eq!(update.refs_descriptor(0), 0b0000_1010);
+        // type would be lost when copied into ordinary cell
+        assert!(update.to_cell().is_err());
+
+        let mut data = merkle_update_data(&old, &old);
+        assert_eq!(
+            Cell::try_new(data.clone(), refs.clone(), CellType::MerkleUpdate, 0)
+                .unwrap_err()
+                .to_string(),
+            "MerkleUpdate hash mismatch in reference [1]",
+        );
+        data = merkle_update_data(&old, &new);
+        data.set(8 + 2 * 256 + 14, true);
+        assert_eq!(
+            Cell::try_new(data, refs.clone(), CellType::MerkleUpdate, 0)
+                .unwrap_err()
+                .to_string(),
+            "MerkleUpdate depth mismatch in reference [0]",
+        );
+        assert!(Cell::try_new(
+            merkle_update_data(&old, &new),
+            refs.clone(),
+            CellType::MerkleUpdate,
+            0b001
+        )
+        .is_err());
+        assert!(Cell::try_new(
+            merkle_update_data(&old, &new),
+            refs[..1].to_vec(),
+            CellType::MerkleUpdate,
+            0
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn from_hex_data() {
+        assert_eq!(
+            Cell::from_hex_data("0000000F", 32).unwrap().hash(),
+            hex!("57b520dbcb9d135863fc33963cde9f6db2ded1430d88056810a2c9434a3860f9")
+        );
+        assert_eq!(Cell::from_hex_data("", 0).unwrap(), Cell::new());
+        assert!(Cell::from_hex_data("abcdef", 16).is_err());
+        assert!(Cell::from_hex_data("abcdef", 25).is_err());
+        assert!(Cell::from_hex_data("xyz", 8).is_err());
+        assert!(Cell::from_hex_data("ff".repeat(128), 1024).is_err());
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn from_base64_data() {
+        assert_eq!(
+            Cell::from_base64_data("q83v", 24).unwrap(),
+            Cell::from_hex_data("abcdef", 24).unwrap(),
+        );
+        assert!(Cell::from_base64_data("q83v", 16).is_err());
+    }
+
+    #[test]
+    fn to_dot() {
+        let leaf = Arc::new(Cell::from_hex_data("0000000f", 32).unwrap());
+        let cell = Cell {
+            r#type: CellType::Ordinary,
+            data: BitVec::from_element(0xAB),
+            references: vec![leaf.clone(), leaf],
+        };
+        assert_eq!(
+            cell.to_dot(),
+            format!(
+                "digraph cells {{\n    \
+                    node [shape=box];\n    \
+                    c0 [label=\"{}\\n8 bits\"];\n    \
+                    c0 -> c1 [label=\"0\"];\n    \
+                    c0 -> c1 [label=\"1\"];\n    \
+                    c1 [label=\"57b520db\\n32 bits\"];\n\
+                }}\n",
+                hex::encode(&cell.hash()[..4]),
+            ),
+        );
+    }
+
+    #[test]
+    fn to_dot_shared_dag() {
+        // 2^64 paths from root
+        let mut cell = Arc::new(Cell::new());
+        for _ in 0..64 {
+            cell = Arc::new(Cell {
+                r#type: CellType::Ordinary,
+                data: BitVec::new(),
+                references: vec![cell.clone(), cell],
+            });
+        }
+        let dot = cell.to_dot();
+        assert_eq!(dot.matches(" -> ").count(), 2 * 64);
+    }
+
+    #[test]
+    fn memory_usage_shared() {
+        let shared = Arc::new(Cell::new());
+        let cell = Cell {
+            r#type: CellType::Ordinary,
+            data: BitVec::new(),
+            references: vec![shared.clone(), shared.clone()],
+        };
+        assert_eq!(
+            cell.memory_usage(),
+            cell.shallow_memory_usage() + shared.shallow_memory_usage()
+        );
+    }
 }
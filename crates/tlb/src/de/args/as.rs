@@ -1,4 +1,3 @@
-use core::mem::MaybeUninit;
 use std::{rc::Rc, sync::Arc};
 
 use crate::{
@@ -46,18 +45,18 @@ where
         parser: &mut CellParser<'de>,
         args: Self::Args,
     ) -> Result<[T; N], CellParserError<'de>> {
-        let mut arr: [MaybeUninit<T>; N] = unsafe { MaybeUninit::uninit().assume_init() };
-        for a in &mut arr {
-            a.write(parser.parse_as_with::<T, As>(args.clone())?);
-        }
-        Ok(unsafe { arr.as_ptr().cast::<[T; N]>().read() })
+        array_util::try_from_fn(|i| {
+            parser
+                .parse_as_with::<T, As>(args.clone())
+                .with_context(|| format!("[{i}]"))
+        })
     }
 }
 
-impl<'de, 'a: 'de, T, As> CellDeserializeAsWithArgs<'de, Vec<T>> for Vec<As>
+impl<'de, T, As> CellDeserializeAsWithArgs<'de, Vec<T>> for Vec<As>
 where
     As: CellDeserializeAsWithArgs<'de, T>,
-    As::Args: Clone + 'a,
+    As::Args: Clone + 'de,
 {
     type Args = (usize, As::Args);
 
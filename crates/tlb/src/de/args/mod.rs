@@ -1,6 +1,6 @@
 pub mod r#as;
 
-use std::{mem::MaybeUninit, rc::Rc, sync::Arc};
+use std::{rc::Rc, sync::Arc};
 
 use crate::{
     bits::de::BitReaderExt,
@@ -40,11 +40,9 @@ where
         parser: &mut CellParser<'de>,
         args: Self::Args,
     ) -> Result<Self, CellParserError<'de>> {
-        let mut arr: [MaybeUninit<T>; N] = unsafe { MaybeUninit::uninit().assume_init() };
-        for (i, a) in arr.iter_mut().enumerate() {
-            a.write(T::parse_with(parser, args.clone()).with_context(|| format!("[{i}]"))?);
-        }
-        Ok(unsafe { arr.as_ptr().cast::<[T; N]>().read() })
+        array_util::try_from_fn(|i| {
+            T::parse_with(parser, args.clone()).with_context(|| format!("[{i}]"))
+        })
     }
 }
 
@@ -78,10 +76,10 @@ impl_cell_deserialize_with_args_for_tuple!(0:T0,1:T1,2:T2,3:T3,4:T4,5:T5,6:T6,7:
 impl_cell_deserialize_with_args_for_tuple!(0:T0,1:T1,2:T2,3:T3,4:T4,5:T5,6:T6,7:T7,8:T8);
 impl_cell_deserialize_with_args_for_tuple!(0:T0,1:T1,2:T2,3:T3,4:T4,5:T5,6:T6,7:T7,8:T8,9:T9);
 
-impl<'de, 'a: 'de, T> CellDeserializeWithArgs<'de> for Vec<T>
+impl<'de, T> CellDeserializeWithArgs<'de> for Vec<T>
 where
     T: CellDeserializeWithArgs<'de>,
-    T::Args: Clone + 'a,
+    T::Args: Clone + 'de,
 {
     type Args = (usize, T::Args);
 
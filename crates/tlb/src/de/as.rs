@@ -1,7 +1,6 @@
-use core::mem::MaybeUninit;
 use std::{rc::Rc, sync::Arc};
 
-use crate::{either::Either, r#as::AsWrap, ResultExt};
+use crate::{either::Either, r#as::AsWrap, Both, ResultExt};
 
 use super::{CellDeserialize, CellParser, CellParserError};
 
@@ -24,11 +23,7 @@ where
 {
     #[inline]
     fn parse_as(parser: &mut CellParser<'de>) -> Result<[T; N], CellParserError<'de>> {
-        let mut arr: [MaybeUninit<T>; N] = unsafe { MaybeUninit::uninit().assume_init() };
-        for a in &mut arr {
-            a.write(parser.parse_as::<T, As>()?);
-        }
-        Ok(unsafe { arr.as_ptr().cast::<[T; N]>().read() })
+        array_util::try_from_fn(|i| parser.parse_as::<T, As>().with_context(|| format!("[{i}]")))
     }
 }
 
@@ -97,6 +92,33 @@ where
     }
 }
 
+/// Implementation of `Unit`:
+/// ```tlb
+/// unit$_ = Unit;
+/// ```
+impl<'de> CellDeserializeAs<'de, ()> for () {
+    #[inline]
+    fn parse_as(_parser: &mut CellParser<'de>) -> Result<(), CellParserError<'de>> {
+        Ok(())
+    }
+}
+
+/// Implementation of `Both X Y`:
+/// ```tlb
+/// pair$_ {X:Type} {Y:Type} first:X second:Y = Both X Y;
+/// ```
+impl<'de, X, Y, AsX, AsY> CellDeserializeAs<'de, Both<X, Y>> for Both<AsX, AsY>
+where
+    AsX: CellDeserializeAs<'de, X>,
+    AsY: CellDeserializeAs<'de, Y>,
+{
+    #[inline]
+    fn parse_as(parser: &mut CellParser<'de>) -> Result<Both<X, Y>, CellParserError<'de>> {
+        let Both { first, second } = parser.parse::<Both<AsWrap<X, AsX>, AsWrap<Y, AsY>>>()?;
+        Ok(Both::new(first.into_inner(), second.into_inner()))
+    }
+}
+
 /// Implementation of [`Either X Y`](https://docs.ton.org/develop/data-formats/tl-b-types#either):
 /// ```tlb
 /// left$0 {X:Type} {Y:Type} value:X = Either X Y;
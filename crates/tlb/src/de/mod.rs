@@ -5,14 +5,19 @@ mod parser;
 
 pub use self::parser::*;
 
-use core::mem::{self, MaybeUninit};
-use std::{rc::Rc, sync::Arc};
+/// Derive macro for [`CellDeserialize`](trait@CellDeserialize), see [`tlb_derive`]
+#[cfg(feature = "derive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
+pub use tlb_derive::CellDeserialize;
+
+use core::{mem, str};
+use std::{borrow::Cow, rc::Rc, sync::Arc};
 
 use crate::{
     bits::de::BitReaderExt,
     either::Either,
     r#as::{FromInto, Same},
-    Cell, ResultExt,
+    Both, Cell, CellType, Error, ResultExt,
 };
 
 /// A type that can be **de**serialized from [`CellParser`].
@@ -25,6 +30,10 @@ pub trait CellDeserialize<'de>: Sized {
 pub trait CellDeserializeOwned: for<'de> CellDeserialize<'de> {}
 impl<T> CellDeserializeOwned for T where T: for<'de> CellDeserialize<'de> {}
 
+/// Implementation of `Unit`:
+/// ```tlb
+/// unit$_ = Unit;
+/// ```
 impl<'de> CellDeserialize<'de> for () {
     #[inline]
     fn parse(_parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
@@ -38,11 +47,7 @@ where
 {
     #[inline]
     fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
-        let mut arr: [MaybeUninit<T>; N] = unsafe { MaybeUninit::uninit().assume_init() };
-        for a in &mut arr {
-            a.write(T::parse(parser)?);
-        }
-        Ok(unsafe { arr.as_ptr().cast::<[T; N]>().read() })
+        array_util::try_from_fn(|i| T::parse(parser).with_context(|| format!("[{i}]")))
     }
 }
 
@@ -104,6 +109,24 @@ where
     }
 }
 
+/// Implementation of `Both X Y`:
+/// ```tlb
+/// pair$_ {X:Type} {Y:Type} first:X second:Y = Both X Y;
+/// ```
+impl<'de, X, Y> CellDeserialize<'de> for Both<X, Y>
+where
+    X: CellDeserialize<'de>,
+    Y: CellDeserialize<'de>,
+{
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            first: parser.parse().context("first")?,
+            second: parser.parse().context("second")?,
+        })
+    }
+}
+
 /// Implementation of [`Either X Y`](https://docs.ton.org/develop/data-formats/tl-b-types#either):
 /// ```tlb
 /// left$0 {X:Type} {Y:Type} value:X = Either X Y;
@@ -138,12 +161,101 @@ where
     }
 }
 
+/// Text from the rest of cell data, which is borrowed without copying,
+/// so it must be aligned to bytes of the underlying cell data. Use
+/// [`Cow<str>`](Cow) to fall back to copying otherwise:
+/// ```rust
+/// # use tlb::{bits::{de::BitReader, ser::BitWriterExt}, Cell};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut builder = Cell::builder();
+/// builder.pack(false)?.pack(*b"hello")?;
+/// let cell = builder.into_cell();
+///
+/// let mut parser = cell.parser();
+/// parser.skip(1)?;
+/// assert!(parser.parse::<&str>().is_err());
+/// assert_eq!(parser.parse::<std::borrow::Cow<str>>()?, "hello");
+/// # Ok(())
+/// # }
+/// ```
+impl<'de> CellDeserialize<'de> for &'de str {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let bytes = parser
+            .read_bytes_borrowed(rest_bytes(parser)?)?
+            .ok_or_else(|| Error::custom("data is not aligned to bytes"))?;
+        str::from_utf8(bytes).map_err(Error::custom)
+    }
+}
+
+/// Text from the rest of cell data, which is borrowed when possible,
+/// see [`&str`](str)
+impl<'de> CellDeserialize<'de> for Cow<'de, str> {
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        let n = rest_bytes(parser)?;
+        if let Some(bytes) = parser.read_bytes_borrowed(n)? {
+            return str::from_utf8(bytes)
+                .map(Cow::Borrowed)
+                .map_err(Error::custom);
+        }
+        let bytes = parser.read_bitvec(n * 8)?.into_vec();
+        String::from_utf8(bytes)
+            .map(Cow::Owned)
+            .map_err(Error::custom)
+    }
+}
+
+/// Number of whole bytes left in the parser
+#[inline]
+fn rest_bytes<'de>(parser: &CellParser<'de>) -> Result<usize, CellParserError<'de>> {
+    let bits = parser.data.len();
+    if !bits.is_multiple_of(8) {
+        return Err(Error::custom(format!(
+            "data is not a whole number of bytes: {bits} bits"
+        )));
+    }
+    Ok(bits / 8)
+}
+
 impl<'de> CellDeserialize<'de> for Cell {
     #[inline]
     fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        // exotic cells keep their type only when taken as a whole
+        if parser.is_untouched() {
+            return parser.take_cell().cloned();
+        }
         Ok(Self {
+            r#type: CellType::Ordinary,
             data: mem::take(&mut parser.data).to_bitvec(),
             references: mem::take(&mut parser.references).to_vec(),
         })
     }
 }
+
+/// Borrow the whole cell without copying it, which is only possible when
+/// nothing was parsed from it yet, e.g. when it's a reference:
+/// ```rust
+/// # use tlb::{r#as::Ref, Cell};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let mut builder = Cell::builder();
+/// # builder.store_as::<_, Ref>(Cell::from_hex_data("cafe", 16)?)?;
+/// # let cell = builder.into_cell();
+/// let body: &Cell = cell.parse_fully_as::<_, Ref>()?;
+/// assert_eq!(*body, *cell.references[0]);
+/// # Ok(())
+/// # }
+/// ```
+impl<'de> CellDeserialize<'de> for &'de Cell {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        parser.take_cell()
+    }
+}
+
+/// Same as for [`&Cell`](Cell), but only when the cell is a reference, so
+/// it can be cheaply cloned later
+impl<'de> CellDeserialize<'de> for &'de Arc<Cell> {
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        parser.take_shared_cell()
+    }
+}
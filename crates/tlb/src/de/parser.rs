@@ -5,10 +5,13 @@ use tlbits::ResultExt;
 
 use crate::{
     bits::{
-        bitvec::{order::Msb0, slice::BitSlice},
-        de::BitReader,
+        bitvec::{domain::Domain, order::Msb0, slice::BitSlice},
+        de::{r#as::BitUnpackAs, BitReader, BitUnpack},
     },
-    Cell, Error,
+    error::ParseError,
+    hash::CellHashCache,
+    r#as::{DataRef, Same},
+    Cell, CellType, Error,
 };
 
 use super::{
@@ -20,16 +23,55 @@ use super::{
 /// [`Error`] for [`CellParser`]
 pub type CellParserError<'de> = <CellParser<'de> as BitReader>::Error;
 
+/// Limits for [`CellParser`] created with [`Cell::parser_with_limits()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellParserLimits {
+    /// Maximum number of nested references that can be followed
+    /// while parsing.
+    pub max_depth: u16,
+}
+
+impl CellParserLimits {
+    /// Default [`max_depth`](CellParserLimits::max_depth), which is also
+    /// the maximum depth of cells allowed in TON.
+    pub const DEFAULT_MAX_DEPTH: u16 = 1024;
+}
+
+impl Default for CellParserLimits {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+        }
+    }
+}
+
 /// Cell parser created with [`Cell::parser()`].
 pub struct CellParser<'de> {
     pub(super) data: &'de BitSlice<u8, Msb0>,
     pub(super) references: &'de [Arc<Cell>],
+    /// depth budget left for nested references
+    pub(super) max_depth: u16,
+    /// cell being parsed
+    cell: &'de Cell,
+    /// the same cell if it's shared, i.e. parsed from reference
+    shared: Option<&'de Arc<Cell>>,
+    /// whether the cell is a part of Merkle proof body, see
+    /// [`.as_proof_body()`](CellParser::as_proof_body)
+    virtualized: bool,
 }
 
 impl<'de> CellParser<'de> {
     #[inline]
-    pub(crate) const fn new(data: &'de BitSlice<u8, Msb0>, references: &'de [Arc<Cell>]) -> Self {
-        Self { data, references }
+    pub(crate) fn new(cell: &'de Cell, limits: CellParserLimits) -> Self {
+        Self {
+            data: &cell.data,
+            references: &cell.references,
+            max_depth: limits.max_depth,
+            cell,
+            shared: None,
+            virtualized: false,
+        }
     }
 
     /// Parse the value using its [`CellDeserialize`] implementation
@@ -129,22 +171,267 @@ impl<'de> CellParser<'de> {
             .map(|(i, v)| v.with_context(|| format!("[{i}]")))
     }
 
+    /// Parse the value as raw bits from the next reference, i.e. `^X`.
+    /// The child cell is ensured to be fully consumed.
+    #[inline]
+    pub fn parse_ref_data<T>(&mut self) -> Result<T, CellParserError<'de>>
+    where
+        T: BitUnpack,
+    {
+        self.parse_as::<T, DataRef>()
+    }
+
+    /// Parse the value as raw bits from the next reference using an adapter.  
+    /// See [`DataRef`].
+    #[inline]
+    pub fn parse_ref_data_as<T, As>(&mut self) -> Result<T, CellParserError<'de>>
+    where
+        As: BitUnpackAs<T> + ?Sized,
+    {
+        self.parse_as::<T, DataRef<As>>()
+    }
+
+    /// Returns iterator over parsers for all remaining references, which
+    /// are consumed in order.
+    /// ```rust
+    /// # use tlb::{
+    /// #     bits::{de::BitReaderExt, ser::BitWriterExt},
+    /// #     de::CellParserError,
+    /// #     r#as::Ref,
+    /// #     Cell,
+    /// # };
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut builder = Cell::builder();
+    /// # builder.store_many_as::<_, Ref>([1u32, 2, 3].map(|n| {
+    /// #     let mut b = Cell::builder();
+    /// #     b.pack(n).unwrap();
+    /// #     b.into_cell()
+    /// # }))?;
+    /// # let cell = builder.into_cell();
+    /// let mut parser = cell.parser();
+    /// let nums: Vec<u32> = parser
+    ///     .refs()
+    ///     .map(|p| p?.unpack())
+    ///     .collect::<Result<_, CellParserError>>()?;
+    /// assert_eq!(nums, [1, 2, 3]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn refs(&mut self) -> impl Iterator<Item = Result<Self, CellParserError<'de>>> + '_ {
+        iter::from_fn(move || (!self.references.is_empty()).then(|| self.pop_reference_parser()))
+            .enumerate()
+            .map(|(i, p)| p.with_context(|| format!("^[{i}]")))
+    }
+
+    /// Returns iterator that parses values using [`CellDeserialize`]
+    /// implementation from all remaining references, i.e. `^T` each. Every
+    /// child cell is ensured to be fully consumed.
+    /// ```rust
+    /// # use tlb::{r#as::Data, Cell, de::CellParserError};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let mut builder = Cell::builder();
+    /// # for n in [1u32, 2, 3] {
+    /// #     builder.store_ref_data(n)?;
+    /// # }
+    /// # let cell = builder.into_cell();
+    /// let mut parser = cell.parser();
+    /// let nums: Vec<u32> = parser
+    ///     .parse_iter_refs_as::<_, Data>()
+    ///     .collect::<Result<_, CellParserError>>()?;
+    /// assert_eq!(nums, [1, 2, 3]);
+    /// assert!(parser.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn parse_iter_refs<T>(
+        &mut self,
+    ) -> impl Iterator<Item = Result<T, CellParserError<'de>>> + '_
+    where
+        T: CellDeserialize<'de>,
+    {
+        self.parse_iter_refs_as::<_, Same>()
+    }
+
+    /// Returns iterator that parses values using an adapter from all
+    /// remaining references. See [`.parse_iter_refs()`](CellParser::parse_iter_refs).
+    #[inline]
+    pub fn parse_iter_refs_as<T, As>(
+        &mut self,
+    ) -> impl Iterator<Item = Result<T, CellParserError<'de>>> + '_
+    where
+        As: CellDeserializeAs<'de, T> + ?Sized,
+    {
+        iter::from_fn(move || {
+            (!self.references.is_empty()).then(|| {
+                let mut parser = self.pop_reference_parser()?;
+                let v = parser.parse_as::<T, As>()?;
+                parser.ensure_empty()?;
+                Ok(v)
+            })
+        })
+        .enumerate()
+        .map(|(i, v)| v.with_context(|| format!("^[{i}]")))
+    }
+
+    /// Take the whole cell being parsed without copying it, which is only
+    /// possible if nothing was parsed from it yet
+    #[inline]
+    pub(super) fn take_cell(&mut self) -> Result<&'de Cell, CellParserError<'de>> {
+        self.ensure_untouched()?;
+        self.data = Default::default();
+        self.references = &[];
+        Ok(self.cell)
+    }
+
+    /// Same as [`.take_cell()`](CellParser::take_cell), but returns shared
+    /// cell, which is only known when it was parsed from reference
+    #[inline]
+    pub(super) fn take_shared_cell(&mut self) -> Result<&'de Arc<Cell>, CellParserError<'de>> {
+        let shared = self
+            .shared
+            .ok_or_else(|| Error::custom("cell is not shared"))?;
+        self.take_cell()?;
+        Ok(shared)
+    }
+
+    /// Returns whether nothing was parsed from the cell yet
+    #[inline]
+    pub(super) fn is_untouched(&self) -> bool {
+        // data and references can only be consumed from the front
+        self.data.len() == self.cell.data.len()
+            && self.references.len() == self.cell.references.len()
+    }
+
+    #[inline]
+    fn ensure_untouched(&self) -> Result<(), CellParserError<'de>> {
+        if !self.is_untouched() {
+            return Err(Error::custom(
+                "cell can only be borrowed before anything is parsed from it",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Pop next reference as-is without parsing or copying it, see
+    /// [`SharedRef`](crate::r#as::SharedRef)
+    #[inline]
+    pub fn parse_reference(&mut self) -> Result<Arc<Cell>, CellParserError<'de>> {
+        self.pop_reference().cloned()
+    }
+
     #[inline]
     fn pop_reference(&mut self) -> Result<&'de Arc<Cell>, CellParserError<'de>> {
         let (first, rest) = self
             .references
             .split_first()
             .ok_or_else(|| Error::custom("no more references left"))?;
+        if self.virtualized && first.r#type == CellType::PrunedBranch {
+            return Err(Error::custom("cell is pruned from Merkle proof"));
+        }
         self.references = rest;
         Ok(first)
     }
 
+    /// Pop next reference and return parser for it with decremented
+    /// depth budget.
+    #[inline]
+    fn pop_reference_parser(&mut self) -> Result<Self, CellParserError<'de>> {
+        let max_depth = self
+            .max_depth
+            .checked_sub(1)
+            .ok_or_else(|| Error::custom("max depth exceeded"))?;
+        let cell = self.pop_reference()?;
+        Ok(Self {
+            data: &cell.data,
+            references: &cell.references,
+            max_depth,
+            cell,
+            shared: Some(cell),
+            virtualized: self.virtualized,
+        })
+    }
+
+    /// Return parser for the body of Merkle proof being parsed, which
+    /// reads it as the original cell, so that typed values can be parsed
+    /// from the proof as they would be from the original tree.
+    ///
+    /// Hash and depth stored in the proof are checked to match the ones
+    /// of its body, i.e. [hash of the original cell](Cell::hash_at).
+    /// Following references that were pruned from the proof fails rather
+    /// than reading data of pruned branches.
+    /// ```rust
+    /// # use std::sync::Arc;
+    /// # use tlb::{
+    /// #     bits::{
+    /// #         bitvec::{order::Msb0, vec::BitVec},
+    /// #         de::BitReaderExt,
+    /// #         ser::BitWriterExt,
+    /// #     },
+    /// #     r#as::Ref,
+    /// #     Cell, CellType,
+    /// # };
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut builder = Cell::builder();
+    /// builder.pack(0xdeadbeef_u32)?.store_as::<_, Ref>(())?;
+    /// let body = builder.into_cell();
+    ///
+    /// // _ tag:(## 8) { tag = 3 } virtual_hash:bits256 depth:uint16 ^Cell
+    /// let mut data = BitVec::<u8, Msb0>::from_slice(&[3]);
+    /// data.extend_from_raw_slice(&body.hash());
+    /// data.extend_from_raw_slice(&1u16.to_be_bytes());
+    /// let proof = Cell::try_new(data, vec![Arc::new(body)], CellType::MerkleProof, 0)?;
+    ///
+    /// let mut parser = proof.parser();
+    /// let mut body = parser.as_proof_body()?;
+    /// assert_eq!(body.unpack::<u32>()?, 0xdeadbeef);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn as_proof_body(&mut self) -> Result<Self, CellParserError<'de>> {
+        let max_depth = self
+            .max_depth
+            .checked_sub(1)
+            .ok_or_else(|| Error::custom("max depth exceeded"))?;
+        let proof = self.take_cell()?;
+        if proof.r#type != CellType::MerkleProof {
+            return Err(Error::custom(format!(
+                "expected MerkleProof cell, got {:?}",
+                proof.r#type
+            )));
+        }
+        proof
+            .r#type
+            .check(&proof.data, proof.references.len())
+            .and_then(|()| {
+                proof.r#type.check_merkle_references(
+                    &proof.data,
+                    &proof.references,
+                    &mut CellHashCache::new(),
+                )
+            })
+            .map_err(Error::custom)?;
+        let body = &proof.references[0];
+        Ok(Self {
+            data: &body.data,
+            references: &body.references,
+            max_depth,
+            cell: body,
+            shared: Some(body),
+            virtualized: true,
+        })
+    }
+
     #[inline]
     pub(crate) fn parse_reference_as<T, As>(&mut self) -> Result<T, CellParserError<'de>>
     where
         As: CellDeserializeAs<'de, T> + ?Sized,
     {
-        self.pop_reference()?.parse_fully_as::<T, As>()
+        let mut parser = self.pop_reference_parser()?;
+        let v = parser.parse_as::<T, As>()?;
+        parser.ensure_empty()?;
+        Ok(v)
     }
 
     #[inline]
@@ -155,7 +442,55 @@ impl<'de> CellParser<'de> {
     where
         As: CellDeserializeAsWithArgs<'de, T> + ?Sized,
     {
-        self.pop_reference()?.parse_fully_as_with::<T, As>(args)
+        let mut parser = self.pop_reference_parser()?;
+        let v = parser.parse_as_with::<T, As>(args)?;
+        parser.ensure_empty()?;
+        Ok(v)
+    }
+
+    /// Read next `n` bytes without copying them, which is only possible
+    /// when they are aligned to bytes of underlying cell data, i.e. after
+    /// whole bytes were read from the cell so far. Otherwise, returns
+    /// `None` and consumes nothing.
+    /// ```rust
+    /// # use tlb::{
+    /// #     bits::{de::{BitReader, BitReaderExt}, ser::BitWriterExt},
+    /// #     Cell,
+    /// # };
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut builder = Cell::builder();
+    /// builder.pack(0x01_u8)?.pack(*b"hi")?;
+    /// let cell = builder.into_cell();
+    ///
+    /// let mut parser = cell.parser();
+    /// assert_eq!(parser.unpack::<u8>()?, 0x01);
+    /// assert_eq!(parser.read_bytes_borrowed(2)?, Some(b"hi".as_slice()));
+    ///
+    /// let mut parser = cell.parser();
+    /// parser.skip(1)?;
+    /// assert_eq!(parser.read_bytes_borrowed(2)?, None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_bytes_borrowed(
+        &mut self,
+        n: usize,
+    ) -> Result<Option<&'de [u8]>, CellParserError<'de>> {
+        let bits = n
+            .checked_mul(8)
+            .filter(|bits| *bits <= self.data.len())
+            .ok_or_else(|| Error::custom("EOF"))?;
+        let (bytes, rest) = self.data.split_at(bits);
+        let Domain::Region {
+            head: None,
+            body,
+            tail: None,
+        } = bytes.domain()
+        else {
+            return Ok(None);
+        };
+        self.data = rest;
+        Ok(Some(body))
     }
 
     /// Returns whether this parser has no more data and references.
@@ -179,21 +514,21 @@ impl<'de> CellParser<'de> {
 }
 
 impl<'de> BitReader for CellParser<'de> {
-    type Error = <&'de BitSlice<u8, Msb0> as BitReader>::Error;
+    type Error = ParseError;
 
     #[inline]
     fn read_bit(&mut self) -> Result<bool, Self::Error> {
-        self.data.read_bit()
+        self.data.read_bit().map_err(Error::custom)
     }
 
     #[inline]
     fn read_bits_into(&mut self, dst: &mut BitSlice<u8, Msb0>) -> Result<(), Self::Error> {
-        self.data.read_bits_into(dst)
+        self.data.read_bits_into(dst).map_err(Error::custom)
     }
 
     #[inline]
     fn skip(&mut self, n: usize) -> Result<(), Self::Error> {
-        self.data.skip(n)
+        self.data.skip(n).map_err(Error::custom)
     }
 }
 
@@ -203,6 +538,276 @@ impl<'de> CellDeserialize<'de> for CellParser<'de> {
         Ok(Self {
             data: mem::take(&mut parser.data),
             references: mem::take(&mut parser.references),
+            max_depth: parser.max_depth,
+            cell: parser.cell,
+            shared: parser.shared,
+            virtualized: parser.virtualized,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bits::{de::BitReaderExt, r#as::VarNBits, ser::BitWriterExt},
+        r#as::{Data, DataRef, Ref},
+        ser::{CellBuilder, CellBuilderError, CellSerialize, CellSerializeExt},
+    };
+
+    use std::borrow::Cow;
+
+    use super::*;
+
+    /// Chain of nested references
+    struct Nested(Option<Box<Nested>>);
+
+    impl Nested {
+        fn with_depth(depth: u16) -> Self {
+            (0..depth).fold(Self(None), |n, _| Self(Some(n.into())))
+        }
+    }
+
+    impl CellSerialize for Nested {
+        fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+            builder.store_as::<_, Option<Ref>>(self.0.as_deref())?;
+            Ok(())
+        }
+    }
+
+    impl<'de> CellDeserialize<'de> for Nested {
+        fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+            Ok(Self(parser.parse_as::<_, Option<Ref>>()?))
+        }
+    }
+
+    #[test]
+    fn max_depth() {
+        let cell = Nested::with_depth(5).to_cell().unwrap();
+
+        for (max_depth, ok) in [(0, false), (4, false), (5, true), (6, true)] {
+            let mut parser = cell.parser_with_limits(CellParserLimits { max_depth });
+            assert_eq!(
+                parser.parse::<Nested>().is_ok(),
+                ok,
+                "max_depth: {max_depth}"
+            );
+        }
+    }
+
+    #[test]
+    fn refs() {
+        let cell = Nested::with_depth(1).to_cell().unwrap();
+        let mut parser = cell.parser();
+        let _: bool = parser.unpack().unwrap();
+        let nested: Vec<Nested> = parser
+            .refs()
+            .map(|p| p?.parse())
+            .collect::<Result<_, CellParserError>>()
+            .unwrap();
+        assert_eq!(nested.len(), 1);
+        assert!(parser.is_empty());
+
+        let mut parser = cell.parser_with_limits(CellParserLimits { max_depth: 0 });
+        let _: bool = parser.unpack().unwrap();
+        assert!(parser.refs().next().unwrap().is_err());
+    }
+
+    #[test]
+    fn indexed_errors() {
+        let mut builder = Cell::builder();
+        builder.store_ref_data(1u8).unwrap();
+        builder.store_ref_data(()).unwrap();
+        let cell = builder.into_cell();
+
+        assert_eq!(
+            cell.parse_fully_as::<[u8; 2], [DataRef; 2]>()
+                .unwrap_err()
+                .to_string(),
+            "[1]: ^: EOF",
+        );
+        assert_eq!(
+            cell.parser()
+                .parse_as_with::<Vec<u8>, Vec<DataRef<VarNBits>>>((2, 8))
+                .unwrap_err()
+                .to_string(),
+            "[1]: ^: [0]: EOF",
+        );
+    }
+
+    #[test]
+    fn parse_iter_refs() {
+        let mut builder = Cell::builder();
+        builder.pack(true).unwrap();
+        builder.store_ref_data(1u8).unwrap();
+        builder.store_ref_data(2u16).unwrap();
+        let cell = builder.into_cell();
+
+        let mut parser = cell.parser();
+        let _: bool = parser.unpack().unwrap();
+        assert_eq!(
+            parser
+                .parse_iter_refs::<Nested>()
+                .map(|r| r.map(|_| ()).unwrap_err().to_string())
+                .collect::<Vec<_>>(),
+            [
+                "^[0]: more data left: 7 bits, 0 references",
+                "^[1]: more data left: 15 bits, 0 references",
+            ],
+        );
+        assert!(parser.is_empty());
+
+        let mut parser = cell.parser();
+        let _: bool = parser.unpack().unwrap();
+        assert_eq!(
+            parser
+                .parse_iter_refs_as::<u8, Data>()
+                .map(|r| r.map_err(|err| err.to_string()))
+                .collect::<Vec<_>>(),
+            [
+                Ok(1),
+                Err("^[1]: more data left: 8 bits, 0 references".to_string())
+            ],
+        );
+    }
+
+    #[test]
+    fn parse_str() {
+        let mut builder = Cell::builder();
+        builder
+            .pack(0x01_u8)
+            .unwrap()
+            .pack("привет".as_bytes())
+            .unwrap();
+        let cell = builder.into_cell();
+
+        let mut parser = cell.parser();
+        let _: u8 = parser.unpack().unwrap();
+        let s: &str = parser.parse().unwrap();
+        assert_eq!(s, "привет");
+        // borrowed from the cell
+        assert!(cell
+            .data
+            .as_raw_slice()
+            .as_ptr_range()
+            .contains(&s.as_ptr()));
+        assert!(parser.is_empty());
+
+        let mut parser = cell.parser();
+        let _: u8 = parser.unpack().unwrap();
+        let _: bool = parser.unpack().unwrap();
+        assert!(parser.parse::<&str>().is_err());
+
+        let mut builder = Cell::builder();
+        builder.pack([false; 4]).unwrap().pack(*b"hi").unwrap();
+        let cell = builder.into_cell();
+        let mut parser = cell.parser();
+        let _: [bool; 4] = parser.unpack().unwrap();
+        assert!(parser.parse::<&str>().is_err());
+        assert!(matches!(
+            parser.parse::<Cow<str>>().unwrap(),
+            Cow::Owned(s) if s == "hi"
+        ));
+
+        let mut builder = Cell::builder();
+        builder.pack([0xff_u8, 0xfe]).unwrap();
+        assert!(builder.into_cell().parse_fully::<&str>().is_err());
+    }
+
+    #[test]
+    fn parse_borrowed_cell() {
+        let body = Arc::new(Cell::from_hex_data("cafe", 16).unwrap());
+        let mut builder = Cell::builder();
+        builder
+            .pack(0xabu8)
+            .unwrap()
+            .store_reference(body.clone())
+            .unwrap();
+        let cell = builder.into_cell();
+
+        let mut parser = cell.parser();
+        let _: u8 = parser.unpack().unwrap();
+        let got: &Cell = parser.parse_as::<_, Ref>().unwrap();
+        assert!(core::ptr::eq(got, &*body));
+        parser.ensure_empty().unwrap();
+
+        let mut parser = cell.parser();
+        let _: u8 = parser.unpack().unwrap();
+        let got: &Arc<Cell> = parser.parse_as::<_, Ref>().unwrap();
+        assert!(Arc::ptr_eq(got, &body));
+
+        // the whole cell
+        assert!(core::ptr::eq(cell.parse_fully::<&Cell>().unwrap(), &cell));
+        // not a reference
+        assert!(cell.parser().parse::<&Arc<Cell>>().is_err());
+        // partially parsed
+        let mut parser = cell.parser();
+        let _: u8 = parser.unpack().unwrap();
+        assert!(parser.parse::<&Cell>().is_err());
+    }
+
+    #[test]
+    fn as_proof_body() {
+        use crate::bits::bitvec::vec::BitVec;
+
+        let pruned_cell = Cell::from_hex_data("cafe", 16).unwrap();
+        let mut builder = Cell::builder();
+        builder
+            .pack(0xab_u8)
+            .unwrap()
+            .store_as::<_, Ref>(&pruned_cell)
+            .unwrap()
+            .store_as::<_, Ref>(())
+            .unwrap();
+        let original = builder.into_cell();
+
+        // _ tag:(## 8) { tag = 1 } level_mask:(## 8) hash:bits256 depth:uint16
+        let mut data = BitVec::<u8, Msb0>::from_slice(&[1, 0b001]);
+        data.extend_from_raw_slice(&pruned_cell.hash());
+        data.extend_from_raw_slice(&0u16.to_be_bytes());
+        let pruned = Cell::try_new(data, Vec::new(), CellType::PrunedBranch, 0b001).unwrap();
+        let body = Cell::try_new(
+            original.data.clone(),
+            vec![pruned.into(), original.references[1].clone()],
+            CellType::Ordinary,
+            0b001,
+        )
+        .unwrap();
+        assert_eq!(body.hash_at(0), original.hash());
+
+        // _ tag:(## 8) { tag = 3 } virtual_hash:bits256 depth:uint16 ^Cell
+        let mut data = BitVec::<u8, Msb0>::from_slice(&[3]);
+        data.extend_from_raw_slice(&original.hash());
+        data.extend_from_raw_slice(&1u16.to_be_bytes());
+        let proof =
+            Cell::try_new(data, vec![body.clone().into()], CellType::MerkleProof, 0).unwrap();
+
+        let mut parser = proof.parser();
+        let mut body_parser = parser.as_proof_body().unwrap();
+        assert!(parser.is_empty());
+        assert_eq!(body_parser.unpack::<u8>().unwrap(), 0xab);
+        assert_eq!(
+            body_parser.parse_as::<Cell, Ref>().unwrap_err().to_string(),
+            "^: cell is pruned from Merkle proof",
+        );
+
+        // not a proof
+        assert!(body.parser().as_proof_body().is_err());
+        // partially parsed
+        let mut parser = proof.parser();
+        parser.skip(8).unwrap();
+        assert!(parser.as_proof_body().is_err());
+        // stored hash does not match the body
+        let mut forged = proof.clone();
+        let bit = forged.data[8];
+        forged.data.set(8, !bit);
+        assert_eq!(
+            forged
+                .parser()
+                .as_proof_body()
+                .map(|_| ())
+                .unwrap_err()
+                .to_string(),
+            "MerkleProof hash mismatch in reference [0]",
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Errors of building and parsing cells.
+//!
+//! [`CellBuilderError`](crate::ser::CellBuilderError) and
+//! [`CellParserError`](crate::de::CellParserError) are distinct types,
+//! so the phase where failure occurred is preserved even after both are
+//! converted into a common error type:
+//! ```rust
+//! # use tlb::{error::{BuildError, ErrorKind, ParseError}, r#as::Data, Cell};
+//! fn kind(err: &(dyn std::error::Error + 'static)) -> Option<ErrorKind> {
+//!     err.downcast_ref::<BuildError>()
+//!         .map(BuildError::kind)
+//!         .or_else(|| err.downcast_ref::<ParseError>().map(ParseError::kind))
+//! }
+//!
+//! // not enough bits
+//! let err: Box<dyn std::error::Error> = Cell::new()
+//!     .parse_fully_as::<u8, Data>()
+//!     .unwrap_err()
+//!     .into();
+//! assert_eq!(kind(&*err), Some(ErrorKind::Parse));
+//!
+//! // too many bits
+//! let err: Box<dyn std::error::Error> = Cell::builder()
+//!     .store_as::<_, Data>([0u8; 128])
+//!     .err()
+//!     .unwrap()
+//!     .into();
+//! assert_eq!(kind(&*err), Some(ErrorKind::Build));
+//! ```
+use core::fmt::{self, Display};
+use std::error::Error as StdError;
+
+use crate::{Error, StringError};
+
+/// Phase where the error occurred
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// Building cells, see [`BuildError`]
+    Build,
+    /// Parsing cells, see [`ParseError`]
+    Parse,
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Build => "build",
+            Self::Parse => "parse",
+        })
+    }
+}
+
+macro_rules! phase_error {
+    ($(#[$meta:meta])* $name:ident: $kind:ident) => {
+        $(#[$meta])*
+        #[derive(Debug)]
+        pub struct $name(StringError);
+
+        impl $name {
+            /// Phase where the error occurred
+            #[inline]
+            pub const fn kind(&self) -> ErrorKind {
+                ErrorKind::$kind
+            }
+        }
+
+        impl Display for $name {
+            #[inline]
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                Display::fmt(&self.0, f)
+            }
+        }
+
+        impl StdError for $name {}
+
+        impl Error for $name {
+            #[inline]
+            fn custom<T>(msg: T) -> Self
+            where
+                T: Display,
+            {
+                Self(StringError::custom(msg))
+            }
+
+            #[inline]
+            fn context<C>(self, context: C) -> Self
+            where
+                C: Display,
+            {
+                Self(self.0.context(context))
+            }
+        }
+    };
+}
+
+phase_error! {
+    /// Error of building cells, i.e.
+    /// [`CellBuilderError`](crate::ser::CellBuilderError)
+    BuildError: Build
+}
+
+phase_error! {
+    /// Error of parsing cells, i.e.
+    /// [`CellParserError`](crate::de::CellParserError)
+    ParseError: Parse
+}
@@ -0,0 +1,384 @@
+//! Backends for [cell hash](crate::Cell::hash) calculation.
+//!
+//! Cell hashes are always SHA-256, but its implementation can be chosen:
+//! any [`Digest`] with 256-bit output can be passed to
+//! [`Cell::hash_with()`](crate::Cell::hash_with), while
+//! [`Cell::hash()`](crate::Cell::hash) uses [`DefaultCellDigest`].
+//!
+//! With `ring` feature enabled, [`DefaultCellDigest`] is [`RingSha256`],
+//! which uses accelerated implementation from [`ring`](https://docs.rs/ring)
+//! and significantly speeds up hashing of large cell trees.
+pub use sha2::{digest, Sha256};
+
+use core::marker::PhantomData;
+use std::{collections::HashMap, sync::Arc};
+
+use bitvec::field::BitField;
+use sha2::digest::{consts::U32, Digest, OutputSizeUser};
+
+use crate::{cell::level_of, Cell, CellType};
+
+/// SHA-256 [`Digest`] which can be used to calculate cell hashes, see
+/// [`Cell::hash_with()`](crate::Cell::hash_with)
+pub trait CellDigest: Digest + OutputSizeUser<OutputSize = U32> {}
+
+impl<D> CellDigest for D where D: Digest + OutputSizeUser<OutputSize = U32> {}
+
+/// [`CellDigest`] used by [`Cell::hash()`](crate::Cell::hash)
+#[cfg(not(feature = "ring"))]
+pub type DefaultCellDigest = Sha256;
+
+/// [`CellDigest`] used by [`Cell::hash()`](crate::Cell::hash)
+#[cfg(feature = "ring")]
+pub type DefaultCellDigest = RingSha256;
+
+#[cfg(feature = "ring")]
+pub use self::ring_sha256::RingSha256;
+
+#[cfg(feature = "ring")]
+mod ring_sha256 {
+    use core::fmt::{self, Debug};
+
+    use ring::digest::{Context, SHA256};
+    use sha2::digest::{consts::U32, FixedOutput, HashMarker, Output, OutputSizeUser, Update};
+
+    /// SHA-256 [`Digest`](sha2::Digest) backed by [`ring`](https://docs.rs/ring)
+    #[derive(Clone)]
+    pub struct RingSha256(Context);
+
+    impl Default for RingSha256 {
+        #[inline]
+        fn default() -> Self {
+            Self(Context::new(&SHA256))
+        }
+    }
+
+    impl Debug for RingSha256 {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("RingSha256")
+        }
+    }
+
+    impl HashMarker for RingSha256 {}
+
+    impl OutputSizeUser for RingSha256 {
+        type OutputSize = U32;
+    }
+
+    impl Update for RingSha256 {
+        #[inline]
+        fn update(&mut self, data: &[u8]) {
+            self.0.update(data);
+        }
+    }
+
+    impl FixedOutput for RingSha256 {
+        #[inline]
+        fn finalize_into(self, out: &mut Output<Self>) {
+            out.copy_from_slice(self.0.finish().as_ref());
+        }
+    }
+}
+
+/// Cache of [cell hashes](crate::Cell::hash) for trees with shared
+/// subtrees, where each cell referred to by the same [`Arc`] is hashed
+/// only once.
+///
+/// [Levels](crate::Cell::level) and depths of cells are cached along
+/// with their hashes, since they are needed to calculate hashes of
+/// parent cells.
+///
+/// Levels are calculated separately from hashes, so looking them up
+/// (e.g. when building cells bottom-up with
+/// [`Cell::try_new_with_cache()`](crate::Cell::try_new_with_cache)) does
+/// not require hashing.
+///
+/// Cached cells are kept alive until the cache is dropped.
+/// ```rust
+/// # use std::sync::Arc;
+/// # use tlb::{hash::CellHashCache, Cell};
+/// let leaf = Arc::new(Cell::from_hex_data("deadbeef", 32).unwrap());
+/// let mut root = Cell::new();
+/// root.references = vec![leaf.clone(), leaf];
+/// let root = Arc::new(root);
+///
+/// let mut hashes = CellHashCache::new();
+/// assert_eq!(hashes.hash(&root), root.hash());
+/// assert_eq!(hashes.level(&root), 0);
+/// assert_eq!(hashes.len(), 2);
+/// ```
+pub struct CellHashCache<D = DefaultCellDigest> {
+    /// cells are held, so that their addresses can not be reused
+    cells: HashMap<*const Cell, CachedCell>,
+    _digest: PhantomData<D>,
+}
+
+struct CachedCell {
+    /// only held to keep the address alive
+    _cell: Arc<Cell>,
+    level_mask: u8,
+    /// calculated on demand
+    hash: Option<CellHashInfo>,
+}
+
+/// Depths and hashes of the cell at each of its significant levels, see
+/// [Hashes of exotic cells](https://docs.ton.org/develop/data-formats/exotic-cells#hashes-of-exotic-cells)
+#[derive(Clone, Copy)]
+pub(crate) struct CellHashInfo {
+    level_mask: u8,
+    depths: [u16; 4],
+    hashes: [[u8; 32]; 4],
+}
+
+impl CellHashInfo {
+    /// Index of depth and hash at given level
+    #[inline]
+    fn index(&self, level: u8) -> usize {
+        (self.level_mask as u32 & ((1 << level.min(3)) - 1)).count_ones() as usize
+    }
+
+    /// Hash at given level, where all levels above the level of the cell
+    /// have its representation hash
+    #[inline]
+    pub fn hash(&self, level: u8) -> [u8; 32] {
+        self.hashes[self.index(level)]
+    }
+
+    /// Depth at given level, see [`.hash()`](CellHashInfo::hash)
+    #[inline]
+    pub fn depth(&self, level: u8) -> u16 {
+        self.depths[self.index(level)]
+    }
+
+    #[inline]
+    pub fn repr_hash(&self) -> [u8; 32] {
+        self.hash(level_of(self.level_mask))
+    }
+
+    #[cfg(test)]
+    #[inline]
+    pub fn repr_depth(&self) -> u16 {
+        self.depth(level_of(self.level_mask))
+    }
+}
+
+impl CellHashCache {
+    /// Create cache using [`DefaultCellDigest`], use
+    /// [`Default`] for other [`CellDigest`]s
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<D> CellHashCache<D>
+where
+    D: CellDigest,
+{
+    /// Number of cached cells
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Returns [hash](crate::Cell::hash_with) of the cell, calculating it
+    /// for not yet cached cells of its tree
+    #[inline]
+    pub fn hash(&mut self, cell: &Arc<Cell>) -> [u8; 32] {
+        self.info(cell).repr_hash()
+    }
+
+    /// Returns [level](crate::Cell::level) of the cell, calculating it
+    /// for not yet cached cells of its tree
+    #[inline]
+    pub fn level(&mut self, cell: &Arc<Cell>) -> u8 {
+        level_of(self.level_mask(cell))
+    }
+
+    /// Returns [level mask](crate::Cell::level_mask) of the cell, see
+    /// [`.level()`](CellHashCache::level)
+    pub fn level_mask(&mut self, cell: &Arc<Cell>) -> u8 {
+        if let Some(cached) = self.cells.get(&Arc::as_ptr(cell)) {
+            return cached.level_mask;
+        }
+        let level_mask = self.compute_level_mask(cell);
+        self.cells.insert(
+            Arc::as_ptr(cell),
+            CachedCell {
+                _cell: cell.clone(),
+                level_mask,
+                hash: None,
+            },
+        );
+        level_mask
+    }
+
+    /// Calculate level mask of given cell, which is not cached itself,
+    /// since it is not necessarily held in [`Arc`], while its references
+    /// are.
+    pub(crate) fn compute_level_mask(&mut self, cell: &Cell) -> u8 {
+        let refs_mask = cell
+            .references
+            .iter()
+            .fold(0, |mask, r| mask | self.level_mask(r));
+        cell.r#type.level_mask(&cell.data, refs_mask)
+    }
+
+    pub(crate) fn info(&mut self, cell: &Arc<Cell>) -> CellHashInfo {
+        let level_mask = self.level_mask(cell);
+        let cached = &self.cells[&Arc::as_ptr(cell)];
+        if let Some(info) = cached.hash {
+            return info;
+        }
+        let info = self.compute(cell, level_mask);
+        self.cells
+            .get_mut(&Arc::as_ptr(cell))
+            .expect("level mask is cached")
+            .hash = Some(info);
+        info
+    }
+
+    /// Calculate depths and hashes of given cell with given level mask,
+    /// see [`.compute_level_mask()`](CellHashCache::compute_level_mask)
+    pub(crate) fn compute(&mut self, cell: &Cell, level_mask: u8) -> CellHashInfo {
+        let refs: Vec<CellHashInfo> = cell.references.iter().map(|r| self.info(r)).collect();
+        let mut info = CellHashInfo {
+            level_mask,
+            depths: [0; 4],
+            hashes: [[0; 32]; 4],
+        };
+
+        // pruned branch stores hashes and depths of all levels but its own
+        let stored = if cell.r#type == CellType::PrunedBranch {
+            let stored = level_mask.count_ones() as usize;
+            // tag:(## 8) level_mask:(## 8) hashes:(n * bits256) depths:(n * uint16)
+            let (hashes, depths) = cell.data[16.min(cell.data.len())..]
+                .split_at((stored * 256).min(cell.data.len().saturating_sub(16)));
+            for (i, hash) in hashes.chunks_exact(256).enumerate() {
+                for (byte, bits) in info.hashes[i].iter_mut().zip(hash.chunks(8)) {
+                    *byte = bits.load_be();
+                }
+            }
+            for (i, depth) in depths.chunks_exact(16).take(stored).enumerate() {
+                info.depths[i] = depth.load_be();
+            }
+            stored
+        } else {
+            0
+        };
+
+        // references of Merkle cells are one level deeper
+        let refs_level_offset = cell.r#type.is_merkle() as u8;
+        let mut hash_i = 0;
+        for level in 0..=level_of(level_mask) {
+            // only levels present in mask are significant
+            if level != 0 && level_mask & (1 << (level - 1)) == 0 {
+                continue;
+            }
+            if hash_i < stored {
+                hash_i += 1;
+                continue;
+            }
+            let refs_level = level + refs_level_offset;
+            info.depths[hash_i] = refs
+                .iter()
+                .map(|r| r.depth(refs_level) + 1)
+                .max()
+                .unwrap_or(0);
+            let prev_hash = (hash_i > stored).then(|| &info.hashes[hash_i - 1]);
+            let hash = D::digest(
+                cell.repr_with_refs(
+                    level_mask & ((1 << level) - 1),
+                    prev_hash,
+                    refs.iter()
+                        .map(|r| (r.depth(refs_level), r.hash(refs_level))),
+                ),
+            )
+            .into();
+            info.hashes[hash_i] = hash;
+            hash_i += 1;
+        }
+        info
+    }
+}
+
+impl<D> Default for CellHashCache<D>
+where
+    D: CellDigest,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            cells: HashMap::new(),
+            _digest: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{bits::ser::BitWriterExt, r#as::Ref, Cell};
+
+    use super::*;
+
+    fn cell() -> Cell {
+        let mut builder = Cell::builder();
+        builder.pack(0xdeadbeefu32).unwrap();
+        builder.store_as::<_, Ref>(Cell::new()).unwrap();
+        builder.into_cell()
+    }
+
+    #[test]
+    fn default_cell_digest() {
+        let cell = cell();
+        assert_eq!(cell.hash(), cell.hash_with::<Sha256>());
+        assert_eq!(cell.hash(), cell.hash_with::<DefaultCellDigest>());
+    }
+
+    #[cfg(feature = "ring")]
+    #[test]
+    fn ring_sha256() {
+        let cell = cell();
+        assert_eq!(cell.hash_with::<RingSha256>(), cell.hash_with::<Sha256>());
+    }
+
+    #[test]
+    fn cell_hash_cache() {
+        let leaf = Arc::new(cell());
+        let mut root = Arc::new(Cell::new());
+        for _ in 0..4 {
+            let mut parent = Cell::new();
+            parent.references = vec![root.clone(), leaf.clone(), root];
+            root = Arc::new(parent);
+        }
+
+        let mut hashes = CellHashCache::<Sha256>::default();
+        assert_eq!(hashes.hash(&root), root.hash_with::<Sha256>());
+        // leaf, its reference, empty cell and 4 levels
+        assert_eq!(hashes.len(), 7);
+        assert_eq!(hashes.hash(&leaf), leaf.hash());
+        assert_eq!(hashes.len(), 7);
+    }
+
+    #[test]
+    fn cell_hash_cache_shared_dag() {
+        // each cell references the next one 4 times, so there are 4^64
+        // paths from the root, but only 65 unique cells
+        let mut root = Arc::new(Cell::new());
+        for _ in 0..64 {
+            let mut parent = Cell::new();
+            parent.references = vec![root; 4];
+            root = Arc::new(parent);
+        }
+
+        let mut hashes = CellHashCache::<Sha256>::default();
+        assert_eq!(hashes.hash(&root), root.hash_with::<Sha256>());
+        assert_eq!(hashes.level(&root), 0);
+        assert_eq!(hashes.len(), 65);
+    }
+}
@@ -0,0 +1,218 @@
+use core::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+};
+use std::sync::{Arc, OnceLock};
+
+use crate::{
+    de::{
+        r#as::{CellDeserializeAs, CellDeserializeAsOwned},
+        CellDeserialize, CellParser, CellParserError,
+    },
+    r#as::{Same, SharedRef},
+    ser::{r#as::CellSerializeAs, CellBuilder, CellBuilderError, CellSerialize},
+    Cell,
+};
+
+/// Value stored in a reference to the child cell, i.e. `^T`, which is
+/// decoded using an adapter only when accessed.
+///
+/// The original cell is kept on parse and stored back untouched, so parts
+/// of the tree that are not understood (or not needed) are preserved
+/// bit-exactly:
+/// ```rust
+/// # use tlb::{r#as::Data, Cell, Lazy};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut builder = Cell::builder();
+/// builder.store(Lazy::<_, Data>::new(0xdeadbeef_u32)?)?;
+/// # let cell = builder.into_cell();
+///
+/// let lazy: Lazy<u32, Data> = cell.parse_fully()?;
+/// assert_eq!(lazy.cell().data.len(), 32);
+/// assert_eq!(*lazy.get()?, 0xdeadbeef);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Lazy<T, As: ?Sized = Same> {
+    cell: Arc<Cell>,
+    value: OnceLock<T>,
+    _phantom: PhantomData<As>,
+}
+
+impl<T, As> Lazy<T, As>
+where
+    As: ?Sized,
+{
+    /// Serialize the value into a new cell, which is then used as-is
+    #[inline]
+    pub fn new(value: T) -> Result<Self, CellBuilderError>
+    where
+        As: CellSerializeAs<T>,
+    {
+        let mut builder = Cell::builder();
+        As::store_as(&value, &mut builder)?;
+        Ok(Self {
+            cell: builder.into_cell().into(),
+            value: value.into(),
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Wrap already serialized cell without decoding it
+    #[inline]
+    pub fn from_cell(cell: impl Into<Arc<Cell>>) -> Self {
+        Self {
+            cell: cell.into(),
+            value: OnceLock::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Original cell
+    #[inline]
+    pub fn cell(&self) -> &Arc<Cell> {
+        &self.cell
+    }
+
+    /// Unwrap original cell
+    #[inline]
+    pub fn into_cell(self) -> Arc<Cell> {
+        self.cell
+    }
+
+    /// Decode the value from the cell each time it's called, so it can
+    /// borrow from the cell
+    #[inline]
+    pub fn parse<'de>(&'de self) -> Result<T, CellParserError<'de>>
+    where
+        As: CellDeserializeAs<'de, T>,
+    {
+        self.cell.parse_fully_as::<T, As>()
+    }
+
+    /// Decode the value on first access and return cached one afterwards
+    pub fn get(&self) -> Result<&T, CellParserError<'_>>
+    where
+        As: CellDeserializeAsOwned<T>,
+    {
+        if let Some(v) = self.value.get() {
+            return Ok(v);
+        }
+        let v = self.cell.parse_fully_as::<T, As>()?;
+        Ok(self.value.get_or_init(|| v))
+    }
+}
+
+impl<T, As> Debug for Lazy<T, As>
+where
+    T: Debug,
+    As: ?Sized,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lazy")
+            .field("cell", &self.cell)
+            .field("value", &self.value.get())
+            .finish()
+    }
+}
+
+impl<T, As> Clone for Lazy<T, As>
+where
+    T: Clone,
+    As: ?Sized,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            cell: self.cell.clone(),
+            value: self.value.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, As> PartialEq for Lazy<T, As>
+where
+    As: ?Sized,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.cell == other.cell
+    }
+}
+
+impl<T, As> Eq for Lazy<T, As> where As: ?Sized {}
+
+impl<T, As> CellSerialize for Lazy<T, As>
+where
+    As: ?Sized,
+{
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder.store_as::<_, &SharedRef>(&self.cell)?;
+        Ok(())
+    }
+}
+
+impl<'de, T, As> CellDeserialize<'de> for Lazy<T, As>
+where
+    As: ?Sized,
+{
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        parser.parse_as::<_, SharedRef>().map(Self::from_cell)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bits::ser::BitWriterExt,
+        r#as::{Data, Ref},
+        ser::CellSerializeExt,
+    };
+
+    use super::*;
+
+    #[test]
+    fn lazy_preserves_cell() {
+        let mut inner = Cell::builder();
+        inner
+            .pack(0xdeadbeef_u32)
+            .unwrap()
+            // unknown trailing bits and subtree
+            .pack(0x7_u8)
+            .unwrap()
+            .store_as::<_, Ref>(Cell::from_hex_data("cafe", 16).unwrap())
+            .unwrap();
+        let inner = inner.into_cell();
+        let mut builder = Cell::builder();
+        builder.store_as::<_, Ref>(&inner).unwrap();
+        let cell = builder.into_cell();
+
+        let lazy: Lazy<u32, Data> = cell.parse_fully().unwrap();
+        // decoding requires the whole cell to be consumed
+        assert!(lazy.get().is_err());
+        assert!(lazy.parse().is_err());
+        assert_eq!(**lazy.cell(), inner);
+        // shared, not copied
+        assert!(Arc::ptr_eq(lazy.cell(), &cell.references[0]));
+        assert!(Arc::ptr_eq(
+            &lazy.to_cell().unwrap().references[0],
+            &cell.references[0]
+        ));
+    }
+
+    #[test]
+    fn lazy_get_cached() {
+        let lazy = Lazy::<_, Data>::new(0xdeadbeef_u32).unwrap();
+        assert_eq!(*lazy.get().unwrap(), 0xdeadbeef);
+
+        let cell = lazy.to_cell().unwrap();
+        assert_eq!(cell.references.len(), 1);
+        let got: Lazy<u32, Data> = cell.parse_fully().unwrap();
+        assert_eq!(got, lazy);
+        assert_eq!(got.parse().unwrap(), 0xdeadbeef);
+        assert!(core::ptr::eq(got.get().unwrap(), got.get().unwrap()));
+    }
+}
@@ -10,11 +10,10 @@
 //! Let's first define a struct `Hello` that holds these parameters:
 //!
 //! ```rust
-//! # use num_bigint::BigUint;
 //! # use tlb::Cell;
 //! struct Hello {
 //!     pub query_id: u64,
-//!     pub amount: BigUint,
+//!     pub amount: u128,
 //!     pub payload: Option<Cell>,
 //! }
 //! ```
@@ -25,18 +24,16 @@
 //! [`CellSerialize`](crate::ser::CellSerialize) on it:
 //!
 //! ```
-//! # use num_bigint::BigUint;
 //! # use tlb::{
 //! #   r#as::Ref,
 //! #   bits::{r#as::{NBits, VarInt}, ser::BitWriterExt},
 //! #   Cell,
 //! #   ser::{CellSerialize, CellBuilder, CellBuilderError},
-//! #   StringError,
 //! # };
 //! #
 //! # struct Hello {
 //! #     pub query_id: u64,
-//! #     pub amount: BigUint,
+//! #     pub amount: u128,
 //! #     pub payload: Option<Cell>,
 //! # }
 //! impl CellSerialize for Hello {
@@ -54,13 +51,13 @@
 //!     }
 //! }
 //!
-//! # fn main() -> Result<(), StringError> {
+//! # fn main() -> Result<(), CellBuilderError> {
 //! // create a builder
 //! let mut builder = Cell::builder();
 //! // serialize value into builder
 //! builder.store(Hello {
 //!     query_id: 0,
-//!     amount: 1_000u64.into(),
+//!     amount: 1_000,
 //!     payload: None,
 //! })?;
 //! // convert builder into cell
@@ -75,7 +72,6 @@
 //! [`CellDeserialize`](crate::de::CellDeserialize) on it:
 //!
 //! ```rust
-//! # use num_bigint::BigUint;
 //! # use tlb::{
 //! #   r#as::{Ref, ParseFully},
 //! #   bits::{r#as::{NBits, VarInt}, de::BitReaderExt, ser::BitWriterExt},
@@ -83,12 +79,11 @@
 //! #   de::{CellDeserialize, CellParser, CellParserError},
 //! #   Error,
 //! #   ser::{CellSerialize, CellBuilder, CellBuilderError},
-//! #   StringError,
 //! # };
 //! # #[derive(Debug, PartialEq)]
 //! # struct Hello {
 //! #     pub query_id: u64,
-//! #     pub amount: BigUint,
+//! #     pub amount: u128,
 //! #     pub payload: Option<Cell>,
 //! # }
 //! # impl CellSerialize for Hello {
@@ -123,10 +118,10 @@
 //!     }
 //! }
 //!
-//! # fn main() -> Result<(), StringError> {
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
 //! # let orig = Hello {
 //! #     query_id: 0,
-//! #     amount: 1_000u64.into(),
+//! #     amount: 1_000,
 //! #     payload: None,
 //! # };
 //! # let mut builder = Cell::builder();
@@ -138,14 +133,20 @@
 //! # Ok(())
 //! # }
 //! ```
+pub mod aliases;
 pub mod r#as;
 mod cell;
 pub mod de;
+pub mod error;
+pub mod hash;
+mod lazy;
+mod rest;
 pub mod ser;
+pub mod tag;
 
-pub use self::cell::*;
+pub use self::{cell::*, lazy::*, rest::*};
 
-pub use tlbits::{self as bits, either, Error, ResultExt, StringError};
+pub use tlbits::{self as bits, either, Both, Error, ResultExt, StringError};
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,171 @@
+use impl_tools::autoimpl;
+
+use crate::{
+    de::{r#as::CellDeserializeAs, CellDeserialize, CellParser, CellParserError},
+    r#as::AsWrap,
+    ser::{r#as::CellSerializeAs, CellBuilder, CellBuilderError, CellSerialize},
+    Cell, ResultExt,
+};
+
+/// Value followed by the rest of the cell, i.e. bits and references left
+/// after parsing `T`, which are appended back as-is on serialization.
+///
+/// This allows to read-modify-write cells where only leading fields are
+/// modeled, e.g. data of contracts. `WithRest<As>` can also be used as an
+/// adapter for `WithRest<T>`:
+/// ```rust
+/// # use tlb::{
+/// #     bits::{de::BitReaderExt, ser::BitWriterExt},
+/// #     r#as::{Data, Ref},
+/// #     ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt},
+/// #     Cell, WithRest,
+/// # };
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut builder = Cell::builder();
+/// builder
+///     // seqno:uint32
+///     .pack(1u32)?
+///     // fields we don't know about
+///     .pack(0xabu8)?
+///     .store_as::<_, Ref>(Cell::new())?;
+/// let data = builder.into_cell();
+///
+/// let mut seqno: WithRest<u32> = data.parse_fully_as::<_, WithRest<Data>>()?;
+/// *seqno += 1;
+///
+/// let updated = seqno.wrap_as::<WithRest<Data>>().to_cell()?;
+/// assert_eq!(updated.data[32..], data.data[32..]);
+/// assert_eq!(updated.references, data.references);
+/// assert_eq!(updated.parser().unpack::<u32>()?, 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[autoimpl(Deref using self.value)]
+#[autoimpl(DerefMut using self.value)]
+pub struct WithRest<T> {
+    pub value: T,
+    /// Bits and references left after `value`
+    pub rest: Cell,
+}
+
+impl<T> WithRest<T> {
+    #[inline]
+    pub const fn new(value: T, rest: Cell) -> Self {
+        Self { value, rest }
+    }
+
+    /// Unwrap inner value discarding the rest
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> From<T> for WithRest<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self::new(value, Cell::new())
+    }
+}
+
+impl<T> CellSerialize for WithRest<T>
+where
+    T: CellSerialize,
+{
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .store(&self.value)?
+            .store(&self.rest)
+            .context("rest")?;
+        Ok(())
+    }
+}
+
+impl<'de, T> CellDeserialize<'de> for WithRest<T>
+where
+    T: CellDeserialize<'de>,
+{
+    #[inline]
+    fn parse(parser: &mut CellParser<'de>) -> Result<Self, CellParserError<'de>> {
+        Ok(Self {
+            value: parser.parse()?,
+            rest: parser.parse()?,
+        })
+    }
+}
+
+impl<T, As> CellSerializeAs<WithRest<T>> for WithRest<As>
+where
+    As: CellSerializeAs<T>,
+{
+    #[inline]
+    fn store_as(source: &WithRest<T>, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder.store(WithRest::new(
+            AsWrap::<&T, As>::new(&source.value),
+            // cheap, since only references to children are cloned
+            source.rest.clone(),
+        ))?;
+        Ok(())
+    }
+}
+
+impl<'de, T, As> CellDeserializeAs<'de, WithRest<T>> for WithRest<As>
+where
+    As: CellDeserializeAs<'de, T>,
+{
+    #[inline]
+    fn parse_as(parser: &mut CellParser<'de>) -> Result<WithRest<T>, CellParserError<'de>> {
+        let WithRest { value, rest } = parser.parse::<WithRest<AsWrap<T, As>>>()?;
+        Ok(WithRest::new(value.into_inner(), rest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bits::{de::BitReaderExt, ser::BitWriterExt},
+        r#as::{Data, Ref},
+        ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt},
+    };
+
+    use super::*;
+
+    fn data() -> Cell {
+        let mut builder = Cell::builder();
+        builder
+            .pack(1u32)
+            .unwrap()
+            .pack(0b101u8)
+            .unwrap()
+            .store_as::<_, Ref>(Cell::from_hex_data("cafe", 16).unwrap())
+            .unwrap();
+        builder.into_cell()
+    }
+
+    #[test]
+    fn with_rest_round_trip() {
+        let data = data();
+        let got: WithRest<u32> = data.parse_fully_as::<_, WithRest<Data>>().unwrap();
+        assert_eq!(*got, 1);
+        assert_eq!(got.rest.data.len(), 8);
+        assert_eq!(got.rest.references, data.references);
+        assert_eq!(got.wrap_as::<WithRest<Data>>().to_cell().unwrap(), data);
+
+        // everything is left
+        let got: WithRest<()> = data.parse_fully().unwrap();
+        assert_eq!(got.rest, data);
+        assert_eq!(got.to_cell().unwrap(), data);
+    }
+
+    #[test]
+    fn with_rest_modify() {
+        let mut got: WithRest<u32> = data().parse_fully_as::<_, WithRest<Data>>().unwrap();
+        *got = 2;
+        let cell = got.wrap_as::<WithRest<Data>>().to_cell().unwrap();
+        assert_eq!(cell.parser().unpack::<u32>().unwrap(), 2);
+        assert_eq!(cell.data[32..], data().data[32..]);
+        assert_eq!(cell.references, data().references);
+    }
+}
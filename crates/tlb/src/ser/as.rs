@@ -1,6 +1,6 @@
 use std::{rc::Rc, sync::Arc};
 
-use crate::{either::Either, r#as::AsWrap, ResultExt};
+use crate::{either::Either, r#as::AsWrap, Both, ResultExt};
 
 use super::{CellBuilder, CellBuilderError, CellSerialize};
 
@@ -119,6 +119,36 @@ where
     }
 }
 
+/// Implementation of `Unit`:
+/// ```tlb
+/// unit$_ = Unit;
+/// ```
+impl CellSerializeAs<()> for () {
+    #[inline]
+    fn store_as(_source: &(), _builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        Ok(())
+    }
+}
+
+/// Implementation of `Both X Y`:
+/// ```tlb
+/// pair$_ {X:Type} {Y:Type} first:X second:Y = Both X Y;
+/// ```
+impl<X, Y, AsX, AsY> CellSerializeAs<Both<X, Y>> for Both<AsX, AsY>
+where
+    AsX: CellSerializeAs<X>,
+    AsY: CellSerializeAs<Y>,
+{
+    #[inline]
+    fn store_as(source: &Both<X, Y>, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder.store(Both::new(
+            AsWrap::<&X, AsX>::new(&source.first),
+            AsWrap::<&Y, AsY>::new(&source.second),
+        ))?;
+        Ok(())
+    }
+}
+
 /// Implementation of [`Either X Y`](https://docs.ton.org/develop/data-formats/tl-b-types#either):
 /// ```tlb
 /// left$0 {X:Type} {Y:Type} value:X = Either X Y;
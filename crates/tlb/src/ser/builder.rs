@@ -1,11 +1,17 @@
-use std::sync::Arc;
+use core::ptr;
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock},
+};
 
 use crate::{
     bits::{
         bitvec::{order::Msb0, slice::BitSlice, vec::BitVec},
-        ser::{BitWriter, LimitWriter},
+        ser::{r#as::BitPackAs, BitPack, BitWriter, LimitWriter},
     },
-    Cell, Error, ResultExt,
+    error::BuildError,
+    r#as::DataRef,
+    Cell, CellType, Error, ResultExt,
 };
 
 use super::{
@@ -19,6 +25,28 @@ type CellBitWriter = LimitWriter<BitVec<u8, Msb0>>;
 /// [`Error`] for [`CellBuilder`]
 pub type CellBuilderError = <CellBuilder as BitWriter>::Error;
 
+/// Limits for [`CellBuilder`] created with [`Cell::builder_with_limits()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellBuilderLimits {
+    /// Maximum number of cells in the tree being built, including the
+    /// root one. Cells referenced multiple times are counted each time.
+    pub max_cells: usize,
+}
+
+impl CellBuilderLimits {
+    /// Default [`max_cells`](CellBuilderLimits::max_cells), i.e. unlimited
+    pub const DEFAULT_MAX_CELLS: usize = usize::MAX;
+}
+
+impl Default for CellBuilderLimits {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_cells: Self::DEFAULT_MAX_CELLS,
+        }
+    }
+}
+
 /// Cell builder created with [`Cell::builder()`].
 ///
 /// [`CellBuilder`] can then be converted to constructed [`Cell`] by using
@@ -26,21 +54,92 @@ pub type CellBuilderError = <CellBuilder as BitWriter>::Error;
 pub struct CellBuilder {
     data: CellBitWriter,
     references: Vec<Arc<Cell>>,
+    /// maximum number of references in this cell
+    max_references: usize,
+    /// number of cells in the tree including this one, only used to enforce
+    /// `max_cells`, see [`.cells_count()`](CellBuilder::cells_count)
+    cells: usize,
+    /// cells budget for the tree including this one
+    max_cells: usize,
+    /// whether references are only counted without building child cells
+    counting: bool,
 }
 
-const MAX_BITS_LEN: usize = 1023;
-const MAX_REFS_COUNT: usize = 4;
+/// Snapshot of [`CellBuilder`] state created with
+/// [`.snapshot()`](CellBuilder::snapshot).
+///
+/// Since data can only be appended to the builder, the snapshot only
+/// records current number of bits and references, so it's cheap to create.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellBuilderSnapshot {
+    bits: usize,
+    references: usize,
+    cells: usize,
+}
 
 impl CellBuilder {
     #[inline]
     #[must_use]
-    pub(crate) const fn new() -> Self {
+    pub(crate) const fn new(limits: CellBuilderLimits) -> Self {
         Self {
-            data: LimitWriter::new(BitVec::EMPTY, MAX_BITS_LEN),
+            data: LimitWriter::new(BitVec::EMPTY, Cell::MAX_BITS_LEN),
             references: Vec::new(),
+            max_references: Cell::MAX_REFS_COUNT,
+            cells: 1,
+            max_cells: limits.max_cells,
+            counting: false,
         }
     }
 
+    /// Builder that does not limit number of bits and references in the
+    /// root cell, so that its contents can be counted even if they do not
+    /// fit into a single cell. Child cells are not built, references to
+    /// them are only counted.
+    #[inline]
+    #[must_use]
+    const fn counting() -> Self {
+        Self {
+            data: LimitWriter::new(BitVec::EMPTY, usize::MAX),
+            references: Vec::new(),
+            max_references: usize::MAX,
+            cells: 1,
+            max_cells: CellBuilderLimits::DEFAULT_MAX_CELLS,
+            counting: true,
+        }
+    }
+
+    /// Count number of bits and references the value would take in the
+    /// current cell, see [`CellSerialize::estimated_size()`]
+    #[inline]
+    pub(crate) fn estimate<T>(value: &T) -> Result<(usize, usize), CellBuilderError>
+    where
+        T: CellSerialize + ?Sized,
+    {
+        let mut builder = Self::counting();
+        value.store(&mut builder)?;
+        Ok((builder.data.len(), builder.references.len()))
+    }
+
+    /// Number of cells in the tree built so far, including this one,
+    /// counted as in [`max_cells`](CellBuilderLimits::max_cells).
+    ///
+    /// It's computed on demand by traversing references stored so far,
+    /// where each distinct cell is visited only once.
+    #[inline]
+    pub fn cells_count(&self) -> usize {
+        let mut counts = HashMap::new();
+        self.references
+            .iter()
+            .map(|r| count_cells_memo(r, &mut counts))
+            .fold(1, usize::saturating_add)
+    }
+
+    /// Number of bits that can still be stored in the current cell
+    #[inline]
+    pub fn bits_left(&self) -> usize {
+        self.data.limit() - self.data.len()
+    }
+
     /// Store the value using its [`CellSerialize`] implementation
     #[inline]
     pub fn store<T>(&mut self, value: T) -> Result<&mut Self, CellBuilderError>
@@ -159,12 +258,94 @@ impl CellBuilder {
         Ok(self)
     }
 
+    /// Store given value as raw bits into a new child cell and add a
+    /// reference to it, i.e. `^X`.
+    #[inline]
+    pub fn store_ref_data<T>(&mut self, value: T) -> Result<&mut Self, CellBuilderError>
+    where
+        T: BitPack,
+    {
+        self.store_as::<T, DataRef>(value)
+    }
+
+    /// Store given value as raw bits into a new child cell using an adapter
+    /// and add a reference to it.  
+    /// See [`DataRef`].
+    #[inline]
+    pub fn store_ref_data_as<T, As>(&mut self, value: T) -> Result<&mut Self, CellBuilderError>
+    where
+        As: BitPackAs<T> + ?Sized,
+    {
+        self.store_as::<T, DataRef<As>>(value)
+    }
+
+    /// Add a reference to already built cell as-is, i.e. `^Cell`.
+    ///
+    /// Unlike storing it with [`Ref`](crate::r#as::Ref), the cell is not
+    /// copied, which is cheap for shared subtrees, e.g. cached code cells.
+    /// See [`SharedRef`](crate::r#as::SharedRef).
+    ///
+    /// The subtree is traversed to check it against
+    /// [`max_cells`](CellBuilderLimits::max_cells) only if it's limited.
+    #[inline]
+    pub fn store_reference(
+        &mut self,
+        cell: impl Into<Arc<Cell>>,
+    ) -> Result<&mut Self, CellBuilderError> {
+        let cell = cell.into();
+        if self.references.len() >= self.max_references {
+            return Err(Error::custom("too many references"));
+        }
+        if self.counting || self.max_cells == CellBuilderLimits::DEFAULT_MAX_CELLS {
+            self.references.push(cell);
+            return Ok(self);
+        }
+        let cells = self.cells.saturating_add(count_cells(&cell));
+        if cells > self.max_cells {
+            return Err(Error::custom(format!(
+                "too many cells, max: {}",
+                self.max_cells
+            )));
+        }
+        self.cells = cells;
+        self.references.push(cell);
+        Ok(self)
+    }
+
+    /// Create builder for the next reference with the rest of cells budget
     #[inline]
-    fn ensure_reference(&self) -> Result<(), CellBuilderError> {
-        if self.references.len() == MAX_REFS_COUNT {
+    fn reference_builder(&self) -> Result<Self, CellBuilderError> {
+        if self.references.len() >= self.max_references {
             return Err(Error::custom("too many references"));
         }
-        Ok(())
+        if self.cells >= self.max_cells {
+            return Err(Error::custom(format!(
+                "too many cells, max: {}",
+                self.max_cells
+            )));
+        }
+        Ok(Self::new(CellBuilderLimits {
+            max_cells: self.max_cells - self.cells,
+        }))
+    }
+
+    #[inline]
+    fn push_reference(&mut self, builder: Self) {
+        self.cells += builder.cells;
+        self.references.push(builder.into_cell().into());
+    }
+
+    /// Count the reference without building the child cell
+    #[inline]
+    fn push_placeholder(&mut self) -> Result<&mut Self, CellBuilderError> {
+        static PLACEHOLDER: OnceLock<Arc<Cell>> = OnceLock::new();
+
+        if self.references.len() >= self.max_references {
+            return Err(Error::custom("too many references"));
+        }
+        self.references
+            .push(PLACEHOLDER.get_or_init(|| Arc::new(Cell::new())).clone());
+        Ok(self)
     }
 
     #[inline]
@@ -175,10 +356,12 @@ impl CellBuilder {
     where
         As: CellSerializeAs<T> + ?Sized,
     {
-        self.ensure_reference()?;
-        let mut builder = Self::new();
+        if self.counting {
+            return self.push_placeholder();
+        }
+        let mut builder = self.reference_builder()?;
         builder.store_as::<T, As>(value)?;
-        self.references.push(builder.into_cell().into());
+        self.push_reference(builder);
         Ok(self)
     }
 
@@ -191,40 +374,318 @@ impl CellBuilder {
     where
         As: CellSerializeAsWithArgs<T> + ?Sized,
     {
-        self.ensure_reference()?;
-        let mut builder = Self::new();
+        if self.counting {
+            return self.push_placeholder();
+        }
+        let mut builder = self.reference_builder()?;
         builder.store_as_with::<T, As>(value, args)?;
-        self.references.push(builder.into_cell().into());
+        self.push_reference(builder);
         Ok(self)
     }
 
+    /// Serialize the value with `store` only once into a separate builder
+    /// and store it as `Either X ^X`: inline if it fits into the rest of
+    /// current cell and as a reference to the child cell otherwise.
+    pub(crate) fn store_inline_or_ref(
+        &mut self,
+        store: impl FnOnce(&mut Self) -> Result<(), CellBuilderError>,
+    ) -> Result<&mut Self, CellBuilderError> {
+        let mut builder = if self.counting {
+            Self::counting()
+        } else {
+            // inlined value does not take a cell on its own
+            Self::new(CellBuilderLimits {
+                max_cells: self.max_cells - self.cells + 1,
+            })
+        };
+        store(&mut builder)?;
+
+        if self.data.len() + 1 + builder.data.len() <= Cell::MAX_BITS_LEN
+            && self.references.len() + builder.references.len() <= Cell::MAX_REFS_COUNT
+        {
+            // left$0
+            self.write_bit(false)?;
+            self.cells += builder.cells - 1;
+            self.references.extend(builder.references);
+            self.write_bitslice(&builder.data.into_inner())?;
+            return Ok(self);
+        }
+
+        // right$1
+        self.write_bit(true)?;
+        if self.counting {
+            return self.push_placeholder();
+        }
+        if self.references.len() >= self.max_references {
+            return Err(Error::custom("too many references"));
+        }
+        if builder.cells > self.max_cells - self.cells {
+            return Err(Error::custom(format!(
+                "too many cells, max: {}",
+                self.max_cells
+            )));
+        }
+        self.push_reference(builder);
+        Ok(self)
+    }
+
+    /// Take a snapshot of current state, so it can be later restored with
+    /// [`.revert()`](CellBuilder::revert).
+    #[inline]
+    #[must_use]
+    pub fn snapshot(&self) -> CellBuilderSnapshot {
+        CellBuilderSnapshot {
+            bits: self.data.len(),
+            references: self.references.len(),
+            cells: self.cells,
+        }
+    }
+
+    /// Discard everything stored after given [snapshot](CellBuilder::snapshot)
+    /// was taken.
+    #[inline]
+    pub fn revert(&mut self, snapshot: CellBuilderSnapshot) {
+        self.data.truncate(snapshot.bits);
+        self.references.truncate(snapshot.references);
+        self.cells = snapshot.cells;
+    }
+
     /// Convert builder to [`Cell`]
     #[inline]
     #[must_use]
     pub fn into_cell(self) -> Cell {
         Cell {
+            r#type: CellType::Ordinary,
             data: self.data.into_inner(),
             references: self.references,
         }
     }
 }
 
+/// Number of cells in the tree, where cells referenced multiple times are
+/// counted each time, as in [`CellBuilderLimits::max_cells`]. Each distinct
+/// cell is visited only once, so it's linear even for heavily shared trees.
+fn count_cells(root: &Cell) -> usize {
+    count_cells_memo(root, &mut HashMap::new())
+}
+
+/// [`count_cells()`] reusing counts of cells already visited
+fn count_cells_memo(root: &Cell, counts: &mut HashMap<*const Cell, usize>) -> usize {
+    if root.references.is_empty() {
+        return 1;
+    }
+    // (cell, whether its references were already visited)
+    let mut stack = vec![(root, false)];
+    while let Some((cell, visited)) = stack.pop() {
+        if visited {
+            let count = cell
+                .references
+                .iter()
+                .map(|r| counts[&Arc::as_ptr(r)])
+                .fold(1, usize::saturating_add);
+            counts.insert(ptr::from_ref(cell), count);
+            continue;
+        }
+        if counts.contains_key(&ptr::from_ref(cell)) {
+            continue;
+        }
+        stack.push((cell, true));
+        stack.extend(cell.references.iter().map(|r| (&**r, false)));
+    }
+    counts[&ptr::from_ref(root)]
+}
+
 impl BitWriter for CellBuilder {
-    type Error = <CellBitWriter as BitWriter>::Error;
+    type Error = BuildError;
 
     #[inline]
     fn write_bit(&mut self, bit: bool) -> Result<(), Self::Error> {
-        self.data.write_bit(bit)?;
+        self.data.write_bit(bit).map_err(Error::custom)?;
         Ok(())
     }
 
     #[inline]
     fn write_bitslice(&mut self, bits: &BitSlice<u8, Msb0>) -> Result<(), Self::Error> {
-        self.data.write_bitslice(bits)
+        self.data.write_bitslice(bits).map_err(Error::custom)?;
+        Ok(())
     }
 
     #[inline]
     fn repeat_bit(&mut self, n: usize, bit: bool) -> Result<(), Self::Error> {
-        self.data.repeat_bit(n, bit)
+        self.data.repeat_bit(n, bit).map_err(Error::custom)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bits::{r#as::NBits, ser::BitWriterExt},
+        r#as::{Data, EitherInlineOrRef, Ref},
+        ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt},
+    };
+
+    use super::*;
+
+    #[test]
+    fn snapshot_revert() {
+        let mut builder = Cell::builder();
+        builder.pack(0xabu8).unwrap();
+        builder.store_as::<_, Ref>(()).unwrap();
+        let snapshot = builder.snapshot();
+
+        builder.pack(u64::MAX).unwrap();
+        builder.store_as::<_, Ref>(()).unwrap();
+        builder.revert(snapshot);
+        builder.pack(0xcdu8).unwrap();
+
+        let mut expected = Cell::builder();
+        expected.pack(0xabcdu16).unwrap();
+        expected.store_as::<_, Ref>(()).unwrap();
+
+        assert_eq!(builder.into_cell(), expected.into_cell());
+    }
+
+    #[test]
+    fn max_cells() {
+        let inner = ((), ());
+        let inner = inner.wrap_as::<(Ref, Ref)>();
+        let tree = ((), inner);
+        let tree = tree.wrap_as::<(Ref, Ref)>();
+
+        let mut builder = Cell::builder_with_limits(CellBuilderLimits { max_cells: 5 });
+        builder.store(tree).unwrap();
+        assert_eq!(builder.cells_count(), 5);
+
+        let snapshot = builder.snapshot();
+        assert!(builder.store_as::<_, Ref>(()).is_err());
+        builder.revert(snapshot);
+        assert_eq!(builder.cells_count(), 5);
+
+        let mut builder = Cell::builder_with_limits(CellBuilderLimits { max_cells: 4 });
+        assert_eq!(
+            builder.store(tree).err().unwrap().to_string(),
+            "^: ^: too many cells, max: 2"
+        );
+    }
+
+    #[test]
+    fn store_reference() {
+        let leaf = Arc::new(Cell::new());
+        let mut shared = Cell::builder();
+        shared
+            .store_reference(leaf.clone())
+            .unwrap()
+            .store_reference(leaf.clone())
+            .unwrap();
+        let shared = Arc::new(shared.into_cell());
+
+        let mut builder = Cell::builder();
+        builder
+            .store_reference(shared.clone())
+            .unwrap()
+            .store_reference(shared.clone())
+            .unwrap();
+        // counted on demand without limit, too
+        assert_eq!(builder.cells_count(), 7);
+
+        let mut builder = Cell::builder_with_limits(CellBuilderLimits { max_cells: 10 });
+        builder.store_reference(shared.clone()).unwrap();
+        assert_eq!(builder.cells_count(), 4);
+        // children of stored cell are shared, too
+        builder.store(&*shared).unwrap();
+        assert_eq!(builder.cells_count(), 6);
+        let cell = builder.into_cell();
+        assert!(Arc::ptr_eq(&cell.references[0], &shared));
+        assert!(Arc::ptr_eq(&cell.references[1], &leaf));
+
+        let mut builder = Cell::builder_with_limits(CellBuilderLimits { max_cells: 3 });
+        assert_eq!(
+            builder.store_reference(shared).err().unwrap().to_string(),
+            "too many cells, max: 3"
+        );
+    }
+
+    #[test]
+    fn count_cells_shared() {
+        let mut cell = Arc::new(Cell::new());
+        for _ in 0..100 {
+            cell = Arc::new(Cell {
+                r#type: CellType::Ordinary,
+                data: BitVec::new(),
+                references: vec![cell.clone(), cell],
+            });
+        }
+        assert_eq!(count_cells(&cell), usize::MAX);
+    }
+
+    #[test]
+    fn ref_data() {
+        let mut builder = Cell::builder();
+        builder
+            .store_ref_data(0xabu8)
+            .unwrap()
+            .store_ref_data_as::<_, NBits<7>>(0x7fu8)
+            .unwrap();
+        let cell = builder.into_cell();
+        assert!(cell.data.is_empty());
+        assert_eq!(cell.references[1].data.len(), 7);
+
+        let mut parser = cell.parser();
+        assert_eq!(parser.parse_ref_data::<u8>().unwrap(), 0xab);
+        assert_eq!(parser.parse_ref_data_as::<u8, NBits<7>>().unwrap(), 0x7f);
+        parser.ensure_empty().unwrap();
+    }
+
+    #[test]
+    fn estimate() {
+        let refs = [(); 5];
+        let refs = refs.wrap_as::<[Ref; 5]>();
+        assert_eq!(refs.estimated_refs().unwrap(), 5);
+        assert!(Cell::builder().store(refs).is_err());
+
+        let data = (0xabu8, [u64::MAX; 16]);
+        let value = (data.wrap_as::<(Data, [Data; 16])>(), refs);
+        assert_eq!(value.estimated_bits().unwrap(), 8 + 16 * 64);
+        assert_eq!(value.estimated_refs().unwrap(), 5);
+
+        let inner = ((), ());
+        let inner = inner.wrap_as::<(Ref, Ref)>();
+        let tree = (0xcdu8, inner);
+        let tree = tree.wrap_as::<(Data, Ref)>();
+        assert_eq!(tree.estimated_bits().unwrap(), 8);
+        assert_eq!(tree.estimated_refs().unwrap(), 1);
+        assert_eq!(
+            tree.estimated_bits().unwrap(),
+            tree.to_cell().unwrap().data.len()
+        );
+    }
+
+    #[test]
+    fn estimate_does_not_build_children() {
+        struct Unreachable;
+
+        impl CellSerialize for Unreachable {
+            fn store(&self, _builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+                unreachable!("child cell must not be built")
+            }
+        }
+
+        let value = (0xabu8, Unreachable).wrap_as::<(Data, Ref)>();
+        assert_eq!(value.estimated_size().unwrap(), (8, 1));
+    }
+
+    #[test]
+    fn estimate_either_inline_or_ref() {
+        for prefix in [0, 23, 24, 800] {
+            let value = (BitVec::<u8, Msb0>::repeat(true, prefix), [u8::MAX; 125]);
+            let value = value.wrap_as::<(Data, EitherInlineOrRef<Data>)>();
+            let cell = value.to_cell().unwrap();
+            assert_eq!(
+                value.estimated_size().unwrap(),
+                (cell.data.len(), cell.references.len()),
+                "{prefix}"
+            );
+        }
     }
 }
@@ -5,24 +5,67 @@ mod builder;
 
 pub use self::builder::*;
 
+/// Derive macro for [`CellSerialize`](trait@CellSerialize), see [`tlb_derive`]
+#[cfg(feature = "derive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
+pub use tlb_derive::CellSerialize;
+
 use std::{rc::Rc, sync::Arc};
 
 use impl_tools::autoimpl;
 
-use crate::{
-    bits::ser::BitWriterExt,
-    either::Either,
-    r#as::{Ref, Same},
-    Cell, ResultExt,
-};
+use crate::{bits::ser::BitWriterExt, either::Either, r#as::Same, Both, Cell, Error, ResultExt};
 
 /// A type that can be **ser**ilalized into [`CellBuilder`].
 #[autoimpl(for <T: trait + ?Sized> &T, &mut T, Box<T>, Rc<T>, Arc<T>)]
 pub trait CellSerialize {
     /// Store the value into [`CellBuilder`]
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError>;
+
+    /// Number of bits and references the value would take in the current
+    /// cell, which are counted in a single pass by storing it without
+    /// limits on bits and references and without building child cells,
+    /// so that it can be checked to fit into a cell before choosing a
+    /// layout:
+    /// ```rust
+    /// # use tlb::{r#as::Ref, ser::{r#as::CellSerializeWrapAsExt, CellSerialize}, Cell};
+    /// let cell = Cell::from_hex_data("ff".repeat(100), 800).unwrap();
+    /// let value = (cell.clone(), cell.clone());
+    /// assert_eq!(value.estimated_size().unwrap(), (1600, 0));
+    /// assert!(value.estimated_bits().unwrap() > Cell::MAX_BITS_LEN);
+    ///
+    /// let value = (&cell).wrap_as::<Ref>();
+    /// assert_eq!(value.estimated_size().unwrap(), (0, 1));
+    /// ```
+    ///
+    /// Values stored with
+    /// [`EitherInlineOrRef`](crate::r#as::EitherInlineOrRef) are counted
+    /// as inline only if they would fit into the cell. Since child cells
+    /// are not built, errors in their serialization are not reported.
+    #[inline]
+    fn estimated_size(&self) -> Result<(usize, usize), CellBuilderError> {
+        CellBuilder::estimate(self)
+    }
+
+    /// Number of bits the value would take in the current cell, see
+    /// [`.estimated_size()`](CellSerialize::estimated_size)
+    #[inline]
+    fn estimated_bits(&self) -> Result<usize, CellBuilderError> {
+        self.estimated_size().map(|(bits, _)| bits)
+    }
+
+    /// Number of references the value would take in the current cell,
+    /// see [`.estimated_size()`](CellSerialize::estimated_size)
+    #[inline]
+    fn estimated_refs(&self) -> Result<usize, CellBuilderError> {
+        self.estimated_size().map(|(_, refs)| refs)
+    }
 }
 
+/// Implementation of `Unit`:
+/// ```tlb
+/// unit$_ = Unit;
+/// ```
 impl CellSerialize for () {
     #[inline]
     fn store(&self, _builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
@@ -78,6 +121,26 @@ impl_cell_serialize_for_tuple!(0:T0,1:T1,2:T2,3:T3,4:T4,5:T5,6:T6,7:T7);
 impl_cell_serialize_for_tuple!(0:T0,1:T1,2:T2,3:T3,4:T4,5:T5,6:T6,7:T7,8:T8);
 impl_cell_serialize_for_tuple!(0:T0,1:T1,2:T2,3:T3,4:T4,5:T5,6:T6,7:T7,8:T8,9:T9);
 
+/// Implementation of `Both X Y`:
+/// ```tlb
+/// pair$_ {X:Type} {Y:Type} first:X second:Y = Both X Y;
+/// ```
+impl<X, Y> CellSerialize for Both<X, Y>
+where
+    X: CellSerialize,
+    Y: CellSerialize,
+{
+    #[inline]
+    fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
+        builder
+            .store(&self.first)
+            .context("first")?
+            .store(&self.second)
+            .context("second")?;
+        Ok(())
+    }
+}
+
 /// Implementation of [`Either X Y`](https://docs.ton.org/develop/data-formats/tl-b-types#either):
 /// ```tlb
 /// left$0 {X:Type} {Y:Type} value:X = Either X Y;
@@ -122,13 +185,24 @@ where
     }
 }
 
+/// Exotic cells can not be stored this way, since their type would be
+/// lost, use [`SharedRef`](crate::r#as::SharedRef) to store them as-is.
 impl CellSerialize for Cell {
     #[inline]
     fn store(&self, builder: &mut CellBuilder) -> Result<(), CellBuilderError> {
-        builder
-            .pack(self.data.as_bitslice())?
-            .store_many_as::<_, Ref>(&self.references)?;
-
+        if self.r#type.is_exotic() {
+            return Err(Error::custom(format!(
+                "{:?} cell can only be stored as shared reference",
+                self.r#type
+            )));
+        }
+        builder.pack(self.data.as_bitslice())?;
+        // children are shared rather than copied
+        for (i, r) in self.references.iter().enumerate() {
+            builder
+                .store_reference(r.clone())
+                .with_context(|| format!("[{i}]"))?;
+        }
         Ok(())
     }
 }
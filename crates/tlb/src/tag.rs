@@ -0,0 +1,344 @@
+//! TL-B constructor tags and [`tag_dispatch!`](crate::tag_dispatch)
+use core::fmt::{self, Display};
+
+use crate::bits::{
+    de::{BitReader, BitReaderExt},
+    r#as::VarNBits,
+    ser::{BitPack, BitWriter, BitWriterExt},
+};
+
+use crate::Error;
+
+pub use crate::bits::integer::Tag32;
+
+/// Constructor tag from TL-B schema, i.e. `#0ec3c86d` or `$10`
+/// ```rust
+/// # use tlb::tag::Tag;
+/// const TAG: Tag = Tag::parse("#0ec3c86d");
+/// assert_eq!(TAG, Tag::new(0x0ec3c86d, 32));
+/// assert_eq!(Tag::parse("$10"), Tag::new(0b10, 2));
+/// assert_eq!(Tag::parse("$10").to_string(), "$10");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tag {
+    value: u64,
+    len: u32,
+}
+
+impl Tag {
+    /// Maximum length of the tag in bits
+    pub const MAX_LEN: u32 = 64;
+
+    /// Create tag from its `len` least significant bits of `value`.
+    ///
+    /// # Panics
+    /// If `len` exceeds [`MAX_LEN`](Tag::MAX_LEN) or `value` does not fit
+    /// into `len` bits
+    #[inline]
+    #[must_use]
+    pub const fn new(value: u64, len: u32) -> Self {
+        assert!(len <= Self::MAX_LEN, "tag is too long");
+        assert!(
+            len == Self::MAX_LEN || value >> len == 0,
+            "tag value does not fit into its length"
+        );
+        Self { value, len }
+    }
+
+    /// Parse tag from TL-B notation: `#` followed by hex digits or `$`
+    /// followed by binary digits, where `_` stands for an empty tag.
+    ///
+    /// # Panics
+    /// If given string is not a valid tag
+    #[must_use]
+    pub const fn parse(s: &str) -> Self {
+        let s = s.as_bytes();
+        assert!(!s.is_empty(), "empty tag");
+        let digit_bits = match s[0] {
+            b'#' => 4,
+            b'$' => 1,
+            _ => panic!("tag must start with '#' or '$'"),
+        };
+        if s.len() == 2 && s[1] == b'_' {
+            return Self::new(0, 0);
+        }
+        let mut value: u64 = 0;
+        let mut len: u32 = 0;
+        let mut i = 1;
+        while i < s.len() {
+            let digit = match s[i] {
+                b'0'..=b'9' => s[i] - b'0',
+                b'a'..=b'f' => s[i] - b'a' + 10,
+                b'A'..=b'F' => s[i] - b'A' + 10,
+                _ => panic!("invalid tag digit"),
+            };
+            assert!(digit >> digit_bits == 0, "invalid tag digit");
+            len += digit_bits;
+            assert!(len <= Self::MAX_LEN, "tag is too long");
+            value = (value << digit_bits) | digit as u64;
+            i += 1;
+        }
+        Self::new(value, len)
+    }
+
+    /// Value of the tag
+    #[inline]
+    pub const fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Length of the tag in bits
+    #[inline]
+    pub const fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns whether the tag is empty, i.e. `$_`
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Unpack the tag that is one of `expected` ones. Tags are expected to
+    /// be prefix-free, so they can have different lengths, as in
+    /// `CommonMsgInfo` with `$0`, `$10` and `$11`.
+    ///
+    /// Returns [`UnknownTag`] error if no one matches.
+    /// ```rust
+    /// # use tlb::{bits::bitvec::{bits, order::Msb0}, tag::Tag, StringError};
+    /// # fn main() -> Result<(), StringError> {
+    /// const TAGS: &[Tag] = &[Tag::parse("$0"), Tag::parse("$10"), Tag::parse("$11")];
+    /// let mut reader = bits![u8, Msb0; 1, 0];
+    /// assert_eq!(Tag::unpack_one_of(&mut reader, TAGS)?, TAGS[1]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn unpack_one_of<R>(mut reader: R, expected: &[Self]) -> Result<Self, R::Error>
+    where
+        R: BitReader,
+    {
+        let mut got = Self::new(0, 0);
+        // read incrementally up to the next shortest tag length
+        while let Some(len) = expected
+            .iter()
+            .map(Self::len)
+            .filter(|&len| len > got.len)
+            .min()
+        {
+            let more: u64 = reader.unpack_as_with::<_, VarNBits>(len - got.len)?;
+            got = Self {
+                value: got.value.checked_shl(len - got.len).unwrap_or(0) | more,
+                len,
+            };
+            if expected.contains(&got) {
+                return Ok(got);
+            }
+        }
+        Err(Error::custom(UnknownTag {
+            got,
+            expected: expected.to_vec(),
+        }))
+    }
+}
+
+impl Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.len == 0 {
+            return f.write_str("$_");
+        }
+        if self.len.is_multiple_of(4) {
+            write!(f, "#{:01$x}", self.value, (self.len / 4) as usize)
+        } else {
+            write!(f, "${:01$b}", self.value, self.len as usize)
+        }
+    }
+}
+
+impl BitPack for Tag {
+    #[inline]
+    fn pack<W>(&self, mut writer: W) -> Result<(), W::Error>
+    where
+        W: BitWriter,
+    {
+        writer.pack_as_with::<_, VarNBits>(self.value, self.len)?;
+        Ok(())
+    }
+}
+
+/// Error returned by [`Tag::unpack_one_of`].
+///
+/// 32-bit tags (i.e. opcodes) are displayed as [`Tag32`] in canonical
+/// `0x%08x` format, while shorter ones are kept in TL-B notation:
+/// ```rust
+/// # use tlb::tag::{Tag, UnknownTag};
+/// let err = UnknownTag {
+///     got: Tag::new(0xdeadbeef, 32),
+///     expected: vec![Tag::parse("#0f8a7ea5"), Tag::parse("$10")],
+/// };
+/// assert_eq!(
+///     err.to_string(),
+///     "unknown tag: 0xdeadbeef, expected one of: 0x0f8a7ea5, $10",
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownTag {
+    /// Bits read until no more tags could match
+    pub got: Tag,
+    /// Expected tags
+    pub expected: Vec<Tag>,
+}
+
+impl UnknownTag {
+    fn fmt_tag(tag: &Tag, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if tag.len == 32 {
+            return Display::fmt(&Tag32(tag.value as u32), f);
+        }
+        Display::fmt(tag, f)
+    }
+}
+
+impl Display for UnknownTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unknown tag: ")?;
+        Self::fmt_tag(&self.got, f)?;
+        f.write_str(", expected one of: ")?;
+        for (i, tag) in self.expected.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            Self::fmt_tag(tag, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnknownTag {}
+
+/// Declare constructor tags of enum variants once and implement
+/// [`CellSerialize`](crate::ser::CellSerialize) and
+/// [`CellDeserialize`](crate::de::CellDeserialize) for it.
+///
+/// Each variant must have exactly one unnamed field, which is
+/// **de**/**ser**ialized after the [`Tag`] using an optional adapter
+/// (defaults to [`Same`](crate::r#as::Same)). Unknown tags are reported
+/// with [`UnknownTag`] error listing all expected ones.
+/// ```rust
+/// # use tlb::{
+/// #     r#as::{Data, Ref},
+/// #     ser::{r#as::CellSerializeWrapAsExt, CellSerializeExt},
+/// #     tag_dispatch, Cell,
+/// # };
+/// #[derive(Debug, PartialEq)]
+/// enum Action {
+///     /// ```tlb
+///     /// action_a#0a x:uint32 = Action;
+///     /// ```
+///     A(u32),
+///     /// ```tlb
+///     /// action_b$1 code:^Cell = Action;
+///     /// ```
+///     B(Cell),
+/// }
+///
+/// tag_dispatch! {
+///     Action {
+///         "#0a" => A as Data,
+///         "$1" => B as Ref,
+///     }
+/// }
+///
+/// let cell = Action::A(1).to_cell().unwrap();
+/// assert_eq!(cell.parse_fully::<Action>().unwrap(), Action::A(1));
+///
+/// let cell = 0u8.wrap_as::<Data>().to_cell().unwrap();
+/// assert_eq!(
+///     cell.parse_fully::<Action>().unwrap_err().to_string(),
+///     "unknown tag: #00, expected one of: #0a, $1",
+/// );
+/// ```
+#[macro_export]
+macro_rules! tag_dispatch {
+    ($ty:ty { $($tag:literal => $variant:ident $(as $as:ty)?),+ $(,)? }) => {
+        impl $crate::ser::CellSerialize for $ty {
+            fn store(
+                &self,
+                builder: &mut $crate::ser::CellBuilder,
+            ) -> ::core::result::Result<(), $crate::ser::CellBuilderError> {
+                match self {$(
+                    Self::$variant(v) => {
+                        $crate::bits::ser::BitWriterExt::pack(
+                            builder,
+                            const { $crate::tag::Tag::parse($tag) },
+                        )?;
+                        $crate::ResultExt::context(
+                            builder.store_as::<_, &$crate::tag_dispatch!(@as $($as)?)>(v),
+                            ::core::stringify!($variant),
+                        )?;
+                    }
+                )+}
+                ::core::result::Result::Ok(())
+            }
+        }
+
+        impl<'de> $crate::de::CellDeserialize<'de> for $ty {
+            fn parse(
+                parser: &mut $crate::de::CellParser<'de>,
+            ) -> ::core::result::Result<Self, $crate::de::CellParserError<'de>> {
+                const TAGS: &[$crate::tag::Tag] = &[$($crate::tag::Tag::parse($tag)),+];
+                let tag = $crate::tag::Tag::unpack_one_of(&mut *parser, TAGS)?;
+                $(
+                    if tag == const { $crate::tag::Tag::parse($tag) } {
+                        return $crate::ResultExt::context(
+                            parser
+                                .parse_as::<_, $crate::tag_dispatch!(@as $($as)?)>()
+                                .map(Self::$variant),
+                            ::core::stringify!($variant),
+                        );
+                    }
+                )+
+                ::core::unreachable!()
+            }
+        }
+    };
+    (@as) => { $crate::r#as::Same };
+    (@as $as:ty) => { $as };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bits::bitvec::{bits, order::Msb0};
+
+    use super::*;
+
+    #[test]
+    fn parse_display() {
+        for s in ["#0ec3c86d", "$10", "$0", "#_", "$_", "$101"] {
+            let tag = Tag::parse(s);
+            let s = if s == "#_" { "$_" } else { s };
+            assert_eq!(tag.to_string(), s);
+        }
+        assert_eq!(Tag::parse("#FF"), Tag::new(0xff, 8));
+        assert_eq!(Tag::parse("$1011").to_string(), "#b");
+    }
+
+    #[test]
+    fn unpack_one_of() {
+        const TAGS: &[Tag] = &[Tag::parse("$0"), Tag::parse("$10"), Tag::parse("$11")];
+        let mut reader = bits![u8, Msb0; 0, 1, 1, 1, 0];
+        assert_eq!(Tag::unpack_one_of(&mut reader, TAGS).unwrap(), TAGS[0]);
+        assert_eq!(Tag::unpack_one_of(&mut reader, TAGS).unwrap(), TAGS[2]);
+        assert_eq!(Tag::unpack_one_of(&mut reader, TAGS).unwrap(), TAGS[1]);
+    }
+
+    #[test]
+    fn unknown_tag() {
+        const TAGS: &[Tag] = &[Tag::parse("$0"), Tag::parse("#a")];
+        let mut reader = bits![u8, Msb0; 1, 0, 1, 1];
+        assert_eq!(
+            Tag::unpack_one_of::<_>(&mut reader, TAGS)
+                .unwrap_err()
+                .to_string(),
+            "unknown tag: #b, expected one of: $0, #a"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Complete flow of sending TONs with a comment from a wallet:
+//! derive the wallet from mnemonic, build and sign external message for
+//! current `seqno` and pack it into BoC ready to be broadcasted.
+//!
+//! Network is not accessed: `seqno` is given as an argument, it can be
+//! obtained with `seqno` get-method of the wallet, and resulting BoC is
+//! printed instead of being sent, e.g. with `sendBoc` method of any
+//! HTTP API provider.
+//!
+//! ```sh
+//! TON_MNEMONIC="word1 ... word24" cargo run --example wallet_transfer -- \
+//!     [SEQNO] [DESTINATION] [AMOUNT] [COMMENT]
+//! ```
+use std::{env, error::Error};
+
+use chrono::Duration;
+use toner::{
+    contracts::{
+        opcodes,
+        wallet::{
+            mnemonic::Mnemonic, v4r2::V4R2, Network, PendingExternal, SystemClock, Wallet,
+            WalletOpSendMessage,
+        },
+    },
+    tlb::{bits::ser::BitWriterExt, ser::CellSerializeExt, Cell},
+    ton::{
+        action::SendMsgAction,
+        boc::{BagOfCells, BagOfCellsArgs},
+        currency::CoinsExt,
+        message::Message,
+        FriendlyFlags, MsgAddress,
+    },
+};
+
+/// Mnemonic used when `TON_MNEMONIC` is not set, do not send funds to it
+const TEST_MNEMONIC: &str = "jewel loop vast intact snack drip fatigue lunch erode green indoor balance together scrub hen monster hour narrow banner warfare increase panel sound spell";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = env::args().skip(1);
+    let seqno: u32 = args.next().as_deref().unwrap_or("0").parse()?;
+    let dst: MsgAddress = args
+        .next()
+        .as_deref()
+        .unwrap_or("EQAWezezpqKTbO6xjCussXDdIeJ7XxTcErjA6uD3T3r7AwTk")
+        .parse()?;
+    let amount = u64::from_ton_str(args.next().as_deref().unwrap_or("0.01"))?;
+    let comment = args
+        .next()
+        .unwrap_or_else(|| "Hello from toner".to_string());
+
+    // derive the wallet
+    let mnemonic: Mnemonic = env::var("TON_MNEMONIC")
+        .as_deref()
+        .unwrap_or(TEST_MNEMONIC)
+        .parse()?;
+    let wallet =
+        Wallet::<V4R2>::derive_default_for(mnemonic.generate_keypair(None)?, Network::Testnet)?;
+    println!(
+        "wallet: {}",
+        wallet.address().display_friendly(FriendlyFlags {
+            non_production: true,
+            ..Default::default()
+        })
+    );
+
+    // transfer with text comment
+    let mut body = Cell::builder();
+    body
+        // text_comment#00000000
+        .pack(opcodes::TEXT_COMMENT)?
+        // text:bytes
+        .pack(comment.as_bytes())?;
+    let transfer = WalletOpSendMessage {
+        mode: SendMsgAction::PAY_FEES_SEPARATELY | SendMsgAction::IGNORE_ERRORS,
+        message: Message::transfer(dst, amount.into(), true)
+            .map_body(|()| body.into_cell())
+            .normalize()?,
+    };
+
+    // sign external message for current seqno, which also deploys the
+    // wallet if it's the first one
+    let pending = PendingExternal::new(
+        SystemClock,
+        Duration::minutes(1),
+        seqno,
+        [transfer],
+        seqno == 0,
+    );
+    println!("dedup key: {}", pending.dedup_key(&wallet)?);
+    let msg = pending.sign(&wallet)?;
+
+    // pack into BoC
+    let boc = BagOfCells::from_root(msg.to_cell()?).to_base64(BagOfCellsArgs::new(false, true))?;
+    println!("boc: {boc}");
+    Ok(())
+}
@@ -0,0 +1,276 @@
+//! Golden-file round-trip tests of TL-B types.
+//!
+//! Fixture is a JSON file with known-good BoC, which is parsed, decoded as
+//! given `type`, serialized back and the resulting hash is compared to
+//! the expected one. The BoC itself is also re-packed and unpacked again
+//! to make sure the whole round-trip is lossless:
+//! ```json
+//! {
+//!   "description": "what is being tested",
+//!   "type": "cell" | "message" | "state_init" | "jetton_transfer",
+//!   "boc": "<base64 or hex encoded BoC>",
+//!   "hash": "<hex-encoded representation hash of the root cell>",
+//!   "decoded": { /* optional: expected decoded fields */ }
+//! }
+//! ```
+//!
+//! Own types can be checked by implementing [`Decoded`] and
+//! [registering](Fixtures::register) them:
+//! ```rust
+//! # use serde_json::{json, Value};
+//! # use toner::{fixtures::{Decoded, Fixtures}, tlb::Cell};
+//! struct Counter(Cell);
+//! # impl toner::tlb::ser::CellSerialize for Counter {
+//! #     fn store(
+//! #         &self,
+//! #         builder: &mut toner::tlb::ser::CellBuilder,
+//! #     ) -> Result<(), toner::tlb::ser::CellBuilderError> {
+//! #         self.0.store(builder)
+//! #     }
+//! # }
+//! # impl<'de> toner::tlb::de::CellDeserialize<'de> for Counter {
+//! #     fn parse(
+//! #         parser: &mut toner::tlb::de::CellParser<'de>,
+//! #     ) -> Result<Self, toner::tlb::de::CellParserError<'de>> {
+//! #         parser.parse().map(Self)
+//! #     }
+//! # }
+//!
+//! impl Decoded for Counter {
+//!     fn to_json(&self) -> Value {
+//!         json!({ "bits": self.0.data.len() })
+//!     }
+//! }
+//!
+//! let mut fixtures = Fixtures::default();
+//! fixtures.register::<Counter>("counter");
+//! fixtures
+//!     .check(&json!({
+//!         "type": "counter",
+//!         "boc": "b5ee9c72010101010002000000",
+//!         "hash": "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+//!         "decoded": { "bits": 0 },
+//!     }))
+//!     .unwrap();
+//! ```
+use std::{collections::HashMap, fs, path::Path};
+
+use serde_json::{json, Value};
+use tlb::{
+    bits::{de::unpack_fully, ser::pack_with},
+    de::CellDeserialize,
+    ser::{CellSerialize, CellSerializeExt},
+    Cell, Error, ResultExt, StringError,
+};
+use tlb_ton::{
+    boc::{BagOfCells, BagOfCellsArgs},
+    message::{CommonMsgInfo, Message},
+    state_init::StateInit,
+};
+use ton_contracts::jetton::JettonTransfer;
+
+/// Type which can be decoded from root cell of a fixture
+pub trait Decoded: CellSerialize + for<'de> CellDeserialize<'de> {
+    /// Human-readable projection to compare against `decoded` field
+    fn to_json(&self) -> Value;
+}
+
+impl Decoded for Cell {
+    fn to_json(&self) -> Value {
+        json!({
+            "bits": self.data.len(),
+            "refs": self.references.len(),
+        })
+    }
+}
+
+impl Decoded for Message {
+    fn to_json(&self) -> Value {
+        let (info, dst) = match &self.info {
+            CommonMsgInfo::Internal(info) => ("int", info.dst),
+            CommonMsgInfo::ExternalIn(info) => ("ext_in", info.dst),
+            CommonMsgInfo::ExternalOut(info) => ("ext_out", info.dst),
+        };
+        json!({
+            "info": info,
+            "dst": dst.to_string(),
+            "state_init": self.init.is_some(),
+        })
+    }
+}
+
+impl Decoded for StateInit {
+    fn to_json(&self) -> Value {
+        json!({
+            "code": self.code.as_ref().map(|c| hex::encode(c.hash())),
+        })
+    }
+}
+
+impl Decoded for JettonTransfer {
+    fn to_json(&self) -> Value {
+        json!({
+            "query_id": self.query_id,
+            "amount": self.amount.to_string(),
+            "dst": self.dst.to_string(),
+            "response_dst": self.response_dst.to_string(),
+            "forward_ton_amount": self.forward_ton_amount.to_string(),
+        })
+    }
+}
+
+type Check = fn(&Cell, Option<&Value>) -> Result<(), StringError>;
+
+/// Set of [`Decoded`] types fixtures are checked against, keyed by
+/// their `type`.
+///
+/// [`Default`] one knows `cell`, `message`, `state_init` and
+/// `jetton_transfer`.
+#[derive(Debug, Clone)]
+pub struct Fixtures {
+    types: HashMap<String, Check>,
+}
+
+impl Fixtures {
+    /// Register `T` as given `type` of fixtures
+    pub fn register<T>(&mut self, r#type: impl Into<String>) -> &mut Self
+    where
+        T: Decoded,
+    {
+        self.types.insert(r#type.into(), check_typed::<T>);
+        self
+    }
+
+    /// Check single fixture
+    pub fn check(&self, fixture: &Value) -> Result<(), StringError> {
+        let field = |name: &str| {
+            fixture[name]
+                .as_str()
+                .ok_or_else(|| Error::custom(format!("{name}: missing")))
+        };
+
+        let boc_str = field("boc")?;
+        let boc = BagOfCells::parse_base64(boc_str)
+            .or_else(|_| BagOfCells::parse_hex(boc_str))
+            .context("boc")?;
+        let root = boc
+            .single_root()
+            .ok_or_else(|| Error::custom("single root expected"))?;
+
+        let expected_hash = field("hash")?;
+        if hex::encode(root.hash()) != expected_hash {
+            return Err(Error::custom(format!(
+                "hash mismatch: expected {expected_hash}, got {}",
+                hex::encode(root.hash())
+            )));
+        }
+
+        // BoC round-trip
+        for has_crc32c in [false, true] {
+            let packed = pack_with(&boc, BagOfCellsArgs::new(false, has_crc32c))?;
+            let unpacked: BagOfCells = unpack_fully(packed)?;
+            if unpacked.single_root().map(|root| root.hash()) != Some(root.hash()) {
+                return Err(Error::custom(format!(
+                    "BoC round-trip (has_crc32c: {has_crc32c}) changed hash"
+                )));
+            }
+        }
+
+        let r#type = fixture["type"].as_str().unwrap_or("cell");
+        let check = self
+            .types
+            .get(r#type)
+            .ok_or_else(|| Error::custom(format!("unknown type: {type}")))?;
+        check(root, fixture.get("decoded"))
+    }
+
+    /// Check fixture in given JSON file
+    pub fn check_file(&self, path: impl AsRef<Path>) -> Result<(), StringError> {
+        let path = path.as_ref();
+        fs::read_to_string(path)
+            .map_err(Error::custom)
+            .and_then(|s| serde_json::from_str(&s).map_err(Error::custom))
+            .and_then(|fixture| self.check(&fixture))
+            .with_context(|| path.display())
+    }
+
+    /// Check all `*.json` fixtures in given directory, failures of all
+    /// of them are reported together
+    pub fn check_dir(&self, dir: impl AsRef<Path>) -> Result<(), StringError> {
+        let dir = dir.as_ref();
+        let mut paths = fs::read_dir(dir)
+            .map_err(Error::custom)
+            .with_context(|| dir.display())?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(Error::custom)?;
+        paths.retain(|path| path.extension().is_some_and(|ext| ext == "json"));
+        paths.sort();
+        if paths.is_empty() {
+            return Err(Error::custom(format!(
+                "no fixtures found in {}",
+                dir.display()
+            )));
+        }
+
+        let failed: Vec<_> = paths
+            .iter()
+            .filter_map(|path| self.check_file(path).err())
+            .map(|err| err.to_string())
+            .collect();
+        if !failed.is_empty() {
+            return Err(Error::custom(format!(
+                "fixtures failed:\n{}",
+                failed.join("\n")
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for Fixtures {
+    fn default() -> Self {
+        let mut fixtures = Self {
+            types: HashMap::new(),
+        };
+        fixtures
+            .register::<Cell>("cell")
+            .register::<Message>("message")
+            .register::<StateInit>("state_init")
+            .register::<JettonTransfer>("jetton_transfer");
+        fixtures
+    }
+}
+
+/// Parse `root` as `T`, serialize it back and compare hashes and
+/// (optionally) decoded representation.
+fn check_typed<T>(root: &Cell, decoded: Option<&Value>) -> Result<(), StringError>
+where
+    T: Decoded,
+{
+    let value: T = root.parse_fully().map_err(Error::custom)?;
+    let got = value.to_cell().map_err(Error::custom)?;
+    if got.hash() != root.hash() {
+        return Err(Error::custom(format!(
+            "re-serialized hash mismatch: expected {}, got {}",
+            hex::encode(root.hash()),
+            hex::encode(got.hash()),
+        )));
+    }
+    if let Some(expected) = decoded {
+        let got = value.to_json();
+        // only fields mentioned in fixture are checked
+        for (k, v) in expected
+            .as_object()
+            .ok_or_else(|| Error::custom("decoded: expected object"))?
+        {
+            if got.get(k) != Some(v) {
+                return Err(Error::custom(format!(
+                    "decoded.{k}: expected {v}, got {}",
+                    got.get(k).unwrap_or(&Value::Null)
+                )));
+            }
+        }
+    }
+    Ok(())
+}
@@ -2,3 +2,8 @@
 pub use tlb;
 pub use tlb_ton as ton;
 pub use ton_contracts as contracts;
+
+#[cfg(feature = "fixtures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fixtures")))]
+pub mod fixtures;
+pub mod provider;
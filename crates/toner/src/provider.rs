@@ -0,0 +1,324 @@
+//! Abstraction over providers of access to TON blockchain, e.g. liteservers
+//! or HTTP APIs, to broadcast messages through.
+use core::fmt::{self, Display};
+use std::{error::Error, thread, time::Duration};
+
+use tlb_ton::boc::BagOfCells;
+
+/// Reason why [`Provider::send_boc()`] failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendBocError<E> {
+    /// The same message was already accepted, e.g. "duplicate message"
+    /// response. When it's received on retry, previous attempt has
+    /// actually succeeded.
+    Duplicate,
+    /// Temporary failure which can be retried, e.g. timeout or rate limit.
+    /// Note that the message might have been delivered anyway.
+    Retryable(E),
+    /// Permanent failure, e.g. malformed or rejected message
+    Fatal(E),
+}
+
+impl<E> Display for SendBocError<E>
+where
+    E: Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Duplicate => f.write_str("duplicate message"),
+            Self::Retryable(err) | Self::Fatal(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+impl<E> Error for SendBocError<E> where E: Error {}
+
+/// Provider of access to TON blockchain
+pub trait Provider {
+    type Error;
+
+    /// Broadcast given BoC with external message once
+    fn send_boc(&self, boc: &BagOfCells) -> Result<(), SendBocError<Self::Error>>;
+
+    /// Broadcast given BoC, retrying [retryable](SendBocError::Retryable)
+    /// failures with delays given by `policy`.
+    ///
+    /// Delays are waited with [`RetryPolicy::sleep()`], which blocks the
+    /// current thread by default, so either avoid calling this on async
+    /// executor threads or override it with a runtime-aware wait.
+    ///
+    /// Retries of the same BoC can not be applied twice, so
+    /// [duplicate](SendBocError::Duplicate) responses are treated as
+    /// success. Error of the last attempt is returned when `policy`
+    /// gives up.
+    /// ```rust
+    /// # use std::{cell::Cell, time::Duration};
+    /// # use tlb::Cell as TlbCell;
+    /// # use tlb_ton::boc::BagOfCells;
+    /// # use toner::provider::{ExponentialBackoff, Provider, SendBocError};
+    /// struct Flaky(Cell<u32>);
+    ///
+    /// impl Provider for Flaky {
+    ///     type Error = &'static str;
+    ///
+    ///     fn send_boc(&self, _boc: &BagOfCells) -> Result<(), SendBocError<Self::Error>> {
+    ///         self.0.set(self.0.get() + 1);
+    ///         match self.0.get() {
+    ///             // delivered, but response timed out
+    ///             1 => Err(SendBocError::Retryable("timeout")),
+    ///             _ => Err(SendBocError::Duplicate),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let provider = Flaky(Cell::new(0));
+    /// let policy = ExponentialBackoff::new(Duration::from_millis(1), 3);
+    /// # let boc = BagOfCells::from_root(TlbCell::new());
+    /// provider.send_boc_with_retry(&boc, policy).unwrap();
+    /// assert_eq!(provider.0.get(), 2);
+    /// ```
+    fn send_boc_with_retry<P>(&self, boc: &BagOfCells, mut policy: P) -> Result<(), Self::Error>
+    where
+        P: RetryPolicy,
+    {
+        let mut retries = 0;
+        loop {
+            let err = match self.send_boc(boc) {
+                Ok(()) | Err(SendBocError::Duplicate) => return Ok(()),
+                Err(SendBocError::Fatal(err)) => return Err(err),
+                Err(SendBocError::Retryable(err)) => err,
+            };
+            retries += 1;
+            let Some(delay) = policy.delay(retries) else {
+                return Err(err);
+            };
+            policy.sleep(delay);
+        }
+    }
+}
+
+impl<T> Provider for &T
+where
+    T: Provider + ?Sized,
+{
+    type Error = T::Error;
+
+    #[inline]
+    fn send_boc(&self, boc: &BagOfCells) -> Result<(), SendBocError<Self::Error>> {
+        (**self).send_boc(boc)
+    }
+}
+
+/// Policy of retrying [`Provider::send_boc_with_retry()`]
+pub trait RetryPolicy {
+    /// Delay before given `retry`, starting from 1, or `None` to give up
+    fn delay(&mut self, retry: u32) -> Option<Duration>;
+
+    /// Wait for given `delay` before the next retry.
+    ///
+    /// Default implementation blocks the current thread with
+    /// [`thread::sleep()`].
+    #[inline]
+    fn sleep(&mut self, delay: Duration) {
+        thread::sleep(delay);
+    }
+}
+
+impl<F> RetryPolicy for F
+where
+    F: FnMut(u32) -> Option<Duration>,
+{
+    #[inline]
+    fn delay(&mut self, retry: u32) -> Option<Duration> {
+        self(retry)
+    }
+}
+
+/// [`RetryPolicy`] doubling the delay after each retry up to
+/// [`max_delay`](ExponentialBackoff::max_delay), giving up after
+/// [`max_retries`](ExponentialBackoff::max_retries):
+/// ```rust
+/// # use std::time::Duration;
+/// # use toner::provider::{ExponentialBackoff, RetryPolicy};
+/// let mut policy = ExponentialBackoff {
+///     max_delay: Duration::from_secs(3),
+///     ..ExponentialBackoff::new(Duration::from_secs(1), 4)
+/// };
+/// assert_eq!(policy.delay(1), Some(Duration::from_secs(1)));
+/// assert_eq!(policy.delay(2), Some(Duration::from_secs(2)));
+/// assert_eq!(policy.delay(3), Some(Duration::from_secs(3)));
+/// assert_eq!(policy.delay(4), Some(Duration::from_secs(3)));
+/// assert_eq!(policy.delay(5), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExponentialBackoff {
+    /// Delay before the first retry
+    pub initial_delay: Duration,
+    /// Upper bound of the delay
+    pub max_delay: Duration,
+    /// Number of retries after the first attempt
+    pub max_retries: u32,
+}
+
+impl ExponentialBackoff {
+    /// Policy with given `initial_delay` and `max_retries` and no
+    /// upper bound of the delay
+    #[inline]
+    pub const fn new(initial_delay: Duration, max_retries: u32) -> Self {
+        Self {
+            initial_delay,
+            max_delay: Duration::MAX,
+            max_retries,
+        }
+    }
+}
+
+impl Default for ExponentialBackoff {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            max_retries: 5,
+        }
+    }
+}
+
+impl RetryPolicy for ExponentialBackoff {
+    fn delay(&mut self, retry: u32) -> Option<Duration> {
+        if retry == 0 || retry > self.max_retries {
+            return None;
+        }
+        Some(
+            2u32.checked_pow(retry - 1)
+                .and_then(|factor| self.initial_delay.checked_mul(factor))
+                .map_or(self.max_delay, |delay| delay.min(self.max_delay)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use tlb::Cell;
+
+    use super::*;
+
+    fn boc() -> BagOfCells {
+        BagOfCells::from_root(Cell::new())
+    }
+
+    /// Provider replying with given responses in order
+    struct Mock(RefCell<Vec<Result<(), SendBocError<u32>>>>);
+
+    impl Mock {
+        fn new(responses: impl IntoIterator<Item = Result<(), SendBocError<u32>>>) -> Self {
+            let mut responses: Vec<_> = responses.into_iter().collect();
+            responses.reverse();
+            Self(RefCell::new(responses))
+        }
+
+        fn remaining(&self) -> usize {
+            self.0.borrow().len()
+        }
+    }
+
+    impl Provider for Mock {
+        type Error = u32;
+
+        fn send_boc(&self, _boc: &BagOfCells) -> Result<(), SendBocError<u32>> {
+            self.0.borrow_mut().pop().expect("unexpected attempt")
+        }
+    }
+
+    fn no_delay(max_retries: u32) -> ExponentialBackoff {
+        ExponentialBackoff::new(Duration::ZERO, max_retries)
+    }
+
+    #[test]
+    fn retries_until_success() {
+        let provider = Mock::new([
+            Err(SendBocError::Retryable(1)),
+            Err(SendBocError::Retryable(2)),
+            Ok(()),
+        ]);
+        assert_eq!(provider.send_boc_with_retry(&boc(), no_delay(2)), Ok(()));
+        assert_eq!(provider.remaining(), 0);
+    }
+
+    #[test]
+    fn duplicate_is_success() {
+        let provider = Mock::new([
+            Err(SendBocError::Retryable(1)),
+            Err(SendBocError::Duplicate),
+        ]);
+        assert_eq!(provider.send_boc_with_retry(&boc(), no_delay(5)), Ok(()));
+        assert_eq!(provider.remaining(), 0);
+    }
+
+    #[test]
+    fn fatal_is_not_retried() {
+        let provider = Mock::new([Err(SendBocError::Fatal(1)), Ok(())]);
+        assert_eq!(provider.send_boc_with_retry(&boc(), no_delay(5)), Err(1));
+        assert_eq!(provider.remaining(), 1);
+    }
+
+    #[test]
+    fn gives_up_with_last_error() {
+        let provider = Mock::new([
+            Err(SendBocError::Retryable(1)),
+            Err(SendBocError::Retryable(2)),
+            Ok(()),
+        ]);
+        assert_eq!(provider.send_boc_with_retry(&boc(), no_delay(1)), Err(2));
+        assert_eq!(provider.remaining(), 1);
+    }
+
+    #[test]
+    fn custom_policy() {
+        let provider = Mock::new([Err(SendBocError::Retryable(1)), Ok(())]);
+        let mut retries = Vec::new();
+        provider
+            .send_boc_with_retry(&boc(), |retry| {
+                retries.push(retry);
+                Some(Duration::ZERO)
+            })
+            .unwrap();
+        assert_eq!(retries, [1]);
+    }
+
+    #[test]
+    fn custom_sleep() {
+        /// Records delays instead of blocking
+        struct Recorded<'a>(&'a mut Vec<Duration>);
+
+        impl RetryPolicy for Recorded<'_> {
+            fn delay(&mut self, retry: u32) -> Option<Duration> {
+                (retry <= 2).then(|| Duration::from_secs(retry.into()))
+            }
+
+            fn sleep(&mut self, delay: Duration) {
+                self.0.push(delay);
+            }
+        }
+
+        let provider = Mock::new([
+            Err(SendBocError::Retryable(1)),
+            Err(SendBocError::Retryable(2)),
+            Ok(()),
+        ]);
+        let mut delays = Vec::new();
+        provider
+            .send_boc_with_retry(&boc(), Recorded(&mut delays))
+            .unwrap();
+        assert_eq!(delays, [Duration::from_secs(1), Duration::from_secs(2)]);
+    }
+
+    #[test]
+    fn exponential_backoff_overflow() {
+        let mut policy = ExponentialBackoff::new(Duration::from_secs(1), u32::MAX);
+        assert_eq!(policy.delay(64), Some(Duration::MAX));
+        assert_eq!(policy.delay(0), None);
+    }
+}
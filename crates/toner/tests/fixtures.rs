@@ -0,0 +1,14 @@
+//! Golden-file round-trip tests, see [`toner::fixtures`].
+//!
+//! To add a new test vector just drop a file into
+//! [`tests/fixtures`](./fixtures).
+use toner::fixtures::Fixtures;
+
+const FIXTURES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+#[test]
+fn fixtures() {
+    Fixtures::default()
+        .check_dir(FIXTURES_DIR)
+        .unwrap_or_else(|err| panic!("{err}"));
+}
@@ -0,0 +1,155 @@
+//! Compatibility tests against reference implementation.
+//!
+//! Test vectors in [`tests/reference`](./reference) were produced by the
+//! official C++ tools (`func`/`fift`) or taken from TON documentation, and
+//! are compared **byte-for-byte** with what this crate produces:
+//! * `boc.json`: BoC as serialized by `fift` with `2 boc+>B`, i.e. with
+//!   crc32c and without index, must be reproduced exactly when re-packed
+//!   with [`CellOrder::DfsPreorder`]
+//! * `address.json`: raw address and its user-friendly representations
+//!   as printed by `fift` with `smca>$`
+//!
+//! Only add vectors of known origin here, golden files produced by this
+//! crate itself belong to [`tests/fixtures`](./fixtures).
+//!
+//! With `fift` feature enabled, the same vectors are also checked against
+//! a local `fift` binary (`$FIFT` or `fift` in `$PATH`), which requires
+//! `$FIFTPATH` to point to its standard library.
+use std::{fs, path::Path};
+
+use serde_json::Value;
+use toner::ton::{
+    boc::{BagOfCells, BagOfCellsArgs, CellOrder},
+    MsgAddress,
+};
+
+const REFERENCE_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/reference");
+
+/// Same args as `fift` uses for `2 boc+>B`
+const FIFT_BOC_ARGS: BagOfCellsArgs =
+    BagOfCellsArgs::new(false, true).with_cell_order(CellOrder::DfsPreorder);
+
+fn vectors(name: &str) -> Vec<Value> {
+    let path = Path::new(REFERENCE_DIR).join(name);
+    serde_json::from_str(&fs::read_to_string(&path).unwrap())
+        .unwrap_or_else(|err| panic!("{}: {err}", path.display()))
+}
+
+fn field<'a>(v: &'a Value, name: &str) -> &'a str {
+    v[name]
+        .as_str()
+        .unwrap_or_else(|| panic!("{}: {name}: missing", v["description"]))
+}
+
+#[test]
+fn boc() {
+    for v in vectors("boc.json") {
+        let expected = field(&v, "boc");
+        let boc = BagOfCells::parse_base64(expected).unwrap();
+        assert_eq!(
+            boc.to_base64(FIFT_BOC_ARGS).unwrap(),
+            expected,
+            "{}",
+            v["description"],
+        );
+    }
+}
+
+#[test]
+fn address() {
+    for v in vectors("address.json") {
+        let description = &v["description"];
+        let addr: MsgAddress = MsgAddress::from_hex(field(&v, "raw")).unwrap();
+        assert_eq!(addr.to_hex(), field(&v, "raw"), "{description}");
+
+        for (name, non_bounceable, non_production) in [
+            ("bounceable", false, false),
+            ("non_bounceable", true, false),
+            ("testnet_bounceable", false, true),
+        ] {
+            let Some(expected) = v[name].as_str() else {
+                continue;
+            };
+            assert_eq!(
+                addr.to_base64_url_flags(non_bounceable, non_production),
+                expected,
+                "{description}: {name}",
+            );
+            assert_eq!(
+                MsgAddress::from_base64_url_flags(expected).unwrap(),
+                (addr, non_bounceable, non_production),
+                "{description}: {name}",
+            );
+        }
+    }
+}
+
+#[cfg(feature = "fift")]
+mod fift {
+    use std::{
+        env,
+        process::Command,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::*;
+
+    /// Run Fift `script` and return its stdout
+    fn run(script: &str) -> String {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let path = env::temp_dir().join(format!(
+            "toner-reference-{}-{}.fif",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed),
+        ));
+        fs::write(&path, script).unwrap();
+        let output = Command::new(env::var("FIFT").unwrap_or_else(|_| "fift".to_string()))
+            .arg("-s")
+            .arg(&path)
+            .output()
+            .expect("failed to run fift");
+        fs::remove_file(&path).unwrap();
+        assert!(
+            output.status.success(),
+            "fift failed:\n{script}\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    }
+
+    #[test]
+    fn boc() {
+        for v in vectors("boc.json") {
+            let boc = BagOfCells::parse_base64(field(&v, "boc")).unwrap();
+            let got = boc.to_bytes(FIFT_BOC_ARGS).unwrap();
+            let expected = run(&format!(
+                "B{{{}}} B>boc 2 boc+>B Bx. cr",
+                hex::encode_upper(&got)
+            ));
+            assert_eq!(hex::encode_upper(got), expected, "{}", v["description"]);
+        }
+    }
+
+    #[test]
+    fn address() {
+        for v in vectors("address.json") {
+            let addr = MsgAddress::from_hex(field(&v, "raw")).unwrap();
+            for (non_bounceable, non_production) in [(false, false), (true, false), (false, true)] {
+                // +1: non-bounceable, +2: testnet, +4: base64url
+                let flags = 4 + u8::from(non_bounceable) + 2 * u8::from(non_production);
+                let expected = run(&format!(
+                    "\"TonUtil.fif\" include {} 0x{} {flags} smca>$ type cr",
+                    addr.workchain_id,
+                    hex::encode(addr.address),
+                ));
+                assert_eq!(
+                    addr.to_base64_url_flags(non_bounceable, non_production),
+                    expected,
+                    "{}",
+                    v["description"],
+                );
+            }
+        }
+    }
+}
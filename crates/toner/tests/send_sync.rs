@@ -0,0 +1,44 @@
+//! Types shared between threads must stay [`Send`] and [`Sync`], even when
+//! `As` adapters in their type parameters are not.
+use std::{rc::Rc, sync::Arc};
+
+use toner::{
+    tlb::{
+        bits::r#as::AsWrap,
+        de::{CellParser, CellParserError},
+        r#as::{Data, Same},
+        ser::{CellBuilder, CellBuilderError},
+        Cell,
+    },
+    ton::{
+        boc::{BagOfCells, CodeCellCache, IndexedBagOfCells, ParsedBagOfCells},
+        hashmap::Dict,
+    },
+};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn tlbits() {
+    assert_send_sync::<AsWrap<u8, Rc<Same>>>();
+    assert_send_sync::<AsWrap<&u8, Rc<Same>>>();
+}
+
+#[test]
+fn tlb() {
+    assert_send_sync::<Cell>();
+    assert_send_sync::<Arc<Cell>>();
+    assert_send_sync::<CellBuilder>();
+    assert_send_sync::<CellParser<'static>>();
+    assert_send_sync::<CellParserError<'static>>();
+    assert_send_sync::<CellBuilderError>();
+}
+
+#[test]
+fn tlb_ton() {
+    assert_send_sync::<BagOfCells>();
+    assert_send_sync::<ParsedBagOfCells>();
+    assert_send_sync::<IndexedBagOfCells<'static>>();
+    assert_send_sync::<CodeCellCache>();
+    assert_send_sync::<Dict<8, u8, u32, Rc<Data>>>();
+}